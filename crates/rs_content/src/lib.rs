@@ -9,6 +9,8 @@ pub trait Content: erased_serde::Serialize + Downcast + Asset {
     fn get_type_text(&self) -> &'static str;
     fn get_name(&self) -> String;
     fn set_name(&mut self, new_name: String);
+    /// Changes the url in place, the states that are not serialized are kept.
+    fn set_url(&mut self, url: url::Url);
 }
 
 impl_downcast!(Content);
@@ -63,6 +63,10 @@ impl ContentFolder {
         self.files.push(file);
     }
 
+    pub(crate) fn remove_file(&mut self, url: &url::Url) {
+        self.files.retain(|x| &x.borrow().get_url() != url);
+    }
+
     fn check_file(&self, file: &EContentFileType) {
         let file_ref = file.borrow();
         let mut base_url = self.get_url();
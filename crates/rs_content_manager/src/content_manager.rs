@@ -1,15 +1,17 @@
 use crate::content_folder::ContentFolder;
-use notify::ReadDirectoryChangesWatcher;
+use crate::dependency_graph::{DependencyGraph, replace_content_urls};
+use crate::redirector::{REDIRECTOR_TYPE, Redirector, Redirectors};
+use notify::RecommendedWatcher;
 use notify_debouncer_full::{DebouncedEvent, Debouncer, FileIdMap};
 use pathdiff::diff_paths;
 use rs_content::Content;
-use rs_core_minimal::path_ext::CanonicalizeSlashExt;
+use rs_core_minimal::{path_ext::CanonicalizeSlashExt, settings::EContentFileFormat};
 use rs_engine::{CONTENT_ROOT, CONTENT_SCHEME, content::content_file_type::EContentFileType};
 use rs_foundation::new::{SingleThreadMut, SingleThreadMutType};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, HashMap},
     fs::File,
     io::BufReader,
     path::{Path, PathBuf},
@@ -32,6 +34,7 @@ type ContentSaver = Box<dyn Fn(EContentFileType) -> crate::error::Result<Value>>
 enum LoadResult {
     File(EContentFileType),
     Folder,
+    Redirector(Redirector),
     Skip,
 }
 
@@ -41,8 +44,13 @@ pub struct ContentManager {
     content_folders: HashMap<PathBuf, ContentFolder>,
     creators: HashMap<String, ContentCreator>,
     saver: HashMap<String, ContentSaver>,
+    file_format: EContentFileFormat,
+    dependency_graph: DependencyGraph,
+    redirectors: Redirectors,
+    /// The contents rewritten by the redirectors, their files still refer to the old urls.
+    redirected_contents: BTreeSet<url::Url>,
     file_receiver: Option<Receiver<Result<Vec<DebouncedEvent>, Vec<notify::Error>>>>,
-    file_debouncer: Option<Debouncer<ReadDirectoryChangesWatcher, FileIdMap>>,
+    file_debouncer: Option<Debouncer<RecommendedWatcher, FileIdMap>>,
 }
 
 impl ContentManager {
@@ -82,6 +90,7 @@ impl ContentManager {
         type TextureFile = rs_engine::content::texture::TextureFile;
         type Level = rs_engine::content::level::Level;
         type Material = rs_engine::content::material::Material;
+        type MaterialFunction = rs_engine::content::material_function::MaterialFunction;
         type IBL = rs_engine::content::ibl::IBL;
         type ParticleSystem = rs_engine::content::particle_system::ParticleSystem;
         type Sound = rs_engine::content::sound::Sound;
@@ -90,6 +99,7 @@ impl ContentManager {
         type MaterialParamentersCollection =
             rs_engine::content::material_paramenters_collection::MaterialParamentersCollection;
         type RenderTarget2D = rs_engine::content::render_target_2d::RenderTarget2D;
+        type Prefab = rs_engine::content::prefab::Prefab;
 
         register_content_type!(StaticMesh);
         register_content_type!(SkeletonMesh);
@@ -105,12 +115,18 @@ impl ContentManager {
         register_content_type!(BlendAnimations);
         register_content_type!(MaterialParamentersCollection);
         register_content_type!(RenderTarget2D);
+        register_content_type!(MaterialFunction);
+        register_content_type!(Prefab);
 
         let mut manager = Self {
             content_root_folder_path,
             content_files: vec![],
             creators,
             saver: saver,
+            file_format: EContentFileFormat::Json,
+            dependency_graph: DependencyGraph::default(),
+            redirectors: Redirectors::default(),
+            redirected_contents: BTreeSet::new(),
             file_receiver: None,
             file_debouncer: None,
             content_folders: HashMap::new(),
@@ -192,6 +208,8 @@ impl ContentManager {
         let _ = self.content_root_folder_path.try_exists()?;
         self.content_files.clear();
         self.content_folders.clear();
+        self.redirectors.clear();
+        self.redirected_contents.clear();
         for entry in WalkDir::new(&self.content_root_folder_path) {
             let entry = entry?;
             let path = entry.path();
@@ -206,11 +224,19 @@ impl ContentManager {
                     LoadResult::File(content_file) => {
                         self.content_files.push(content_file);
                     }
+                    LoadResult::Redirector(redirector) => {
+                        self.redirectors.insert(redirector.url, redirector.target);
+                    }
                     _ => {}
                 },
                 Err(err) => log::warn!("{err}"),
             }
         }
+        self.redirected_contents = self.apply_redirectors();
+        self.rebuild_dependency_graph();
+        for (url, dependency) in self.dependency_graph.find_dangling_references() {
+            log::warn!("{} refers to the missing content {}", url, dependency);
+        }
         Ok(())
     }
 
@@ -227,8 +253,39 @@ impl ContentManager {
             ty: type_text.to_string(),
             content,
         };
-        let contents = serde_json::to_string_pretty(&content)?;
-        Ok(std::fs::write(p, contents)?)
+        self.write_file(&p, &content)
+    }
+
+    fn save_redirector(&self, redirector: Redirector) -> crate::error::Result<()> {
+        let p = Self::try_create_path(&self.content_root_folder_path, &redirector.url)?;
+        let content = ContentMeta {
+            ty: REDIRECTOR_TYPE.to_string(),
+            content: redirector,
+        };
+        self.write_file(&p, &content)
+    }
+
+    fn write_file<T: Serialize>(
+        &self,
+        path: &Path,
+        content: &ContentMeta<T>,
+    ) -> crate::error::Result<()> {
+        let contents = match self.file_format {
+            EContentFileFormat::Json => serde_json::to_string_pretty(content)?,
+            EContentFileFormat::MergeFriendly => {
+                crate::text_format::to_merge_friendly_string(&serde_json::to_value(content)?)
+            }
+        };
+        Ok(std::fs::write(path, contents)?)
+    }
+
+    /// The format of the saved files, the files of all formats can be loaded.
+    pub fn file_format(&self) -> EContentFileFormat {
+        self.file_format
+    }
+
+    pub fn set_file_format(&mut self, file_format: EContentFileFormat) {
+        self.file_format = file_format;
     }
 
     fn try_create_path(
@@ -268,6 +325,10 @@ impl ContentManager {
             let type_text = object.get("type").map(|x| x.as_str()).flatten().ok_or(
                 crate::error::Error::MissingValue("Not a type text".to_string()),
             )?;
+            if type_text == REDIRECTOR_TYPE {
+                let meta: ContentMeta<Redirector> = serde_json::from_value(object)?;
+                return Ok(LoadResult::Redirector(meta.content));
+            }
             let creator = creators
                 .get(type_text)
                 .ok_or(crate::error::Error::MissingValue(format!(
@@ -389,6 +450,8 @@ impl ContentManager {
             {
                 folder.insert_file(new_file.clone());
                 self.content_files.push(new_file);
+                self.redirectors.remove(&url);
+                self.dependency_graph.update(&new_file);
             } else {
                 errors.insert(url, crate::error::Error::Other(format!("")));
             }
@@ -396,7 +459,41 @@ impl ContentManager {
         errors
     }
 
-    pub fn delete_contents(&mut self, contents: Vec<EContentFileType>) {
+    /// The contents that refer to the contents to be deleted, the contents among them are left out.
+    pub fn find_external_references(
+        &self,
+        contents: &[EContentFileType],
+    ) -> BTreeMap<url::Url, Vec<url::Url>> {
+        let urls: Vec<url::Url> = contents.iter().map(|x| x.borrow().get_url()).collect();
+        let mut external_references = BTreeMap::new();
+        for url in urls.iter() {
+            let references: Vec<url::Url> = self
+                .dependency_graph
+                .find_references(url)
+                .into_iter()
+                .filter(|x| !urls.contains(x))
+                .collect();
+            if !references.is_empty() {
+                external_references.insert(url.clone(), references);
+            }
+        }
+        external_references
+    }
+
+    /// Deletes the contents, nothing is deleted if other contents still refer to them unless it is forced.
+    pub fn delete_contents(
+        &mut self,
+        contents: Vec<EContentFileType>,
+        is_force: bool,
+    ) -> crate::error::Result<()> {
+        let external_references = self.find_external_references(&contents);
+        if !external_references.is_empty() {
+            if !is_force {
+                return Err(crate::error::Error::Referenced(external_references));
+            }
+            log::warn!("Delete referenced contents: {:?}", external_references);
+        }
+        let urls: Vec<url::Url> = contents.iter().map(|x| x.borrow().get_url()).collect();
         let content_root_folder_path = self.content_root_folder_path.clone();
         let paths = contents
             .iter()
@@ -411,6 +508,233 @@ impl ContentManager {
                 log::warn!("{err}");
             }
         }
+        for url in urls.iter() {
+            self.dependency_graph.remove(url);
+        }
+        Ok(())
+    }
+
+    /// Scans the content again, should be called after the content is modified.
+    pub fn update_dependencies(&mut self, content: &EContentFileType) {
+        self.dependency_graph.update(content);
+    }
+
+    /// Scans the contents again, should be called after the contents are modified.
+    pub fn rebuild_dependency_graph(&mut self) {
+        self.dependency_graph.rebuild(&self.content_files);
+    }
+
+    /// The content refers to the urls outside its file, such as the urls in the graph of a material.
+    /// They block the deletion of the contents and are rewritten when the contents are moved.
+    pub fn set_external_dependencies(&mut self, url: &url::Url, dependencies: BTreeSet<url::Url>) {
+        self.dependency_graph
+            .set_external_dependencies(url.clone(), dependencies);
+        if let Some(content) = self
+            .content_files
+            .iter()
+            .find(|x| &x.borrow().get_url() == url)
+        {
+            self.dependency_graph.update(content);
+        }
+    }
+
+    pub fn dependency_graph(&self) -> &DependencyGraph {
+        &self.dependency_graph
+    }
+
+    /// The contents that refer to the content.
+    pub fn find_references(&self, url: &url::Url) -> Vec<url::Url> {
+        self.dependency_graph.find_references(url)
+    }
+
+    /// The contents the content refers to.
+    pub fn find_dependencies(&self, url: &url::Url) -> Vec<url::Url> {
+        self.dependency_graph.find_dependencies(url)
+    }
+
+    /// Replaces the old url in the contents that refer to it, used after a content is renamed or moved.
+    /// Returns the rewritten contents, they are new objects in the same cells
+    /// and the states that are not serialized should be loaded again.
+    pub fn rewrite_references(&mut self, old_url: &url::Url, new_url: &url::Url) -> Vec<url::Url> {
+        self.rebuild_dependency_graph();
+        let rewritten_urls = self.rewrite_referencing_contents(old_url, new_url);
+        self.rebuild_dependency_graph();
+        rewritten_urls
+    }
+
+    /// Finds the references in the current dependency graph, doesn't rebuild it.
+    fn rewrite_referencing_contents(
+        &self,
+        old_url: &url::Url,
+        new_url: &url::Url,
+    ) -> Vec<url::Url> {
+        let references = self.dependency_graph.find_references(old_url);
+        let mut rewritten_urls = vec![];
+        for content in self.content_files.iter() {
+            let url = content.borrow().get_url();
+            if !references.contains(&url) {
+                continue;
+            }
+            match Self::rewrite_content(content, old_url, new_url) {
+                Ok(true) => rewritten_urls.push(url),
+                Ok(false) => {}
+                Err(err) => log::warn!("{}: {}", url, err),
+            }
+        }
+        rewritten_urls
+    }
+
+    /// Returns true if the content refers to the old url.
+    fn rewrite_content(
+        content: &EContentFileType,
+        old_url: &url::Url,
+        new_url: &url::Url,
+    ) -> crate::error::Result<bool> {
+        let mut value = serde_json::to_value(content)?;
+        if !replace_content_urls(&mut value, old_url, new_url) {
+            return Ok(false);
+        }
+        let new_content: Box<dyn Content> = serde_json::from_value(value)?;
+        *content.borrow_mut() = new_content;
+        Ok(true)
+    }
+
+    pub fn redirectors(&self) -> &Redirectors {
+        &self.redirectors
+    }
+
+    /// The url of the content now, follows the redirectors if the content was moved or renamed.
+    pub fn resolve_url(&self, url: &url::Url) -> url::Url {
+        self.redirectors.resolve(url).unwrap_or_else(|| url.clone())
+    }
+
+    /// Rewrites the references to the redirected urls in memory, the files are not changed.
+    fn apply_redirectors(&mut self) -> BTreeSet<url::Url> {
+        let mut rewritten_urls = BTreeSet::new();
+        if self.redirectors.is_empty() {
+            return rewritten_urls;
+        }
+        self.rebuild_dependency_graph();
+        for (url, target) in self.redirectors.resolve_all() {
+            rewritten_urls.extend(self.rewrite_referencing_contents(&url, &target));
+        }
+        self.rebuild_dependency_graph();
+        rewritten_urls
+    }
+
+    fn folder_relative_path(&self, url: &url::Url) -> Option<PathBuf> {
+        let path = Self::get_path(&self.content_root_folder_path, url)?;
+        let parent = path.parent()?;
+        Some(Self::make_relative_path(
+            parent,
+            &self.content_root_folder_path,
+        ))
+    }
+
+    /// Moves or renames the content. A redirector is saved at the old url,
+    /// so the references that are not rewritten in memory still resolve at load time.
+    /// The moved content keeps its states that are not serialized.
+    /// Returns the rewritten contents, includes the moved content.
+    pub fn move_content(
+        &mut self,
+        content: EContentFileType,
+        new_url: url::Url,
+    ) -> crate::error::Result<Vec<url::Url>> {
+        let old_url = content.borrow().get_url();
+        if old_url == new_url {
+            return Ok(vec![]);
+        }
+        if self
+            .content_files
+            .iter()
+            .any(|x| x.borrow().get_url() == new_url)
+        {
+            return Err(crate::error::Error::Other(format!(
+                "{} already exists",
+                new_url
+            )));
+        }
+        let old_folder_path = self
+            .folder_relative_path(&old_url)
+            .ok_or(crate::error::Error::MissingValue(format!("{old_url}")))?;
+        let new_folder_path = self
+            .folder_relative_path(&new_url)
+            .filter(|x| self.content_folders.contains_key(x))
+            .ok_or(crate::error::Error::MissingValue(format!(
+                "No folder for {new_url}"
+            )))?;
+
+        content.borrow_mut().set_url(new_url.clone());
+        if let Err(err) = self.save(content.clone()) {
+            content.borrow_mut().set_url(old_url.clone());
+            return Err(err);
+        }
+        if let Some(folder) = self.content_folders.get_mut(&old_folder_path) {
+            folder.remove_file(&old_url);
+        }
+        if let Some(folder) = self.content_folders.get_mut(&new_folder_path) {
+            folder.insert_file(content.clone());
+        }
+
+        let redirector = Redirector {
+            url: old_url.clone(),
+            target: new_url.clone(),
+        };
+        if let Err(err) = self.save_redirector(redirector) {
+            log::warn!("Fail to save the redirector of {}: {}", old_url, err);
+        }
+        self.redirectors.remove(&new_url);
+        self.redirectors.insert(old_url.clone(), new_url.clone());
+
+        self.dependency_graph
+            .replace_external_url(&old_url, &new_url);
+        let mut rewritten_urls = self.rewrite_references(&old_url, &new_url);
+        self.redirected_contents
+            .extend(rewritten_urls.iter().cloned());
+        rewritten_urls.push(new_url);
+        Ok(rewritten_urls)
+    }
+
+    /// Saves the contents that referred to the redirected urls and deletes the redirectors,
+    /// the old urls no longer resolve afterwards.
+    pub fn fix_up_redirectors(&mut self) -> HashMap<url::Url, crate::error::Error> {
+        let rewritten_urls = self.apply_redirectors();
+        self.redirected_contents.extend(rewritten_urls);
+        let mut errors = HashMap::new();
+        for content in self.content_files.clone() {
+            let url = content.borrow().get_url();
+            if !self.redirected_contents.contains(&url) {
+                continue;
+            }
+            if let Err(err) = self.save(content) {
+                errors.insert(url, err);
+            }
+        }
+        if !errors.is_empty() {
+            return errors;
+        }
+        for (url, _) in self.redirectors.iter() {
+            if self
+                .content_files
+                .iter()
+                .any(|x| &x.borrow().get_url() == url)
+            {
+                continue;
+            }
+            let Some(path) = Self::get_path(&self.content_root_folder_path, url) else {
+                continue;
+            };
+            match std::fs::remove_file(path) {
+                Ok(_) => {}
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => {
+                    errors.insert(url.clone(), err.into());
+                }
+            }
+        }
+        self.redirectors.clear();
+        self.redirected_contents.clear();
+        errors
     }
 
     pub fn on_delete_by_paths(&mut self, paths: &[PathBuf]) {
@@ -455,8 +779,17 @@ impl ContentManager {
 #[cfg(test)]
 mod test {
     use crate::content_manager::{CONTENT_FILE_EXTENSION, ContentManager, ContentMeta};
-    use rs_engine::{build_content_file_url, content::static_mesh::AssetInfo};
-    use std::path::PathBuf;
+    use crate::dependency_graph::collect_content_urls;
+    use rs_engine::{
+        build_content_file_url,
+        content::{
+            content_file_type::EContentFileType, level::Level, level_streaming::SubLevel,
+            material::Material, static_mesh::AssetInfo, texture::TextureFile,
+        },
+        handle::HandleManager,
+    };
+    use rs_foundation::new::SingleThreadMut;
+    use std::{collections::BTreeSet, path::PathBuf};
 
     fn mock_contents() {
         let path = rs_core_minimal::file_manager::get_engine_build_tmp_dir()
@@ -562,4 +895,255 @@ mod test {
         content_manager.process_file_changed_notification();
         assert_eq!(content_manager.content_folders.len(), 4);
     }
+
+    #[test]
+    fn test_move_chain() {
+        let path = rs_core_minimal::file_manager::get_engine_build_tmp_dir()
+            .join("content_manager_redirector")
+            .join("content");
+        let _ = std::fs::remove_dir_all(&path);
+        let _ = std::fs::create_dir_all(path.join("Folder"));
+        let new_level = |name: &str, sub_level: &str| -> EContentFileType {
+            let mut level = Level::new(name);
+            level
+                .sub_levels
+                .push(SubLevel::new(build_content_file_url(sub_level).unwrap()));
+            SingleThreadMut::new(Box::new(level) as Box<dyn rs_content::Content>)
+        };
+        let sub_level_url = |content_manager: &ContentManager, name: &str| -> url::Url {
+            let content =
+                content_manager.content_map()[&build_content_file_url(name).unwrap()].clone();
+            let value = serde_json::to_value(&content).unwrap();
+            let mut urls = BTreeSet::new();
+            collect_content_urls(&value["content"]["sub_levels"], &mut urls);
+            urls.into_iter().next().unwrap()
+        };
+
+        let mut content_manager = ContentManager::from_path(path.clone());
+        let main = new_level("Main", "Sub");
+        let sub = new_level("Sub", "Main");
+        assert!(
+            content_manager
+                .append(vec![main.clone(), sub.clone()])
+                .is_empty()
+        );
+        content_manager.save(main.clone()).unwrap();
+        content_manager.save(sub.clone()).unwrap();
+
+        let sub_url = build_content_file_url("Sub").unwrap();
+        let sub2_url = build_content_file_url("Sub2").unwrap();
+        let sub3_url = build_content_file_url("Folder/Sub3").unwrap();
+        let rewritten_urls = content_manager
+            .move_content(sub.clone(), sub2_url.clone())
+            .unwrap();
+        assert!(rewritten_urls.contains(&build_content_file_url("Main").unwrap()));
+        content_manager
+            .move_content(sub.clone(), sub3_url.clone())
+            .unwrap();
+        assert_eq!(sub.borrow().get_url(), sub3_url);
+        assert_eq!(sub_level_url(&content_manager, "Main"), sub3_url);
+        assert_eq!(content_manager.resolve_url(&sub_url), sub3_url);
+        assert_eq!(content_manager.resolve_url(&sub2_url), sub3_url);
+        assert!(
+            content_manager
+                .move_content(
+                    main.clone(),
+                    build_content_file_url("Missing/Main").unwrap()
+                )
+                .is_err()
+        );
+
+        // Another content still refers to the first url on disk.
+        content_manager.save(new_level("Other", "Sub")).unwrap();
+        let mut content_manager = ContentManager::from_path(path.clone());
+        assert_eq!(content_manager.content_files().len(), 3);
+        assert_eq!(content_manager.redirectors().len(), 2);
+        assert_eq!(content_manager.resolve_url(&sub_url), sub3_url);
+        assert_eq!(sub_level_url(&content_manager, "Main"), sub3_url);
+        assert_eq!(sub_level_url(&content_manager, "Other"), sub3_url);
+        assert!(
+            content_manager
+                .dependency_graph()
+                .find_dangling_references()
+                .is_empty()
+        );
+
+        // Moves back to the first url.
+        let sub = content_manager.content_map()[&sub3_url].clone();
+        content_manager
+            .move_content(sub.clone(), sub_url.clone())
+            .unwrap();
+        assert_eq!(content_manager.resolve_url(&sub_url), sub_url);
+        assert_eq!(content_manager.resolve_url(&sub2_url), sub_url);
+        assert_eq!(content_manager.resolve_url(&sub3_url), sub_url);
+
+        assert!(content_manager.fix_up_redirectors().is_empty());
+        assert!(content_manager.redirectors().is_empty());
+        assert!(
+            !path
+                .join("Sub2")
+                .with_added_extension(CONTENT_FILE_EXTENSION)
+                .exists()
+        );
+        let content_manager = ContentManager::from_path(path.clone());
+        assert!(content_manager.redirectors().is_empty());
+        assert_eq!(content_manager.content_files().len(), 3);
+        assert_eq!(sub_level_url(&content_manager, "Other"), sub_url);
+        assert_eq!(sub_level_url(&content_manager, "Main"), sub_url);
+    }
+
+    #[test]
+    fn test_delete_referenced() {
+        let path = rs_core_minimal::file_manager::get_engine_build_tmp_dir()
+            .join("content_manager_delete")
+            .join("content");
+        let _ = std::fs::remove_dir_all(&path);
+        let _ = std::fs::create_dir_all(&path);
+        let new_level = |name: &str, sub_levels: &[&str]| -> EContentFileType {
+            let mut level = Level::new(name);
+            for sub_level in sub_levels {
+                level
+                    .sub_levels
+                    .push(SubLevel::new(build_content_file_url(sub_level).unwrap()));
+            }
+            SingleThreadMut::new(Box::new(level) as Box<dyn rs_content::Content>)
+        };
+
+        let mut content_manager = ContentManager::from_path(path.clone());
+        let main = new_level("Main", &["Sub"]);
+        let sub = new_level("Sub", &[]);
+        assert!(
+            content_manager
+                .append(vec![main.clone(), sub.clone()])
+                .is_empty()
+        );
+        content_manager.save(main.clone()).unwrap();
+        content_manager.save(sub.clone()).unwrap();
+        let main_url = build_content_file_url("Main").unwrap();
+        let sub_url = build_content_file_url("Sub").unwrap();
+        assert_eq!(
+            content_manager.find_references(&sub_url),
+            vec![main_url.clone()]
+        );
+
+        match content_manager.delete_contents(vec![sub.clone()], false) {
+            Err(crate::error::Error::Referenced(references)) => {
+                assert_eq!(references[&sub_url], vec![main_url.clone()]);
+            }
+            _ => panic!(),
+        }
+        assert_eq!(content_manager.content_files().len(), 2);
+        assert!(
+            path.join("Sub")
+                .with_added_extension(CONTENT_FILE_EXTENSION)
+                .exists()
+        );
+
+        // The references among the deleted contents don't block the deletion.
+        content_manager
+            .delete_contents(vec![main.clone(), sub.clone()], false)
+            .unwrap();
+        assert!(content_manager.content_files().is_empty());
+        assert!(
+            !path
+                .join("Sub")
+                .with_added_extension(CONTENT_FILE_EXTENSION)
+                .exists()
+        );
+        assert!(content_manager.find_references(&sub_url).is_empty());
+
+        let main = new_level("Main", &["Sub"]);
+        let sub = new_level("Sub", &[]);
+        assert!(
+            content_manager
+                .append(vec![main.clone(), sub.clone()])
+                .is_empty()
+        );
+        content_manager
+            .delete_contents(vec![sub.clone()], true)
+            .unwrap();
+        assert_eq!(content_manager.content_files().len(), 1);
+        assert_eq!(
+            content_manager
+                .dependency_graph()
+                .find_dangling_references(),
+            vec![(main_url, sub_url)]
+        );
+    }
+
+    #[test]
+    fn test_move_keeps_runtime() {
+        let path = rs_core_minimal::file_manager::get_engine_build_tmp_dir()
+            .join("content_manager_runtime")
+            .join("content");
+        let _ = std::fs::remove_dir_all(&path);
+        let _ = std::fs::create_dir_all(&path);
+        let mut content_manager = ContentManager::from_path(path.clone());
+        let asset_url = url::Url::parse("asset://asset/Material").unwrap();
+        let mut material = Material::new(build_content_file_url("Material").unwrap(), asset_url);
+        let pipeline_handle = HandleManager::new().next_material_render_pipeline();
+        material.set_pipeline_handle(pipeline_handle.clone());
+        let material: EContentFileType =
+            SingleThreadMut::new(Box::new(material) as Box<dyn rs_content::Content>);
+        assert!(content_manager.append(vec![material.clone()]).is_empty());
+
+        let new_url = build_content_file_url("Material2").unwrap();
+        content_manager
+            .move_content(material.clone(), new_url.clone())
+            .unwrap();
+        let material = material.borrow();
+        let material = material.downcast_ref::<Material>().unwrap();
+        assert_eq!(material.url, new_url);
+        assert_eq!(material.get_pipeline_handle(), Some(pipeline_handle));
+    }
+
+    #[test]
+    fn test_external_dependencies() {
+        let path = rs_core_minimal::file_manager::get_engine_build_tmp_dir()
+            .join("content_manager_external")
+            .join("content");
+        let _ = std::fs::remove_dir_all(&path);
+        let _ = std::fs::create_dir_all(&path);
+        let mut content_manager = ContentManager::from_path(path.clone());
+        let material_url = build_content_file_url("Material").unwrap();
+        let texture_url = build_content_file_url("Texture").unwrap();
+        let asset_url = url::Url::parse("asset://asset/Material").unwrap();
+        let material: EContentFileType =
+            SingleThreadMut::new(Box::new(Material::new(material_url.clone(), asset_url))
+                as Box<dyn rs_content::Content>);
+        let texture: EContentFileType = SingleThreadMut::new(Box::new(TextureFile::new(
+            texture_url.clone(),
+        ))
+            as Box<dyn rs_content::Content>);
+        assert!(
+            content_manager
+                .append(vec![material.clone(), texture.clone()])
+                .is_empty()
+        );
+        content_manager.save(texture.clone()).unwrap();
+        // The graph of the material samples the texture.
+        content_manager
+            .set_external_dependencies(&material_url, BTreeSet::from([texture_url.clone()]));
+        content_manager.rebuild_dependency_graph();
+        assert_eq!(
+            content_manager.find_references(&texture_url),
+            vec![material_url.clone()]
+        );
+        match content_manager.delete_contents(vec![texture.clone()], false) {
+            Err(crate::error::Error::Referenced(references)) => {
+                assert_eq!(references[&texture_url], vec![material_url.clone()]);
+            }
+            _ => panic!(),
+        }
+
+        let new_texture_url = build_content_file_url("Texture2").unwrap();
+        content_manager
+            .move_content(texture.clone(), new_texture_url.clone())
+            .unwrap();
+        assert!(content_manager.find_references(&texture_url).is_empty());
+        assert_eq!(
+            content_manager.find_references(&new_texture_url),
+            vec![material_url.clone()]
+        );
+    }
 }
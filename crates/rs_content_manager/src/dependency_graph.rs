@@ -0,0 +1,249 @@
+use rs_engine::{CONTENT_ROOT, CONTENT_SCHEME, content::content_file_type::EContentFileType};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+
+/// The references between contents, found by the content urls in the serialized contents.
+#[derive(Debug, Default, Clone)]
+pub struct DependencyGraph {
+    dependencies: HashMap<url::Url, BTreeSet<url::Url>>,
+    references: HashMap<url::Url, BTreeSet<url::Url>>,
+    /// The urls the contents refer to outside the content files, such as the urls in the material graphs.
+    external_dependencies: HashMap<url::Url, BTreeSet<url::Url>>,
+}
+
+impl DependencyGraph {
+    pub fn build(contents: &[EContentFileType]) -> DependencyGraph {
+        let mut graph = DependencyGraph::default();
+        graph.rebuild(contents);
+        graph
+    }
+
+    /// Scans all the contents again, the external dependencies are kept.
+    pub fn rebuild(&mut self, contents: &[EContentFileType]) {
+        self.dependencies.clear();
+        self.references.clear();
+        for content in contents {
+            self.update(content);
+        }
+    }
+
+    /// Scans the content again, the other contents are not changed.
+    pub fn update(&mut self, content: &EContentFileType) {
+        let url = content.borrow().get_url();
+        match serde_json::to_value(content) {
+            Ok(value) => {
+                let mut dependencies = BTreeSet::new();
+                collect_content_urls(&value, &mut dependencies);
+                self.insert(url, dependencies);
+            }
+            Err(err) => {
+                log::warn!("{}: {}", url, err);
+                self.insert(url, BTreeSet::new());
+            }
+        }
+    }
+
+    pub fn insert(&mut self, url: url::Url, mut dependencies: BTreeSet<url::Url>) {
+        self.remove(&url);
+        if let Some(external_dependencies) = self.external_dependencies.get(&url) {
+            dependencies.extend(external_dependencies.iter().cloned());
+        }
+        dependencies.remove(&url);
+        for dependency in dependencies.iter() {
+            self.references
+                .entry(dependency.clone())
+                .or_default()
+                .insert(url.clone());
+        }
+        self.dependencies.insert(url, dependencies);
+    }
+
+    pub fn remove(&mut self, url: &url::Url) {
+        let Some(dependencies) = self.dependencies.remove(url) else {
+            return;
+        };
+        for dependency in dependencies {
+            if let Some(references) = self.references.get_mut(&dependency) {
+                references.remove(url);
+                if references.is_empty() {
+                    self.references.remove(&dependency);
+                }
+            }
+        }
+    }
+
+    /// Replaces the urls the content refers to outside the content files,
+    /// takes effect when the content is scanned again.
+    pub fn set_external_dependencies(&mut self, url: url::Url, dependencies: BTreeSet<url::Url>) {
+        if dependencies.is_empty() {
+            self.external_dependencies.remove(&url);
+        } else {
+            self.external_dependencies.insert(url, dependencies);
+        }
+    }
+
+    /// Replaces the old url in the external dependencies, used after a content is renamed or moved.
+    pub fn replace_external_url(&mut self, old_url: &url::Url, new_url: &url::Url) {
+        if let Some(dependencies) = self.external_dependencies.remove(old_url) {
+            self.external_dependencies
+                .insert(new_url.clone(), dependencies);
+        }
+        for dependencies in self.external_dependencies.values_mut() {
+            if dependencies.remove(old_url) {
+                dependencies.insert(new_url.clone());
+            }
+        }
+    }
+
+    /// The contents the content refers to.
+    pub fn find_dependencies(&self, url: &url::Url) -> Vec<url::Url> {
+        self.dependencies
+            .get(url)
+            .map(|x| x.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// The contents that refer to the content.
+    pub fn find_references(&self, url: &url::Url) -> Vec<url::Url> {
+        self.references
+            .get(url)
+            .map(|x| x.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// The pairs of the content and the url it refers to, the url doesn't belong to any content.
+    pub fn find_dangling_references(&self) -> Vec<(url::Url, url::Url)> {
+        let mut dangling_references = vec![];
+        for (url, dependencies) in self.dependencies.iter() {
+            for dependency in dependencies {
+                if !self.dependencies.contains_key(dependency) {
+                    dangling_references.push((url.clone(), dependency.clone()));
+                }
+            }
+        }
+        dangling_references.sort();
+        dangling_references
+    }
+}
+
+fn content_url_prefix() -> String {
+    format!("{}://{}/", CONTENT_SCHEME, CONTENT_ROOT)
+}
+
+pub fn collect_content_urls(value: &Value, urls: &mut BTreeSet<url::Url>) {
+    match value {
+        Value::String(text) => {
+            if text.starts_with(&content_url_prefix())
+                && let Ok(url) = url::Url::parse(text)
+            {
+                urls.insert(url);
+            }
+        }
+        Value::Array(array) => {
+            for item in array {
+                collect_content_urls(item, urls);
+            }
+        }
+        Value::Object(map) => {
+            for item in map.values() {
+                collect_content_urls(item, urls);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Returns true if any url is replaced.
+pub fn replace_content_urls(value: &mut Value, old_url: &url::Url, new_url: &url::Url) -> bool {
+    match value {
+        Value::String(text) => {
+            if text.as_str() == old_url.as_str() {
+                *text = new_url.to_string();
+                return true;
+            }
+            false
+        }
+        Value::Array(array) => array.iter_mut().fold(false, |is_replaced, item| {
+            replace_content_urls(item, old_url, new_url) || is_replaced
+        }),
+        Value::Object(map) => map.values_mut().fold(false, |is_replaced, item| {
+            replace_content_urls(item, old_url, new_url) || is_replaced
+        }),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DependencyGraph, collect_content_urls, replace_content_urls};
+    use rs_engine::build_content_file_url;
+    use serde_json::json;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_collect_and_replace() {
+        let mut value = json!({
+            "url": "content://Content/Level",
+            "actors": [{"mesh": "content://Content/Mesh"}, {"mesh": "asset://asset/Mesh.fbx"}],
+        });
+        let mut urls = BTreeSet::new();
+        collect_content_urls(&value, &mut urls);
+        let mesh_url = build_content_file_url("Mesh").unwrap();
+        let level_url = build_content_file_url("Level").unwrap();
+        assert_eq!(urls, BTreeSet::from([level_url, mesh_url.clone()]));
+
+        let new_mesh_url = build_content_file_url("Folder/Mesh").unwrap();
+        assert!(replace_content_urls(&mut value, &mesh_url, &new_mesh_url));
+        assert_eq!(value["actors"][0]["mesh"], "content://Content/Folder/Mesh");
+        assert!(!replace_content_urls(&mut value, &mesh_url, &new_mesh_url));
+    }
+
+    #[test]
+    fn test_graph() {
+        let level = build_content_file_url("Level").unwrap();
+        let mesh = build_content_file_url("Mesh").unwrap();
+        let material = build_content_file_url("Material").unwrap();
+        let missing = build_content_file_url("Missing").unwrap();
+        let mut graph = DependencyGraph::default();
+        graph.insert(
+            level.clone(),
+            BTreeSet::from([level.clone(), mesh.clone(), missing.clone()]),
+        );
+        graph.insert(mesh.clone(), BTreeSet::from([material.clone()]));
+        graph.insert(material.clone(), BTreeSet::new());
+
+        assert_eq!(graph.find_references(&mesh), vec![level.clone()]);
+        assert_eq!(graph.find_dependencies(&mesh), vec![material.clone()]);
+        assert!(graph.find_references(&level).is_empty());
+        assert_eq!(
+            graph.find_dangling_references(),
+            vec![(level.clone(), missing.clone())]
+        );
+
+        graph.insert(level.clone(), BTreeSet::from([material.clone()]));
+        assert!(graph.find_references(&mesh).is_empty());
+        graph.remove(&mesh);
+        assert_eq!(graph.find_references(&material), vec![level.clone()]);
+    }
+
+    #[test]
+    fn test_external_dependencies() {
+        let material = build_content_file_url("Material").unwrap();
+        let texture = build_content_file_url("Texture").unwrap();
+        let new_texture = build_content_file_url("Folder/Texture").unwrap();
+        let mut graph = DependencyGraph::default();
+        graph.set_external_dependencies(material.clone(), BTreeSet::from([texture.clone()]));
+        graph.insert(material.clone(), BTreeSet::new());
+        graph.insert(texture.clone(), BTreeSet::new());
+        assert_eq!(graph.find_references(&texture), vec![material.clone()]);
+
+        graph.replace_external_url(&texture, &new_texture);
+        graph.insert(material.clone(), BTreeSet::new());
+        assert!(graph.find_references(&texture).is_empty());
+        assert_eq!(graph.find_references(&new_texture), vec![material.clone()]);
+
+        graph.set_external_dependencies(material.clone(), BTreeSet::new());
+        graph.insert(material.clone(), BTreeSet::new());
+        assert!(graph.find_references(&new_texture).is_empty());
+    }
+}
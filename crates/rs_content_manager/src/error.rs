@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -17,6 +18,9 @@ pub enum Error {
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
+    #[error("The contents are still referenced: {0:?}")]
+    Referenced(BTreeMap<url::Url, Vec<url::Url>>),
+
     #[error("Other error: {0}")]
     Other(String),
 }
@@ -1,3 +1,6 @@
 pub mod content_folder;
 pub mod content_manager;
+pub mod dependency_graph;
 pub mod error;
+pub mod redirector;
+pub mod text_format;
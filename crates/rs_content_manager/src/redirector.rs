@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub const REDIRECTOR_TYPE: &str = "Redirector";
+
+/// Saved at the old location of a moved or renamed content, points to the new location.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Redirector {
+    pub url: url::Url,
+    pub target: url::Url,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Redirectors {
+    redirectors: BTreeMap<url::Url, url::Url>,
+}
+
+impl Redirectors {
+    pub fn insert(&mut self, url: url::Url, target: url::Url) {
+        self.redirectors.insert(url, target);
+    }
+
+    pub fn remove(&mut self, url: &url::Url) -> Option<url::Url> {
+        self.redirectors.remove(url)
+    }
+
+    pub fn clear(&mut self) {
+        self.redirectors.clear();
+    }
+
+    pub fn get(&self, url: &url::Url) -> Option<&url::Url> {
+        self.redirectors.get(url)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.redirectors.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.redirectors.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&url::Url, &url::Url)> {
+        self.redirectors.iter()
+    }
+
+    /// Follows the chain of the redirectors to the final url.
+    /// Returns none if the url is not redirected or the chain is a loop.
+    pub fn resolve(&self, url: &url::Url) -> Option<url::Url> {
+        let mut visited: BTreeSet<&url::Url> = BTreeSet::new();
+        let mut current = url;
+        while let Some(target) = self.redirectors.get(current) {
+            if !visited.insert(current) {
+                log::warn!("The redirectors of {} form a loop", url);
+                return None;
+            }
+            current = target;
+        }
+        if current == url {
+            None
+        } else {
+            Some(current.clone())
+        }
+    }
+
+    /// The redirected urls and their final urls.
+    pub fn resolve_all(&self) -> Vec<(url::Url, url::Url)> {
+        self.redirectors
+            .keys()
+            .filter_map(|url| self.resolve(url).map(|target| (url.clone(), target)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Redirectors;
+    use rs_engine::build_content_file_url;
+
+    #[test]
+    fn test_chain() {
+        let a = build_content_file_url("A").unwrap();
+        let b = build_content_file_url("B").unwrap();
+        let c = build_content_file_url("Folder/C").unwrap();
+        let mut redirectors = Redirectors::default();
+        redirectors.insert(a.clone(), b.clone());
+        assert_eq!(redirectors.resolve(&a), Some(b.clone()));
+        redirectors.insert(b.clone(), c.clone());
+        assert_eq!(redirectors.resolve(&a), Some(c.clone()));
+        assert_eq!(redirectors.resolve(&b), Some(c.clone()));
+        assert_eq!(redirectors.resolve(&c), None);
+        assert_eq!(
+            redirectors.resolve_all(),
+            vec![(a.clone(), c.clone()), (b.clone(), c.clone())]
+        );
+
+        // Moves back to the first url.
+        redirectors.remove(&a);
+        redirectors.insert(c.clone(), a.clone());
+        assert_eq!(redirectors.resolve(&a), None);
+        assert_eq!(redirectors.resolve(&b), Some(a.clone()));
+        assert_eq!(redirectors.resolve(&c), Some(a.clone()));
+    }
+
+    #[test]
+    fn test_loop() {
+        let a = build_content_file_url("A").unwrap();
+        let b = build_content_file_url("B").unwrap();
+        let mut redirectors = Redirectors::default();
+        redirectors.insert(a.clone(), b.clone());
+        redirectors.insert(b.clone(), a.clone());
+        assert_eq!(redirectors.resolve(&a), None);
+        assert!(redirectors.resolve_all().is_empty());
+    }
+}
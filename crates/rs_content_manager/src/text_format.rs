@@ -0,0 +1,129 @@
+use serde_json::Value;
+
+const INDENT: &str = "  ";
+
+/// Writes the value as pretty JSON with sorted keys. The objects of an array are separated by empty lines,
+/// so every actor or scene node is a block that git can diff and merge on its own.
+pub fn to_merge_friendly_string(value: &Value) -> String {
+    let mut text = String::new();
+    write_value(value, 0, &mut text);
+    text.push('\n');
+    text
+}
+
+fn write_indent(depth: usize, text: &mut String) {
+    for _ in 0..depth {
+        text.push_str(INDENT);
+    }
+}
+
+fn write_value(value: &Value, depth: usize, text: &mut String) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            text.push_str("{\n");
+            for (index, key) in keys.iter().enumerate() {
+                if index > 0 {
+                    text.push_str(",\n");
+                }
+                write_indent(depth + 1, text);
+                text.push_str(&Value::String(key.to_string()).to_string());
+                text.push_str(": ");
+                write_value(&map[*key], depth + 1, text);
+            }
+            text.push('\n');
+            write_indent(depth, text);
+            text.push('}');
+        }
+        Value::Array(array) if !array.is_empty() => {
+            let separator = if array.iter().all(|x| x.is_object()) {
+                ",\n\n"
+            } else {
+                ",\n"
+            };
+            text.push_str("[\n");
+            for (index, item) in array.iter().enumerate() {
+                if index > 0 {
+                    text.push_str(separator);
+                }
+                write_indent(depth + 1, text);
+                write_value(item, depth + 1, text);
+            }
+            text.push('\n');
+            write_indent(depth, text);
+            text.push(']');
+        }
+        _ => text.push_str(&value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::to_merge_friendly_string;
+    use crate::content_manager::{CONTENT_FILE_EXTENSION, ContentManager};
+    use rs_core_minimal::settings::EContentFileFormat;
+    use rs_engine::{
+        content::{content_file_type::EContentFileType, level::Level},
+        scene_node::SceneNode,
+    };
+    use rs_foundation::new::SingleThreadMut;
+    use serde_json::json;
+
+    #[test]
+    fn test_sorted_keys() {
+        let value = json!({"b": [1, 2.5, "text"], "a": {"d": [], "c": {}}, "e": null});
+        let text = to_merge_friendly_string(&value);
+        assert_eq!(
+            text,
+            "{\n  \"a\": {\n    \"c\": {},\n    \"d\": []\n  },\n  \"b\": [\n    1,\n    2.5,\n    \"text\"\n  ],\n  \"e\": null\n}\n"
+        );
+        let load_value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(load_value, value);
+    }
+
+    #[test]
+    fn test_object_blocks() {
+        let value = json!({"actors": [{"name": "A\n\"1\""}, {"name": "B"}]});
+        let text = to_merge_friendly_string(&value);
+        assert!(text.contains("},\n\n    {"));
+        let load_value: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(load_value, value);
+    }
+
+    #[test]
+    fn test_level_round_trip() {
+        let path = rs_core_minimal::file_manager::get_engine_build_tmp_dir()
+            .join("text_format")
+            .join("content");
+        let _ = std::fs::remove_dir_all(&path);
+        let _ = std::fs::create_dir_all(&path);
+        let mut level = Level::new("Level");
+        let actor = level.create_and_insert_actor();
+        actor
+            .borrow()
+            .scene_node
+            .borrow_mut()
+            .add_child(SceneNode::new_sp("Child".to_string()));
+        let content: EContentFileType =
+            SingleThreadMut::new(Box::new(level) as Box<dyn rs_content::Content>);
+        let value = serde_json::to_value(&content).unwrap();
+        let file_path = path
+            .join("Level")
+            .with_added_extension(CONTENT_FILE_EXTENSION);
+
+        for file_format in [EContentFileFormat::Json, EContentFileFormat::MergeFriendly] {
+            let mut content_manager = ContentManager::from_path(path.clone());
+            content_manager.set_file_format(file_format);
+            content_manager.save(content.clone()).unwrap();
+            let text = std::fs::read_to_string(&file_path).unwrap();
+
+            let mut content_manager = ContentManager::from_path(path.clone());
+            content_manager.set_file_format(file_format);
+            let load_content = content_manager.content_files()[0].clone();
+            assert_eq!(serde_json::to_value(&load_content).unwrap(), value);
+            content_manager.save(load_content).unwrap();
+            assert_eq!(std::fs::read_to_string(&file_path).unwrap(), text);
+        }
+    }
+}
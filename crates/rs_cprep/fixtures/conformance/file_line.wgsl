@@ -0,0 +1,3 @@
+let line = __LINE__;
+#define CURRENT_LINE __LINE__
+let current_line = CURRENT_LINE;
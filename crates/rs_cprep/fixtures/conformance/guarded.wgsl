@@ -0,0 +1,4 @@
+#ifndef GUARDED
+#define GUARDED 2
+let guarded = GUARDED;
+#endif
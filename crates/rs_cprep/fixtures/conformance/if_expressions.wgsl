@@ -0,0 +1,46 @@
+#define A
+#define B 3
+#define C 0x10
+#define MAX_LIGHTS 4u
+
+#if defined(A) && (B > 2)
+let a = 1;
+#else
+let a = 0;
+#endif
+
+#if defined B && !defined(D)
+let b = 1;
+#endif
+
+#if D
+let d = 1;
+#elif C == 16 && B * 2 == 6
+let c = 16;
+#elif 1
+let c = 1;
+#else
+let c = 0;
+#endif
+
+#if MAX_LIGHTS > 2 ? B : 0
+let lights = MAX_LIGHTS;
+#endif
+
+#ifdef A
+#if !defined(A)
+let nested = 0;
+#elif (B << 1) == 6 || (B % 2 != 1)
+let nested = 1;
+#endif
+#endif
+
+#if 0
+#if 1
+let skipped = 1;
+#else
+let skipped = 2;
+#endif
+#elif defined(B)
+let taken = B;
+#endif
@@ -0,0 +1,10 @@
+#define foo foo + 1
+#define PI (3.14)
+#define f(x) x * 2
+#define g f
+let a = foo;
+let b = PI;
+let c = f(3);
+let d = f;
+let e = g(4);
+let max = f(f(1));
@@ -0,0 +1,3 @@
+#pragma once
+#define ONCE 1
+let once = ONCE;
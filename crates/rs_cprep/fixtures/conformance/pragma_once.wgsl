@@ -0,0 +1,5 @@
+#include "once.wgsl"
+#include "once.wgsl"
+#include "guarded.wgsl"
+#include "guarded.wgsl"
+let main = ONCE + GUARDED;
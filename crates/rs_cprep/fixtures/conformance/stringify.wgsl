@@ -0,0 +1,7 @@
+#define STR(x) #x
+#define XSTR(x) STR(x)
+#define VALUE 42
+let a = STR(hello   world);
+let b = STR("quoted");
+let c = XSTR(VALUE);
+let d = STR(VALUE);
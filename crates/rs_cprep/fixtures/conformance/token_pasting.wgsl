@@ -0,0 +1,8 @@
+#define GLOBAL_CONSTANTS_GROUP 0
+#define GLOBAL_CONSTANTS_BINDING 1
+#define GROUP_BINDING(x) @group(x ## _GROUP) @binding(x ## _BINDING)
+#define CAT(a, b) a##b
+#define MAX(a, b) select(b, a, a > b)
+GROUP_BINDING(GLOBAL_CONSTANTS) var<uniform> constants: Constants;
+let CAT(value, 1) = MAX(1, 2);
+let max_value = MAX(CAT(value, 1), 3);
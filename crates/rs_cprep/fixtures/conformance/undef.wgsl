@@ -0,0 +1,11 @@
+#define SIZE 4
+let before = SIZE;
+#undef SIZE
+let after = SIZE;
+#ifdef SIZE
+let defined_size = 1;
+#else
+let undefined_size = 1;
+#endif
+#define SIZE 8
+let redefined = SIZE;
@@ -0,0 +1,8 @@
+#define CALL(f, ...) f(__VA_ARGS__)
+#define VEC(...) vec4<f32>(__VA_ARGS__)
+#define EMPTY()
+let a = CALL(max, 1.0, 2.0);
+let b = VEC(1.0, 2.0, 3.0, 4.0);
+let c = CALL(foo);
+let d = VEC((1.0, 2.0), 3.0);
+let e = 1 EMPTY() + 2;
@@ -15,6 +15,9 @@ pub enum TokType {
     #[token("elif")]   KwElif,
     #[token("else")]   KwElse,
     #[token("endif")]  KwEndif,
+    #[token("error")]  KwError,
+    #[token("warning")] KwWarning,
+    #[token("pragma")] KwPragma,
 
     #[token("defined")]  KwDefined,
 
@@ -96,10 +99,6 @@ impl<'a> Lexer<'a> {
         self.previous.push(last);
     }
 
-    pub fn last(&self) -> Option<&Token<'a>> {
-        self.previous.last()
-    }
-
     pub fn previous(&self) -> &[Token<'a>] {
         &self.previous
     }
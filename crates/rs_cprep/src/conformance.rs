@@ -0,0 +1,82 @@
+//! Compares the output of the fixtures in `fixtures/conformance` with a C preprocessor.
+//! The `.expected` files are the output of `clang -E -P -x c -std=c11 -I. <file>`,
+//! they are written by `regenerate_expected` after the fixtures are changed.
+//! `test_clang` compares with clang directly, both need clang and are ignored by default,
+//! run them with `cargo test -- --ignored`.
+//! The outputs are compared by tokens, since the blank lines and the spaces are different.
+
+use crate::processor::Preprocessor;
+use std::path::{Path, PathBuf};
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/conformance")
+}
+
+fn fixtures() -> Vec<PathBuf> {
+    let mut fixtures: Vec<PathBuf> = std::fs::read_dir(fixtures_dir())
+        .unwrap()
+        .filter_map(|x| x.ok().map(|x| x.path()))
+        .filter(|x| x.extension().is_some_and(|x| x == "expected"))
+        .map(|x| x.with_extension("wgsl"))
+        .collect();
+    fixtures.sort();
+    fixtures
+}
+
+fn tokens(code: &str) -> Vec<&str> {
+    let re = regex::Regex::new(r#""(?:[^"\\]|\\.)*"|[A-Za-z0-9_.]+|\S"#).unwrap();
+    re.find_iter(code).map(|x| x.as_str()).collect()
+}
+
+fn process(path: &Path) -> String {
+    let mut processor = Preprocessor::empty();
+    processor.add_include_dir(fixtures_dir().to_string_lossy().to_string());
+    processor
+        .process_file(path)
+        .unwrap_or_else(|err| panic!("{:?}: {}", path, err))
+}
+
+#[test]
+fn test_fixtures() {
+    let fixtures = fixtures();
+    assert!(!fixtures.is_empty());
+    for path in fixtures {
+        let expected = std::fs::read_to_string(path.with_extension("expected")).unwrap();
+        let contents = process(&path);
+        assert_eq!(tokens(&contents), tokens(&expected), "{:?}", path);
+    }
+}
+
+fn clang(path: &Path) -> String {
+    let output = std::process::Command::new("clang")
+        .args(["-E", "-P", "-x", "c", "-std=c11", "-I."])
+        .arg(path)
+        .current_dir(fixtures_dir())
+        .output()
+        .expect("clang is not installed");
+    assert!(output.status.success(), "{:?}", path);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+#[ignore = "needs clang"]
+fn test_clang() {
+    for path in fixtures() {
+        let expected = clang(&path);
+        let contents = process(&path);
+        assert_eq!(tokens(&contents), tokens(&expected), "{:?}", path);
+    }
+}
+
+/// Overwrites the `.expected` fixtures only if `RS_CPREP_BLESS=1`, so running the ignored tests
+/// doesn't silently accept a regression.
+#[test]
+#[ignore = "needs clang, writes the fixtures if RS_CPREP_BLESS=1"]
+fn regenerate_expected() {
+    if std::env::var("RS_CPREP_BLESS").as_deref() != Ok("1") {
+        return;
+    }
+    for path in fixtures() {
+        std::fs::write(path.with_extension("expected"), clang(&path)).unwrap();
+    }
+}
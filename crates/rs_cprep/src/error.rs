@@ -1,11 +1,38 @@
+use crate::source_map::SourceLocation;
+
 #[derive(Debug)]
 pub enum Error {
     Other(Option<String>),
+    IO(std::io::Error, Option<String>),
+    MissingFile(std::path::PathBuf),
+    MissingInclude(String, SourceLocation),
+    /// Raised by `#error`.
+    User(String, SourceLocation),
+    Syntax(String, SourceLocation),
+}
+
+impl Error {
+    pub fn location(&self) -> Option<&SourceLocation> {
+        match self {
+            Error::MissingInclude(_, location)
+            | Error::User(_, location)
+            | Error::Syntax(_, location) => Some(location),
+            Error::Other(_) | Error::IO(_, _) | Error::MissingFile(_) => None,
+        }
+    }
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(format!("{:?}", self).as_ref())
+        match self {
+            Error::MissingFile(path) => write!(f, "{:?} is not exist", path),
+            Error::MissingInclude(file, location) => {
+                write!(f, "{}: #include \"{}\" is not found", location, file)
+            }
+            Error::User(message, location) => write!(f, "{}: #error {}", location, message),
+            Error::Syntax(message, location) => write!(f, "{}: {}", location, message),
+            Error::Other(_) | Error::IO(_, _) => f.write_str(format!("{:?}", self).as_ref()),
+        }
     }
 }
 
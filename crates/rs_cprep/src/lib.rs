@@ -1,6 +1,9 @@
 mod c_lexer;
+#[cfg(test)]
+mod conformance;
 pub mod error;
 pub mod processor;
+pub mod source_map;
 
 lalrpop_util::lalrpop_mod!(pub(crate) pp_expr);
 
@@ -22,5 +25,11 @@ mod test {
         assert_eq!(parser.parse("(0 || 0)"), Ok(false));
         assert_eq!(parser.parse("((false) || false)"), Ok(false));
         assert!(parser.parse("((22)").is_err());
+        assert_eq!(parser.parse("0x10 == 16"), Ok(true));
+        assert_eq!(parser.parse("!!2"), Ok(true));
+        assert_eq!(parser.parse("-1 < 0 && ~0 == -1"), Ok(true));
+        assert_eq!(parser.parse("1 ? 0 : 1"), Ok(false));
+        assert_eq!(parser.parse("0 ? 0 : 1 ? 2 : 0"), Ok(true));
+        assert!(parser.parse("1 / 0").is_err());
     }
 }
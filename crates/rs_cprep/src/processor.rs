@@ -1,8 +1,10 @@
-use crate::c_lexer::TokType;
+use crate::{
+    c_lexer::TokType,
+    source_map::{Diagnostic, SourceLocation, SourceMap},
+};
 use std::{
     collections::{HashMap, HashSet},
     io::Read,
-    iter::zip,
 };
 
 #[derive(Clone, Debug)]
@@ -11,6 +13,8 @@ pub struct Definition {
     args: Vec<String>,
     content: String,
     stack: Vec<TokType>,
+    /// Decided by the token right after the name, a function-like macro may have no arguments.
+    is_function: Option<bool>,
 }
 
 impl Definition {
@@ -21,6 +25,7 @@ impl Definition {
             args: vec![],
             content: String::with_capacity(CAPACITY),
             stack: vec![],
+            is_function: None,
         }
     }
 
@@ -28,22 +33,39 @@ impl Definition {
         let (args, content) = Self::args(string);
         Self {
             name: Some(name),
+            is_function: Some(!args.is_empty()),
             args,
             content,
             stack: vec![],
         }
     }
 
-    fn push(&mut self, previous: &[crate::c_lexer::Token], token: &crate::c_lexer::Token) -> bool {
+    fn is_function(&self) -> bool {
+        self.is_function == Some(true)
+    }
+
+    fn is_variadic(&self) -> bool {
+        self.args.last().is_some_and(|x| x == "...")
+    }
+
+    fn push(
+        &mut self,
+        previous: &[crate::c_lexer::Token],
+        token: &crate::c_lexer::Token,
+    ) -> Result<bool, String> {
         let _ = previous;
         let mut is_stop = false;
         if self.name.is_none() {
             if token.str == " " {
-            } else {
-                assert_eq!(token.ty, TokType::Ident);
+            } else if token.ty == TokType::Ident {
                 self.name = Some(token.str.to_string());
+            } else {
+                return Err(format!("Expected a macro name, found \"{}\"", token.str));
             }
         } else {
+            if self.is_function.is_none() {
+                self.is_function = Some(token.ty == TokType::LParen);
+            }
             if token.ty == TokType::Newline {
                 if let Some(last) = self.stack.last() {
                     if *last == TokType::Newline {
@@ -62,12 +84,12 @@ impl Definition {
             self.stack.push(token.ty.clone());
         }
 
-        if is_stop {
+        if is_stop && self.is_function() {
             let (args, new_content) = Self::args(&self.content);
             self.args = args;
             self.content = new_content;
         }
-        is_stop
+        Ok(is_stop)
     }
 
     fn args(content: &str) -> (Vec<String>, String) {
@@ -78,8 +100,10 @@ impl Definition {
         if let Some(caps) = re.captures(content_trim.trim()) {
             new_content = content_trim[caps.get_match().end()..content_trim.len()].to_string();
             let args_content = &caps[0][1..caps[0].len() - 1];
-            for value in args_content.split(",") {
-                args.push(value.trim().to_string());
+            if !args_content.trim().is_empty() {
+                for value in args_content.split(",") {
+                    args.push(value.trim().to_string());
+                }
             }
         } else {
             new_content = content.to_string();
@@ -88,66 +112,199 @@ impl Definition {
     }
 }
 
+impl std::fmt::Display for Definition {
+    /// Formats the definition as it is written after `#define`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name.as_deref().unwrap_or_default())?;
+        if self.is_function() {
+            write!(f, "({})", self.args.join(", "))?;
+        }
+        let content = self.content.trim();
+        if !content.is_empty() {
+            write!(f, " {}", content)?;
+        }
+        Ok(())
+    }
+}
+
+enum ResolveResult {
+    Continue,
+    Done,
+    /// The name of a function-like macro is not followed by `(`.
+    NotInvocation,
+}
+
 struct ResolveDefinition {
     definition: Definition,
-    content: String,
-    args_content: String,
-    stack: Vec<()>,
+    args: Vec<String>,
+    current_arg: String,
+    depth: usize,
+    /// The spaces between the name and `(`.
+    skipped: String,
+    location: SourceLocation,
 }
 
 impl ResolveDefinition {
-    fn new(definition: Definition) -> Self {
+    fn new(definition: Definition, location: SourceLocation) -> Self {
         const CAPACITY: usize = 1 << 5;
         Self {
             definition,
-            content: String::with_capacity(CAPACITY),
-            args_content: String::with_capacity(CAPACITY),
-            stack: vec![],
+            args: vec![],
+            current_arg: String::with_capacity(CAPACITY),
+            depth: 0,
+            skipped: String::new(),
+            location,
         }
     }
 
-    fn push(&mut self, previous: &[crate::c_lexer::Token], token: &crate::c_lexer::Token) -> bool {
+    fn name(&self) -> &str {
+        self.definition.name.as_deref().unwrap_or_default()
+    }
+
+    fn push(
+        &mut self,
+        previous: &[crate::c_lexer::Token],
+        token: &crate::c_lexer::Token,
+    ) -> Result<ResolveResult, String> {
         let _ = previous;
-        let mut is_stop = false;
-        if self.definition.args.is_empty() {
-            is_stop = true;
-            self.content = self.definition.content.clone();
-        } else {
-            if self.stack.is_empty() {
-                if token.ty == TokType::Space {
-                } else if token.ty == TokType::LParen {
-                    self.stack.push(());
-                } else {
-                    panic!();
+        if self.depth == 0 {
+            return match token.ty {
+                TokType::Space | TokType::Newline => {
+                    self.skipped.push_str(token.str);
+                    Ok(ResolveResult::Continue)
                 }
-            } else if self.stack.len() == 1 {
-                if token.ty == TokType::RParen {
-                    is_stop = true;
-                    self.args_content = self.args_content.trim().to_string();
-                    let _ = validate(&self.args_content).unwrap();
-                    let mut args: Vec<String> = vec![];
-                    for value in self.args_content.split(",") {
-                        args.push(value.trim().to_string());
-                    }
-                    assert_eq!(args.len(), self.definition.args.len());
-                    let mut new_content = self.definition.content.clone();
-                    for (to, from) in zip(args.iter(), self.definition.args.iter()) {
-                        new_content = new_content.replace(from, to);
+                TokType::LParen => {
+                    self.depth = 1;
+                    Ok(ResolveResult::Continue)
+                }
+                _ => Ok(ResolveResult::NotInvocation),
+            };
+        }
+        match token.ty {
+            TokType::LParen => {
+                self.depth += 1;
+                self.current_arg.push_str(token.str);
+            }
+            TokType::RParen => {
+                self.depth -= 1;
+                if self.depth == 0 {
+                    self.args.push(std::mem::take(&mut self.current_arg));
+                    if self.definition.args.is_empty()
+                        && self.args.len() == 1
+                        && self.args[0].trim().is_empty()
+                    {
+                        self.args.clear();
                     }
-                    let re = regex::Regex::new(r"\s*##\s*").unwrap();
-                    new_content = re.replace_all(&new_content, "").to_string();
-                    self.content = new_content;
-                } else {
-                    self.args_content.push_str(token.str);
+                    return Ok(ResolveResult::Done);
                 }
-            } else {
-                panic!();
+                self.current_arg.push_str(token.str);
+            }
+            TokType::Other if token.str == "," && self.depth == 1 => {
+                self.args.push(std::mem::take(&mut self.current_arg));
+            }
+            TokType::Newline | TokType::Backslash => {
+                self.current_arg.push(' ');
+            }
+            _ => {
+                self.current_arg.push_str(token.str);
+            }
+        }
+        Ok(ResolveResult::Continue)
+    }
+
+    /// The arguments are expanded by `expand` before the substitution,
+    /// except the operands of `#` and `##`.
+    fn substitute(
+        &self,
+        expand: &mut impl FnMut(&str) -> crate::error::Result<String>,
+    ) -> crate::error::Result<String> {
+        let syntax_error = |err: String| crate::error::Error::Syntax(err, self.location.clone());
+        let params = &self.definition.args;
+        let is_variadic = self.definition.is_variadic();
+        let named = if is_variadic {
+            params.len() - 1
+        } else {
+            params.len()
+        };
+        if (is_variadic && self.args.len() < named) || (!is_variadic && self.args.len() != named) {
+            return Err(syntax_error(format!(
+                "\"{}\" expects {} arguments, found {}",
+                self.name(),
+                named,
+                self.args.len()
+            )));
+        }
+        let variadic = self
+            .args
+            .get(named..)
+            .map(|x| x.join(",").trim().to_string())
+            .unwrap_or_default();
+        let arg = |name: &str| -> Option<String> {
+            if is_variadic && name == "__VA_ARGS__" {
+                return Some(variadic.clone());
             }
+            params[..named]
+                .iter()
+                .position(|x| x == name)
+                .map(|i| self.args[i].trim().to_string())
+        };
+
+        let mut lexer = crate::c_lexer::Lexer::new(&self.definition.content);
+        let mut tokens = vec![];
+        while let Some(token) = lexer.next() {
+            tokens.push(token);
+        }
+        let is_visible = |i: &usize| !matches!(tokens[*i].ty, TokType::Space | TokType::Newline);
+        let next_visible = |i: usize| (i..tokens.len()).find(is_visible);
+        let previous_visible = |i: usize| (0..i).rev().find(is_visible);
+        let is_paste = |i: Option<usize>| {
+            i.is_some_and(|i| {
+                tokens[i].ty == TokType::Hash
+                    && (tokens.get(i + 1).is_some_and(|x| x.ty == TokType::Hash)
+                        || (i > 0 && tokens[i - 1].ty == TokType::Hash))
+            })
+        };
+        let mut content = String::with_capacity(self.definition.content.len());
+        let mut i = 0;
+        while i < tokens.len() {
+            let token = &tokens[i];
+            if token.ty == TokType::Hash {
+                if tokens.get(i + 1).is_some_and(|x| x.ty == TokType::Hash) {
+                    // Token pasting, the rescanning of the result concatenates the tokens.
+                    content.truncate(content.trim_end().len());
+                    i = next_visible(i + 2).unwrap_or(tokens.len());
+                    continue;
+                }
+                if let Some(next) = next_visible(i + 1)
+                    && let Some(value) = arg(tokens[next].str)
+                {
+                    content.push_str(&stringify(&value));
+                    i = next + 1;
+                    continue;
+                }
+                return Err(syntax_error(format!(
+                    "\"#\" is not followed by a macro parameter in \"{}\"",
+                    self.name()
+                )));
+            }
+            match arg(token.str).filter(|_| token.ty == TokType::Ident) {
+                Some(value) if is_paste(previous_visible(i)) || is_paste(next_visible(i + 1)) => {
+                    content.push_str(&value);
+                }
+                Some(value) => content.push_str(&expand(&value)?),
+                None => content.push_str(token.str),
+            }
+            i += 1;
         }
-        is_stop
+        Ok(content)
     }
 }
 
+fn stringify(value: &str) -> String {
+    let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
 #[derive(Clone, Debug)]
 struct BranchResult {
     is_stop: bool,
@@ -202,7 +359,7 @@ impl MacroBranch {
         macros: &HashMap<String, Definition>,
         previous: &[crate::c_lexer::Token],
         token: &crate::c_lexer::Token,
-    ) -> &BranchResult {
+    ) -> Result<&BranchResult, String> {
         let _ = previous;
         let branch_result = &mut self.branch_result;
         branch_result.is_stop = false;
@@ -212,7 +369,7 @@ impl MacroBranch {
             if token.ty == TokType::KwEndif {
                 branch_result.is_stop = true;
             }
-            return branch_result;
+            return Ok(branch_result);
         }
         match &token.ty {
             TokType::KwIf => {
@@ -225,46 +382,40 @@ impl MacroBranch {
                 unreachable!();
             }
             TokType::KwElif => {
-                if let Some(last) = previous.last() {
-                    if matches!(last.ty, crate::c_lexer::TokType::Hash) {
-                        if let Some(result) = self.expression.result {
-                            if result {
-                                self.is_expression_consume = false;
-                                self.expression.result = None;
-                            } else {
-                                self.is_expression_consume = true;
-                                self.expression.ty = TokType::KwElif;
-                                self.expression.expression.clear();
-                            }
+                if is_directive(previous) {
+                    if let Some(result) = self.expression.result {
+                        if result {
+                            self.is_expression_consume = false;
+                            self.expression.result = None;
+                        } else {
+                            self.is_expression_consume = true;
+                            self.expression.ty = TokType::KwElif;
+                            self.expression.expression.clear();
                         }
-
-                        branch_result.is_consume = true;
                     }
+
+                    branch_result.is_consume = true;
                 }
             }
             TokType::KwElse => {
-                if let Some(last) = previous.last() {
-                    if matches!(last.ty, crate::c_lexer::TokType::Hash) {
-                        if let Some(result) = self.expression.result {
-                            if result {
-                                self.expression.result = None;
-                            } else {
-                                self.expression.result = Some(true);
-                            }
+                if is_directive(previous) {
+                    if let Some(result) = self.expression.result {
+                        if result {
+                            self.expression.result = None;
+                        } else {
+                            self.expression.result = Some(true);
                         }
-                        self.is_expression_consume = false;
-                        self.expression.ty = TokType::KwElse;
-                        self.expression.expression.clear();
-                        branch_result.is_consume = true;
                     }
+                    self.is_expression_consume = false;
+                    self.expression.ty = TokType::KwElse;
+                    self.expression.expression.clear();
+                    branch_result.is_consume = true;
                 }
             }
             TokType::KwEndif => {
-                if let Some(last) = previous.last() {
-                    if matches!(last.ty, crate::c_lexer::TokType::Hash) {
-                        branch_result.is_stop = true;
-                        branch_result.is_consume = true;
-                    }
+                if is_directive(previous) {
+                    branch_result.is_stop = true;
+                    branch_result.is_consume = true;
                 }
             }
             TokType::Newline => {
@@ -275,7 +426,7 @@ impl MacroBranch {
                             let is_true = Self::resolve_no_args_definition(
                                 self.expression.expression.clone(),
                                 macros,
-                            );
+                            )?;
                             self.expression.result = Some(is_true);
                             self.is_expression_consume = false;
                         }
@@ -286,7 +437,7 @@ impl MacroBranch {
                             let is_true = Self::resolve_no_args_definition(
                                 self.expression.expression.clone(),
                                 macros,
-                            );
+                            )?;
                             self.expression.result = Some(is_true);
                             self.is_expression_consume = false;
                         }
@@ -342,24 +493,45 @@ impl MacroBranch {
             }
         }
 
-        branch_result
+        Ok(branch_result)
     }
 
     fn resolve_no_args_definition(
-        mut expression: String,
+        expression: String,
         macros: &HashMap<String, Definition>,
-    ) -> bool {
+    ) -> Result<bool, String> {
         let parser = crate::pp_expr::PPParser::new();
-        for (name, def) in macros {
-            assert!(def.args.is_empty());
-            let re = regex::Regex::new(&format!(r"\b{}\b", name)).unwrap();
-            expression = re.replace_all(&expression, &def.content).to_string();
-        }
-        let result = parser.parse(&expression);
-        if let Err(err) = result {
-            panic!("Error: {err}, expression: \"{expression}\"");
-        }
-        result.unwrap()
+        let defined = regex::Regex::new(
+            r"\bdefined\s*(?:\(\s*([A-Za-z_][A-Za-z0-9_]*)\s*\)|([A-Za-z_][A-Za-z0-9_]*))",
+        )
+        .unwrap();
+        let expression = defined.replace_all(&expression, |caps: &regex::Captures| {
+            let name = caps.get(1).or(caps.get(2)).expect("Valid").as_str();
+            if macros.contains_key(name) { "1" } else { "0" }
+        });
+
+        let mut macros = macros.clone();
+        let mut output = ProcessOutput::default();
+        process_internal(
+            &expression,
+            Origin::File(""),
+            &mut macros,
+            &mut |_| Ok(None),
+            &mut output,
+        )
+        .map_err(|err| err.to_string())?;
+
+        let suffix = regex::Regex::new(r"\b(0[xX][0-9A-Fa-f]+|[0-9]+)[uUlL]+\b").unwrap();
+        let expression = suffix.replace_all(&output.contents, "$1");
+        // The identifiers left after the expansion are zero.
+        let ident = regex::Regex::new(r"\b[A-Za-z_][A-Za-z0-9_]*\b").unwrap();
+        let expression = ident.replace_all(&expression, |caps: &regex::Captures| match &caps[0] {
+            "true" | "false" => caps[0].to_string(),
+            _ => "0".to_string(),
+        });
+        parser
+            .parse(&expression)
+            .map_err(|err| format!("{err}, expression: \"{}\"", expression.trim()))
     }
 }
 
@@ -380,84 +552,187 @@ impl MacroBranchStack {
         macros: &HashMap<String, Definition>,
         previous: &[crate::c_lexer::Token],
         token: &crate::c_lexer::Token,
-    ) -> BranchResult {
+    ) -> Result<BranchResult, String> {
         let mut branch_result = BranchResult::new();
         branch_result.is_stop = false;
         match &token.ty {
             TokType::KwIf | TokType::KwIfdef | TokType::KwIfndef => {
-                if let Some(last) = previous.last() {
-                    if matches!(last.ty, crate::c_lexer::TokType::Hash) {
-                        if let Some(last_mut) = self.stack.last_mut() {
-                            if let Some(result) = last_mut.expression.result {
-                                self.stack.push(MacroBranch::new(token.ty.clone(), !result));
-                            } else {
-                                self.stack.push(MacroBranch::new(token.ty.clone(), true));
-                            }
+                if is_directive(previous) {
+                    if let Some(last_mut) = self.stack.last_mut() {
+                        if let Some(result) = last_mut.expression.result {
+                            self.stack.push(MacroBranch::new(token.ty.clone(), !result));
                         } else {
-                            self.stack.push(MacroBranch::new(token.ty.clone(), false));
+                            self.stack.push(MacroBranch::new(token.ty.clone(), true));
                         }
-                        branch_result.is_consume = true;
                     } else {
-                        if let Some(last_mut) = self.stack.last_mut() {
-                            branch_result = last_mut.branch_result.clone();
-                        }
+                        self.stack.push(MacroBranch::new(token.ty.clone(), false));
+                    }
+                    branch_result.is_consume = true;
+                } else {
+                    if let Some(last_mut) = self.stack.last_mut() {
+                        branch_result = last_mut.branch_result.clone();
                     }
                 }
             }
 
             _ => {
                 if let Some(last_mut) = self.stack.last_mut() {
-                    branch_result = last_mut.push(macros, previous, token).clone();
+                    branch_result = last_mut.push(macros, previous, token)?.clone();
                     if branch_result.is_stop {
                         self.stack.remove(self.stack.len() - 1);
                     }
                 }
             }
         }
-        branch_result
+        Ok(branch_result)
     }
 }
 
-fn validate(input: &str) -> Result<(), String> {
-    let allowed = regex::Regex::new(r"^[A-Za-z,_\s]+$").unwrap();
-    if !allowed.is_match(input) {
-        return Err(format!("\"{input}\" Contains illegal characters"));
+/// Whether the previous tokens are `#` at the start of a line, spaces are allowed around it.
+fn is_directive(previous: &[crate::c_lexer::Token]) -> bool {
+    let mut tokens = previous.iter().rev().filter(|x| x.ty != TokType::Space);
+    match tokens.next() {
+        Some(token) if token.ty == TokType::Hash => {
+            matches!(
+                tokens.next(),
+                None | Some(crate::c_lexer::Token {
+                    ty: TokType::Newline,
+                    ..
+                })
+            )
+        }
+        _ => false,
     }
-    let re = regex::Regex::new(r"^\s*[A-Za-z_]+(?:,\s*[A-Za-z_]+)*$").unwrap();
-    if !re.is_match(input) {
-        if input.contains(' ') && !input.contains(',') {
-            return Err(format!("\"{input}\" Missing comma"));
-        } else {
-            return Err(format!("\"{input}\" Format error"));
+}
+
+/// The processed contents with the mapping of every output line back to the source.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessOutput {
+    pub contents: String,
+    pub source_map: SourceMap,
+    /// Collected from `#warning`.
+    pub warnings: Vec<Diagnostic>,
+    /// The names of all included files in the order of the first inclusion.
+    pub includes: Vec<String>,
+    is_line_blank: bool,
+    pragma_once: HashSet<String>,
+    expanding: Vec<String>,
+}
+
+impl ProcessOutput {
+    fn push(&mut self, text: &str, location: &SourceLocation) {
+        for segment in text.split_inclusive('\n') {
+            let line_start = self.contents.rfind('\n').map(|x| x + 1).unwrap_or(0);
+            let is_blank = segment.trim().is_empty();
+            if line_start == self.contents.len() {
+                self.source_map.push(location.clone());
+                self.is_line_blank = true;
+            } else if self.is_line_blank && !is_blank {
+                // The leading spaces belong to the directive, maps the line to the first visible text.
+                let prefix_len = self.contents.len() - line_start;
+                if let Some(last) = self.source_map.last_mut() {
+                    *last = SourceLocation {
+                        file: location.file.clone(),
+                        line: location.line,
+                        // May be 0 if the text is at the start of its line.
+                        column: location.column.saturating_sub(prefix_len),
+                    };
+                }
+            }
+            if !is_blank {
+                self.is_line_blank = false;
+            }
+            self.contents.push_str(segment);
+        }
+    }
+}
+
+/// Where the processed code comes from.
+#[derive(Clone, Copy)]
+enum Origin<'a> {
+    File(&'a str),
+    /// The contents of a macro, every line maps to where the macro is used.
+    Expansion(&'a SourceLocation),
+}
+
+struct LineIndex<'a> {
+    origin: Origin<'a>,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    fn new(code: &str, origin: Origin<'a>) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(code.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            origin,
+            line_starts,
+        }
+    }
+
+    fn location(&self, offset: usize) -> SourceLocation {
+        match self.origin {
+            Origin::File(file) => {
+                let line = self.line_starts.partition_point(|x| *x <= offset) - 1;
+                SourceLocation {
+                    file: file.to_string(),
+                    line: line + 1,
+                    column: offset - self.line_starts[line] + 1,
+                }
+            }
+            Origin::Expansion(location) => location.clone(),
         }
     }
-    Ok(())
 }
 
 pub fn process_simple(
     code: &str,
     custom_include: &mut impl FnMut(&str) -> Box<dyn std::io::Read>,
-) -> String {
+) -> crate::error::Result<String> {
     let mut macros: HashMap<String, Definition> = HashMap::new();
-    let contents = process_internal(code, &mut macros, custom_include);
-    contents
+    let mut output = ProcessOutput::default();
+    process_internal(
+        code,
+        Origin::File(""),
+        &mut macros,
+        &mut |file| {
+            let mut file_contents = String::new();
+            custom_include(file)
+                .read_to_string(&mut file_contents)
+                .map_err(|err| crate::error::Error::IO(err, Some(file.to_string())))?;
+            Ok(Some((file.to_string(), file_contents)))
+        },
+        &mut output,
+    )?;
+    Ok(output.contents)
 }
 
+/// `custom_include` returns the name and the contents of the file, or none if the file is not found.
+/// A function-like macro name at the end of an expansion is returned to take the arguments from the following code.
 fn process_internal(
     code: &str,
+    origin: Origin,
     macros: &mut HashMap<String, Definition>,
-    custom_include: &mut impl FnMut(&str) -> Box<dyn std::io::Read>,
-) -> String {
+    custom_include: &mut impl FnMut(&str) -> crate::error::Result<Option<(String, String)>>,
+    output: &mut ProcessOutput,
+) -> crate::error::Result<Option<ResolveDefinition>> {
+    let line_index = LineIndex::new(code, origin);
     let mut lexer = crate::c_lexer::Lexer::new(code);
-    let mut contents = String::with_capacity(code.len() * 2);
+    output.contents.reserve(code.len() * 2);
     let mut include_state: bool = false;
     let mut define_definition: Option<Definition> = None;
     let mut resolve_definition: Option<ResolveDefinition> = None;
+    let mut directive: Option<(TokType, String, SourceLocation)> = None;
 
     let mut macro_branch_stack: MacroBranchStack = MacroBranchStack::new();
 
     while let Some(item) = lexer.next() {
-        let branch_result = macro_branch_stack.push(macros, lexer.previous(), &item);
+        let location = line_index.location(item.span.start);
+        let syntax_error = |err: String| crate::error::Error::Syntax(err, location.clone());
+        let branch_result = macro_branch_stack
+            .push(macros, lexer.previous(), &item)
+            .map_err(syntax_error)?;
 
         if branch_result.is_consume {
             lexer.enqueue_last(item);
@@ -468,7 +743,9 @@ fn process_internal(
             panic!();
         }
         if let Some(define_definition_mut) = &mut define_definition {
-            let is_stop = define_definition_mut.push(lexer.previous(), &item);
+            let is_stop = define_definition_mut
+                .push(lexer.previous(), &item)
+                .map_err(syntax_error)?;
             if is_stop {
                 let name = define_definition_mut.name.as_ref().expect("Valid");
                 let _ = macros.insert(name.clone(), define_definition_mut.clone());
@@ -479,53 +756,122 @@ fn process_internal(
             }
         }
         if let Some(resolve_definition_mut) = &mut resolve_definition {
-            let is_stop = resolve_definition_mut.push(lexer.previous(), &item);
-            if is_stop {
-                let new_contents =
-                    process_internal(&resolve_definition_mut.content, macros, custom_include);
-                contents.push_str(&new_contents);
-                resolve_definition = None;
-                lexer.enqueue_last(item);
-                continue;
-            } else {
+            let result = resolve_definition_mut
+                .push(lexer.previous(), &item)
+                .map_err(syntax_error)?;
+            match result {
+                ResolveResult::Continue => {
+                    lexer.enqueue_last(item);
+                    continue;
+                }
+                ResolveResult::Done => {
+                    let resolve_definition_mut = resolve_definition.take().expect("Valid");
+                    let content = resolve_definition_mut.substitute(&mut |arg| {
+                        expand_argument(
+                            arg,
+                            &resolve_definition_mut.location,
+                            macros,
+                            custom_include,
+                            output,
+                        )
+                    })?;
+                    resolve_definition = expand(
+                        resolve_definition_mut.name(),
+                        &content,
+                        &resolve_definition_mut.location,
+                        macros,
+                        custom_include,
+                        output,
+                    )?;
+                    lexer.enqueue_last(item);
+                    continue;
+                }
+                ResolveResult::NotInvocation => {
+                    let resolve_definition_mut = resolve_definition.take().expect("Valid");
+                    output.push(
+                        resolve_definition_mut.name(),
+                        &resolve_definition_mut.location,
+                    );
+                    output.push(
+                        &resolve_definition_mut.skipped,
+                        &resolve_definition_mut.location,
+                    );
+                }
+            }
+        }
+        if let Some((_, text, _)) = &mut directive {
+            if item.ty != TokType::Newline {
+                text.push_str(item.str);
                 lexer.enqueue_last(item);
                 continue;
             }
+            finish_directive(directive.take().expect("Valid"), origin, macros, output)?;
         }
 
         match item.ty {
             crate::c_lexer::TokType::KwDefine => {
-                if let Some(last) = lexer.last() {
-                    if matches!(last.ty, crate::c_lexer::TokType::Hash) {
-                        define_definition = Some(Definition::new());
-                    }
+                if is_directive(lexer.previous()) {
+                    define_definition = Some(Definition::new());
+                } else {
+                    output.push(item.str, &location);
                 }
             }
-            crate::c_lexer::TokType::KwUndef => {
-                unimplemented!();
-            }
             crate::c_lexer::TokType::KwInclude => {
-                if let Some(last) = lexer.last() {
-                    if matches!(last.ty, crate::c_lexer::TokType::Hash) {
-                        include_state = true;
-                    }
+                if is_directive(lexer.previous()) {
+                    include_state = true;
+                } else {
+                    output.push(item.str, &location);
                 }
             }
-            crate::c_lexer::TokType::Ident => {
-                if let Some(def) = macros.get(item.str) {
-                    if def.args.is_empty() {
-                        let content = def.content.clone();
-                        let new_contents = process_internal(&content, macros, custom_include);
-                        contents.push_str(&new_contents);
-                    } else {
-                        resolve_definition = Some(ResolveDefinition::new(def.clone()));
-                    }
+            crate::c_lexer::TokType::KwUndef
+            | crate::c_lexer::TokType::KwError
+            | crate::c_lexer::TokType::KwWarning
+            | crate::c_lexer::TokType::KwPragma => {
+                if is_directive(lexer.previous()) {
+                    directive = Some((item.ty.clone(), String::new(), location));
                 } else {
-                    contents.push_str(item.str);
+                    output.push(item.str, &location);
                 }
             }
+            crate::c_lexer::TokType::KwIfdef
+            | crate::c_lexer::TokType::KwIfndef
+            | crate::c_lexer::TokType::KwElif
+            | crate::c_lexer::TokType::KwEndif
+            | crate::c_lexer::TokType::KwElse => {
+                if is_directive(lexer.previous()) {
+                    return Err(crate::error::Error::Syntax(
+                        format!("#{} without #if", item.str),
+                        location,
+                    ));
+                }
+                output.push(item.str, &location);
+            }
+            crate::c_lexer::TokType::Ident => match item.str {
+                "__LINE__" => output.push(&location.line.to_string(), &location),
+                "__FILE__" => output.push(&stringify(&location.file), &location),
+                _ => match macros.get(item.str) {
+                    Some(_) if output.expanding.iter().any(|x| x == item.str) => {
+                        output.push(item.str, &location);
+                    }
+                    Some(def) if def.is_function() => {
+                        resolve_definition = Some(ResolveDefinition::new(def.clone(), location));
+                    }
+                    Some(def) => {
+                        let content = def.content.clone();
+                        resolve_definition = expand(
+                            item.str,
+                            &content,
+                            &location,
+                            macros,
+                            custom_include,
+                            output,
+                        )?;
+                    }
+                    None => output.push(item.str, &location),
+                },
+            },
             crate::c_lexer::TokType::Integer => {
-                contents.push_str(item.str);
+                output.push(item.str, &location);
             }
             crate::c_lexer::TokType::String => {
                 if include_state {
@@ -534,43 +880,146 @@ fn process_internal(
                         if let Some(captures) = re.captures(item.str) {
                             if captures.len() == 2 {
                                 let capture = &captures[1];
-                                let mut reader = custom_include(capture);
-                                let mut file_contents = String::new();
-                                let _ = reader.read_to_string(&mut file_contents).expect("Valid");
-                                file_contents =
-                                    process_internal(&file_contents, macros, custom_include);
-                                contents.push_str(&file_contents);
+                                let Some((file, file_contents)) = custom_include(capture)? else {
+                                    return Err(crate::error::Error::MissingInclude(
+                                        capture.to_string(),
+                                        location,
+                                    ));
+                                };
+                                if !output.includes.contains(&file) {
+                                    output.includes.push(file.clone());
+                                }
+                                if !output.pragma_once.contains(&file) {
+                                    process_internal(
+                                        &file_contents,
+                                        Origin::File(&file),
+                                        macros,
+                                        custom_include,
+                                        output,
+                                    )?;
+                                }
                             }
                         }
                     }
                     include_state = false;
                 } else {
-                    contents.push_str(item.str);
+                    output.push(item.str, &location);
                 }
             }
             crate::c_lexer::TokType::Hash
-            | crate::c_lexer::TokType::KwIfdef
-            | crate::c_lexer::TokType::KwIfndef
-            | crate::c_lexer::TokType::KwElif
-            | crate::c_lexer::TokType::KwEndif
             | crate::c_lexer::TokType::KwDefined
             | crate::c_lexer::TokType::Backslash => {}
             crate::c_lexer::TokType::LParen
             | crate::c_lexer::TokType::KwIf
-            | crate::c_lexer::TokType::KwElse
             | crate::c_lexer::TokType::RParen
             | crate::c_lexer::TokType::Other
             | crate::c_lexer::TokType::Newline
             | crate::c_lexer::TokType::Space => {
-                contents.push_str(item.str);
+                output.push(item.str, &location);
             }
         }
         lexer.enqueue_last(item);
     }
 
-    contents
+    if let Some(resolve_definition) = resolve_definition {
+        if resolve_definition.depth == 0 && matches!(origin, Origin::Expansion(_)) {
+            if let Some(directive) = directive {
+                finish_directive(directive, origin, macros, output)?;
+            }
+            return Ok(Some(resolve_definition));
+        }
+        if resolve_definition.depth > 0 {
+            return Err(crate::error::Error::Syntax(
+                format!(
+                    "Unterminated argument list invoking \"{}\"",
+                    resolve_definition.name()
+                ),
+                resolve_definition.location,
+            ));
+        }
+        output.push(resolve_definition.name(), &resolve_definition.location);
+        output.push(&resolve_definition.skipped, &resolve_definition.location);
+    }
+    if let Some(directive) = directive {
+        finish_directive(directive, origin, macros, output)?;
+    }
+
+    Ok(None)
+}
+
+/// The macro is not expanded again inside its own expansion.
+fn expand(
+    name: &str,
+    content: &str,
+    location: &SourceLocation,
+    macros: &mut HashMap<String, Definition>,
+    custom_include: &mut impl FnMut(&str) -> crate::error::Result<Option<(String, String)>>,
+    output: &mut ProcessOutput,
+) -> crate::error::Result<Option<ResolveDefinition>> {
+    output.expanding.push(name.to_string());
+    let result = process_internal(
+        content,
+        Origin::Expansion(location),
+        macros,
+        custom_include,
+        output,
+    );
+    output.expanding.pop();
+    result
+}
+
+fn expand_argument(
+    arg: &str,
+    location: &SourceLocation,
+    macros: &mut HashMap<String, Definition>,
+    custom_include: &mut impl FnMut(&str) -> crate::error::Result<Option<(String, String)>>,
+    output: &ProcessOutput,
+) -> crate::error::Result<String> {
+    let mut argument_output = ProcessOutput {
+        expanding: output.expanding.clone(),
+        ..Default::default()
+    };
+    if let Some(resolve_definition) = process_internal(
+        arg,
+        Origin::Expansion(location),
+        macros,
+        custom_include,
+        &mut argument_output,
+    )? {
+        argument_output.push(resolve_definition.name(), location);
+        argument_output.push(&resolve_definition.skipped, location);
+    }
+    Ok(argument_output.contents)
+}
+
+fn finish_directive(
+    (ty, text, location): (TokType, String, SourceLocation),
+    origin: Origin,
+    macros: &mut HashMap<String, Definition>,
+    output: &mut ProcessOutput,
+) -> crate::error::Result<()> {
+    let text = text.trim().to_string();
+    match ty {
+        TokType::KwError => return Err(crate::error::Error::User(text, location)),
+        TokType::KwWarning => output.warnings.push(Diagnostic {
+            location,
+            message: text,
+        }),
+        TokType::KwUndef => {
+            macros.remove(&text);
+        }
+        TokType::KwPragma => match origin {
+            Origin::File(file) if text == "once" => {
+                output.pragma_once.insert(file.to_string());
+            }
+            _ => output.push(&format!("#pragma {}", text), &location),
+        },
+        _ => unreachable!(),
+    }
+    Ok(())
 }
 
+#[derive(Clone, Debug)]
 pub struct Preprocessor {
     include_dirs: HashSet<String>,
     defines: HashMap<String, Definition>,
@@ -603,42 +1052,106 @@ impl Preprocessor {
             .insert(name.to_string(), Definition::input(name.to_string(), value));
     }
 
+    /// The definition of the macro, formatted as it is written after `#define`.
+    pub fn get_define(&self, name: &str) -> Option<String> {
+        self.defines.get(name).map(|x| x.to_string())
+    }
+
+    pub fn get_define_names(&self) -> impl Iterator<Item = &str> {
+        self.defines.keys().map(|x| x.as_str())
+    }
+
+    /// Expands the macros in `contents` without changing the definitions.
+    pub fn expand(&self, contents: &str) -> crate::error::Result<String> {
+        self.clone().process(contents)
+    }
+
     pub fn process(&mut self, contents: &str) -> crate::error::Result<String> {
-        let contents = process_internal(contents, &mut self.defines, &mut |file| {
-            let include_dirs = &self.include_dirs;
-            for dir in include_dirs {
-                let path = std::path::Path::new(dir).join(file);
-                if path.exists() {
-                    let file_contents =
-                        std::fs::read_to_string(&path).expect(&format!("{:?}", &path));
-                    let buffer = file_contents.as_bytes().to_vec();
-                    let buff = std::io::Cursor::new(buffer);
-                    return Box::new(buff);
+        Ok(self.process_with_source_map("", contents)?.contents)
+    }
+
+    /// `name` is the file name used by the source map and the errors.
+    pub fn process_with_source_map(
+        &mut self,
+        name: &str,
+        contents: &str,
+    ) -> crate::error::Result<ProcessOutput> {
+        let include_dirs = &self.include_dirs;
+        let mut output = ProcessOutput::default();
+        process_internal(
+            contents,
+            Origin::File(name),
+            &mut self.defines,
+            &mut |file| {
+                for dir in include_dirs {
+                    let path = std::path::Path::new(dir).join(file);
+                    if path.exists() {
+                        let file_contents = std::fs::read_to_string(&path).map_err(|err| {
+                            crate::error::Error::IO(err, Some(format!("{:?}", &path)))
+                        })?;
+                        return Ok(Some((path.to_string_lossy().to_string(), file_contents)));
+                    }
                 }
-            }
-            panic!("#include \"{}\"", file);
-        });
-        Ok(contents)
+                Ok(None)
+            },
+            &mut output,
+        )?;
+        Ok(output)
     }
 
     pub fn process_file<P>(&mut self, path: P) -> crate::error::Result<String>
     where
         P: AsRef<std::path::Path>,
     {
-        if path.as_ref().exists() {
-            let file_contents =
-                std::fs::read_to_string(&path).expect(&format!("{:?}", path.as_ref()));
-            return self.process(&file_contents);
-        } else {
-            panic!();
+        Ok(self.process_file_with_source_map(path)?.contents)
+    }
+
+    pub fn process_file_with_source_map<P>(
+        &mut self,
+        path: P,
+    ) -> crate::error::Result<ProcessOutput>
+    where
+        P: AsRef<std::path::Path>,
+    {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(crate::error::Error::MissingFile(path.to_path_buf()));
         }
+        let file_contents = std::fs::read_to_string(path)
+            .map_err(|err| crate::error::Error::IO(err, Some(format!("{:?}", path))))?;
+        self.process_with_source_map(&path.to_string_lossy(), &file_contents)
     }
 }
 
 #[cfg(test)]
 pub mod test {
-    use crate::processor::{Definition, MacroBranch, process_simple};
-    use std::collections::HashMap;
+    use crate::processor::{
+        Definition, MacroBranch, Origin, Preprocessor, ProcessOutput, process_simple,
+    };
+    use std::{collections::HashMap, io::Read};
+
+    fn process_internal(
+        code: &str,
+        macros: &mut HashMap<String, Definition>,
+        custom_include: &mut impl FnMut(&str) -> Box<dyn std::io::Read>,
+    ) -> String {
+        let mut output = ProcessOutput::default();
+        crate::processor::process_internal(
+            code,
+            Origin::File(""),
+            macros,
+            &mut |file| {
+                let mut file_contents = String::new();
+                custom_include(file)
+                    .read_to_string(&mut file_contents)
+                    .unwrap();
+                Ok(Some((file.to_string(), file_contents)))
+            },
+            &mut output,
+        )
+        .unwrap();
+        output.contents
+    }
 
     fn process_include(file: &str) -> Box<dyn std::io::Read> {
         let _ = file;
@@ -670,7 +1183,7 @@ GROUP_BINDING(    GLOBAL_CONSTANTS    )";
             format!("MAX_BONES"),
             Definition::input(format!("MAX_BONES"), "1"),
         );
-        let contents = crate::processor::process_internal(code, &mut macros, &mut |_| {
+        let contents = process_internal(code, &mut macros, &mut |_| {
             todo!();
         });
         assert_eq!(
@@ -693,7 +1206,7 @@ GROUP_BINDING(    GLOBAL_CONSTANTS    )";
             format!("GLOBAL_CONSTANTS_BINDING"),
             Definition::input(format!("GLOBAL_CONSTANTS_BINDING"), "2"),
         );
-        let contents = crate::processor::process_internal(code, &mut macros, &mut |_| {
+        let contents = process_internal(code, &mut macros, &mut |_| {
             todo!();
         });
         assert_eq!(contents, "\n @group(1) @binding(2)");
@@ -709,7 +1222,7 @@ GROUP_BINDING(    GLOBAL_CONSTANTS    )";
 #include \"1\"";
 
         let mut macros: HashMap<String, Definition> = HashMap::new();
-        let contents = crate::processor::process_internal(code, &mut macros, &mut process_include);
+        let contents = process_internal(code, &mut macros, &mut process_include);
         assert_eq!(contents, " \n\nB\n\n \n \n \n \n ");
         assert_eq!(macros.get("A").is_some(), true);
         assert_eq!(macros.get("A").unwrap().content, "");
@@ -727,24 +1240,12 @@ qwe
             format!("MAX_BONES"),
             Definition::input(format!("MAX_BONES"), "1"),
         );
-        let contents = crate::processor::process_internal(code, &mut macros, &mut |_| {
+        let contents = process_internal(code, &mut macros, &mut |_| {
             todo!();
         });
         assert_eq!(contents, "\n\n 1\nqwe\n");
     }
 
-    #[test]
-    fn test1() {
-        assert!(crate::processor::validate("x y").is_err());
-        assert!(crate::processor::validate("x, y").is_ok());
-        assert!(crate::processor::validate(" x, y").is_ok());
-        assert!(crate::processor::validate("xa io, pw").is_err());
-        assert!(crate::processor::validate("xa, io, pw").is_ok());
-        assert!(crate::processor::validate("pw1, io").is_err());
-        assert!(crate::processor::validate("").is_err());
-        assert!(crate::processor::validate("a").is_ok());
-    }
-
     #[test]
     fn test3() {
         let content = "    (                              def, y )ghi(jkl)";
@@ -797,7 +1298,8 @@ qwe
 
         let contents = process_simple(code, &mut |_| {
             todo!();
-        });
+        })
+        .unwrap();
         assert_eq!(contents, "\n    \n        c\n    \n");
     }
 
@@ -814,7 +1316,8 @@ qwe
 #endif";
         let contents = process_simple(code, &mut |_| {
             todo!();
-        });
+        })
+        .unwrap();
         assert_eq!("\n    b\n", contents);
     }
 
@@ -825,7 +1328,7 @@ a
 #endif";
         let mut macros: HashMap<String, Definition> = HashMap::new();
         macros.insert(format!("MAX"), Definition::input(format!("MAX"), "1"));
-        let contents = crate::processor::process_internal(code, &mut macros, &mut |_| {
+        let contents = process_internal(code, &mut macros, &mut |_| {
             todo!();
         });
         assert_eq!(contents, "\na\n");
@@ -840,7 +1343,7 @@ b
 #endif";
         let mut macros: HashMap<String, Definition> = HashMap::new();
         macros.insert(format!("MAX"), Definition::input(format!("MAX"), "1"));
-        let contents = crate::processor::process_internal(code, &mut macros, &mut |_| {
+        let contents = process_internal(code, &mut macros, &mut |_| {
             todo!();
         });
         assert_eq!(contents, "\nb\n");
@@ -855,19 +1358,19 @@ b
         );
         assert_eq!(
             MacroBranch::resolve_no_args_definition("MAX".to_string(), &macros),
-            true
+            Ok(true)
         );
         assert_eq!(
             MacroBranch::resolve_no_args_definition(" MAX ".to_string(), &macros),
-            true
+            Ok(true)
         );
         assert_eq!(
             MacroBranch::resolve_no_args_definition(" MAX".to_string(), &macros),
-            true
+            Ok(true)
         );
         assert_eq!(
             MacroBranch::resolve_no_args_definition(" MAX && 0".to_string(), &macros),
-            false
+            Ok(false)
         );
     }
 
@@ -875,7 +1378,7 @@ b
     fn test14() {
         let code = "if";
         let mut macros: HashMap<String, Definition> = HashMap::new();
-        let contents = crate::processor::process_internal(code, &mut macros, &mut |_| {
+        let contents = process_internal(code, &mut macros, &mut |_| {
             todo!();
         });
         assert_eq!(contents, "if");
@@ -889,7 +1392,7 @@ b
     return 0.0;
 }";
         let mut macros: HashMap<String, Definition> = HashMap::new();
-        let contents = crate::processor::process_internal(code, &mut macros, &mut |_| {
+        let contents = process_internal(code, &mut macros, &mut |_| {
             todo!();
         });
         assert_eq!(
@@ -907,7 +1410,7 @@ if true {
 }
 #endif";
         let mut macros: HashMap<String, Definition> = HashMap::new();
-        let contents = crate::processor::process_internal(code, &mut macros, &mut |_| {
+        let contents = process_internal(code, &mut macros, &mut |_| {
             todo!();
         });
         assert_eq!("\n\nif true {\n} else {\n}\n", contents);
@@ -918,7 +1421,7 @@ if true {
         let code = "#include \"A\"
 #include \"A\"";
         let mut macros: HashMap<String, Definition> = HashMap::new();
-        let contents = crate::processor::process_internal(code, &mut macros, &mut |_| {
+        let contents = process_internal(code, &mut macros, &mut |_| {
             let contents = "#ifndef B
 #define B
 if 1 {
@@ -941,9 +1444,95 @@ if 1 {
 }
 #endif";
         let mut macros: HashMap<String, Definition> = HashMap::new();
-        let contents = crate::processor::process_internal(code, &mut macros, &mut |_| {
+        let contents = process_internal(code, &mut macros, &mut |_| {
             panic!();
         });
         assert_eq!("\n", contents);
     }
+
+    #[test]
+    fn test_source_map() {
+        let dir = std::env::temp_dir().join("rs_cprep_test_source_map");
+        let _ = std::fs::create_dir_all(&dir);
+        std::fs::write(dir.join("a.wgsl"), "#define C 1\nlet a = C;\n").unwrap();
+        let code = "#include \"a.wgsl\"
+#ifdef B
+let b = 0;
+#endif
+  let c = 2;";
+        let mut processor = Preprocessor::empty();
+        processor.add_include_dir(dir.to_string_lossy().to_string());
+        let output = processor
+            .process_with_source_map("main.wgsl", code)
+            .unwrap();
+        let lines: Vec<&str> = output.contents.split('\n').collect();
+        let a = dir.join("a.wgsl").to_string_lossy().to_string();
+        let c = lines.iter().position(|x| x.contains("let c")).unwrap() + 1;
+        let location = output.source_map.get(c).unwrap();
+        assert_eq!((location.file.as_str(), location.line), ("main.wgsl", 5));
+        assert_eq!(output.source_map.resolve(c, 3).unwrap().column, 3);
+        let a_line = lines.iter().position(|x| x.contains("let a")).unwrap() + 1;
+        assert_eq!(lines[a_line - 1], "let a =  1;");
+        let location = output.source_map.resolve(a_line, 1).unwrap();
+        assert_eq!((location.file.as_str(), location.line), (a.as_str(), 2));
+        assert_eq!(location.column, 1);
+        assert_eq!(output.source_map.len(), lines.len());
+        assert_eq!(output.includes, vec![a]);
+    }
+
+    #[test]
+    fn test_define() {
+        let mut processor = Preprocessor::empty();
+        processor.add_define("A", "2");
+        processor
+            .process("#define B (A + 1)\n#define ADD(x, y) x + y\n#define C\n")
+            .unwrap();
+        assert_eq!(processor.get_define("B").unwrap(), "B (A + 1)");
+        assert_eq!(processor.get_define("ADD").unwrap(), "ADD(x, y) x + y");
+        assert_eq!(processor.get_define("C").unwrap(), "C");
+        assert_eq!(processor.get_define("D"), None);
+        let mut names: Vec<&str> = processor.get_define_names().collect();
+        names.sort();
+        assert_eq!(names, vec!["A", "ADD", "B", "C"]);
+        assert_eq!(processor.expand("B").unwrap().trim(), "(2 + 1)");
+        assert_eq!(processor.expand("#define E 1").unwrap().trim(), "");
+        assert_eq!(processor.get_define("E"), None);
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let mut processor = Preprocessor::empty();
+        let err = processor
+            .process_with_source_map("main.wgsl", "a\n  #include \"missing.wgsl\"")
+            .unwrap_err();
+        assert!(
+            matches!(err, crate::error::Error::MissingInclude(ref file, _) if file == "missing.wgsl")
+        );
+        let location = err.location().unwrap();
+        assert_eq!((location.line, location.column), (2, 12));
+
+        let code = "#ifdef A
+#error A is not supported
+#endif
+#warning Deprecated
+error";
+        let output = processor
+            .process_with_source_map("main.wgsl", code)
+            .unwrap();
+        assert_eq!(output.contents, "\n\nerror");
+        assert_eq!(output.warnings.len(), 1);
+        assert_eq!(output.warnings[0].message, "Deprecated");
+        assert_eq!(output.warnings[0].location.line, 4);
+
+        processor.add_define("A", "");
+        let err = processor
+            .process_with_source_map("main.wgsl", code)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "main.wgsl:2:2: #error A is not supported");
+
+        assert!(matches!(
+            processor.process_file("missing.wgsl"),
+            Err(crate::error::Error::MissingFile(_))
+        ));
+    }
 }
@@ -0,0 +1,69 @@
+/// A position in the source files, the line and column are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl std::fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub location: SourceLocation,
+    pub message: String,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// Maps every line of the processed contents back to the source file and line.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    lines: Vec<SourceLocation>,
+}
+
+impl SourceMap {
+    pub(crate) fn push(&mut self, location: SourceLocation) {
+        self.lines.push(location);
+    }
+
+    pub(crate) fn last_mut(&mut self) -> Option<&mut SourceLocation> {
+        self.lines.last_mut()
+    }
+
+    /// `line` is 1-based.
+    pub fn get(&self, line: usize) -> Option<&SourceLocation> {
+        self.lines.get(line.checked_sub(1)?)
+    }
+
+    /// Maps the 1-based line and column of the processed contents to the source.
+    /// The column is exact unless a macro is expanded before it on the same line.
+    pub fn resolve(&self, line: usize, column: usize) -> Option<SourceLocation> {
+        let location = self.get(line)?;
+        Some(SourceLocation {
+            file: location.file.clone(),
+            line: location.line,
+            column: location.column + column.saturating_sub(1),
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SourceLocation> {
+        self.lines.iter()
+    }
+}
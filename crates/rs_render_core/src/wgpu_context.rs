@@ -273,6 +273,10 @@ impl WGPUContext {
         &self.queue
     }
 
+    pub fn get_adapter_info(&self) -> wgpu::AdapterInfo {
+        self.adapter.get_info()
+    }
+
     pub fn remove_window(&mut self, window_id: isize) {
         self.surfaces.remove(&window_id);
     }
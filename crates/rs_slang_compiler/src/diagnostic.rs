@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ESeverity {
+    Error,
+    Warning,
+    Note,
+}
+
+/// A problem reported by slangc, such as `main.slang(3): error 30015: undefined identifier 'c'.`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    /// One-based.
+    pub line: usize,
+    /// One-based, only known if slangc points the caret at the source.
+    pub column: Option<usize>,
+    pub severity: ESeverity,
+    pub code: Option<u32>,
+    pub message: String,
+}
+
+fn parse_header(line: &str) -> Option<Diagnostic> {
+    let (location, rest) = line.split_once("): ")?;
+    let (path, line_number) = location.rsplit_once('(')?;
+    let line_number = line_number.parse().ok()?;
+    let (kind, message) = rest.split_once(": ")?;
+    let (severity, code) = match kind.rsplit_once(' ') {
+        Some((severity, code)) if code.parse::<u32>().is_ok() => (severity, code.parse().ok()),
+        _ => (kind, None),
+    };
+    let severity = match severity {
+        "error" | "fatal error" | "internal error" => ESeverity::Error,
+        "warning" => ESeverity::Warning,
+        "note" => ESeverity::Note,
+        _ => return None,
+    };
+    Some(Diagnostic {
+        path: PathBuf::from(path),
+        line: line_number,
+        column: None,
+        severity,
+        code,
+        message: message.to_string(),
+    })
+}
+
+/// Parses the diagnostics from the standard error of slangc, the unrecognized lines are ignored.
+pub fn parse_diagnostics(stderr: &str) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = vec![];
+    for line in stderr.lines() {
+        if let Some(diagnostic) = parse_header(line) {
+            diagnostics.push(diagnostic);
+            continue;
+        }
+        let Some(diagnostic) = diagnostics.last_mut() else {
+            continue;
+        };
+        if diagnostic.column.is_none()
+            && line.trim().starts_with('^')
+            && let Some(index) = line.find('^')
+        {
+            diagnostic.column = Some(index + 1);
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ESeverity, parse_diagnostics};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_diagnostics() {
+        let stderr = r#"C:/shaders/main.slang(3): error 30015: undefined identifier 'c'.
+    return a + c;
+               ^
+main.slang(7): warning 41012: unused variable 'x'
+(0): note: see declaration of 'x'
+"#;
+        let diagnostics = parse_diagnostics(stderr);
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0].path, PathBuf::from("C:/shaders/main.slang"));
+        assert_eq!(diagnostics[0].line, 3);
+        assert_eq!(diagnostics[0].column, Some(16));
+        assert_eq!(diagnostics[0].severity, ESeverity::Error);
+        assert_eq!(diagnostics[0].code, Some(30015));
+        assert_eq!(diagnostics[0].message, "undefined identifier 'c'.");
+        assert_eq!(diagnostics[1].severity, ESeverity::Warning);
+        assert_eq!(diagnostics[1].column, None);
+        assert_eq!(diagnostics[2].path, PathBuf::new());
+        assert_eq!(diagnostics[2].code, None);
+        assert_eq!(diagnostics[2].severity, ESeverity::Note);
+    }
+}
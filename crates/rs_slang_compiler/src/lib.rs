@@ -1,3 +1,4 @@
+pub mod diagnostic;
 pub mod error;
 pub mod intellisense_support;
 
@@ -51,6 +52,21 @@ fn find_slangc() -> Option<PathBuf> {
     return None;
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ECompileTarget {
+    Wgsl,
+    SpirV,
+}
+
+impl ECompileTarget {
+    fn get_name(&self) -> &'static str {
+        match self {
+            ECompileTarget::Wgsl => "wgsl",
+            ECompileTarget::SpirV => "spirv",
+        }
+    }
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct CompileOptions {
     pub includes: HashSet<PathBuf>,
@@ -59,6 +75,8 @@ pub struct CompileOptions {
     pub preserve_params: bool,
     pub obfuscate: bool,
     pub no_mangle: bool,
+    /// Inferred from the extension of `output_path` if none.
+    pub target: Option<ECompileTarget>,
     pub input_path: PathBuf,
     pub output_path: PathBuf,
 }
@@ -89,6 +107,7 @@ pub fn compile(
         preserve_params,
         obfuscate,
         no_mangle,
+        target,
     } = compile_options;
     if !input_path.exists() {
         return Err(crate::error::Error::Io(std::io::ErrorKind::NotFound.into()));
@@ -112,6 +131,10 @@ pub fn compile(
     if *no_mangle {
         command.arg("-no-mangle");
     }
+    if let Some(target) = target {
+        command.arg("-target");
+        command.arg(target.get_name());
+    }
 
     command.arg(input_path);
 
@@ -1,6 +1,7 @@
 use crate::error::Result;
 use crate::{
     EEndianType,
+    artifact_index::{ArtifactIndex, ArtifactShardInfo},
     asset::{self},
     file_header::{
         self, ARTIFACT_FILE_MAGIC_NUMBERS, FileHeader, HEADER_LENGTH_SIZE, IDENTIFICATION_SIZE,
@@ -26,15 +27,7 @@ pub struct ArtifactFileHeader {
     pub resource_map: std::collections::HashMap<url::Url, ResourceInfo>,
 }
 
-pub fn encode_artifact_tasks_disk<R>(
-    endian_type: Option<EEndianType>,
-    settings: Settings,
-    tasks: &mut [ResourceEncodeTask<R>],
-    target_path: &Path,
-) -> Result<()>
-where
-    R: Seek + Read,
-{
+fn create_artifact_file(target_path: &Path) -> Result<BufWriter<std::fs::File>> {
     let parent = target_path
         .parent()
         .ok_or(crate::error::Error::NotFound(Some(format!(
@@ -47,7 +40,42 @@ where
     let file = std::fs::File::create(target_path).map_err(|err| {
         crate::error::Error::IO(err, Some(format!("Can not create file {:?}", target_path)))
     })?;
-    let mut buf_writer = BufWriter::new(file);
+    Ok(BufWriter::new(file))
+}
+
+fn write_artifact_header<W: Write>(
+    writer: &mut W,
+    endian_type: Option<EEndianType>,
+    settings: Settings,
+    infos: &[ResourceInfo],
+) -> Result<()> {
+    let mut fileheader = ArtifactFileHeader {
+        resource_map: HashMap::new(),
+        settings,
+    };
+    for info in infos {
+        fileheader
+            .resource_map
+            .insert(info.url.clone(), info.clone());
+    }
+    let header_encoded_data =
+        FileHeader::write_header(ARTIFACT_FILE_MAGIC_NUMBERS, &fileheader, endian_type)?;
+    writer.write_all(&header_encoded_data).map_err(|err| {
+        crate::error::Error::IO(err, Some(format!("Failed to write header data.")))
+    })?;
+    Ok(())
+}
+
+pub fn encode_artifact_tasks_disk<R>(
+    endian_type: Option<EEndianType>,
+    settings: Settings,
+    tasks: &mut [ResourceEncodeTask<R>],
+    target_path: &Path,
+) -> Result<()>
+where
+    R: Seek + Read,
+{
+    let mut buf_writer = create_artifact_file(target_path)?;
     let mut infos: Vec<ResourceInfo> = vec![];
     let mut offset: u64 = 0;
     for task in tasks.iter_mut() {
@@ -68,20 +96,7 @@ where
         offset += length;
         infos.push(info);
     }
-    let mut fileheader = ArtifactFileHeader {
-        resource_map: HashMap::new(),
-        settings,
-    };
-    for info in infos {
-        fileheader
-            .resource_map
-            .insert(info.url.clone(), info.clone());
-    }
-    let header_encoded_data =
-        FileHeader::write_header(ARTIFACT_FILE_MAGIC_NUMBERS, &fileheader, endian_type)?;
-    buf_writer.write(&header_encoded_data).map_err(|err| {
-        crate::error::Error::IO(err, Some(format!("Failed to write header data.")))
-    })?;
+    write_artifact_header(&mut buf_writer, endian_type, settings, &infos)?;
     for task in tasks.iter_mut() {
         let bytes = std::io::copy(&mut task.reader, &mut buf_writer)
             .map_err(|err| crate::error::Error::IO(err, Some(format!("Failed to copy data."))))?;
@@ -114,20 +129,199 @@ where
     encode_artifact_tasks_disk(endian_type, settings, &mut tasks, target_path)
 }
 
+/// The payload of a shard, spooled to a file next to the artifact until the encoder finishes.
+struct ArtifactShard {
+    chunk: Option<String>,
+    spool_path: PathBuf,
+    spool: Option<BufWriter<std::fs::File>>,
+    infos: Vec<ResourceInfo>,
+    size: u64,
+}
+
+impl ArtifactShard {
+    fn new(chunk: Option<String>, spool_path: PathBuf) -> ArtifactShard {
+        ArtifactShard {
+            chunk,
+            spool_path,
+            spool: None,
+            infos: vec![],
+            size: 0,
+        }
+    }
+
+    fn push(&mut self, task: ResourceEncodeTask<Cursor<Vec<u8>>>) -> Result<()> {
+        if self.spool.is_none() {
+            self.spool = Some(create_artifact_file(&self.spool_path)?);
+        }
+        let spool = self.spool.as_mut().expect("Not null");
+        let data = task.reader.into_inner();
+        spool.write_all(&data).map_err(|err| {
+            crate::error::Error::IO(err, Some(format!("Failed to write {}", task.url)))
+        })?;
+        let length = data.len() as u64;
+        self.infos.push(ResourceInfo {
+            url: task.url,
+            resource_type: task.resource_type,
+            offset: self.size,
+            length,
+        });
+        self.size += length;
+        Ok(())
+    }
+
+    fn write(
+        &mut self,
+        endian_type: Option<EEndianType>,
+        settings: Settings,
+        target_path: &Path,
+    ) -> Result<()> {
+        let mut buf_writer = create_artifact_file(target_path)?;
+        write_artifact_header(&mut buf_writer, endian_type, settings, &self.infos)?;
+        if let Some(mut spool) = self.spool.take() {
+            spool.flush().map_err(|err| {
+                crate::error::Error::IO(err, Some(format!("Failed to flush {:?}", self.spool_path)))
+            })?;
+            drop(spool);
+            let mut file = std::fs::File::open(&self.spool_path).map_err(|err| {
+                crate::error::Error::IO(
+                    err,
+                    Some(format!("Can not open file {:?}", self.spool_path)),
+                )
+            })?;
+            std::io::copy(&mut file, &mut buf_writer).map_err(|err| {
+                crate::error::Error::IO(err, Some(format!("Failed to copy data.")))
+            })?;
+            let _ = std::fs::remove_file(&self.spool_path);
+        }
+        buf_writer.flush().map_err(|err| {
+            crate::error::Error::IO(err, Some(format!("Failed to flush {:?}", target_path)))
+        })?;
+        Ok(())
+    }
+}
+
+impl Drop for ArtifactShard {
+    fn drop(&mut self) {
+        if self.spool.take().is_some() {
+            let _ = std::fs::remove_file(&self.spool_path);
+        }
+    }
+}
+
+/// Streams the encoded assets to disk, so only one asset is held in memory at a time.
+/// The assets can be split into several artifacts by size or by chunk,
+/// which are described by an [`ArtifactIndex`] written next to the main artifact.
 pub struct ArtifactAssetEncoder {
     settings: Settings,
-    tasks: Vec<ResourceEncodeTask<Cursor<Vec<u8>>>>,
     endian_type: Option<EEndianType>,
     target_path: PathBuf,
+    max_shard_size: Option<u64>,
+    chunk: Option<String>,
+    shards: Vec<ArtifactShard>,
+    error: Option<crate::error::Error>,
 }
 
 impl ArtifactAssetEncoder {
     pub fn new(endian_type: Option<EEndianType>, settings: Settings, target_path: &Path) -> Self {
         Self {
             settings,
-            tasks: vec![],
             endian_type,
             target_path: target_path.to_path_buf(),
+            max_shard_size: None,
+            chunk: None,
+            shards: vec![ArtifactShard::new(
+                None,
+                Self::get_spool_path(target_path, 0),
+            )],
+            error: None,
+        }
+    }
+
+    /// A new shard is started once the payload of the current one would exceed `max_shard_size`,
+    /// an asset larger than it still gets a shard of its own.
+    pub fn with_max_shard_size(mut self, max_shard_size: Option<u64>) -> Self {
+        self.max_shard_size = max_shard_size;
+        self
+    }
+
+    /// The following assets are written to the shards of `chunk`, `None` is the chunk of the main artifact.
+    pub fn set_chunk(&mut self, chunk: Option<String>) {
+        self.chunk = chunk;
+    }
+
+    pub fn get_endian_type(&self) -> Option<EEndianType> {
+        self.endian_type
+    }
+
+    fn get_spool_path(target_path: &Path, index: usize) -> PathBuf {
+        let mut file_name = target_path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(format!(".{}.spool", index));
+        target_path.with_file_name(file_name)
+    }
+
+    /// The main artifact keeps its name, the others are named like `main.1.rs`.
+    fn get_shard_file_name(target_path: &Path, index: usize) -> String {
+        let file_name = target_path
+            .file_name()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if index == 0 {
+            return file_name;
+        }
+        match target_path.extension() {
+            Some(extension) => format!(
+                "{}.{}.{}",
+                target_path
+                    .file_stem()
+                    .unwrap_or_default()
+                    .to_string_lossy(),
+                index,
+                extension.to_string_lossy()
+            ),
+            None => format!("{}.{}", file_name, index),
+        }
+    }
+
+    fn select_shard(&mut self, length: u64) -> usize {
+        let current = self
+            .shards
+            .iter()
+            .rposition(|x| x.chunk == self.chunk)
+            .filter(|index| {
+                let shard = &self.shards[*index];
+                match self.max_shard_size {
+                    Some(max_shard_size) => {
+                        shard.size == 0 || shard.size + length <= max_shard_size
+                    }
+                    None => true,
+                }
+            });
+        match current {
+            Some(index) => index,
+            None => {
+                let index = self.shards.len();
+                self.shards.push(ArtifactShard::new(
+                    self.chunk.clone(),
+                    Self::get_spool_path(&self.target_path, index),
+                ));
+                index
+            }
+        }
+    }
+
+    fn push(&mut self, encoded_data: Result<Vec<u8>>, task: ResourceEncodeTask<Cursor<Vec<u8>>>) {
+        if self.error.is_some() {
+            return;
+        }
+        let result = encoded_data.and_then(|data| {
+            let index = self.select_shard(data.len() as u64);
+            self.shards[index].push(ResourceEncodeTask {
+                reader: Cursor::new(data),
+                ..task
+            })
+        });
+        if let Err(err) = result {
+            self.error = Some(err);
         }
     }
 
@@ -149,10 +343,9 @@ impl ArtifactAssetEncoder {
     /// name, which the artifact reader cannot match back to an `Asset` when
     /// decoding. Use [`Self::encode_content`] for content types instead.
     pub fn encode_asset(&mut self, asset: &dyn Asset) {
-        let asset_encoded_data = asset::encode_asset(asset, self.endian_type).unwrap();
-        let reader = Cursor::new(asset_encoded_data);
-        let task = asset.build_resource_encode_task(reader);
-        self.tasks.push(task);
+        let asset_encoded_data = asset::encode_asset(asset, self.endian_type);
+        let task = asset.build_resource_encode_task(Cursor::new(vec![]));
+        self.push(asset_encoded_data, task);
     }
 
     /// Encode a content type into this artifact as a task.
@@ -173,19 +366,41 @@ impl ArtifactAssetEncoder {
     /// decoding path. Always use this method for anything that implements
     /// `Content`, and reserve [`Self::encode_asset`] for pure `Asset` types.
     pub fn encode_content(&mut self, asset: &dyn Content) {
-        let asset_encoded_data = asset::encode_asset(asset, self.endian_type).unwrap();
-        let reader = Cursor::new(asset_encoded_data);
-        let task = asset.build_resource_encode_task(reader);
-        self.tasks.push(task);
+        let asset_encoded_data = asset::encode_asset(asset, self.endian_type);
+        let task = asset.build_resource_encode_task(Cursor::new(vec![]));
+        self.push(asset_encoded_data, task);
     }
 
-    pub fn finish(&mut self) -> Result<()> {
-        encode_artifact_tasks_disk(
-            self.endian_type,
-            self.settings.clone(),
-            &mut self.tasks,
-            &self.target_path,
-        )
+    /// Writes the artifacts and the index, the first error of encoding is returned here.
+    pub fn finish(&mut self) -> Result<ArtifactIndex> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+        let mut artifact_index = ArtifactIndex {
+            endian_type: self.endian_type.unwrap_or_default(),
+            shards: vec![],
+        };
+        for (index, shard) in self.shards.iter_mut().enumerate() {
+            let file_name = Self::get_shard_file_name(&self.target_path, index);
+            shard.write(
+                self.endian_type,
+                self.settings.clone(),
+                &self.target_path.with_file_name(&file_name),
+            )?;
+            log::trace!(
+                "Shard: {}, {:?}, bytes: {}",
+                file_name,
+                shard.chunk,
+                shard.size
+            );
+            artifact_index.shards.push(ArtifactShardInfo {
+                file_name,
+                chunk: shard.chunk.clone(),
+                size: shard.size,
+            });
+        }
+        artifact_index.write(&ArtifactIndex::get_path(&self.target_path))?;
+        Ok(artifact_index)
     }
 }
 
@@ -197,6 +412,9 @@ pub struct ArtifactReader {
     buf_reader: crate::java_input_stream::JavaInputStream,
     payload_offset: u64,
     endian_type: Option<EEndianType>,
+    shards: Vec<ArtifactReader>,
+    /// The shards holding the resources which are not in this artifact.
+    shard_indices: HashMap<url::Url, usize>,
 }
 
 impl ArtifactReader {
@@ -237,6 +455,8 @@ impl ArtifactReader {
             buf_reader,
             payload_offset,
             endian_type,
+            shards: vec![],
+            shard_indices: HashMap::new(),
         });
     }
 
@@ -267,9 +487,46 @@ impl ArtifactReader {
             buf_reader,
             payload_offset,
             endian_type,
+            shards: vec![],
+            shard_indices: HashMap::new(),
         })
     }
 
+    /// Opens a cooked artifact, the endianness and the other shards are read from its index if there is one.
+    #[cfg(not(target_os = "android"))]
+    pub fn open(path: &Path) -> Result<ArtifactReader> {
+        let index_path = ArtifactIndex::get_path(path);
+        if !index_path.exists() {
+            return Self::new(path, None);
+        }
+        let artifact_index = ArtifactIndex::read(&index_path)?;
+        let endian_type = Some(artifact_index.endian_type);
+        let mut reader = Self::new(path, endian_type)?;
+        let file_name = path.file_name().map(|x| x.to_string_lossy().to_string());
+        for shard in artifact_index.shards {
+            if Some(&shard.file_name) == file_name.as_ref() {
+                continue;
+            }
+            reader.add_shard(Self::new(
+                &path.with_file_name(&shard.file_name),
+                endian_type,
+            )?);
+        }
+        Ok(reader)
+    }
+
+    /// The resources of `shard` are looked up through this artifact afterwards.
+    pub fn add_shard(&mut self, shard: ArtifactReader) {
+        let index = self.shards.len();
+        for (url, resource_info) in shard.artifact_file_header.resource_map.iter() {
+            self.shard_indices.insert(url.clone(), index);
+            self.artifact_file_header
+                .resource_map
+                .insert(url.clone(), resource_info.clone());
+        }
+        self.shards.push(shard);
+    }
+
     pub fn get_artifact_file_header(&self) -> &ArtifactFileHeader {
         &self.artifact_file_header
     }
@@ -325,6 +582,9 @@ impl ArtifactReader {
         T: Asset + Serialize + ?Sized,
         Box<T>: Asset + DeserializeOwned,
     {
+        if let Some(index) = self.shard_indices.get(url) {
+            return self.shards[*index].asset_internal(url, expected_resource_type);
+        }
         let resource_info = self.artifact_file_header.resource_map.get(url).ok_or(
             crate::error::Error::NotFound(Some(format!("Resource does not contain {}", url))),
         )?;
@@ -359,22 +619,24 @@ impl ArtifactReader {
     pub fn check_assets(&mut self) -> Result<()> {
         for (url, resource_info) in self.artifact_file_header.resource_map.clone() {
             log::trace!("url: {}, type: {:?}", url, resource_info.resource_type);
-            let offset = resource_info.offset;
-            let length = resource_info.length;
-            let _ = self
-                .buf_reader
-                .seek(SeekFrom::Start(self.payload_offset + offset))
-                .map_err(|err| {
-                    crate::error::Error::IO(
-                        err,
-                        Some(format!("Failed to seek {}", self.payload_offset + offset)),
-                    )
+            if !self.shard_indices.contains_key(&url) {
+                let offset = resource_info.offset;
+                let length = resource_info.length;
+                let _ = self
+                    .buf_reader
+                    .seek(SeekFrom::Start(self.payload_offset + offset))
+                    .map_err(|err| {
+                        crate::error::Error::IO(
+                            err,
+                            Some(format!("Failed to seek {}", self.payload_offset + offset)),
+                        )
+                    })?;
+                let mut buf: Vec<u8> = vec![0; length as usize];
+                let _ = self.buf_reader.read_exact(&mut buf).map_err(|err| {
+                    let msg = format!("Failed to read the exact number of bytes.");
+                    crate::error::Error::IO(err, Some(msg))
                 })?;
-            let mut buf: Vec<u8> = vec![0; length as usize];
-            let _ = self.buf_reader.read_exact(&mut buf).map_err(|err| {
-                let msg = format!("Failed to read the exact number of bytes.");
-                crate::error::Error::IO(err, Some(msg))
-            })?;
+            }
 
             if resource_info.resource_type.kind() == ASSET_KIND {
                 let asset = self.asset(&url, None)?;
@@ -410,7 +672,9 @@ impl ArtifactReader {
 
 #[cfg(test)]
 mod test {
-    use super::ArtifactFileHeader;
+    use super::{ArtifactAssetEncoder, ArtifactFileHeader, ArtifactReader};
+    use crate::{EEndianType, shader_source_code::ShaderSourceCode};
+    use rs_core_minimal::settings::Settings;
 
     #[test]
     fn test_case_artifact() {
@@ -419,4 +683,46 @@ mod test {
         let _decoded: ArtifactFileHeader =
             crate::bincode_legacy::deserialize(&encoded[..], None).unwrap();
     }
+
+    #[test]
+    fn test_shards() {
+        let dir = std::env::temp_dir().join("rs_artifact_test_shards");
+        let _ = std::fs::remove_dir_all(&dir);
+        let target_path = dir.join("main.rs");
+        let mut encoder =
+            ArtifactAssetEncoder::new(Some(EEndianType::Big), Settings::default(), &target_path)
+                .with_max_shard_size(Some(1));
+        let shaders: Vec<ShaderSourceCode> = ["a", "b", "c"]
+            .iter()
+            .map(|name| ShaderSourceCode {
+                name: name.to_string(),
+                id: uuid::Uuid::new_v4(),
+                url: url::Url::parse(&format!("asset://shader/{}", name)).unwrap(),
+                code: name.repeat(8),
+            })
+            .collect();
+        encoder.encode_asset(&shaders[0]);
+        encoder.encode_asset(&shaders[1]);
+        encoder.set_chunk(Some("level".to_string()));
+        encoder.encode_asset(&shaders[2]);
+        let artifact_index = encoder.finish().unwrap();
+
+        let file_names: Vec<&str> = artifact_index
+            .shards
+            .iter()
+            .map(|x| x.file_name.as_str())
+            .collect();
+        assert_eq!(file_names, vec!["main.rs", "main.1.rs", "main.2.rs"]);
+        assert_eq!(artifact_index.shards[2].chunk.as_deref(), Some("level"));
+        assert!(!dir.join("main.rs.0.spool").exists());
+
+        let mut reader = ArtifactReader::open(&target_path).unwrap();
+        assert_eq!(reader.get_artifact_file_header().resource_map.len(), 3);
+        for shader in &shaders {
+            let asset = reader.asset(&shader.url, None).unwrap();
+            let decoded = asset.downcast_ref::<ShaderSourceCode>().unwrap();
+            assert_eq!(decoded.code, shader.code);
+        }
+        reader.check_assets().unwrap();
+    }
 }
@@ -0,0 +1,42 @@
+use crate::{EEndianType, error::Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ArtifactShardInfo {
+    /// Relative to the folder of the main artifact.
+    pub file_name: String,
+    /// Assets of the same chunk are always written to the same shards.
+    pub chunk: Option<String>,
+    pub size: u64,
+}
+
+/// Describes the shards of a cooked artifact, written next to the main artifact.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ArtifactIndex {
+    pub endian_type: EEndianType,
+    /// The first one is the main artifact.
+    pub shards: Vec<ArtifactShardInfo>,
+}
+
+impl ArtifactIndex {
+    pub fn get_path(artifact_path: &Path) -> PathBuf {
+        artifact_path.with_extension("index.json")
+    }
+
+    pub fn read(path: &Path) -> Result<ArtifactIndex> {
+        let file = std::fs::File::open(path).map_err(|err| {
+            crate::error::Error::IO(err, Some(format!("Can not open file {:?}", path)))
+        })?;
+        serde_json::from_reader(std::io::BufReader::new(file))
+            .map_err(|err| crate::error::Error::SerdeJson(err, Some(format!("{:?}", path))))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let data = serde_json::to_string_pretty(self)
+            .map_err(|err| crate::error::Error::SerdeJson(err, None))?;
+        std::fs::write(path, data).map_err(|err| {
+            crate::error::Error::IO(err, Some(format!("Can not write to file {:?}", path)))
+        })
+    }
+}
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Copy, PartialEq, Eq, Debug, Clone, Hash, Serialize, Deserialize)]
+pub enum EShaderTarget {
+    SpirV,
+    GlslEs,
+    Msl,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct GlslEntryPoint {
+    pub name: String,
+    pub code: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub enum ECompiledShaderCode {
+    SpirV(Vec<u32>),
+    /// GLSL only holds one entry point per shader.
+    GlslEs(Vec<GlslEntryPoint>),
+    Msl(String),
+}
+
+impl ECompiledShaderCode {
+    pub fn get_target(&self) -> EShaderTarget {
+        match self {
+            ECompiledShaderCode::SpirV(_) => EShaderTarget::SpirV,
+            ECompiledShaderCode::GlslEs(_) => EShaderTarget::GlslEs,
+            ECompiledShaderCode::Msl(_) => EShaderTarget::Msl,
+        }
+    }
+}
+
+/// The shader translated from `ShaderSourceCode` of the same name.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CompiledShader {
+    pub name: String,
+    pub id: uuid::Uuid,
+    pub url: url::Url,
+    pub code: ECompiledShaderCode,
+}
+
+crate::impl_asset!(CompiledShader);
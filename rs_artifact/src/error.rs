@@ -11,6 +11,7 @@ pub enum Error {
     #[cfg(target_os = "android")]
     Jni(jni::errors::Error),
     NotFound(Option<String>),
+    SerdeJson(serde_json::Error, Option<String>),
 }
 
 impl std::fmt::Display for Error {
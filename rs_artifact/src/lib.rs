@@ -1,6 +1,8 @@
 pub mod artifact;
+pub mod artifact_index;
 pub mod asset;
 pub mod bincode_legacy;
+pub mod compiled_shader;
 pub mod derive_data;
 pub mod endian;
 pub mod error;
@@ -44,6 +44,26 @@ pub struct UpdateDependenciesArgs {
     pub manifest_file: Option<std::path::PathBuf>,
 }
 
+#[derive(Debug, Clone, Args)]
+pub struct PackageArgs {
+    #[arg(long)]
+    pub project_file: std::path::PathBuf,
+    #[arg(long, default_value = "release")]
+    pub profile_type: ProfileType,
+    /// Defaults to `build/package/<platform>-<profile>` in the project folder.
+    #[arg(short, long)]
+    pub output_dir: Option<std::path::PathBuf>,
+    /// The cook profile of the editor, such as `windows` or `android`.
+    #[arg(long)]
+    pub cook_profile: Option<String>,
+    /// Uses the cooked artifact instead of cooking the content with the editor.
+    #[arg(long)]
+    pub artifact_file: Option<std::path::PathBuf>,
+    /// Uses the standalone executable instead of building it.
+    #[arg(long)]
+    pub executable_file: Option<std::path::PathBuf>,
+}
+
 #[derive(Parser, Debug, Clone)]
 #[command(author, version, about, long_about = None)]
 #[command(propagate_version = true)]
@@ -53,4 +73,5 @@ pub enum Cli {
     CreateDefaultLoadPluginsFile,
     UpdateEdition,
     UpdateDependencies(UpdateDependenciesArgs),
+    Package(PackageArgs),
 }
@@ -2,4 +2,5 @@ pub mod build_script;
 pub mod cli;
 pub mod json_project;
 pub mod load_plugins;
+pub mod package;
 pub mod toml_edit;
@@ -433,6 +433,14 @@ fn main() -> anyhow::Result<()> {
                 }
             }
         }
+        Cli::Package(package_args) => {
+            let manifest = rs_build_tool::package::package(&package_args)?;
+            log::info!(
+                "Packaged {} files of {}",
+                manifest.files.len(),
+                manifest.project_name
+            );
+        }
     }
 
     Ok(())
@@ -0,0 +1,559 @@
+use crate::{
+    build_script::{clean, make_build_script},
+    cli::{ModeType, PackageArgs, ProfileType, ProjectArgs},
+};
+use anyhow::anyhow;
+use rs_core_minimal::path_ext::CanonicalizeSlashExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    env::consts::EXE_SUFFIX,
+    path::{Path, PathBuf},
+};
+
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+/// The standalone executable loads the artifact with this name next to it.
+pub const ARTIFACT_FILE_NAME: &str = "main.rs";
+pub const SHADERS_FOLDER_NAME: &str = "shaders";
+/// The index of a sharded artifact is named like `main.index.json`.
+const ARTIFACT_INDEX_EXTENSION: &str = "index.json";
+const STANDALONE_CRATE_NAME: &str = "rs_desktop_standalone";
+/// The dependency graph of the shader compiler, which is not needed by the game.
+const SHADER_DEPENDENCY_FILE_NAME: &str = "dependency";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PackageFile {
+    /// Relative to the package folder, separated by `/`.
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+/// Describes a packaged game, written to the package folder.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PackageManifest {
+    pub project_name: String,
+    /// Such as `linux-x86_64`.
+    pub platform: String,
+    pub profile: String,
+    pub executable: String,
+    pub artifact: String,
+    pub files: Vec<PackageFile>,
+}
+
+/// The outputs of cooking and building which are copied to the package.
+#[derive(Debug, Clone)]
+pub struct PackageInputs {
+    pub project_name: String,
+    pub profile_type: ProfileType,
+    pub executable_file: PathBuf,
+    pub artifact_file: PathBuf,
+    /// The compiled shaders, skipped if it does not exist.
+    pub shaders_dir: PathBuf,
+}
+
+fn get_profile_name(profile_type: &ProfileType) -> &'static str {
+    match profile_type {
+        ProfileType::Release => "release",
+        ProfileType::Debug => "debug",
+    }
+}
+
+pub fn get_platform_name() -> String {
+    format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH)
+}
+
+pub fn get_default_output_dir(project_folder: &Path, profile_type: &ProfileType) -> PathBuf {
+    project_folder.join("build/package").join(format!(
+        "{}-{}",
+        get_platform_name(),
+        get_profile_name(profile_type)
+    ))
+}
+
+fn run_cargo(mut command: std::process::Command) -> anyhow::Result<String> {
+    log::trace!("{:?}", command);
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(anyhow!(
+            "cargo, {:?}\n{}",
+            output.status.code(),
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Runs the `cook` command of the editor, which exports the content of the project to an artifact.
+fn cook_content(
+    project_file: &Path,
+    profile_type: &ProfileType,
+    cook_profile: Option<&str>,
+) -> anyhow::Result<PathBuf> {
+    let engine_root_dir = rs_core_minimal::file_manager::get_engine_root_dir();
+    let mut command = std::process::Command::new("cargo");
+    command
+        .current_dir(engine_root_dir.join("rs_editor"))
+        .arg("run")
+        .arg("--package")
+        .arg("rs_editor")
+        .arg("--bin")
+        .arg("editor")
+        .arg("--no-default-features")
+        .arg("--features")
+        .arg("editor");
+    if *profile_type == ProfileType::Release {
+        command.arg("--release");
+    }
+    command.arg("--").arg("cook").arg(project_file);
+    if let Some(cook_profile) = cook_profile {
+        command.arg("--profile").arg(cook_profile);
+    }
+    let stdout = run_cargo(command)?;
+    let artifact_file = stdout
+        .lines()
+        .map(|x| x.trim())
+        .rfind(|x| !x.is_empty())
+        .ok_or(anyhow!("The editor does not output the artifact"))?;
+    Ok(PathBuf::from(artifact_file))
+}
+
+/// Reverts the changes `make_build_script` makes to the manifests of the engine when it is dropped.
+struct BuildScriptGuard {
+    project_args: ProjectArgs,
+}
+
+impl Drop for BuildScriptGuard {
+    fn drop(&mut self) {
+        if let Err(err) = clean(&self.project_args) {
+            log::warn!("Fail to clean the build script, {}", err);
+        }
+    }
+}
+
+/// Finds the executable of the binary target in the JSON messages of `cargo build`.
+fn find_executable(messages: &str, target_name: &str) -> Option<PathBuf> {
+    messages
+        .lines()
+        .rev()
+        .filter_map(|x| serde_json::from_str::<serde_json::Value>(x).ok())
+        .filter(|x| {
+            x["reason"] == "compiler-artifact"
+                && x["target"]["name"] == target_name
+                && x["target"]["kind"]
+                    .as_array()
+                    .is_some_and(|x| x.iter().any(|x| x == "bin"))
+        })
+        .find_map(|x| x["executable"].as_str().map(PathBuf::from))
+}
+
+/// Builds the standalone executable with the plugin of the project statically linked,
+/// the manifests of the engine must be restored by `clean` afterwards.
+fn build_standalone(project_args: &ProjectArgs) -> anyhow::Result<PathBuf> {
+    make_build_script(project_args)?;
+    let crate_folder =
+        rs_core_minimal::file_manager::get_engine_root_dir().join(STANDALONE_CRATE_NAME);
+    let mut command = std::process::Command::new("cargo");
+    command
+        .current_dir(&crate_folder)
+        .arg("build")
+        .arg("--package")
+        .arg(STANDALONE_CRATE_NAME)
+        .arg("--features")
+        .arg("plugin_shared_crate")
+        .arg("--message-format")
+        .arg("json-render-diagnostics");
+    if project_args.profile_type == ProfileType::Release {
+        command.arg("--release");
+    }
+    let stdout = run_cargo(command)?;
+    find_executable(&stdout, STANDALONE_CRATE_NAME)
+        .ok_or(anyhow!("Cargo does not output the executable"))
+}
+
+fn copy_file(from: &Path, to: &Path) -> anyhow::Result<()> {
+    log::trace!("Copy {:?} to {:?}", from, to);
+    std::fs::copy(from, to)
+        .map_err(|err| anyhow!("Can not copy {:?} to {:?}, {}", from, to, err))?;
+    Ok(())
+}
+
+/// Copies the artifact as `main.rs`, and the other shards and the index if the artifact is sharded.
+fn copy_artifact(artifact_file: &Path, output_dir: &Path) -> anyhow::Result<()> {
+    copy_file(artifact_file, &output_dir.join(ARTIFACT_FILE_NAME))?;
+    let index_file = artifact_file.with_extension(ARTIFACT_INDEX_EXTENSION);
+    if !index_file.is_file() {
+        return Ok(());
+    }
+    let folder = artifact_file.parent().ok_or(anyhow!("No parent folder."))?;
+    let artifact_file_name = artifact_file
+        .file_name()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut index: serde_json::Value = serde_json::from_slice(&std::fs::read(&index_file)?)?;
+    let shards = index
+        .get_mut("shards")
+        .and_then(|x| x.as_array_mut())
+        .ok_or(anyhow!("No shards in {:?}", index_file))?;
+    for shard in shards {
+        let file_name = shard
+            .get("file_name")
+            .and_then(|x| x.as_str())
+            .ok_or(anyhow!("No file name of the shard in {:?}", index_file))?
+            .to_string();
+        if file_name == artifact_file_name {
+            shard["file_name"] = ARTIFACT_FILE_NAME.into();
+        } else {
+            copy_file(&folder.join(&file_name), &output_dir.join(&file_name))?;
+        }
+    }
+    std::fs::write(
+        output_dir.join(Path::new(ARTIFACT_FILE_NAME).with_extension(ARTIFACT_INDEX_EXTENSION)),
+        serde_json::to_string_pretty(&index)?,
+    )?;
+    Ok(())
+}
+
+fn collect_files(
+    folder: &Path,
+    relative: &str,
+    files: &mut Vec<PackageFile>,
+) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(folder)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = match relative.is_empty() {
+            true => name,
+            false => format!("{}/{}", relative, name),
+        };
+        if entry.file_type()?.is_dir() {
+            collect_files(&entry.path(), &path, files)?;
+        } else if path != MANIFEST_FILE_NAME {
+            let data = std::fs::read(entry.path())?;
+            files.push(PackageFile {
+                path,
+                size: data.len() as u64,
+                sha256: rs_core_minimal::misc::get_sha256_from_buf(&data),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Lays out the package in `output_dir`, which is recreated:
+/// the executable named after the project, the artifacts, the shaders and the manifest.
+pub fn assemble_package(
+    inputs: &PackageInputs,
+    output_dir: &Path,
+) -> anyhow::Result<PackageManifest> {
+    if output_dir.exists() {
+        std::fs::remove_dir_all(output_dir)?;
+    }
+    std::fs::create_dir_all(output_dir)?;
+
+    let executable = format!("{}{}", inputs.project_name, EXE_SUFFIX);
+    copy_file(&inputs.executable_file, &output_dir.join(&executable))?;
+    copy_artifact(&inputs.artifact_file, output_dir)?;
+
+    if inputs.shaders_dir.is_dir() {
+        let shaders_dir = output_dir.join(SHADERS_FOLDER_NAME);
+        std::fs::create_dir_all(&shaders_dir)?;
+        for entry in std::fs::read_dir(&inputs.shaders_dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() || entry.file_name() == SHADER_DEPENDENCY_FILE_NAME {
+                continue;
+            }
+            copy_file(&entry.path(), &shaders_dir.join(entry.file_name()))?;
+        }
+    } else {
+        log::warn!("No compiled shaders in {:?}", inputs.shaders_dir);
+    }
+
+    let mut files = vec![];
+    collect_files(output_dir, "", &mut files)?;
+    files.sort_by(|lhs, rhs| lhs.path.cmp(&rhs.path));
+    let manifest = PackageManifest {
+        project_name: inputs.project_name.clone(),
+        platform: get_platform_name(),
+        profile: get_profile_name(&inputs.profile_type).to_string(),
+        executable,
+        artifact: ARTIFACT_FILE_NAME.to_string(),
+        files,
+    };
+    std::fs::write(
+        output_dir.join(MANIFEST_FILE_NAME),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+    Ok(manifest)
+}
+
+pub fn package(package_args: &PackageArgs) -> anyhow::Result<PackageManifest> {
+    let project_file = package_args.project_file.canonicalize_slash()?;
+    let project_folder = project_file.parent().ok_or(anyhow!("No parent folder."))?;
+    let project_name = project_file
+        .file_stem()
+        .ok_or(anyhow!("No project name"))?
+        .to_str()
+        .ok_or(anyhow!("No project name"))?;
+    let profile_type = &package_args.profile_type;
+
+    let artifact_file = match &package_args.artifact_file {
+        Some(artifact_file) => artifact_file.clone(),
+        None => cook_content(
+            &project_file,
+            profile_type,
+            package_args.cook_profile.as_deref(),
+        )?,
+    };
+    let mut _build_script_guard = None;
+    let executable_file = match &package_args.executable_file {
+        Some(executable_file) => executable_file.clone(),
+        None => {
+            let project_args = ProjectArgs {
+                is_enable: true,
+                project_file: project_file.clone(),
+                mode_type: ModeType::Standalone,
+                profile_type: profile_type.clone(),
+                is_enable_dylib: false,
+            };
+            _build_script_guard = Some(BuildScriptGuard {
+                project_args: project_args.clone(),
+            });
+            build_standalone(&project_args)?
+        }
+    };
+    let output_dir = match &package_args.output_dir {
+        Some(output_dir) => output_dir.clone(),
+        None => get_default_output_dir(project_folder, profile_type),
+    };
+    let inputs = PackageInputs {
+        project_name: project_name.to_string(),
+        profile_type: profile_type.clone(),
+        executable_file,
+        artifact_file,
+        shaders_dir: rs_core_minimal::file_manager::get_engine_output_target_dir()
+            .join(SHADERS_FOLDER_NAME),
+    };
+    let manifest = assemble_package(&inputs, &output_dir)?;
+    log::trace!("Package: {:?}", output_dir);
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PackageInputs, PackageManifest, assemble_package, find_executable, package};
+    use crate::cli::{PackageArgs, ProfileType};
+    use std::{env::consts::EXE_SUFFIX, path::Path, path::PathBuf};
+
+    /// An empty project with a plugin which does nothing, like the one created by the editor.
+    fn create_sample_project(folder: &Path, project_name: &str) -> PathBuf {
+        let engine_root_dir = rs_core_minimal::file_manager::get_engine_root_dir()
+            .canonicalize()
+            .unwrap();
+        let engine_path = engine_root_dir.to_str().unwrap().replace('\\', "/");
+        std::fs::create_dir_all(folder.join("src")).unwrap();
+        std::fs::create_dir_all(folder.join("content")).unwrap();
+        std::fs::write(
+            folder.join("Cargo.toml"),
+            format!(
+                r#"[package]
+name = "{project_name}"
+version = "0.1.0"
+edition = "2021"
+metadata = {{ lr = {{ is_plugin = true }} }}
+
+[features]
+default = ["editor", "plugin_shared_crate"]
+editor = ["rs_engine/editor", "rs_render/editor", "rs_core_minimal/editor"]
+plugin_shared_crate = ["rs_engine/plugin_shared_crate"]
+standalone = [
+    "rs_engine/standalone",
+    "rs_render/standalone",
+    "rs_core_minimal/standalone",
+]
+profiler = ["rs_engine/profiler", "rs_render/profiler"]
+network = ["rs_engine/network"]
+
+[dependencies]
+rs_engine = {{ path = "{engine_path}/rs_engine" }}
+rs_render = {{ path = "{engine_path}/rs_render" }}
+rs_core_minimal = {{ path = "{engine_path}/rs_core_minimal" }}
+"#
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            folder.join("src/lib.rs"),
+            r#"use rs_engine::plugin::plugin_crate::Plugin;
+
+pub struct MyPlugin {}
+
+impl Plugin for MyPlugin {}
+
+#[no_mangle]
+pub fn create_plugin() -> Box<dyn Plugin> {
+    Box::new(MyPlugin {})
+}
+"#,
+        )
+        .unwrap();
+        let project = serde_json::json!({
+            "version_str": "0.0.1",
+            "project_name": project_name,
+            "settings": rs_core_minimal::settings::Settings::default(),
+            "endian_type": "Little",
+            "materials": [],
+            "material_functions": [],
+        });
+        let project_file = folder.join(format!("{project_name}.rsproject"));
+        std::fs::write(
+            &project_file,
+            serde_json::to_string_pretty(&project).unwrap(),
+        )
+        .unwrap();
+        project_file
+    }
+
+    #[test]
+    #[ignore = "cooks the content with the editor and builds the standalone executable"]
+    fn test_package() {
+        let dir = std::env::temp_dir().join("rs_build_tool_test_package");
+        let _ = std::fs::remove_dir_all(&dir);
+        let project_name = "sample_game";
+        let project_file = create_sample_project(&dir.join(project_name), project_name);
+        let output_dir = dir.join("package");
+        let engine_root_dir = rs_core_minimal::file_manager::get_engine_root_dir();
+        let read_manifests = || {
+            ["rs_desktop_standalone", "rs_editor", "rs_android"].map(|x| {
+                std::fs::read_to_string(engine_root_dir.join(x).join("Cargo.toml")).unwrap()
+            })
+        };
+        let manifests = read_manifests();
+        let manifest = package(&PackageArgs {
+            project_file: project_file.clone(),
+            profile_type: ProfileType::Debug,
+            output_dir: Some(output_dir.clone()),
+            cook_profile: None,
+            artifact_file: None,
+            executable_file: None,
+        })
+        .unwrap();
+        // Building the standalone executable adds the plugin to the manifests of the engine.
+        assert_eq!(read_manifests(), manifests);
+
+        assert_eq!(manifest.project_name, project_name);
+        assert_eq!(manifest.profile, "debug");
+        assert_eq!(manifest.platform, super::get_platform_name());
+        assert_eq!(manifest.executable, format!("{project_name}{EXE_SUFFIX}"));
+        assert_eq!(manifest.artifact, super::ARTIFACT_FILE_NAME);
+        assert!(manifest.files.iter().any(|x| x.path == manifest.executable));
+        assert!(manifest.files.iter().any(|x| x.path == manifest.artifact));
+        assert!(
+            !manifest
+                .files
+                .iter()
+                .any(|x| x.path == super::MANIFEST_FILE_NAME)
+        );
+        for file in &manifest.files {
+            let data = std::fs::read(output_dir.join(&file.path)).unwrap();
+            assert_eq!(data.len() as u64, file.size, "{}", file.path);
+            assert_eq!(
+                rs_core_minimal::misc::get_sha256_from_buf(&data),
+                file.sha256,
+                "{}",
+                file.path
+            );
+        }
+        let mut files = vec![];
+        super::collect_files(&output_dir, "", &mut files).unwrap();
+        assert_eq!(files.len(), manifest.files.len());
+
+        let file = std::fs::File::open(output_dir.join(super::MANIFEST_FILE_NAME)).unwrap();
+        let written: PackageManifest = serde_json::from_reader(file).unwrap();
+        assert_eq!(written.files, manifest.files);
+    }
+
+    #[test]
+    fn test_assemble_package() {
+        let dir = std::env::temp_dir().join("rs_build_tool_test_assemble_package");
+        let _ = std::fs::remove_dir_all(&dir);
+        let shaders_dir = dir.join("shaders");
+        std::fs::create_dir_all(&shaders_dir).unwrap();
+        std::fs::write(dir.join("rs_desktop_standalone"), "exe").unwrap();
+        std::fs::write(dir.join("cooked.rs"), "artifact").unwrap();
+        std::fs::write(dir.join("cooked.1.rs"), "shard").unwrap();
+        std::fs::write(
+            dir.join("cooked.index.json"),
+            r#"{"endian_type": "Little", "shards": [{"file_name": "cooked.rs"}, {"file_name": "cooked.1.rs"}]}"#,
+        )
+        .unwrap();
+        std::fs::write(shaders_dir.join("grid.wgsl"), "fn main() {}").unwrap();
+        std::fs::write(shaders_dir.join("dependency"), "{}").unwrap();
+
+        let output_dir = dir.join("package");
+        std::fs::create_dir_all(&output_dir).unwrap();
+        std::fs::write(output_dir.join("stale"), "").unwrap();
+        let inputs = PackageInputs {
+            project_name: "game".to_string(),
+            profile_type: ProfileType::Release,
+            executable_file: dir.join("rs_desktop_standalone"),
+            artifact_file: dir.join("cooked.rs"),
+            shaders_dir,
+        };
+        let manifest = assemble_package(&inputs, &output_dir).unwrap();
+
+        let executable = format!("game{}", EXE_SUFFIX);
+        assert_eq!(manifest.executable, executable);
+        let mut paths: Vec<&str> = vec![
+            "cooked.1.rs",
+            "main.index.json",
+            "main.rs",
+            "shaders/grid.wgsl",
+            &executable,
+        ];
+        paths.sort();
+        assert_eq!(
+            manifest
+                .files
+                .iter()
+                .map(|x| x.path.as_str())
+                .collect::<Vec<&str>>(),
+            paths
+        );
+        assert_eq!(
+            std::fs::read_to_string(output_dir.join("main.rs")).unwrap(),
+            "artifact"
+        );
+        let main = manifest.files.iter().find(|x| x.path == "main.rs").unwrap();
+        assert_eq!(main.size, 8);
+        assert_eq!(
+            main.sha256,
+            rs_core_minimal::misc::get_sha256_from_buf(&b"artifact".to_vec())
+        );
+
+        let index: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(output_dir.join("main.index.json")).unwrap())
+                .unwrap();
+        assert_eq!(index["shards"][0]["file_name"], "main.rs");
+        assert_eq!(index["shards"][1]["file_name"], "cooked.1.rs");
+
+        let file = std::fs::File::open(output_dir.join("manifest.json")).unwrap();
+        let written: PackageManifest = serde_json::from_reader(file).unwrap();
+        assert_eq!(written.files, manifest.files);
+    }
+
+    #[test]
+    fn test_find_executable() {
+        let messages = [
+            r#"{"reason":"compiler-artifact","target":{"name":"rs_engine","kind":["lib"]},"executable":null}"#,
+            r#"{"reason":"compiler-artifact","target":{"name":"rs_desktop_standalone","kind":["bin"]},"executable":"/tmp/target/debug/rs_desktop_standalone"}"#,
+            r#"{"reason":"build-finished","success":true}"#,
+            "not json",
+        ]
+        .join("\n");
+        assert_eq!(
+            find_executable(&messages, "rs_desktop_standalone"),
+            Some(PathBuf::from("/tmp/target/debug/rs_desktop_standalone"))
+        );
+        assert_eq!(find_executable(&messages, "rs_engine"), None);
+    }
+}
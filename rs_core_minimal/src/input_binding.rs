@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EMouseButton {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+    Other(u16),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EGamepadButton {
+    South,
+    East,
+    North,
+    West,
+    LeftBumper,
+    RightBumper,
+    LeftTrigger,
+    RightTrigger,
+    Select,
+    Start,
+    Mode,
+    LeftThumb,
+    RightThumb,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EGamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EInputKey {
+    /// The name of a `winit::keyboard::KeyCode`, for example `KeyW`.
+    Keyboard(String),
+    MouseButton(EMouseButton),
+    MouseX,
+    MouseY,
+    MouseWheel,
+    GamepadButton(EGamepadButton),
+    GamepadAxis(EGamepadAxis),
+}
+
+impl EInputKey {
+    pub fn keyboard(name: impl AsRef<str>) -> EInputKey {
+        EInputKey::Keyboard(name.as_ref().to_string())
+    }
+
+    /// Mouse motion and wheel only report deltas, their values are reset every frame.
+    pub fn is_relative_axis(&self) -> bool {
+        matches!(
+            self,
+            EInputKey::MouseX | EInputKey::MouseY | EInputKey::MouseWheel
+        )
+    }
+
+    pub fn is_axis(&self) -> bool {
+        match self {
+            EInputKey::GamepadAxis(_) => true,
+            _ => self.is_relative_axis(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct InputModifiers {
+    #[serde(default)]
+    pub shift: bool,
+    #[serde(default)]
+    pub ctrl: bool,
+    #[serde(default)]
+    pub alt: bool,
+    #[serde(default)]
+    pub logo: bool,
+}
+
+impl InputModifiers {
+    pub fn contains(&self, other: &InputModifiers) -> bool {
+        (self.shift || !other.shift)
+            && (self.ctrl || !other.ctrl)
+            && (self.alt || !other.alt)
+            && (self.logo || !other.logo)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InputActionBinding {
+    pub key: EInputKey,
+    #[serde(default)]
+    pub modifiers: InputModifiers,
+    /// Additional keys that must be held together with `key`.
+    #[serde(default)]
+    pub chord: Vec<EInputKey>,
+}
+
+impl InputActionBinding {
+    pub fn new(key: EInputKey) -> InputActionBinding {
+        InputActionBinding {
+            key,
+            modifiers: InputModifiers::default(),
+            chord: vec![],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InputAxisBinding {
+    pub key: EInputKey,
+    pub scale: f32,
+    #[serde(default)]
+    pub dead_zone: f32,
+    #[serde(default)]
+    pub modifiers: InputModifiers,
+}
+
+impl InputAxisBinding {
+    pub fn new(key: EInputKey, scale: f32) -> InputAxisBinding {
+        InputAxisBinding {
+            key,
+            scale,
+            dead_zone: 0.0,
+            modifiers: InputModifiers::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InputActionMapping {
+    pub name: String,
+    pub bindings: Vec<InputActionBinding>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InputAxisMapping {
+    pub name: String,
+    pub bindings: Vec<InputAxisBinding>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InputContextDescription {
+    pub name: String,
+    /// Contexts with higher priority are evaluated first.
+    pub priority: i32,
+    /// Keys used by this context are hidden from contexts with lower priority.
+    #[serde(default)]
+    pub is_consume_input: bool,
+    #[serde(default)]
+    pub actions: Vec<InputActionMapping>,
+    #[serde(default)]
+    pub axes: Vec<InputAxisMapping>,
+}
+
+impl InputContextDescription {
+    pub fn new(name: impl AsRef<str>, priority: i32) -> InputContextDescription {
+        InputContextDescription {
+            name: name.as_ref().to_string(),
+            priority,
+            is_consume_input: false,
+            actions: vec![],
+            axes: vec![],
+        }
+    }
+}
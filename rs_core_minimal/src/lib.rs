@@ -4,6 +4,7 @@ pub mod error;
 pub mod file_manager;
 pub mod file_type;
 pub mod frustum;
+pub mod input_binding;
 pub mod line_3d;
 pub mod misc;
 pub mod name_generator;
@@ -1,3 +1,4 @@
+use crate::input_binding::InputContextDescription;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -61,12 +62,22 @@ impl RenderSettings {
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub enum EContentFileFormat {
+    #[default]
+    Json,
+    /// Pretty JSON with sorted keys and one block per object of an array, easy to diff and merge.
+    MergeFriendly,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct EditorSettings {
     pub is_auto_open_last_project: bool,
     pub is_enable_log_to_file: bool,
     #[serde(default)]
     pub locale: String,
+    #[serde(default)]
+    pub content_file_format: EContentFileFormat,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -75,12 +86,42 @@ pub struct EngineSettings {
     pub default_level: Option<url::Url>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct InputSettings {
+    #[serde(default)]
+    pub contexts: Vec<InputContextDescription>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PhysicsSettings {
+    /// The duration of one physics step in seconds.
+    pub fixed_time_step: f32,
+    /// The maximum number of steps per frame, the remaining time is dropped to avoid a spiral of death.
+    pub max_substeps: u32,
+    /// Renders rigid bodies between the last two steps instead of snapping to the latest step.
+    pub is_enable_interpolation: bool,
+}
+
+impl Default for PhysicsSettings {
+    fn default() -> Self {
+        Self {
+            fixed_time_step: 1.0 / 60.0,
+            max_substeps: 8,
+            is_enable_interpolation: true,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Settings {
     pub editor_settings: EditorSettings,
     pub render_setting: RenderSettings,
     #[serde(default)]
     pub engine_settings: EngineSettings,
+    #[serde(default)]
+    pub input_settings: InputSettings,
+    #[serde(default)]
+    pub physics_settings: PhysicsSettings,
 }
 
 impl Default for Settings {
@@ -114,8 +155,11 @@ impl Default for Settings {
                 is_auto_open_last_project: true,
                 is_enable_log_to_file: false,
                 locale: String::new(),
+                content_file_format: EContentFileFormat::Json,
             },
             engine_settings: EngineSettings::default(),
+            input_settings: InputSettings::default(),
+            physics_settings: PhysicsSettings::default(),
         }
     }
 }
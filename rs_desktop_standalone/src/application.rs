@@ -1,9 +1,8 @@
 use crate::{application_context::ApplicationContext, custom_event::ECustomEventType};
 use clap::Parser;
-use winit::{
-    application::ApplicationHandler, event_loop::EventLoop,
-    platform::windows::EventLoopBuilderExtWindows,
-};
+#[cfg(target_os = "windows")]
+use winit::platform::windows::EventLoopBuilderExtWindows;
+use winit::{application::ApplicationHandler, event_loop::EventLoop};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -26,9 +25,10 @@ impl Application {
     }
 
     pub fn run(mut self) -> anyhow::Result<()> {
-        let event_loop = EventLoop::<ECustomEventType>::with_user_event()
-            .with_any_thread(true)
-            .build()?;
+        let mut event_loop_builder = EventLoop::<ECustomEventType>::with_user_event();
+        #[cfg(target_os = "windows")]
+        event_loop_builder.with_any_thread(true);
+        let event_loop = event_loop_builder.build()?;
         let result = event_loop.run_app(&mut self);
         Ok(result?)
     }
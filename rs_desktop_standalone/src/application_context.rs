@@ -1,5 +1,5 @@
 use crate::custom_event::ECustomEventType;
-use rs_artifact::{EEndianType, artifact::ArtifactReader};
+use rs_artifact::artifact::ArtifactReader;
 use rs_egui_ext::egui_render::EGUIRenderOutput;
 use rs_engine::{
     engine::Engine,
@@ -53,8 +53,7 @@ impl ApplicationContext {
             Some(input_file) => input_file.as_ref().to_path_buf(),
             None => Path::new("main.rs").to_path_buf(),
         };
-        let artifact_reader =
-            ArtifactReader::new(&artifact_filepath, Some(EEndianType::Little)).ok();
+        let artifact_reader = ArtifactReader::open(&artifact_filepath).ok();
         let mut engine = rs_engine::engine::Engine::new(
             window_id,
             window,
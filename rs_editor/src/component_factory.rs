@@ -2,7 +2,8 @@ use rs_engine::{
     camera_component::CameraComponent,
     collision_componenet::CollisionComponent,
     components::{
-        component::Component, point_light_component::PointLightComponent,
+        character_controller_component::CharacterControllerComponent, component::Component,
+        joint_component::JointComponent, point_light_component::PointLightComponent,
         spot_light_component::SpotLightComponent, text_component::TextComponent,
     },
     scene_node::SceneComponent,
@@ -199,6 +200,49 @@ impl ComponentCreator for TextComponentCreator {
     }
 }
 
+struct CharacterControllerComponentCreator {}
+
+impl ComponentCreator for CharacterControllerComponentCreator {
+    fn create(
+        &self,
+        name: String,
+        transformation: glam::Mat4,
+    ) -> crate::error::Result<Box<dyn Component>> {
+        Ok(Box::new(CharacterControllerComponent::new(
+            name,
+            transformation,
+        )))
+    }
+
+    fn name(&self) -> &'static str {
+        type_name::<CharacterControllerComponent>()
+    }
+
+    fn display_name(&self) -> String {
+        t!("Character Controller").to_string()
+    }
+}
+
+struct JointComponentCreator {}
+
+impl ComponentCreator for JointComponentCreator {
+    fn create(
+        &self,
+        name: String,
+        transformation: glam::Mat4,
+    ) -> crate::error::Result<Box<dyn Component>> {
+        Ok(Box::new(JointComponent::new(name, transformation)))
+    }
+
+    fn name(&self) -> &'static str {
+        type_name::<JointComponent>()
+    }
+
+    fn display_name(&self) -> String {
+        t!("Joint").to_string()
+    }
+}
+
 pub struct ComponentFactory {
     creators: BTreeMap<String, Box<dyn ComponentCreator>>,
 }
@@ -215,6 +259,8 @@ impl ComponentFactory {
         let _ = this.register(Box::new(CollisionComponentCreator {}));
         let _ = this.register(Box::new(CameraComponentCreator {}));
         let _ = this.register(Box::new(TextComponentCreator {}));
+        let _ = this.register(Box::new(CharacterControllerComponentCreator {}));
+        let _ = this.register(Box::new(JointComponentCreator {}));
         return this;
     }
 
@@ -1,9 +1,18 @@
 use std::collections::HashMap;
 
-use crate::{content_edit::ContentEditable, project_context::ProjectContext};
+use crate::{
+    content_edit::{ContentEditable, UIContentPropertyEvent},
+    project_context::ProjectContext,
+    ui::{
+        content_item_property_view::ContentItemPropertyView,
+        misc::{render_combo_box, vec3_widget_mut},
+    },
+};
 use rs_artifact_types::asset::Asset;
 use rs_content::TypedContent;
 use rs_engine::content::level::Level;
+use rs_engine::content::level_streaming::{StreamingVolume, SubLevel};
+use rs_engine::url_extension::UrlExtension;
 use rs_foundation::new::SingleThreadMutType;
 use rs_model_loader::model_loader::ModelLoader;
 use rust_i18n::t;
@@ -26,6 +35,75 @@ impl ContentEditable for LevelContentEditable {
         ui.image(egui::include_image!("../../../Resource/Editor/level.svg"));
     }
 
+    fn render_detail(
+        &self,
+        content: SingleThreadMutType<Box<dyn rs_content::Content>>,
+        content_item_property_view: &mut ContentItemPropertyView,
+        ui: &mut egui::Ui,
+    ) -> Option<Box<dyn UIContentPropertyEvent>> {
+        let level = TypedContent::<Level>::new(content).ok()?;
+        let mut level = level.borrow_mut();
+        let level = &mut *level;
+
+        ui.separator();
+        ui.label(t!("Sub-Levels"));
+        let mut remove_index: Option<usize> = None;
+        for (index, sub_level) in level.sub_levels.iter_mut().enumerate() {
+            ui.push_id(index, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(sub_level.url.get_name_in_editor());
+                    if ui.button(t!("Remove")).clicked() {
+                        remove_index = Some(index);
+                    }
+                });
+                ui.checkbox(
+                    &mut sub_level.is_initially_loaded,
+                    t!("Is Initially Loaded"),
+                );
+                let mut is_streamed = sub_level.streaming_volume.is_some();
+                if ui
+                    .checkbox(&mut is_streamed, t!("Streaming Volume"))
+                    .changed()
+                {
+                    sub_level.streaming_volume = is_streamed.then(StreamingVolume::default);
+                }
+                if let Some(streaming_volume) = sub_level.streaming_volume.as_mut() {
+                    vec3_widget_mut(&mut streaming_volume.center, ui, t!("Center: "));
+                    vec3_widget_mut(&mut streaming_volume.half_extents, ui, t!("Half Extents: "));
+                    ui.add(
+                        egui::DragValue::new(&mut streaming_volume.unload_distance)
+                            .speed(0.1)
+                            .prefix(t!("Unload Distance: "))
+                            .range(0.0..=f32::MAX),
+                    );
+                }
+            });
+        }
+        if let Some(index) = remove_index {
+            level.sub_levels.remove(index);
+        }
+
+        let candidate_items: Vec<url::Url> = content_item_property_view
+            .level_urls
+            .iter()
+            .filter(|x| **x != level.url && !level.sub_levels.iter().any(|y| y.url == **x))
+            .cloned()
+            .collect();
+        let mut selected_url: Option<&url::Url> = None;
+        if render_combo_box(
+            ui,
+            t!("Add Sub-Level"),
+            None,
+            &mut selected_url,
+            &candidate_items,
+        ) {
+            if let Some(url) = selected_url {
+                level.sub_levels.push(SubLevel::new(url.clone()));
+            }
+        }
+        None
+    }
+
     fn open(
         &self,
         content: SingleThreadMutType<Box<dyn rs_content::Content>>,
@@ -105,6 +105,10 @@ impl ContentEditable for MaterialContentEditable {
         let material_editor = material_editor.borrow();
         let snarl = &material_editor.snarl;
         let paramenters = &material_editor.paramenters;
+        let material_functions = crate::material_resolve::collect_material_function_graphs(
+            &project_context.content_manager.borrow(),
+            &project_context.project.material_functions,
+        );
 
         let resolve_result = crate::material_resolve::resolve(
             project_context.module_manager.clone(),
@@ -113,6 +117,7 @@ impl ContentEditable for MaterialContentEditable {
             snarl,
             MaterialOptions::all(),
             paramenters,
+            &material_functions,
         )?;
 
         let mut shader_code: HashMap<MaterialOptions, String> = HashMap::new();
@@ -0,0 +1,99 @@
+use crate::content_edit::ContentEditable;
+use crate::ui::material_view::{EMaterialNodeType, EValueType, MaterialNode};
+use rs_content::TypedContent;
+use rs_engine::build_content_file_url;
+use rs_engine::content::material_function::MaterialFunction;
+use rs_foundation::new::{SingleThreadMut, SingleThreadMutType};
+use rs_localization::t;
+use std::collections::HashMap;
+
+pub(super) struct MaterialFunctionContentEditable {}
+
+impl ContentEditable for MaterialFunctionContentEditable {
+    fn render_thumbnail(
+        &self,
+        content: SingleThreadMutType<Box<dyn rs_content::Content>>,
+        project_folder_path: &std::path::Path,
+        thumbnail_cache: &mut crate::thumbnail_cache::ThumbnailCache,
+        expected_thumbnail_render_szie: egui::Vec2,
+        ui: &mut egui::Ui,
+    ) {
+        let _ = content;
+        let _ = project_folder_path;
+        let _ = thumbnail_cache;
+        let _ = expected_thumbnail_render_szie;
+        ui.image(egui::include_image!(
+            "../../../Resource/Editor/material.svg"
+        ));
+    }
+
+    fn open(
+        &self,
+        content: SingleThreadMutType<Box<dyn rs_content::Content>>,
+        editor_context: &mut crate::editor_context::EditorContext,
+        event_loop_window_target: &winit::event_loop::ActiveEventLoop,
+    ) {
+        let material_function =
+            TypedContent::<MaterialFunction>::new(content).expect("Matched type");
+        editor_context.open_material_function_window(event_loop_window_target, material_function);
+    }
+
+    fn export(
+        &self,
+        content: SingleThreadMutType<Box<dyn rs_content::Content>>,
+        artifact_asset_encoder: &mut rs_artifact::artifact::ArtifactAssetEncoder,
+        associated_assets: &mut HashMap<url::Url, Box<dyn rs_artifact_types::asset::Asset>>,
+        model_loader: &mut rs_model_loader::model_loader::ModelLoader,
+        project_context: &crate::project_context::ProjectContext,
+    ) -> anyhow::Result<()> {
+        // The graph is resolved inline by the materials that call the function.
+        let _ = project_context;
+        let _ = model_loader;
+        let _ = associated_assets;
+        let material_function =
+            TypedContent::<MaterialFunction>::new(content).expect("Matched type");
+        let material_function = material_function.borrow();
+        artifact_asset_encoder.encode_content(&*material_function);
+        Ok(())
+    }
+
+    fn create_default(
+        &self,
+        name: String,
+        editor_context: &mut crate::editor_context::EditorContext,
+    ) -> Option<Box<dyn rs_content::Content>> {
+        let content_url = build_content_file_url(&name).ok()?;
+        let asset_url = crate::material::Material::make_function_url(&content_url);
+        let material_function = MaterialFunction::new(content_url, asset_url);
+        let material_function_editor =
+            crate::material::Material::new(material_function.asset_url.clone(), {
+                let mut snarl = egui_snarl::Snarl::new();
+                snarl.insert_node(
+                    egui::pos2(0.0, 0.0),
+                    MaterialNode {
+                        node_type: EMaterialNodeType::FunctionInput(
+                            "Input".to_string(),
+                            EValueType::F32(0.0),
+                        ),
+                    },
+                );
+                snarl.insert_node(
+                    egui::pos2(300.0, 0.0),
+                    MaterialNode {
+                        node_type: EMaterialNodeType::FunctionOutput("Output".to_string()),
+                    },
+                );
+                snarl
+            });
+        let project_context = editor_context.project_context_mut()?;
+        project_context
+            .project
+            .material_functions
+            .push(SingleThreadMut::new(material_function_editor));
+        Some(Box::new(material_function))
+    }
+
+    fn display_name_for_creation(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(t!("Material Function"))
+    }
+}
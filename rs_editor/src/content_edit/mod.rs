@@ -3,8 +3,10 @@ mod curve;
 mod ibl;
 mod level;
 mod material;
+mod material_function;
 mod material_paramenters_collection;
 mod particle_system;
+mod prefab;
 mod render_target_2d;
 mod skeleton;
 mod skeleton_animation;
@@ -227,6 +229,14 @@ impl ContentEdit {
             TypeId::of::<rs_engine::content::render_target_2d::RenderTarget2D>(),
             Box::new(render_target_2d::RenderTarget2DContentEditable {}),
         );
+        editables.insert(
+            TypeId::of::<rs_engine::content::material_function::MaterialFunction>(),
+            Box::new(material_function::MaterialFunctionContentEditable {}),
+        );
+        editables.insert(
+            TypeId::of::<rs_engine::content::prefab::Prefab>(),
+            Box::new(prefab::PrefabContentEditable {}),
+        );
         ContentEdit { editables }
     }
 
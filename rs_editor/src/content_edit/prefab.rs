@@ -0,0 +1,71 @@
+use crate::{content_edit::ContentEditable, project_context::ProjectContext};
+use rs_artifact_types::asset::Asset;
+use rs_content::TypedContent;
+use rs_engine::{build_content_file_url, content::prefab::Prefab, scene_node::SceneNode};
+use rs_foundation::new::SingleThreadMutType;
+use rs_model_loader::model_loader::ModelLoader;
+use rust_i18n::t;
+use std::collections::HashMap;
+
+pub(super) struct PrefabContentEditable {}
+
+impl ContentEditable for PrefabContentEditable {
+    fn render_thumbnail(
+        &self,
+        content: SingleThreadMutType<Box<dyn rs_content::Content>>,
+        project_folder_path: &std::path::Path,
+        thumbnail_cache: &mut crate::thumbnail_cache::ThumbnailCache,
+        expected_thumbnail_render_szie: egui::Vec2,
+        ui: &mut egui::Ui,
+    ) {
+        let _ = content;
+        let _ = project_folder_path;
+        let _ = thumbnail_cache;
+        let _ = expected_thumbnail_render_szie;
+        ui.image(egui::include_image!("../../../Resource/Editor/model.svg"));
+    }
+
+    fn open(
+        &self,
+        content: SingleThreadMutType<Box<dyn rs_content::Content>>,
+        editor_context: &mut crate::editor_context::EditorContext,
+        event_loop_window_target: &winit::event_loop::ActiveEventLoop,
+    ) {
+        let _ = event_loop_window_target;
+        let prefab = TypedContent::<Prefab>::new(content).expect("Matched type");
+        let url = prefab.borrow().url.clone();
+        editor_context.instantiate_prefab(&url);
+    }
+
+    fn export(
+        &self,
+        content: SingleThreadMutType<Box<dyn rs_content::Content>>,
+        artifact_asset_encoder: &mut rs_artifact::artifact::ArtifactAssetEncoder,
+        associated_assets: &mut HashMap<url::Url, Box<dyn Asset>>,
+        model_loader: &mut ModelLoader,
+        project_context: &ProjectContext,
+    ) -> anyhow::Result<()> {
+        let _ = associated_assets;
+        let _ = project_context;
+        let _ = model_loader;
+        let prefab = TypedContent::<Prefab>::new(content).expect("Matched type");
+        let prefab = prefab.borrow();
+        artifact_asset_encoder.encode_content(&*prefab);
+        Ok(())
+    }
+
+    fn create_default(
+        &self,
+        name: String,
+        editor_context: &mut crate::editor_context::EditorContext,
+    ) -> Option<Box<dyn rs_content::Content>> {
+        let _ = editor_context;
+        let url = build_content_file_url(&name).ok()?;
+        let prefab = Prefab::new(url, SceneNode::new_sp("Scene".to_string()));
+        Some(Box::new(prefab))
+    }
+
+    fn display_name_for_creation(&self) -> Option<std::borrow::Cow<'static, str>> {
+        Some(t!("Prefab"))
+    }
+}
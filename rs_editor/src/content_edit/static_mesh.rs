@@ -1,12 +1,16 @@
 use crate::{
     content_edit::{ContentEditable, UIContentPropertyEvent, UIEvent},
+    import_queue::{EImportOutput, ImportTaskContext},
     load_content::types::{PreLoadingContext, SceneWrapper},
     project_context::ProjectContext,
     ui::content_item_property_view::ContentItemPropertyView,
 };
 use rs_artifact_types::asset::Asset;
 use rs_content::TypedContent;
-use rs_engine::{content::static_mesh::StaticMesh, resource_manager::ResourceManager};
+use rs_engine::{
+    content::static_mesh::StaticMesh, resource_manager::ResourceManager,
+    url_extension::UrlExtension,
+};
 use rs_foundation::new::{MultipleThreadMutType, SingleThreadMutType};
 use rs_metis::{cluster::ClusterCollection, vertex_position::VertexPosition};
 use rs_model_loader::model_loader::ModelLoader;
@@ -24,24 +28,25 @@ pub(super) struct StaticMeshContentEditable {}
 
 impl StaticMeshContentEditable {
     fn create_multi_res_mesh_cache_non_blocking(
-        project_context: &crate::project_context::ProjectContext,
+        editor_context: &mut crate::editor_context::EditorContext,
         static_mesh: &rs_engine::content::static_mesh::StaticMesh,
     ) -> anyhow::Result<()> {
         if !static_mesh.is_enable_multiresolution {
             return Ok(());
         }
-        rs_core_minimal::thread_pool::ThreadPool::global().spawn({
-            let mesh_cluster_dir = project_context.try_create_mesh_cluster_dir()?;
-            let static_mesh_artiface_url = static_mesh.asset_info.get_url();
-            move || match Self::create_multi_res_mesh_cache(
+        let mesh_cluster_dir = editor_context
+            .project_context()
+            .ok_or(anyhow::anyhow!("No project context"))?
+            .try_create_mesh_cluster_dir()?;
+        let static_mesh_artiface_url = static_mesh.asset_info.get_url();
+        let static_mesh_url = static_mesh.url.clone();
+        editor_context.push_import_task(static_mesh.url.get_name_in_editor(), move |context| {
+            Self::create_multi_res_mesh_cache(
                 &mesh_cluster_dir,
                 static_mesh_artiface_url,
-            ) {
-                Ok(_) => {}
-                Err(err) => {
-                    log::warn!("{}", err);
-                }
-            }
+                context,
+            )?;
+            Ok(EImportOutput::MultiResolutionMeshCache { static_mesh_url })
         });
         Ok(())
     }
@@ -49,7 +54,9 @@ impl StaticMeshContentEditable {
     fn create_multi_res_mesh_cache(
         mesh_cluster_dir: &std::path::Path,
         static_mesh_artiface_url: url::Url,
+        context: &ImportTaskContext,
     ) -> anyhow::Result<rs_metis::cluster::ClusterCollection> {
+        context.report("Building clusters", 0.0);
         let rm = ResourceManager::default();
         let static_mesh_result = rm.get_static_mesh(&static_mesh_artiface_url)?;
         let indices = &static_mesh_result.indexes;
@@ -67,6 +74,8 @@ impl StaticMeshContentEditable {
             vertices,
             gpmetis_program_path,
         )?;
+        context.check_canceled()?;
+        context.report("Saving clusters", 0.9);
 
         let filename = static_mesh_result.name.clone();
         let output_path = mesh_cluster_dir.join(filename);
@@ -131,15 +140,11 @@ impl ContentEditable for StaticMeshContentEditable {
         let Some(static_mesh) = content.downcast_mut::<StaticMesh>() else {
             return;
         };
-        let Some(project_context) = editor_context.project_context_mut() else {
-            return;
-        };
-
         match event {
             EEventType::UpdateStaticMeshEnableMultiresolution(new_value) => {
                 static_mesh.is_enable_multiresolution = *new_value;
                 if let Err(err) =
-                    Self::create_multi_res_mesh_cache_non_blocking(project_context, &static_mesh)
+                    Self::create_multi_res_mesh_cache_non_blocking(editor_context, &static_mesh)
                 {
                     log::warn!("{}", err);
                 }
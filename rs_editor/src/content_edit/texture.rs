@@ -155,30 +155,13 @@ impl ContentEditable for TextureContentEditable {
                 log::trace!("{:?}", result);
             }
             EEventType::IsCompressed(is_compressed) => {
-                if let Some(project_context) = editor_context.project_context() {
-                    if *is_compressed {
-                        let mut content_guard = content.borrow_mut();
-                        let Some(texture) = content_guard.downcast_mut::<TextureFile>() else {
-                            return;
-                        };
-                        match crate::editor_context::EditorContext::create_compressed_texture(
-                            project_context,
-                            &texture,
-                        ) {
-                            Ok(compressed_texture) => {
-                                if let Ok(dir) = project_context.try_create_derive_data_dir() {
-                                    let apply_result = editor_context.apply_compressed_texture(
-                                        texture,
-                                        compressed_texture,
-                                        &dir,
-                                    );
-                                    if let Err(err) = apply_result {
-                                        log::warn!("{}", err);
-                                    }
-                                }
-                            }
-                            Err(err) => log::warn!("{}", err),
-                        }
+                if *is_compressed {
+                    let content_guard = content.borrow();
+                    let Some(texture) = content_guard.downcast_ref::<TextureFile>() else {
+                        return;
+                    };
+                    if let Err(err) = editor_context.compress_texture_non_blocking(texture) {
+                        log::warn!("{}", err);
                     }
                 }
             }
@@ -0,0 +1,358 @@
+use anyhow::{Context, anyhow};
+use rs_artifact::{
+    EEndianType, compiled_shader::EShaderTarget, derive_data::compressed_texture::CompressedTexture,
+};
+use rs_core_minimal::file_type::TextureFileType;
+use rs_engine::content::material_function::MaterialFunction;
+use rs_render::cross_compile::RUNTIME_SHADER_TARGETS;
+use rs_texpress::TextureFormatType;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub const BUILTIN_COOK_PROFILE_NAMES: [&str; 5] = ["windows", "linux", "macos", "android", "ios"];
+pub const ANDROID_COOK_PROFILE_NAME: &str = "android";
+/// The texture formats which `Engine::init_resources` can load.
+const RUNTIME_TEXTURE_FORMATS: [TextureFormatType; 1] = [TextureFormatType::BC7];
+
+/// The platform whose runtime reads the cooked artifact.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ECookPlatform {
+    Windows,
+    Linux,
+    Macos,
+    Android,
+    Ios,
+}
+
+impl ECookPlatform {
+    /// The platform of the builtin cook profile named `name`.
+    pub fn from_profile_name(name: &str) -> Option<ECookPlatform> {
+        match name {
+            "windows" => Some(ECookPlatform::Windows),
+            "linux" => Some(ECookPlatform::Linux),
+            "macos" => Some(ECookPlatform::Macos),
+            ANDROID_COOK_PROFILE_NAME => Some(ECookPlatform::Android),
+            "ios" => Some(ECookPlatform::Ios),
+            _ => None,
+        }
+    }
+}
+
+/// The assets of the contents whose urls start with `url_prefix`
+/// are written to the shards of the chunk.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CookChunk {
+    pub name: String,
+    pub url_prefix: String,
+}
+
+/// Describes how the content of a project is cooked for a platform.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CookProfile {
+    pub name: String,
+    /// The options are validated against the runtime of the platform, only the common checks are done if it is none.
+    #[serde(default)]
+    pub platform: Option<ECookPlatform>,
+    pub endian_type: EEndianType,
+    /// The name of a `TextureFormatType`, such as `BC7`.
+    /// The textures marked as compressed are cooked to this format if it is not none.
+    #[serde(default)]
+    pub texture_format: Option<String>,
+    /// The targets which the shaders are cross compiled to,
+    /// derived from the render settings of the project if it is none.
+    #[serde(default)]
+    pub shader_targets: Option<Vec<EShaderTarget>>,
+    /// Leaves out the contents which are only used by the editor, such as material functions.
+    #[serde(default)]
+    pub is_strip_editor_only_data: bool,
+    /// In bytes, the assets are written to one artifact if it is none.
+    #[serde(default)]
+    pub max_shard_size: Option<u64>,
+    #[serde(default)]
+    pub chunks: Vec<CookChunk>,
+}
+
+impl Default for CookProfile {
+    fn default() -> Self {
+        Self {
+            name: "default".to_string(),
+            platform: None,
+            endian_type: EEndianType::Little,
+            texture_format: None,
+            shader_targets: None,
+            is_strip_editor_only_data: false,
+            max_shard_size: None,
+            chunks: vec![],
+        }
+    }
+}
+
+impl CookProfile {
+    pub fn builtin(name: &str) -> Option<CookProfile> {
+        // The runtime can not load ASTC yet, the textures of the mobile platforms are not compressed.
+        // Metal compiles the naga modules at runtime, no shader is precompiled for the apple platforms.
+        let platform = ECookPlatform::from_profile_name(name)?;
+        let (texture_format, shader_targets) = match platform {
+            ECookPlatform::Windows | ECookPlatform::Linux => {
+                (Some(TextureFormatType::BC7), vec![EShaderTarget::SpirV])
+            }
+            ECookPlatform::Macos => (Some(TextureFormatType::BC7), vec![]),
+            ECookPlatform::Android => (None, vec![EShaderTarget::SpirV]),
+            ECookPlatform::Ios => (None, vec![]),
+        };
+        Some(CookProfile {
+            name: name.to_string(),
+            platform: Some(platform),
+            endian_type: EEndianType::Little,
+            texture_format: texture_format.map(|x| format!("{:?}", x)),
+            shader_targets: Some(shader_targets),
+            is_strip_editor_only_data: true,
+            max_shard_size: None,
+            chunks: vec![],
+        })
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<CookProfile> {
+        let file = std::fs::File::open(path).context(anyhow!("Can not open file {:?}", path))?;
+        Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+    }
+
+    pub fn get_texture_format(&self) -> anyhow::Result<Option<TextureFormatType>> {
+        let Some(texture_format) = &self.texture_format else {
+            return Ok(None);
+        };
+        rs_texpress::env::compressonatorcli_supported::all()
+            .into_iter()
+            .chain(rs_texpress::env::ktxcli_supported::all())
+            .find(|x| format!("{:?}", x) == *texture_format)
+            .map(Some)
+            .ok_or(anyhow!("Unsupported texture format: {}", texture_format))
+    }
+
+    /// Rejects the options which the runtime can not read: the Android runtime only reads one
+    /// little endian artifact, the cooked textures are only loaded if they are DDS in BC7
+    /// and the shaders if they are SPIR-V.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.platform == Some(ECookPlatform::Android) {
+            if self.max_shard_size.is_some() || !self.chunks.is_empty() {
+                return Err(anyhow!(
+                    "The artifact of {} can not be sharded, the runtime only reads one artifact",
+                    self.name
+                ));
+            }
+            if !matches!(self.endian_type, EEndianType::Little) {
+                return Err(anyhow!(
+                    "The artifact of {} must be little endian, the runtime only reads little endian",
+                    self.name
+                ));
+            }
+        }
+        if let Some(texture_format) = self.get_texture_format()?
+            && !RUNTIME_TEXTURE_FORMATS.contains(&texture_format)
+        {
+            return Err(anyhow!(
+                "The runtime can not load the textures compressed to {:?}, expect one of {:?}",
+                texture_format,
+                RUNTIME_TEXTURE_FORMATS
+            ));
+        }
+        if let Some(shader_target) = self
+            .shader_targets
+            .iter()
+            .flatten()
+            .find(|x| !RUNTIME_SHADER_TARGETS.contains(x))
+        {
+            return Err(anyhow!(
+                "The runtime can not load the shaders compiled to {:?}, expect one of {:?}",
+                shader_target,
+                RUNTIME_SHADER_TARGETS
+            ));
+        }
+        Ok(())
+    }
+
+    /// The chunk of the first rule matching `url`.
+    pub fn get_chunk(&self, url: &url::Url) -> Option<String> {
+        self.chunks
+            .iter()
+            .find(|x| url.as_str().starts_with(&x.url_prefix))
+            .map(|x| x.name.clone())
+    }
+}
+
+pub fn is_editor_only(content: &dyn rs_content::Content) -> bool {
+    content.downcast_ref::<MaterialFunction>().is_some()
+}
+
+pub fn get_texture_file_type(texture_format: TextureFormatType) -> TextureFileType {
+    if rs_texpress::env::compressonatorcli_supported::dds_compressed().contains(&texture_format) {
+        TextureFileType::Dds
+    } else {
+        TextureFileType::Ktx
+    }
+}
+
+/// The extension of the file written by rs_texpress, DDS if compressonator writes the format and KTX2 otherwise.
+pub fn get_texture_file_extension(texture_format: TextureFormatType) -> &'static str {
+    match get_texture_file_type(texture_format) {
+        TextureFileType::Dds => "dds",
+        TextureFileType::Ktx => "ktx2",
+    }
+}
+
+/// Compresses the image at `path` with rs_texpress, `tmp_file` is the intermediate output.
+pub fn compress_texture(
+    texture_format: TextureFormatType,
+    path: &Path,
+    tmp_file: &Path,
+    image_reference: url::Url,
+    url: Option<url::Url>,
+) -> anyhow::Result<CompressedTexture> {
+    if !path.is_file() {
+        return Err(anyhow!("Not a file, {:?}", path));
+    }
+    let data = rs_texpress::env::TexpressEnv::global_mut(|env| {
+        let _ = env.convert(texture_format, &path, &tmp_file, None::<&Path>)?;
+        let data = std::fs::read(&tmp_file)?;
+        Result::<Vec<u8>, anyhow::Error>::Ok(data)
+    })?;
+    if tmp_file.exists() {
+        let _ = std::fs::remove_file(&tmp_file);
+    }
+    let url = match url {
+        Some(url) => url,
+        None => {
+            let key = rs_core_minimal::misc::get_sha256_from_buf(&data);
+            rs_engine::build_derive_data_url(key)?
+        }
+    };
+    Ok(CompressedTexture {
+        url,
+        source_url: image_reference,
+        data,
+        ty: get_texture_file_type(texture_format),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        ANDROID_COOK_PROFILE_NAME, BUILTIN_COOK_PROFILE_NAMES, CookChunk, CookProfile,
+        ECookPlatform, get_texture_file_extension, get_texture_file_type,
+    };
+    use rs_artifact::{EEndianType, compiled_shader::EShaderTarget};
+    use rs_core_minimal::file_type::TextureFileType;
+    use rs_texpress::TextureFormatType;
+
+    #[test]
+    fn test_cook_profile() {
+        for name in BUILTIN_COOK_PROFILE_NAMES {
+            let cook_profile = CookProfile::builtin(name).unwrap();
+            assert!(cook_profile.validate().is_ok());
+            assert!(cook_profile.shader_targets.is_some());
+            assert_eq!(
+                cook_profile.platform,
+                ECookPlatform::from_profile_name(name)
+            );
+        }
+        assert_eq!(
+            CookProfile::builtin("windows").unwrap().shader_targets,
+            Some(vec![EShaderTarget::SpirV])
+        );
+        assert_eq!(
+            CookProfile::builtin("macos").unwrap().shader_targets,
+            Some(vec![])
+        );
+        assert_eq!(
+            CookProfile::builtin("windows")
+                .unwrap()
+                .get_texture_format()
+                .unwrap(),
+            Some(TextureFormatType::BC7)
+        );
+        assert_eq!(
+            CookProfile::builtin(ANDROID_COOK_PROFILE_NAME)
+                .unwrap()
+                .get_texture_format()
+                .unwrap(),
+            None
+        );
+        assert!(CookProfile::builtin("unknown").is_none());
+        assert_eq!(
+            get_texture_file_type(TextureFormatType::BC7),
+            TextureFileType::Dds
+        );
+        assert_eq!(
+            get_texture_file_type(TextureFormatType::ASTC_6x6_ldr),
+            TextureFileType::Ktx
+        );
+        assert_eq!(get_texture_file_extension(TextureFormatType::BC7), "dds");
+
+        let mut cook_profile: CookProfile = serde_json::from_str(
+            r#"{
+                "name": "demo",
+                "endian_type": "Big",
+                "texture_format": "BC3",
+                "chunks": [{ "name": "level", "url_prefix": "content://Content/Levels/" }]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            cook_profile.get_texture_format().unwrap(),
+            Some(TextureFormatType::BC3)
+        );
+        assert!(cook_profile.shader_targets.is_none());
+        assert!(cook_profile.platform.is_none());
+        assert_eq!(
+            cook_profile.chunks,
+            vec![CookChunk {
+                name: "level".to_string(),
+                url_prefix: "content://Content/Levels/".to_string(),
+            }]
+        );
+        let url = url::Url::parse("content://Content/Levels/Main").unwrap();
+        assert_eq!(cook_profile.get_chunk(&url), Some("level".to_string()));
+        let url = url::Url::parse("content://Content/Textures/Grid").unwrap();
+        assert_eq!(cook_profile.get_chunk(&url), None);
+        assert!(cook_profile.validate().is_err());
+        cook_profile.texture_format = Some("BC7".to_string());
+        assert!(cook_profile.validate().is_ok());
+
+        let mut cook_profile = CookProfile::default();
+        cook_profile.texture_format = Some("BC8".to_string());
+        assert!(cook_profile.get_texture_format().is_err());
+        assert!(cook_profile.validate().is_err());
+
+        let mut cook_profile = CookProfile::builtin(ANDROID_COOK_PROFILE_NAME).unwrap();
+        cook_profile.max_shard_size = Some(1024);
+        assert!(cook_profile.validate().is_err());
+        cook_profile.max_shard_size = None;
+        cook_profile.chunks = vec![CookChunk {
+            name: "level".to_string(),
+            url_prefix: "content://Content/Levels/".to_string(),
+        }];
+        assert!(cook_profile.validate().is_err());
+        cook_profile.texture_format = Some(format!("{:?}", TextureFormatType::ASTC_6x6_ldr));
+        cook_profile.chunks.clear();
+        assert!(cook_profile.validate().is_err());
+        cook_profile.texture_format = None;
+        assert!(cook_profile.validate().is_ok());
+        cook_profile.shader_targets = Some(vec![EShaderTarget::SpirV, EShaderTarget::GlslEs]);
+        assert!(cook_profile.validate().is_err());
+        cook_profile.shader_targets = None;
+        cook_profile.endian_type = EEndianType::Big;
+        assert!(cook_profile.validate().is_err());
+
+        // The checks of Android follow the platform instead of the name.
+        let mut cook_profile: CookProfile = serde_json::from_str(
+            r#"{ "name": "phone", "platform": "Android", "endian_type": "Little" }"#,
+        )
+        .unwrap();
+        assert_eq!(cook_profile.platform, Some(ECookPlatform::Android));
+        cook_profile.max_shard_size = Some(1024);
+        assert!(cook_profile.validate().is_err());
+        cook_profile.platform = None;
+        cook_profile.name = ANDROID_COOK_PROFILE_NAME.to_string();
+        assert!(cook_profile.validate().is_ok());
+    }
+}
@@ -75,6 +75,8 @@ pub struct DataSource {
     pub debug_shading_type: rs_render::global_uniform::EDebugShadingType,
     pub debug_flags: rs_engine::player_viewport::DebugFlags,
     pub is_debug_texture_view_open: bool,
+    pub is_history_view_open: bool,
+    pub is_import_queue_view_open: bool,
     pub is_simulate_real_time: bool,
     pub model_scene_view_data: model_scene_view::DataSource,
     pub opened_curve: Option<TypedContent<Curve>>,
@@ -116,6 +118,8 @@ impl DataSource {
             is_object_property_view_open: false,
             debug_shading_type: rs_render::global_uniform::EDebugShadingType::None,
             is_debug_texture_view_open: false,
+            is_history_view_open: false,
+            is_import_queue_view_open: false,
             is_simulate_real_time: false,
             debug_flags: rs_engine::player_viewport::DebugFlags::empty(),
             model_scene_view_data: model_scene_view::DataSource::default(),
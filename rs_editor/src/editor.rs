@@ -1,19 +1,52 @@
 use crate::{
+    content_edit::ContentEdit,
+    cook::{BUILTIN_COOK_PROFILE_NAMES, CookProfile},
     custom_event::ECustomEventType,
     editor_context::{EWindowType, EditorContext},
+    project_context::ProjectContext,
     windows_manager::WindowsManager,
 };
 use anyhow::anyhow;
 use clap::*;
 use rs_foundation::new::{SingleThreadMut, SingleThreadMutType};
-use winit::{
-    application::ApplicationHandler, dpi::PhysicalSize, event_loop::EventLoop,
-    platform::windows::EventLoopBuilderExtWindows,
-};
+use rs_model_loader::model_loader::ModelLoader;
+#[cfg(target_os = "windows")]
+use winit::platform::windows::EventLoopBuilderExtWindows;
+use winit::{application::ApplicationHandler, dpi::PhysicalSize, event_loop::EventLoop};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
-struct Args {}
+struct Args {
+    #[command(subcommand)]
+    command: Option<ECommand>,
+}
+
+#[derive(Subcommand, Debug)]
+enum ECommand {
+    /// Resolves and validates all materials of a project without opening the editor.
+    CompileMaterials {
+        /// The path of the project file.
+        project_file_path: std::path::PathBuf,
+    },
+    /// Cooks the content of a project into an artifact without opening the editor,
+    /// the path of the artifact is printed to stdout.
+    Cook {
+        /// The path of the project file.
+        project_file_path: std::path::PathBuf,
+        /// A builtin cook profile: windows, linux, macos, android or ios.
+        #[arg(long)]
+        profile: Option<String>,
+        /// A cook profile in JSON, used instead of `profile`.
+        #[arg(long)]
+        profile_file: Option<std::path::PathBuf>,
+        /// Defaults to the build folder of the project.
+        #[arg(short, long)]
+        output_dir: Option<std::path::PathBuf>,
+        /// In bytes, overrides the one of the cook profile.
+        #[arg(long)]
+        max_shard_size: Option<u64>,
+    },
+}
 
 pub struct Editor {}
 
@@ -30,16 +63,88 @@ impl Editor {
     }
 
     fn run_internal(self) -> anyhow::Result<()> {
-        let _ = Args::try_parse()?;
-        self.run_app()?;
+        let args = Args::try_parse()?;
+        match args.command {
+            Some(ECommand::CompileMaterials { project_file_path }) => {
+                Self::compile_materials(&project_file_path)
+            }
+            Some(ECommand::Cook {
+                project_file_path,
+                profile,
+                profile_file,
+                output_dir,
+                max_shard_size,
+            }) => {
+                let mut cook_profile = match (profile_file, profile) {
+                    (Some(profile_file), _) => CookProfile::load(&profile_file)?,
+                    (None, Some(profile)) => CookProfile::builtin(&profile).ok_or(anyhow!(
+                        "Unknown cook profile {}, expect one of {:?}",
+                        profile,
+                        BUILTIN_COOK_PROFILE_NAMES
+                    ))?,
+                    (None, None) => CookProfile::default(),
+                };
+                if max_shard_size.is_some() {
+                    cook_profile.max_shard_size = max_shard_size;
+                }
+                Self::cook(&project_file_path, &cook_profile, output_dir.as_deref())
+            }
+            None => self.run_app(),
+        }
+    }
+
+    fn compile_materials(project_file_path: &std::path::Path) -> anyhow::Result<()> {
+        let report = crate::material_compile::compile_materials(project_file_path)?;
+        for error in &report.errors {
+            eprintln!("{}", error);
+        }
+        println!(
+            "{} shaders compiled, {} errors",
+            report.num_compiled,
+            report.errors.len()
+        );
+        if report.is_success() {
+            Ok(())
+        } else {
+            Err(anyhow!("Failed to compile materials"))
+        }
+    }
+
+    fn cook(
+        project_file_path: &std::path::Path,
+        cook_profile: &CookProfile,
+        output_dir: Option<&std::path::Path>,
+    ) -> anyhow::Result<()> {
+        let mut project_context = ProjectContext::open_headless(project_file_path)?;
+        let mut model_loader = ModelLoader::new();
+        let mut content_edit = ContentEdit::new();
+        let output_dir = match output_dir {
+            Some(output_dir) => output_dir.to_path_buf(),
+            None => project_context.try_create_build_dir()?,
+        };
+        let artifact_file_path = output_dir.join("main.rs");
+        let artifact_index = project_context.cook(
+            &mut model_loader,
+            &mut content_edit,
+            cook_profile,
+            &artifact_file_path,
+        )?;
+        for shard in &artifact_index.shards {
+            eprintln!(
+                "{}: {:?}, {} bytes",
+                shard.file_name, shard.chunk, shard.size
+            );
+        }
+        println!("{}", artifact_file_path.display());
         Ok(())
     }
 
     fn run_app(self) -> anyhow::Result<()> {
         let window_manager = SingleThreadMut::new(WindowsManager::new());
-        let event_loop = EventLoop::<ECustomEventType>::with_user_event()
-            .with_any_thread(true)
-            .build()?;
+        let mut event_loop_builder = EventLoop::<ECustomEventType>::with_user_event();
+        #[cfg(target_os = "windows")]
+        event_loop_builder.with_any_thread(true);
+        let event_loop = event_loop_builder.build()?;
         let event_loop_proxy = event_loop.create_proxy();
         let mut app = EditorApplicationHandler {
             editor_context: None,
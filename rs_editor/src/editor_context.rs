@@ -5,17 +5,20 @@ use crate::{
     custom_event::{ECustomEventType, EFileDialogType},
     data_source::{AssetFile, AssetFolder, DataSource},
     editor_ui::{EditorUI, GizmoEvent},
+    import_queue::{EImportOutput, ImportQueue, ImportTaskContext, ImportTaskId, ModelResources},
+    load_content::types::SceneWrapper,
     material_resolve,
     project::Project,
     project_context::{EFolderUpdateType, ProjectContext},
     standalone_simulation_options::{
         DEFAULT_SERVER_ADDR, MultiplePlayerOptions, StandaloneSimulationType,
     },
+    transaction::{self, TransactionHistory},
     ui::{
         asset_view,
         blend_animations_ui_window::BlendAnimationUIWindow,
         component_edit::ComponentEdit,
-        content_browser, debug_textures_view,
+        content_browser, debug_textures_view, history_view, import_queue_view, level_view,
         material_ui_window::MaterialUIWindow,
         material_view::{self},
         media_ui_window::MediaUIWindow,
@@ -44,7 +47,8 @@ use rs_core_minimal::{
 #[cfg(any(feature = "plugin_shared_crate"))]
 use rs_engine::plugin::plugin_crate::Plugin;
 use rs_engine::{
-    build_built_in_resouce_url,
+    actor::Actor,
+    build_built_in_resouce_url, build_content_file_url,
     camera_component::CameraComponent,
     collision_componenet::CollisionComponent,
     components::{
@@ -53,7 +57,8 @@ use rs_engine::{
     },
     content::{
         blend_animations::BlendAnimations,
-        content_file_type::{EContentFileType, find_content_by_type},
+        content_file_type::{EContentFileType, collect_typed_contents, find_content_by_type},
+        level::Level,
         texture::TextureFile,
     },
     directional_light::DirectionalLight,
@@ -200,6 +205,8 @@ pub struct EditorContext {
     component_factory: SingleThreadMutType<ComponentFactory>,
     component_edit: SingleThreadMutType<ComponentEdit>,
     content_edit: SingleThreadMutType<ContentEdit>,
+    transaction_history: TransactionHistory,
+    import_queue: ImportQueue,
 }
 
 impl EditorContext {
@@ -389,6 +396,8 @@ impl EditorContext {
             component_factory,
             component_edit,
             content_edit,
+            transaction_history: TransactionHistory::default(),
+            import_queue: ImportQueue::new(),
         };
 
         if let Some(file_path) = last_project_path {
@@ -457,8 +466,12 @@ impl EditorContext {
                 self.egui_winit_state.egui_ctx().memory_mut(|writer| {
                     writer.data.clear();
                 });
-                if let Some(ctx) = &mut self.project_context {
-                    ctx.hot_reload.get_library_reload().lock().unwrap().clear();
+                if let Some(hot_reload) = self
+                    .project_context
+                    .as_ref()
+                    .and_then(|x| x.hot_reload.as_ref())
+                {
+                    hot_reload.get_library_reload().lock().unwrap().clear();
                 }
                 event_loop_window_target.exit();
             }
@@ -672,6 +685,10 @@ impl EditorContext {
             return;
         };
         let contnet_manager = project_context.content_manager.clone();
+        let material_functions = material_resolve::collect_material_function_graphs(
+            &contnet_manager.borrow(),
+            &project_context.project.material_functions,
+        );
         for material_editor in project_context.project.materials.clone() {
             let material_editor = material_editor.borrow();
             let material_url = material_editor
@@ -686,6 +703,7 @@ impl EditorContext {
                 snarl,
                 MaterialOptions::all(),
                 paramenters,
+                &material_functions,
             );
             let Ok(resolve_result) = resolve_result else {
                 continue;
@@ -840,6 +858,7 @@ impl EditorContext {
                 self.standalone_ui_windows
                     .retain(|x| !close_windows.contains(&x.get_window_id()));
 
+                let mut is_material_function_updated = false;
                 if let Some(material_ui_window) = self.material_ui_window.as_mut() {
                     if let Some(event) = &mut material_ui_window.material_view.event {
                         match event {
@@ -849,6 +868,9 @@ impl EditorContext {
                                     &material.borrow(),
                                     resolve_result,
                                 );
+                                if let Some(project_context) = self.project_context.as_ref() {
+                                    project_context.update_material_dependencies();
+                                }
                             }
                             material_view::EEventType::AddParamenter(material) => {
                                 let mut material = material.borrow_mut();
@@ -880,9 +902,15 @@ impl EditorContext {
                             material_view::EEventType::ChangeParamenterDefaultValue(_) => {
                                 material_ui_window.material_view.viewer.is_updated = true;
                             }
+                            material_view::EEventType::UpdateFunction(_) => {
+                                is_material_function_updated = true;
+                            }
                         }
                     }
                 }
+                if is_material_function_updated {
+                    self.hotreload_material();
+                }
 
                 match window_type {
                     EWindowType::Main => {
@@ -915,7 +943,11 @@ impl EditorContext {
     fn try_create_plugin(&mut self) -> anyhow::Result<Box<dyn Plugin>> {
         if let Some(project_context) = self.project_context.as_mut() {
             project_context.reload()?;
-            let lib = project_context.hot_reload.get_library_reload();
+            let lib = project_context
+                .hot_reload
+                .as_ref()
+                .ok_or(anyhow!("The plugin is not reloaded"))?
+                .get_library_reload();
             let lib = lib.lock().unwrap();
             let func = lib.load_symbol::<rs_engine::plugin::signature::CreatePlugin>(
                 rs_engine::plugin::symbol_name::CREATE_PLUGIN,
@@ -1154,6 +1186,26 @@ impl EditorContext {
             self.save_current_project();
         }
 
+        if !self.egui_winit_state.egui_ctx().wants_keyboard_input() {
+            if self.keys_detector.is_keys_pressed(
+                &[KeyCode::ControlLeft, KeyCode::ShiftLeft, KeyCode::KeyZ],
+                false,
+            ) || self
+                .keys_detector
+                .is_keys_pressed(&[KeyCode::ControlLeft, KeyCode::KeyY], false)
+            {
+                self.keys_detector
+                    .consume_keys(&[KeyCode::KeyZ, KeyCode::KeyY]);
+                self.redo_level_transaction();
+            } else if self
+                .keys_detector
+                .is_keys_pressed(&[KeyCode::ControlLeft, KeyCode::KeyZ], false)
+            {
+                self.keys_detector.consume_key(&KeyCode::KeyZ);
+                self.undo_level_transaction();
+            }
+        }
+
         if self.keys_detector.is_keys_pressed(&[KeyCode::F5], true) {
             self.open_standalone_window(event_loop_window_target, StandaloneSimulationType::Single);
         }
@@ -1299,6 +1351,7 @@ impl EditorContext {
         let _span = tracy_client::span!();
         let project_context = ProjectContext::open(&file_path)?;
         file_manager::set_current_project_dir(&project_context.get_project_folder_path());
+        self.import_queue.cancel_all();
 
         #[cfg(feature = "plugin_shared_crate")]
         {
@@ -1436,12 +1489,148 @@ impl EditorContext {
     ) {
         self.data_source.level = Some(level.clone());
         let content_map = content_manager.content_map();
-        level
-            .borrow_mut()
-            .initialize(&mut self.engine, &content_map, &mut self.player_viewport);
+        {
+            let mut level = level.borrow_mut();
+            level.initialize(&mut self.engine, &content_map, &mut self.player_viewport);
+            level.load_initial_sub_levels(
+                &mut self.engine,
+                &content_map,
+                &mut self.player_viewport,
+            );
+        }
+        match transaction::level_snapshot(&level.borrow()) {
+            Ok(snapshot) => self.transaction_history.reset(snapshot),
+            Err(err) => log::warn!("{}", err),
+        }
         log::trace!("Open level");
     }
 
+    /// Adds a new instance of the prefab to the opened level.
+    pub(crate) fn instantiate_prefab(&mut self, url: &url::Url) {
+        let Some(level) = self.data_source.level.clone() else {
+            log::warn!("No level is opened");
+            return;
+        };
+        let Some(project_context) = self.project_context.as_ref() else {
+            return;
+        };
+        let content_map = project_context.content_manager.borrow().content_map();
+        let result = level.borrow_mut().instantiate_prefab(
+            url,
+            &mut self.engine,
+            &content_map,
+            &mut self.player_viewport,
+        );
+        match result {
+            Ok(_) => self.transaction_history.mark_pending("Instantiate Prefab"),
+            Err(err) => log::warn!("{}", err),
+        }
+    }
+
+    /// Saves the tree of the actor as a new prefab, the actor becomes an instance of it.
+    fn create_prefab(&mut self, actor: SingleThreadMutType<Actor>) {
+        let names = self.get_all_content_names();
+        let Some(project_context) = self.project_context.as_ref() else {
+            return;
+        };
+        let actor = actor.borrow();
+        let name = make_unique_name(names, &actor.name);
+        let Ok(url) = build_content_file_url(&name) else {
+            return;
+        };
+        let mut scene_node =
+            match rs_engine::content::prefab::to_prefab_tree(&actor.scene_node.borrow()) {
+                Ok(scene_node) => scene_node,
+                Err(err) => {
+                    log::warn!("{}", err);
+                    return;
+                }
+            };
+        scene_node.set_prefab_instance(None);
+        let prefab =
+            rs_engine::content::prefab::Prefab::new(url.clone(), SingleThreadMut::new(scene_node));
+        let errors = project_context
+            .content_manager
+            .borrow_mut()
+            .append(vec![SingleThreadMut::new(Box::new(prefab))]);
+        if !errors.is_empty() {
+            log::warn!("Failed to create prefab {}", url);
+            return;
+        }
+        rs_engine::content::prefab::make_instance(&mut actor.scene_node.borrow_mut(), url);
+    }
+
+    /// Writes the instance back to its prefab, the other instances are rebuilt with their overrides.
+    fn apply_prefab(&mut self, actor: SingleThreadMutType<Actor>) {
+        let Some(level) = self.data_source.level.clone() else {
+            return;
+        };
+        let Some(project_context) = self.project_context.as_ref() else {
+            return;
+        };
+        let content_manager = project_context.content_manager.clone();
+        let content_map = content_manager.borrow().content_map();
+        let Some(instance) = actor
+            .borrow()
+            .scene_node
+            .borrow()
+            .prefab_instance()
+            .cloned()
+        else {
+            return;
+        };
+        let Some(file) = content_map.get(&instance.url).cloned() else {
+            log::warn!("Can not find prefab {}", instance.url);
+            return;
+        };
+        let Ok(prefab) = TypedContent::<rs_engine::content::prefab::Prefab>::new(file.clone())
+        else {
+            return;
+        };
+        // The changes of the other instances are relative to the old prefab.
+        level.borrow().update_prefab_overrides(&content_map);
+        {
+            let scene_node = match rs_engine::content::prefab::to_prefab_tree(
+                &actor.borrow().scene_node.borrow(),
+            ) {
+                Ok(scene_node) => SingleThreadMut::new(scene_node),
+                Err(err) => {
+                    log::warn!("{}", err);
+                    return;
+                }
+            };
+            let mut prefab = prefab.borrow_mut();
+            // The placement of the instance is not a part of the prefab.
+            let (root_prefab_instance, root_transformation) = {
+                let root = prefab.scene_node.borrow();
+                let root_transformation = root.component().get_transformation();
+                (root.prefab_instance().cloned(), root_transformation)
+            };
+            {
+                let mut scene_node = scene_node.borrow_mut();
+                scene_node.set_prefab_instance(root_prefab_instance);
+                scene_node
+                    .component_mut()
+                    .set_transformation(root_transformation);
+            }
+            prefab.scene_node = scene_node;
+        }
+        // Only the placement of the applied instance is left as an override.
+        if let Err(err) =
+            rs_engine::content::prefab::update_overrides(&actor.borrow().scene_node, &content_map)
+        {
+            log::warn!("{}", err);
+        }
+        if let Err(err) = content_manager.borrow().save(file) {
+            log::warn!("{}", err);
+        }
+        level.borrow_mut().resolve_prefab_instances(&content_map);
+        self.editor_ui.object_property_view.selected_object = None;
+        let mut level = level.borrow_mut();
+        level.initialize(&mut self.engine, &content_map, &mut self.player_viewport);
+        level.load_initial_sub_levels(&mut self.engine, &content_map, &mut self.player_viewport);
+    }
+
     fn process_custom_event(
         &mut self,
         event: &ECustomEventType,
@@ -1506,7 +1695,12 @@ impl EditorContext {
         }
     }
 
-    fn open_model_file(&mut self, file_path: PathBuf) -> anyhow::Result<()> {
+    /// Adds the contents and an actor of the model, the resources are converted by the import task.
+    fn open_model_file(
+        &mut self,
+        file_path: PathBuf,
+        resources: ModelResources,
+    ) -> anyhow::Result<()> {
         let exist_names = self.get_all_content_names();
         let project_context = self
             .project_context
@@ -1560,13 +1754,7 @@ impl EditorContext {
         if let Some(skeleton) = load_result.skeleton {
             add_files.push(SingleThreadMut::new(Box::new(skeleton)));
         }
-        Self::content_load_resources(
-            &mut self.engine,
-            &mut self.model_loader,
-            project_context,
-            add_files.clone(),
-            &mut *self.content_edit.borrow_mut(),
-        );
+        resources.add_to(self.engine.get_resource_manager());
         {
             project_context
                 .content_manager
@@ -1657,6 +1845,19 @@ impl EditorContext {
         if let Some(active_level) = self.data_source.level.clone() {
             let mut active_level = active_level.borrow_mut();
             active_level.set_physics_simulate(self.data_source.is_simulate_real_time);
+            if let Some(project_context) = self
+                .project_context
+                .as_ref()
+                .filter(|_| !active_level.sub_levels.is_empty())
+            {
+                let content_map = project_context.content_manager.borrow().content_map();
+                active_level.update_streaming(
+                    self.player_viewport.camera.get_world_location(),
+                    &mut self.engine,
+                    &content_map,
+                    &mut self.player_viewport,
+                );
+            }
             active_level.tick(
                 self.engine.get_game_time(),
                 &mut self.engine,
@@ -1698,6 +1899,8 @@ impl EditorContext {
             }
         }
 
+        self.process_import_queue();
+
         crate::ui::misc::ui_begin(&mut self.egui_winit_state, window);
 
         self.process_ui_event(window, event_loop_window_target);
@@ -1768,16 +1971,16 @@ impl EditorContext {
 
         let mut compile_commands = vec![];
         for buildin_shader in buildin_shaders {
-            let description = buildin_shader.get_shader_description();
-            let name = buildin_shader.get_name();
-            let processed_code = rs_shader_compiler_core::pre_process::pre_process(
-                &description.shader_path,
-                description.include_dirs.iter(),
-                description.definitions.iter(),
-            )?;
-            let filepath = output_path.join(name);
-            std::fs::write(filepath.clone(), processed_code)
-                .context(anyhow!("Can not write to file {:?}", filepath))?;
+            for (name, description) in buildin_shader.get_permutation_descriptions() {
+                let processed_code = rs_shader_compiler_core::pre_process::pre_process(
+                    &description.shader_path,
+                    description.include_dirs.iter(),
+                    description.definitions.iter(),
+                )?;
+                let filepath = output_path.join(name);
+                std::fs::write(filepath.clone(), processed_code)
+                    .context(anyhow!("Can not write to file {:?}", filepath))?;
+            }
 
             let compile_command = buildin_shader.as_ref().to_compile_command();
             compile_commands.push(compile_command);
@@ -1900,6 +2103,23 @@ impl EditorContext {
         };
         let content_manager = project_context.content_manager.clone();
         let module_manager = project_context.module_manager.clone();
+        let asset = {
+            let url = &open_material.borrow().asset_url;
+            log::trace!("open material: {}", url.to_string());
+            project_context
+                .project
+                .materials
+                .iter()
+                .find(|x| &x.borrow().url == url)
+                .cloned()
+        };
+        let Some(asset) = asset else {
+            log::warn!("Miss material");
+            return;
+        };
+        asset
+            .borrow_mut()
+            .set_associated_material(open_material.clone());
         let mut ui_window = MaterialUIWindow::new(
             self.editor_ui.egui_context.clone(),
             &mut *self.window_manager.borrow_mut(),
@@ -1908,28 +2128,57 @@ impl EditorContext {
             content_manager,
             module_manager,
             open_material.borrow().url.clone(),
+            asset,
         )
         .expect("Should be opened");
-        {
-            let url = &open_material.borrow().asset_url;
-            log::trace!("open material: {}", url.to_string());
-            if let Some(project_context) = &self.project_context {
-                if let Some(asset) = project_context
-                    .project
-                    .materials
-                    .iter()
-                    .find(|x| &x.borrow().url == url)
-                {
-                    asset
-                        .borrow_mut()
-                        .set_associated_material(open_material.clone());
-                    ui_window.data_source.current_open_material = Some(asset.clone());
-                };
-            }
-        }
-        assert!(ui_window.data_source.current_open_material.is_some());
         ui_window.material_view.viewer.texture_urls = self.collect_textures();
         ui_window.material_view.viewer.virtual_texture_urls = self.collect_virtual_textures();
+        ui_window.material_view.viewer.material_functions = self.collect_material_functions();
+        ui_window.material_view.viewer.is_updated = true;
+        self.material_ui_window = Some(ui_window);
+    }
+
+    pub(crate) fn open_material_function_window(
+        &mut self,
+        event_loop_window_target: &winit::event_loop::ActiveEventLoop,
+        open_material_function: TypedContent<
+            rs_engine::content::material_function::MaterialFunction,
+        >,
+    ) {
+        let Some(project_context) = &mut self.project_context else {
+            return;
+        };
+        let content_manager = project_context.content_manager.clone();
+        let module_manager = project_context.module_manager.clone();
+        let asset = {
+            let url = &open_material_function.borrow().asset_url;
+            log::trace!("open material function: {}", url.to_string());
+            project_context
+                .project
+                .material_functions
+                .iter()
+                .find(|x| &x.borrow().url == url)
+                .cloned()
+        };
+        let Some(asset) = asset else {
+            log::warn!("Miss material function");
+            return;
+        };
+        let mut ui_window = MaterialUIWindow::new(
+            self.editor_ui.egui_context.clone(),
+            &mut *self.window_manager.borrow_mut(),
+            event_loop_window_target,
+            &mut self.engine,
+            content_manager,
+            module_manager,
+            open_material_function.borrow().url.clone(),
+            asset,
+        )
+        .expect("Should be opened");
+        ui_window.material_view.is_material_function = true;
+        ui_window.material_view.viewer.texture_urls = self.collect_textures();
+        ui_window.material_view.viewer.virtual_texture_urls = self.collect_virtual_textures();
+        ui_window.material_view.viewer.material_functions = self.collect_material_functions();
         ui_window.material_view.viewer.is_updated = true;
         self.material_ui_window = Some(ui_window);
     }
@@ -2051,6 +2300,16 @@ impl EditorContext {
         self.blend_animation_ui_window = Some(ui_window);
     }
 
+    fn collect_material_functions(&self) -> material_resolve::MaterialFunctionGraphs {
+        let Some(project_context) = &self.project_context else {
+            return HashMap::new();
+        };
+        material_resolve::collect_material_function_graphs(
+            &project_context.content_manager.borrow(),
+            &project_context.project.material_functions,
+        )
+    }
+
     fn collect_textures(&self) -> Vec<url::Url> {
         let Some(project_context) = &self.project_context else {
             return vec![];
@@ -2119,8 +2378,15 @@ impl EditorContext {
             &mut self.engine,
             &mut content_manager.borrow_mut(),
             &mut *self.content_edit.borrow_mut(),
+            &self.transaction_history,
+            &self.import_queue,
         );
 
+        let transaction_name = Self::transaction_name(&click_event);
+        if let Some(transaction_name) = transaction_name {
+            self.transaction_history.mark_pending(transaction_name);
+        }
+
         self.process_top_menu_event(window, click_event.menu_event, event_loop_window_target);
         self.process_click_asset_event(click_event.click_aseet, event_loop_window_target);
         if let Some(event) = click_event.content_property_view_event.take() {
@@ -2136,6 +2402,272 @@ impl EditorContext {
         self.process_project_settings_event(click_event.project_settings_event);
         self.process_object_property_view_event(click_event.object_property_view_event);
         self.process_gizmo_event(click_event.gizmo_event);
+        self.process_history_view_event(click_event.history_view_event);
+        self.process_import_queue_view_event(click_event.import_queue_view_event);
+
+        // Continuous edits such as dragging the gizmo are merged into one transaction.
+        let is_pointer_down = self
+            .egui_winit_state
+            .egui_ctx()
+            .input(|input| input.pointer.any_down());
+        if !is_pointer_down {
+            self.commit_level_transaction();
+        }
+    }
+
+    fn transaction_name(click_event: &crate::editor_ui::ClickEvent) -> Option<&'static str> {
+        if let Some(event) = &click_event.click_actor {
+            let name = match event {
+                level_view::EClickEventType::SingleClickActor(_)
+                | level_view::EClickEventType::SingleClickSceneNode(_)
+                | level_view::EClickEventType::DirectionalLight(_)
+                | level_view::EClickEventType::CopyPath(_, _) => None,
+                level_view::EClickEventType::CreateActor => Some("Create Actor"),
+                level_view::EClickEventType::CreateCameraHere => Some("Create Camera"),
+                level_view::EClickEventType::DeleteActor(_) => Some("Delete Actor"),
+                level_view::EClickEventType::DuplicateActor(_) => Some("Duplicate Actor"),
+                level_view::EClickEventType::CreatePrefab(_) => Some("Create Prefab"),
+                level_view::EClickEventType::ApplyPrefab(_) => Some("Apply to Prefab"),
+                level_view::EClickEventType::CreateDirectionalLight => {
+                    Some("Create Directional Light")
+                }
+                level_view::EClickEventType::DeleteDirectionalLight(_) => {
+                    Some("Delete Directional Light")
+                }
+                level_view::EClickEventType::DeleteNode(_, _) => Some("Delete Component"),
+                level_view::EClickEventType::CreateCameraComponent(_)
+                | level_view::EClickEventType::CreateSceneComponent(_)
+                | level_view::EClickEventType::CreateStaticMeshComponent(_)
+                | level_view::EClickEventType::CreateCollisionComponent(_, _)
+                | level_view::EClickEventType::CreateSpotLightComponent(_)
+                | level_view::EClickEventType::CreatePointLightComponent(_)
+                | level_view::EClickEventType::CreateComponent(_, _) => Some("Create Component"),
+            };
+            if name.is_some() {
+                return name;
+            }
+        }
+        if let Some(event) = &click_event.object_property_view_event {
+            return Some(match event {
+                object_property_view::EEventType::UpdateDirectionalLight(..) => {
+                    "Edit Directional Light"
+                }
+                object_property_view::EEventType::ChangeName(_, _) => "Rename",
+                object_property_view::EEventType::ComponentEvent(_) => "Edit Component",
+            });
+        }
+        if let Some(event) = &click_event.gizmo_event {
+            if event.gizmo_result.is_some() {
+                return Some("Transform");
+            }
+        }
+        None
+    }
+
+    fn commit_level_transaction(&mut self) {
+        let Some(name) = self.transaction_history.take_pending() else {
+            return;
+        };
+        let Some(level) = self.data_source.level.as_ref() else {
+            return;
+        };
+        if let Some(project_context) = self.project_context.as_ref() {
+            let content_map = project_context.content_manager.borrow().content_map();
+            level.borrow().update_prefab_overrides(&content_map);
+        }
+        match transaction::level_snapshot(&level.borrow()) {
+            Ok(snapshot) => {
+                if self.transaction_history.record(&name, snapshot) {
+                    log::trace!("Record transaction: {}", name);
+                }
+            }
+            Err(err) => {
+                log::warn!("{}", err);
+            }
+        }
+    }
+
+    fn undo_level_transaction(&mut self) {
+        self.commit_level_transaction();
+        if let Some(snapshot) = self.transaction_history.undo() {
+            self.apply_level_snapshot(&snapshot);
+        }
+    }
+
+    fn redo_level_transaction(&mut self) {
+        self.commit_level_transaction();
+        if let Some(snapshot) = self.transaction_history.redo() {
+            self.apply_level_snapshot(&snapshot);
+        }
+    }
+
+    fn apply_level_snapshot(&mut self, snapshot: &str) {
+        let Some(level) = self.data_source.level.clone() else {
+            return;
+        };
+        let Some(project_context) = self.project_context.as_ref() else {
+            return;
+        };
+        let restored_level = match transaction::restore_level(snapshot) {
+            Ok(restored_level) => restored_level,
+            Err(err) => {
+                log::warn!("{}", err);
+                return;
+            }
+        };
+        // The selected object belongs to the replaced level.
+        self.editor_ui.object_property_view.selected_object = None;
+        let content_manager = project_context.content_manager.clone();
+        let content_manager = content_manager.borrow();
+        let content_map = content_manager.content_map();
+        let mut level = level.borrow_mut();
+        *level = restored_level;
+        level.initialize(&mut self.engine, &content_map, &mut self.player_viewport);
+        level.load_initial_sub_levels(&mut self.engine, &content_map, &mut self.player_viewport);
+        let debug_show_flag = if self.data_source.is_show_debug {
+            rs_engine::debug_show_flag::DebugShowFlag::all()
+        } else {
+            rs_engine::debug_show_flag::DebugShowFlag::empty()
+        };
+        level.set_debug_show_flag(debug_show_flag);
+    }
+
+    fn process_history_view_event(&mut self, event: Option<history_view::EClickEventType>) {
+        let Some(event) = event else {
+            return;
+        };
+        match event {
+            history_view::EClickEventType::Undo => {
+                self.undo_level_transaction();
+            }
+            history_view::EClickEventType::Redo => {
+                self.redo_level_transaction();
+            }
+            history_view::EClickEventType::Jump(position) => {
+                self.commit_level_transaction();
+                if let Some(snapshot) = self.transaction_history.jump_to(position) {
+                    self.apply_level_snapshot(&snapshot);
+                }
+            }
+        }
+    }
+
+    fn process_import_queue_view_event(
+        &mut self,
+        event: Option<import_queue_view::EClickEventType>,
+    ) {
+        let Some(event) = event else {
+            return;
+        };
+        match event {
+            import_queue_view::EClickEventType::Cancel(id) => self.import_queue.cancel(id),
+            import_queue_view::EClickEventType::CancelAll => self.import_queue.cancel_all(),
+            import_queue_view::EClickEventType::ClearDone => self.import_queue.clear_done(),
+        }
+    }
+
+    /// Runs the job on the worker threads and shows the progress in the import queue view.
+    pub(crate) fn push_import_task<F>(&mut self, name: impl Into<String>, job: F) -> ImportTaskId
+    where
+        F: FnOnce(&ImportTaskContext) -> anyhow::Result<EImportOutput> + Send + 'static,
+    {
+        self.data_source.is_import_queue_view_open = true;
+        self.import_queue.push(name, job)
+    }
+
+    fn import_model_file(&mut self, file_path: PathBuf) {
+        let name = file_path
+            .file_name()
+            .map(|x| x.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let Some(project_context) = self.project_context.as_ref() else {
+            return;
+        };
+        let asset_folder_path = project_context.get_asset_folder_path();
+        self.push_import_task(name, move |context| {
+            let asset_reference = file_path
+                .strip_prefix(&asset_folder_path)?
+                .to_str()
+                .ok_or(anyhow!("Incorrect path: {:?}", file_path))?
+                .to_string();
+            context.report("Loading scene", 0.0);
+            let scene = ModelLoader::load_scene_from_file(&file_path)?;
+            let resources = ModelResources::from_scene(&scene, &asset_reference, context)?;
+            context.check_canceled()?;
+            Ok(EImportOutput::Model {
+                file_path,
+                scene: SceneWrapper(Arc::new(scene)),
+                resources,
+            })
+        });
+    }
+
+    /// Applies the outputs of the finished import tasks on the main thread.
+    fn process_import_queue(&mut self) {
+        for (id, output) in self.import_queue.process_messages() {
+            if let Err(err) = self.apply_import_output(output) {
+                log::warn!("{}", err);
+                self.import_queue.fail(id, err);
+            }
+        }
+    }
+
+    fn apply_import_output(&mut self, output: EImportOutput) -> anyhow::Result<()> {
+        match output {
+            EImportOutput::Model {
+                file_path,
+                scene,
+                resources,
+            } => {
+                let scene = Arc::try_unwrap(scene.0)
+                    .map_err(|_| anyhow!("The scene is still in use, {:?}", file_path))?;
+                self.model_loader.cache_scene(&file_path, scene);
+                self.open_model_file(file_path, resources)
+            }
+            EImportOutput::CompressedTexture {
+                texture_url,
+                compressed_texture,
+            } => {
+                let project_context = self
+                    .project_context
+                    .as_ref()
+                    .ok_or(anyhow!("Project context is null"))?;
+                let derive_data_dir = project_context.try_create_derive_data_dir()?;
+                let content = project_context
+                    .content_manager
+                    .borrow()
+                    .content_map()
+                    .get(&texture_url)
+                    .cloned()
+                    .ok_or(anyhow!("Can not find {}", texture_url))?;
+                let mut content = content.borrow_mut();
+                let texture = content
+                    .downcast_mut::<TextureFile>()
+                    .ok_or(anyhow!("{} is not a texture", texture_url))?;
+                self.apply_compressed_texture(texture, compressed_texture, &derive_data_dir)
+            }
+            EImportOutput::MultiResolutionMeshCache { static_mesh_url } => {
+                let project_context = self
+                    .project_context
+                    .as_ref()
+                    .ok_or(anyhow!("Project context is null"))?;
+                let content = project_context
+                    .content_manager
+                    .borrow()
+                    .content_map()
+                    .get(&static_mesh_url)
+                    .cloned()
+                    .ok_or(anyhow!("Can not find {}", static_mesh_url))?;
+                Self::content_load_resources(
+                    &mut self.engine,
+                    &mut self.model_loader,
+                    project_context,
+                    vec![content],
+                    &mut *self.content_edit.borrow_mut(),
+                );
+                Ok(())
+            }
+        }
     }
 
     fn get_all_content_names(&self) -> Vec<String> {
@@ -2171,6 +2703,10 @@ impl EditorContext {
 
     fn save_current_project(&self) {
         if let Some(project_context) = self.project_context.as_ref() {
+            if let Some(level) = self.data_source.level.as_ref() {
+                let content_map = project_context.content_manager.borrow().content_map();
+                level.borrow().update_prefab_overrides(&content_map);
+            }
             let save_status = project_context.save();
             log::trace!("Save project: {:?}", save_status);
         }
@@ -2278,6 +2814,12 @@ impl EditorContext {
                 top_menu::EWindowType::DebugTexture => {
                     self.data_source.is_debug_texture_view_open = true;
                 }
+                top_menu::EWindowType::History => {
+                    self.data_source.is_history_view_open = true;
+                }
+                top_menu::EWindowType::ImportQueue => {
+                    self.data_source.is_import_queue_view_open = true;
+                }
             },
             top_menu::EClickEventType::Tool(tool_type) => match tool_type {
                 top_menu::EToolType::DebugShader => {
@@ -2608,6 +3150,12 @@ impl EditorContext {
                     &mut self.player_viewport,
                 );
             }
+            crate::ui::level_view::EClickEventType::CreatePrefab(actor) => {
+                self.create_prefab(actor);
+            }
+            crate::ui::level_view::EClickEventType::ApplyPrefab(actor) => {
+                self.apply_prefab(actor);
+            }
             crate::ui::level_view::EClickEventType::CreateSpotLightComponent(parent_node) => {
                 let Some(project_context) = self.project_context.as_mut() else {
                     return;
@@ -2763,8 +3311,7 @@ impl EditorContext {
                 }
             }
             content_browser::EClickEventType::OpenFile(file) => {
-                self.editor_ui.content_item_property_view.content = Some(file.clone());
-                self.data_source.is_content_item_property_view_open = true;
+                self.show_content_item_property_view(file.clone());
                 {
                     let content_edit = self.content_edit.clone();
                     let mut content_edit = content_edit.borrow_mut();
@@ -2783,15 +3330,55 @@ impl EditorContext {
                 };
                 let content_manager = project_context.content_manager.clone();
                 let mut content_manager = content_manager.borrow_mut();
-                content_manager.delete_contents(vec![content_file]);
+                let references =
+                    match content_manager.delete_contents(vec![content_file.clone()], false) {
+                        Ok(_) => return,
+                        Err(rs_content_manager::error::Error::Referenced(references)) => references,
+                        Err(err) => {
+                            log::warn!("{}", err);
+                            return;
+                        }
+                    };
+                let description = references
+                    .iter()
+                    .map(|(url, references)| {
+                        let names = references
+                            .iter()
+                            .map(|x| x.get_name_in_editor())
+                            .collect::<Vec<String>>();
+                        format!(
+                            "{} is referenced by {}",
+                            url.get_name_in_editor(),
+                            names.join(", ")
+                        )
+                    })
+                    .collect::<Vec<String>>();
+                let result = rfd::MessageDialog::new()
+                    .set_level(rfd::MessageLevel::Warning)
+                    .set_title(t!("Delete"))
+                    .set_description(format!(
+                        "{}\n{}",
+                        description.join("\n"),
+                        t!("Delete anyway?")
+                    ))
+                    .set_buttons(rfd::MessageButtons::YesNo)
+                    .show();
+                if result != rfd::MessageDialogResult::Yes {
+                    return;
+                }
+                if let Err(err) = content_manager.delete_contents(vec![content_file], true) {
+                    log::warn!("{}", err);
+                }
             }
             content_browser::EClickEventType::Rename(content_file_type, new_name) => {
                 let names = self.get_all_content_names();
                 if names.contains(&new_name) {
                     return;
                 }
-                {
-                    content_file_type.borrow_mut().set_name(new_name);
+                let mut new_url = content_file_type.borrow().get_url();
+                new_url.set_name_in_editor(new_name);
+                if !self.move_content(content_file_type.clone(), new_url) {
+                    return;
                 }
                 if let Some(mut material) = content_file_type
                     .borrow_mut()
@@ -2812,10 +3399,62 @@ impl EditorContext {
                         }
                     }
                 }
+                if let Some(mut material_function) =
+                    content_file_type
+                        .borrow_mut()
+                        .downcast_mut::<rs_engine::content::material_function::MaterialFunction>()
+                {
+                    if let Some(project_context) = self.project_context.as_mut() {
+                        let material_function_asset = project_context
+                            .project
+                            .material_functions
+                            .iter()
+                            .find(|x| x.borrow().url == material_function.asset_url);
+                        if let Some(material_function_asset) = material_function_asset.cloned() {
+                            let mut material_function_asset = material_function_asset.borrow_mut();
+                            crate::material::Material::on_function_url_changed(
+                                &mut material_function,
+                                &mut material_function_asset,
+                            );
+                        }
+                    }
+                }
+            }
+            content_browser::EClickEventType::MoveFile(url, folder) => {
+                let Some(project_context) = self.project_context.as_ref() else {
+                    return;
+                };
+                let content_manager = project_context.content_manager.borrow();
+                let Some(file) = content_manager.content_map().get(&url).cloned() else {
+                    return;
+                };
+                let Some(folder_url) = content_manager
+                    .content_folders()
+                    .get(&folder)
+                    .map(|x| x.get_url())
+                else {
+                    return;
+                };
+                drop(content_manager);
+                let name = file.borrow().get_name();
+                let mut new_url = folder_url.clone();
+                new_url.set_path(&format!("{}/{}", folder_url.path(), name));
+                self.move_content(file, new_url);
+            }
+            content_browser::EClickEventType::FixUpRedirectors => {
+                let Some(project_context) = self.project_context.as_ref() else {
+                    return;
+                };
+                let errors = project_context
+                    .content_manager
+                    .borrow_mut()
+                    .fix_up_redirectors();
+                for (url, err) in errors {
+                    log::warn!("Fail to fix up {}: {}", url, err);
+                }
             }
             content_browser::EClickEventType::Detail(file) => {
-                self.editor_ui.content_item_property_view.content = Some(file.clone());
-                self.data_source.is_content_item_property_view_open = true;
+                self.show_content_item_property_view(file);
             }
             content_browser::EClickEventType::CreateContent(type_id) => {
                 let content_edit = self.content_edit.clone();
@@ -2896,10 +3535,106 @@ impl EditorContext {
                 todo!()
             }
             asset_view::EClickItemType::ImportAsActor(asset_file) => {
-                let result = self.open_model_file(asset_file.path.clone());
-                log::trace!("{:?}", result);
+                self.import_model_file(asset_file.path.clone());
+            }
+        }
+    }
+
+    fn show_content_item_property_view(&mut self, file: EContentFileType) {
+        let url = file.borrow().get_url();
+        let property_view = &mut self.editor_ui.content_item_property_view;
+        property_view.content = Some(file.clone());
+        if let Some(project_context) = self.project_context.as_ref() {
+            let mut content_manager = project_context.content_manager.borrow_mut();
+            content_manager.update_dependencies(&file);
+            property_view.dependencies = content_manager.find_dependencies(&url);
+            property_view.references = content_manager.find_references(&url);
+            property_view.level_urls =
+                collect_typed_contents::<Level>(content_manager.content_files())
+                    .iter()
+                    .map(|x| x.borrow().url.clone())
+                    .collect();
+        }
+        self.data_source.is_content_item_property_view_open = true;
+    }
+
+    /// Moves or renames the content and points the contents and the material graphs that refer to it to the new url,
+    /// the rewritten contents are loaded again and the opened level is initialized again if it is rewritten.
+    /// Returns false if the content is not moved.
+    fn move_content(&mut self, content: EContentFileType, new_url: url::Url) -> bool {
+        let Some(project_context) = self.project_context.as_ref() else {
+            return false;
+        };
+        let content_manager = project_context.content_manager.clone();
+        let old_url = content.borrow().get_url();
+        let rewritten_urls = match content_manager
+            .borrow_mut()
+            .move_content(content, new_url.clone())
+        {
+            Ok(rewritten_urls) => rewritten_urls,
+            Err(err) => {
+                log::warn!("Fail to move {} to {}: {}", old_url, new_url, err);
+                return false;
             }
+        };
+        for url in rewritten_urls.iter() {
+            log::trace!("Rewrite {} in {}", old_url, url);
+        }
+        let mut is_graph_rewritten = false;
+        for graph in project_context
+            .project
+            .materials
+            .iter()
+            .chain(project_context.project.material_functions.iter())
+        {
+            is_graph_rewritten |= graph.borrow_mut().replace_content_url(&old_url, &new_url);
+        }
+        project_context.update_material_dependencies();
+        // The rewritten contents are new objects, their resources are loaded again.
+        let content_map = content_manager.borrow().content_map();
+        let opened_level_url = self
+            .data_source
+            .level
+            .as_ref()
+            .map(|x| x.borrow().url.clone());
+        let reload_files = rewritten_urls
+            .iter()
+            .filter(|x| **x != new_url && Some(*x) != opened_level_url.as_ref())
+            .filter_map(|x| content_map.get(x).cloned())
+            .collect::<Vec<EContentFileType>>();
+        if !reload_files.is_empty() {
+            Self::content_load_resources(
+                &mut self.engine,
+                &mut self.model_loader,
+                project_context,
+                reload_files,
+                &mut *self.content_edit.borrow_mut(),
+            );
+        }
+        if is_graph_rewritten {
+            self.hotreload_material();
         }
+        if let Some(current_folder) = &self.data_source.content_data_source.current_folder {
+            let relative_path = current_folder.relative_path().clone();
+            self.data_source.content_data_source.current_folder = content_manager
+                .borrow()
+                .content_folders()
+                .get(&relative_path)
+                .cloned();
+        }
+        let Some(level) = self.data_source.level.clone() else {
+            return true;
+        };
+        let level_url = level.borrow().url.clone();
+        if !rewritten_urls.contains(&level_url) {
+            return true;
+        }
+        let content_map = content_manager.borrow().content_map();
+        self.editor_ui.object_property_view.selected_object = None;
+        let mut level = level.borrow_mut();
+        level.initialize(&mut self.engine, &content_map, &mut self.player_viewport);
+        level.load_initial_sub_levels(&mut self.engine, &content_map, &mut self.player_viewport);
+        true
     }
 
     fn process_content_item_property_view_event2(
@@ -2981,44 +3716,50 @@ impl EditorContext {
         return Ok(());
     }
 
-    pub(crate) fn create_compressed_texture(
-        project_context: &ProjectContext,
+    /// Compresses the texture in the background, the compressed texture is applied when the task is finished.
+    pub(crate) fn compress_texture_non_blocking(
+        &mut self,
         texture: &TextureFile,
-    ) -> Result<CompressedTexture, anyhow::Error> {
-        let image_reference = texture.image_reference.as_ref().ok_or(anyhow!(""))?;
+    ) -> Result<ImportTaskId, anyhow::Error> {
+        let project_context = self
+            .project_context
+            .as_ref()
+            .ok_or(anyhow!("Project context is null"))?;
+        let image_reference = texture.image_reference.clone().ok_or(anyhow!(""))?;
         let path = project_context
-            .get_asset_path_by_url(image_reference)
+            .get_asset_path_by_url(&image_reference)
             .canonicalize_slash()?;
         #[cfg(target_os = "windows")]
         let tmp_file = project_context
             .try_create_tmp_dir()?
-            .join("compressed_texture.dds");
+            .join(format!("{}.dds", texture.url.get_name_in_editor()));
         #[cfg(not(target_os = "windows"))]
         unimplemented!();
-        assert!(path.is_file());
-        let compressed_texture_file_data = rs_texpress::env::TexpressEnv::global_mut(|env| {
-            let _ = env.convert(
-                rs_texpress::TextureFormatType::BC7,
-                &path,
-                &tmp_file,
-                None::<&Path>,
-            )?;
-            let data = std::fs::read(&tmp_file)?;
-            Result::<Vec<u8>, anyhow::Error>::Ok(data)
-        })?;
-        let key = rs_core_minimal::misc::get_sha256_from_buf(&compressed_texture_file_data);
-        let derive_data_url = rs_engine::build_derive_data_url(key)?;
-        let derive_data_source_url = image_reference.clone();
-        let compressed_texture = CompressedTexture {
-            url: derive_data_url,
-            source_url: derive_data_source_url,
-            data: compressed_texture_file_data,
-            ty: rs_core_minimal::file_type::TextureFileType::Dds,
-        };
-        if tmp_file.exists() {
-            let _ = std::fs::remove_file(&tmp_file);
-        }
-        Ok(compressed_texture)
+        let texture_url = texture.url.clone();
+        let id = self.push_import_task(texture.url.get_name_in_editor(), move |context| {
+            context.report("Compressing texture", 0.0);
+            let compressed_texture =
+                Self::create_compressed_texture(&path, &tmp_file, image_reference)?;
+            Ok(EImportOutput::CompressedTexture {
+                texture_url,
+                compressed_texture,
+            })
+        });
+        Ok(id)
+    }
+
+    fn create_compressed_texture(
+        path: &Path,
+        tmp_file: &Path,
+        image_reference: url::Url,
+    ) -> Result<CompressedTexture, anyhow::Error> {
+        crate::cook::compress_texture(
+            rs_texpress::TextureFormatType::BC7,
+            path,
+            tmp_file,
+            image_reference,
+            None,
+        )
     }
 
     fn process_object_property_view_event(
@@ -2,7 +2,9 @@ use crate::component_factory::ComponentFactory;
 use crate::content_edit::{ContentEdit, UIContentPropertyEvent};
 use crate::data_source::{DataSource, MeshItem};
 use crate::editor_ui::load::ImageLoader;
+use crate::import_queue::ImportQueue;
 use crate::thumbnail_cache::ThumbnailCache;
+use crate::transaction::TransactionHistory;
 use crate::ui::component_edit::ComponentEdit;
 use crate::ui::content_item_property_view::ContentItemPropertyView;
 use crate::ui::debug_textures_view::{self, DebugTexturesView};
@@ -10,8 +12,8 @@ use crate::ui::gizmo_view::GizmoView;
 use crate::ui::object_property_view::{self, ESelectedObjectType, ObjectPropertyView};
 use crate::ui::top_menu::TopMenu;
 use crate::ui::{
-    asset_view, console_cmds_view, content_browser, curve_view, gizmo_settings, level_view,
-    project_settings, top_menu,
+    asset_view, console_cmds_view, content_browser, curve_view, gizmo_settings, history_view,
+    import_queue_view, level_view, project_settings, top_menu,
 };
 use egui::*;
 use rs_content::Content;
@@ -54,6 +56,8 @@ pub struct ClickEvent {
     pub object_property_view_event: Option<object_property_view::EEventType>,
     pub content_property_view_event: Option<ContentPropertyViewEvent>,
     pub gizmo_event: Option<GizmoEvent>,
+    pub history_view_event: Option<history_view::EClickEventType>,
+    pub import_queue_view_event: Option<import_queue_view::EClickEventType>,
 }
 
 pub struct EditorUI {
@@ -114,6 +118,8 @@ impl EditorUI {
         engine: &mut Engine,
         content_manager: &mut ContentManager,
         content_edit: &mut ContentEdit,
+        transaction_history: &TransactionHistory,
+        import_queue: &ImportQueue,
     ) -> ClickEvent {
         let mut click = ClickEvent::default();
 
@@ -230,6 +236,22 @@ impl EditorUI {
             );
         }
 
+        let window = Self::new_window(t!("History"), "History", data_source.input_mode);
+        click.history_view_event = history_view::draw(
+            window,
+            context,
+            &mut data_source.is_history_view_open,
+            transaction_history,
+        );
+
+        let window = Self::new_window(t!("Import Queue"), "Import Queue", data_source.input_mode);
+        click.import_queue_view_event = import_queue_view::draw(
+            window,
+            context,
+            &mut data_source.is_import_queue_view_open,
+            import_queue,
+        );
+
         Self::new_window(
             t!("Content Property"),
             "Content Property",
@@ -0,0 +1,386 @@
+use crate::load_content::types::SceneWrapper;
+use rs_artifact::derive_data::compressed_texture::CompressedTexture;
+use rs_engine::content::{
+    skeleton::Skeleton, skeleton_animation::SkeletonAnimation, skeleton_mesh::SkeletonMesh,
+    static_mesh::AssetInfo,
+};
+use rs_engine::resource_manager::ResourceManager;
+use rs_engine::thread_pool::ThreadPool;
+use rs_model_loader::model_loader::ModelLoader;
+use std::{
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, Sender},
+    },
+};
+
+pub type ImportTaskId = u64;
+
+/// The resources of an imported model, converted from the scene on the worker thread.
+#[derive(Default)]
+pub struct ModelResources {
+    pub static_meshes: Vec<rs_artifact::static_mesh::StaticMesh>,
+    pub skin_meshes: Vec<rs_artifact::skin_mesh::SkinMesh>,
+    pub skeleton: Option<rs_artifact::skeleton::Skeleton>,
+    pub skeleton_animations: Vec<rs_artifact::skeleton_animation::SkeletonAnimation>,
+}
+
+impl ModelResources {
+    /// Converts the meshes, the skeleton and the animations of the scene,
+    /// the urls match the contents that are created for the model.
+    pub fn from_scene(
+        scene: &rs_assimp::scene::Scene<'static>,
+        asset_reference: &str,
+        context: &ImportTaskContext,
+    ) -> anyhow::Result<ModelResources> {
+        let mut resources = ModelResources::default();
+        let mesh_count = scene.meshes.len().max(1) as f32;
+        for (index, mesh) in scene.meshes.iter().enumerate() {
+            context.check_canceled()?;
+            context.report("Converting meshes", index as f32 / mesh_count);
+            let mesh = mesh.borrow();
+            let name = mesh.name.clone();
+            if mesh.bones.is_empty() {
+                let asset_info = AssetInfo {
+                    relative_path: PathBuf::from(asset_reference),
+                    path: name.clone(),
+                };
+                resources
+                    .static_meshes
+                    .push(ModelLoader::to_artifact_static_mesh(
+                        &mesh,
+                        name,
+                        asset_info.get_url(),
+                    ));
+            } else {
+                let url = SkeletonMesh::make_asset_url(asset_reference, &name);
+                resources
+                    .skin_meshes
+                    .push(ModelLoader::to_artifact_skin_mesh(&mesh, name, url));
+            }
+        }
+        context.check_canceled()?;
+        context.report("Converting animations", 1.0);
+        if let (Some(armature), Some(root_node)) =
+            (scene.armatures.values().next(), scene.root_node.clone())
+        {
+            let (name, url) = {
+                let armature = armature.borrow();
+                (
+                    armature.name.clone(),
+                    Skeleton::make_asset_url(asset_reference, &armature.path),
+                )
+            };
+            resources.skeleton = Some(ModelLoader::to_artifact_skeleton(
+                armature.clone(),
+                root_node,
+                name,
+                url,
+            ));
+        }
+        for animation in scene.animations.iter() {
+            let url = SkeletonAnimation::make_asset_url(asset_reference, &animation.name);
+            resources
+                .skeleton_animations
+                .push(ModelLoader::to_artifact_skeleton_animation(
+                    animation,
+                    animation.name.clone(),
+                    url,
+                ));
+        }
+        Ok(resources)
+    }
+
+    pub fn add_to(self, resource_manager: &ResourceManager) {
+        for static_mesh in self.static_meshes {
+            resource_manager.add_static_mesh(static_mesh.url.clone(), Arc::new(static_mesh));
+        }
+        for skin_mesh in self.skin_meshes {
+            resource_manager.add_skin_mesh(skin_mesh.url.clone(), Arc::new(skin_mesh));
+        }
+        if let Some(skeleton) = self.skeleton {
+            resource_manager.add_skeleton(skeleton.url.clone(), Arc::new(skeleton));
+        }
+        for skeleton_animation in self.skeleton_animations {
+            resource_manager.add_skeleton_animation(
+                skeleton_animation.url.clone(),
+                Arc::new(skeleton_animation),
+            );
+        }
+    }
+}
+
+/// The result of a finished task, applied on the main thread.
+pub enum EImportOutput {
+    Model {
+        file_path: PathBuf,
+        scene: SceneWrapper,
+        resources: ModelResources,
+    },
+    CompressedTexture {
+        texture_url: url::Url,
+        compressed_texture: CompressedTexture,
+    },
+    MultiResolutionMeshCache {
+        static_mesh_url: url::Url,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EImportTaskState {
+    Pending,
+    Running,
+    Finished,
+    Failed(String),
+    Canceled,
+}
+
+impl EImportTaskState {
+    pub fn is_done(&self) -> bool {
+        matches!(
+            self,
+            EImportTaskState::Finished | EImportTaskState::Failed(_) | EImportTaskState::Canceled
+        )
+    }
+}
+
+enum EImportMessage {
+    Started(ImportTaskId),
+    Progress(ImportTaskId, String, f32),
+    Finished(ImportTaskId, Result<EImportOutput, String>),
+}
+
+/// Passed to the job on the worker thread to report the progress and check the cancellation.
+pub struct ImportTaskContext {
+    id: ImportTaskId,
+    sender: Sender<EImportMessage>,
+    is_canceled: Arc<AtomicBool>,
+}
+
+impl ImportTaskContext {
+    pub fn report(&self, stage: impl Into<String>, progress: f32) {
+        let _ = self.sender.send(EImportMessage::Progress(
+            self.id,
+            stage.into(),
+            progress.clamp(0.0, 1.0),
+        ));
+    }
+
+    pub fn is_canceled(&self) -> bool {
+        self.is_canceled.load(Ordering::Relaxed)
+    }
+
+    /// Returns an error if the task is canceled, the job should stop at the next stage.
+    pub fn check_canceled(&self) -> anyhow::Result<()> {
+        if self.is_canceled() {
+            Err(anyhow::anyhow!("Canceled"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+pub struct ImportTask {
+    pub id: ImportTaskId,
+    pub name: String,
+    pub stage: String,
+    pub progress: f32,
+    pub state: EImportTaskState,
+    is_canceled: Arc<AtomicBool>,
+}
+
+/// Runs the import jobs on the worker threads, the outputs are returned by `process_messages` on the main thread.
+pub struct ImportQueue {
+    tasks: Vec<ImportTask>,
+    next_id: ImportTaskId,
+    sender: Sender<EImportMessage>,
+    receiver: Receiver<EImportMessage>,
+}
+
+impl ImportQueue {
+    pub fn new() -> ImportQueue {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        ImportQueue {
+            tasks: vec![],
+            next_id: 0,
+            sender,
+            receiver,
+        }
+    }
+
+    pub fn push<F>(&mut self, name: impl Into<String>, job: F) -> ImportTaskId
+    where
+        F: FnOnce(&ImportTaskContext) -> anyhow::Result<EImportOutput> + Send + 'static,
+    {
+        let id = self.next_id;
+        self.next_id += 1;
+        let is_canceled = Arc::new(AtomicBool::new(false));
+        self.tasks.push(ImportTask {
+            id,
+            name: name.into(),
+            stage: String::new(),
+            progress: 0.0,
+            state: EImportTaskState::Pending,
+            is_canceled: is_canceled.clone(),
+        });
+        let context = ImportTaskContext {
+            id,
+            sender: self.sender.clone(),
+            is_canceled,
+        };
+        ThreadPool::global().spawn(move || {
+            let result = context.check_canceled().and_then(|_| {
+                let _ = context.sender.send(EImportMessage::Started(id));
+                job(&context)
+            });
+            let _ = context.sender.send(EImportMessage::Finished(
+                id,
+                result.map_err(|err| err.to_string()),
+            ));
+        });
+        id
+    }
+
+    /// The output of the task is dropped if it is finished after the cancellation.
+    pub fn cancel(&mut self, id: ImportTaskId) {
+        if let Some(task) = self.tasks.iter_mut().find(|x| x.id == id) {
+            if !task.state.is_done() {
+                task.is_canceled.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn cancel_all(&mut self) {
+        for id in self.tasks.iter().map(|x| x.id).collect::<Vec<_>>() {
+            self.cancel(id);
+        }
+    }
+
+    /// Marks the task as failed, used when the output fails to apply.
+    pub fn fail(&mut self, id: ImportTaskId, err: impl ToString) {
+        if let Some(task) = self.tasks.iter_mut().find(|x| x.id == id) {
+            task.state = EImportTaskState::Failed(err.to_string());
+        }
+    }
+
+    pub fn tasks(&self) -> &[ImportTask] {
+        &self.tasks
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.tasks.iter().any(|x| !x.state.is_done())
+    }
+
+    pub fn clear_done(&mut self) {
+        self.tasks.retain(|x| !x.state.is_done());
+    }
+
+    /// Updates the states of the tasks, returns the outputs of the finished tasks.
+    pub fn process_messages(&mut self) -> Vec<(ImportTaskId, EImportOutput)> {
+        let mut outputs = vec![];
+        for message in self.receiver.try_iter() {
+            let id = match &message {
+                EImportMessage::Started(id)
+                | EImportMessage::Progress(id, _, _)
+                | EImportMessage::Finished(id, _) => *id,
+            };
+            let Some(task) = self.tasks.iter_mut().find(|x| x.id == id) else {
+                continue;
+            };
+            let is_canceled = task.is_canceled.load(Ordering::Relaxed);
+            match message {
+                EImportMessage::Started(_) => {
+                    task.state = EImportTaskState::Running;
+                }
+                EImportMessage::Progress(_, stage, progress) => {
+                    task.stage = stage;
+                    task.progress = progress;
+                }
+                EImportMessage::Finished(_, result) => {
+                    if is_canceled {
+                        task.state = EImportTaskState::Canceled;
+                        continue;
+                    }
+                    match result {
+                        Ok(output) => {
+                            task.state = EImportTaskState::Finished;
+                            task.progress = 1.0;
+                            outputs.push((id, output));
+                        }
+                        Err(err) => {
+                            task.state = EImportTaskState::Failed(err);
+                        }
+                    }
+                }
+            }
+        }
+        outputs
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{EImportOutput, EImportTaskState, ImportQueue, ImportTaskId};
+    use rs_engine::build_content_file_url;
+
+    fn wait(queue: &mut ImportQueue) -> Vec<(ImportTaskId, EImportOutput)> {
+        let mut outputs = vec![];
+        let start = std::time::Instant::now();
+        while queue.is_busy() {
+            assert!(start.elapsed() < std::time::Duration::from_secs(10));
+            outputs.append(&mut queue.process_messages());
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+        outputs
+    }
+
+    #[test]
+    fn test_progress() {
+        let mut queue = ImportQueue::new();
+        let url = build_content_file_url("Mesh").unwrap();
+        let id = queue.push("Mesh", {
+            let url = url.clone();
+            move |context| {
+                context.report("Building clusters", 0.5);
+                Ok(EImportOutput::MultiResolutionMeshCache {
+                    static_mesh_url: url,
+                })
+            }
+        });
+        let failed_id = queue.push("Missing", |_| Err(anyhow::anyhow!("Missing file")));
+        let outputs = wait(&mut queue);
+        assert_eq!(outputs.len(), 1);
+        assert_eq!(outputs[0].0, id);
+        let task = queue.tasks().iter().find(|x| x.id == id).unwrap();
+        assert_eq!(task.state, EImportTaskState::Finished);
+        assert_eq!(task.stage, "Building clusters");
+        assert_eq!(task.progress, 1.0);
+        let task = queue.tasks().iter().find(|x| x.id == failed_id).unwrap();
+        assert_eq!(
+            task.state,
+            EImportTaskState::Failed("Missing file".to_string())
+        );
+        queue.clear_done();
+        assert!(queue.tasks().is_empty());
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut queue = ImportQueue::new();
+        let id = queue.push("Model", |context| {
+            while !context.is_canceled() {
+                std::thread::sleep(std::time::Duration::from_millis(1));
+            }
+            Ok(EImportOutput::MultiResolutionMeshCache {
+                static_mesh_url: build_content_file_url("Mesh").unwrap(),
+            })
+        });
+        assert!(queue.is_busy());
+        queue.cancel(id);
+        let outputs = wait(&mut queue);
+        assert!(outputs.is_empty());
+        assert_eq!(queue.tasks()[0].state, EImportTaskState::Canceled);
+    }
+}
@@ -1,19 +1,23 @@
 pub mod build_config;
 pub mod component_factory;
 pub mod content_edit;
+pub mod cook;
 pub mod custom_event;
 pub mod data_source;
 pub mod editor;
 pub mod editor_context;
 pub mod editor_ui;
 pub mod error;
+pub mod import_queue;
 pub mod load_content;
 pub mod material;
+pub mod material_compile;
 pub mod material_resolve;
 pub mod project;
 pub mod project_context;
 pub mod standalone_simulation_options;
 pub mod thumbnail_cache;
+pub mod transaction;
 pub mod ui;
 pub mod watch_shader;
 pub mod windows_manager;
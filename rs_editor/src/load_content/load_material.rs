@@ -2,7 +2,7 @@ use super::types::{PostLoading, PostLoadingContext, PreLoadingContext};
 use crate::editor_context::EditorContext;
 use crate::impl_default_load_future;
 use crate::impl_default_load_future_body;
-use crate::material_resolve::resolve_task;
+use crate::material_resolve::{collect_material_function_graphs, resolve_task};
 use crate::ui::material_view::MaterialNode;
 use rs_artifact::bincode_legacy;
 use rs_content::TypedContent;
@@ -116,6 +116,10 @@ impl<'a> LoadMaterial<'a> {
         let paramenters = material_editor.borrow().paramenters.clone();
         let module_manager = loading_context.module_manager.clone();
         let content_manager = loading_context.content_manager.clone();
+        let material_functions = collect_material_function_graphs(
+            &content_manager.borrow(),
+            &loading_context.project_context.project.material_functions,
+        );
         let mut task = resolve_task(
             module_manager,
             content_manager,
@@ -123,6 +127,7 @@ impl<'a> LoadMaterial<'a> {
             snarl_wrapper.get(),
             MaterialOptions::all(),
             paramenters,
+            material_functions,
         );
         let (sender, receiver) = std::sync::mpsc::channel();
         let resolve_result = task.run();
@@ -4,6 +4,7 @@ use rs_content::TypedContent;
 use rs_editor_core::material::Paramenters;
 use rs_engine::url_extension::UrlExtension;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 
 #[derive(Clone)]
 struct MaterialRuntime {
@@ -55,6 +56,29 @@ impl Material {
         self.url.set_name_in_editor(new_name);
     }
 
+    /// The contents the graph refers to, they are not stored in the material content.
+    pub fn content_urls(&self) -> BTreeSet<url::Url> {
+        self.snarl
+            .nodes()
+            .filter_map(|x| x.node_type.content_url())
+            .cloned()
+            .collect()
+    }
+
+    /// Returns true if the graph refers to the old url.
+    pub fn replace_content_url(&mut self, old_url: &url::Url, new_url: &url::Url) -> bool {
+        let mut is_replaced = false;
+        for node in self.snarl.nodes_mut() {
+            if let Some(url) = node.node_type.content_url_mut()
+                && *url == *old_url
+            {
+                *url = new_url.clone();
+                is_replaced = true;
+            }
+        }
+        is_replaced
+    }
+
     pub fn on_url_changed(
         material: &mut rs_engine::content::material::Material,
         asset: &mut Material,
@@ -65,23 +89,82 @@ impl Material {
     }
 
     pub fn make_url(material_url: &url::Url) -> url::Url {
-        assert!(material_url.as_str().starts_with(&format!(
+        Self::make_url_in_folder(material_url, "material")
+    }
+
+    pub fn on_function_url_changed(
+        material_function: &mut rs_engine::content::material_function::MaterialFunction,
+        asset: &mut Material,
+    ) {
+        let new_url = Self::make_function_url(&material_function.url);
+        material_function.asset_url = new_url.clone();
+        asset.url = new_url;
+    }
+
+    /// The graph of a material function is stored in the same way as the graph of a material.
+    pub fn make_function_url(material_function_url: &url::Url) -> url::Url {
+        Self::make_url_in_folder(material_function_url, "material_function")
+    }
+
+    fn make_url_in_folder(content_url: &url::Url, folder: &str) -> url::Url {
+        assert!(content_url.as_str().starts_with(&format!(
             "{}://{}",
             rs_engine::CONTENT_SCHEME,
             rs_engine::CONTENT_ROOT
         )));
-        let new = material_url.to_string().replace(
+        let new = content_url.to_string().replace(
             &format!(
                 "{}://{}",
                 rs_engine::CONTENT_SCHEME,
                 rs_engine::CONTENT_ROOT
             ),
             &format!(
-                "{}://{}/material",
+                "{}://{}/{}",
                 rs_engine::ASSET_SCHEME,
-                rs_engine::ASSET_ROOT
+                rs_engine::ASSET_ROOT,
+                folder
             ),
         );
         url::Url::parse(&new).expect("Valid url")
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Material;
+    use crate::ui::material_view::{EMaterialNodeType, MaterialFunctionCall, MaterialNode};
+    use rs_engine::build_content_file_url;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_replace_content_url() {
+        let texture_url = build_content_file_url("Texture").unwrap();
+        let function_url = build_content_file_url("Function").unwrap();
+        let mut snarl = egui_snarl::Snarl::new();
+        for node_type in [
+            EMaterialNodeType::Texture(Some(texture_url.clone())),
+            EMaterialNodeType::VirtualTexture(Some(texture_url.clone())),
+            EMaterialNodeType::MaterialFunction(MaterialFunctionCall {
+                url: Some(function_url.clone()),
+                ..Default::default()
+            }),
+            EMaterialNodeType::Texture(None),
+        ] {
+            snarl.insert_node(egui::pos2(0.0, 0.0), MaterialNode { node_type });
+        }
+        let url = url::Url::parse("asset://asset/material/Material").unwrap();
+        let mut material = Material::new(url, snarl);
+        assert_eq!(
+            material.content_urls(),
+            BTreeSet::from([texture_url.clone(), function_url.clone()])
+        );
+
+        let new_texture_url = build_content_file_url("Folder/Texture").unwrap();
+        assert!(material.replace_content_url(&texture_url, &new_texture_url));
+        assert!(!material.replace_content_url(&texture_url, &new_texture_url));
+        assert_eq!(
+            material.content_urls(),
+            BTreeSet::from([new_texture_url, function_url])
+        );
+    }
+}
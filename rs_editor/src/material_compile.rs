@@ -0,0 +1,366 @@
+use crate::{
+    material_resolve::{
+        MaterialFunctionGraphs, ResolveError, TypeCheckError, check_material_function,
+        collect_material_function_graphs, resolve,
+    },
+    project::{CONTENT_FOLDER_NAME, Project},
+    ui::material_view::MaterialNode,
+};
+use anyhow::{Context, anyhow};
+use egui_snarl::Snarl;
+use rs_content_manager::content_manager::ContentManager;
+use rs_engine::{
+    content::{
+        content_file_type::collect_typed_contents, material::Material,
+        material_function::MaterialFunction,
+    },
+    url_extension::UrlExtension,
+};
+use rs_foundation::new::{SingleThreadMut, SingleThreadMutType};
+use rs_module::types::ModuleManager;
+use rs_render_types::MaterialOptions;
+use std::path::Path;
+
+/// A material or a material function that failed to compile.
+#[derive(Debug, Clone)]
+pub struct MaterialCompileError {
+    pub url: url::Url,
+    /// The permutation that failed, none if the error doesn't depend on the permutation.
+    pub options: Option<MaterialOptions>,
+    pub message: String,
+}
+
+impl std::fmt::Display for MaterialCompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = self.url.get_name_in_editor();
+        match &self.options {
+            Some(options) => write!(f, "{} (is_skin: {}): ", name, options.is_skin)?,
+            None => write!(f, "{}: ", name)?,
+        }
+        f.write_str(&self.message)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MaterialCompileReport {
+    /// The number of shaders that pass the validation.
+    pub num_compiled: usize,
+    pub errors: Vec<MaterialCompileError>,
+}
+
+impl MaterialCompileReport {
+    pub fn is_success(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Resolves every material of the project for all permutations and validates the shaders with naga.
+/// Doesn't need a window or a GPU.
+pub fn compile_materials(project_file_path: &Path) -> anyhow::Result<MaterialCompileReport> {
+    let project_folder_path = project_file_path
+        .parent()
+        .ok_or(anyhow!("Can not find parent folder."))?;
+    let file = std::fs::File::open(project_file_path)
+        .context(format!("Can not open file: {:?}", project_file_path))?;
+    let reader = std::io::BufReader::new(file);
+    let project: Project = serde_json::de::from_reader(reader)
+        .context("Failed to deserialize JSON data to a project data structure.")?;
+    let content_manager = SingleThreadMut::new(ContentManager::from_path(
+        project_folder_path.join(CONTENT_FOLDER_NAME),
+    ));
+    Ok(compile_project_materials(&project, content_manager))
+}
+
+pub fn compile_project_materials(
+    project: &Project,
+    content_manager: SingleThreadMutType<ContentManager>,
+) -> MaterialCompileReport {
+    let mut report = MaterialCompileReport::default();
+    let module_manager = SingleThreadMut::new(ModuleManager::new());
+    let material_functions =
+        collect_material_function_graphs(&content_manager.borrow(), &project.material_functions);
+    let (materials, functions) = {
+        let content_manager = content_manager.borrow();
+        (
+            collect_typed_contents::<Material>(content_manager.content_files()),
+            collect_typed_contents::<MaterialFunction>(content_manager.content_files()),
+        )
+    };
+
+    for function in functions {
+        let function = function.borrow();
+        let Some(graph) = material_functions.get(&function.url) else {
+            report.errors.push(MaterialCompileError {
+                url: function.url.clone(),
+                options: None,
+                message: "Miss material function graph".to_string(),
+            });
+            continue;
+        };
+        let graph = graph.borrow();
+        if let Err(err) = check_material_function(
+            &content_manager.borrow(),
+            &function.url,
+            &graph.snarl,
+            &material_functions,
+        ) {
+            report.errors.push(MaterialCompileError {
+                url: function.url.clone(),
+                options: None,
+                message: describe_resolve_error(&graph.snarl, &err),
+            });
+        }
+    }
+
+    for material in materials {
+        let material = material.borrow();
+        let Some(material_editor) = project
+            .materials
+            .iter()
+            .find(|x| x.borrow().url == material.asset_url)
+        else {
+            report.errors.push(MaterialCompileError {
+                url: material.url.clone(),
+                options: None,
+                message: "Miss material graph".to_string(),
+            });
+            continue;
+        };
+        let material_editor = material_editor.borrow();
+        for options in MaterialOptions::all() {
+            let result = compile_material(
+                module_manager.clone(),
+                content_manager.clone(),
+                &material.url,
+                &material_editor,
+                options.clone(),
+                &material_functions,
+            );
+            match result {
+                Ok(()) => report.num_compiled += 1,
+                Err(message) => report.errors.push(MaterialCompileError {
+                    url: material.url.clone(),
+                    options: Some(options),
+                    message,
+                }),
+            }
+        }
+    }
+    report
+}
+
+fn compile_material(
+    module_manager: SingleThreadMutType<ModuleManager>,
+    content_manager: SingleThreadMutType<ContentManager>,
+    material_url: &url::Url,
+    material_editor: &crate::material::Material,
+    options: MaterialOptions,
+    material_functions: &MaterialFunctionGraphs,
+) -> Result<(), String> {
+    let results = resolve(
+        module_manager,
+        content_manager,
+        Some(material_url),
+        &material_editor.snarl,
+        vec![options.clone()],
+        &material_editor.paramenters,
+        material_functions,
+    )
+    .map_err(|err| describe_resolve_error(&material_editor.snarl, &err))?;
+    let result = results
+        .get(&options)
+        .ok_or("The permutation is not resolved".to_string())?;
+    validate_shader_code(&result.shader_code)
+}
+
+/// Parses and validates the shader code, the error contains the location in the source.
+pub fn validate_shader_code(shader_code: &str) -> Result<(), String> {
+    let module =
+        naga::front::wgsl::parse_str(shader_code).map_err(|err| err.emit_to_string(shader_code))?;
+    let mut validator = naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    );
+    validator
+        .validate(&module)
+        .map_err(|err| err.emit_to_string(shader_code))?;
+    Ok(())
+}
+
+/// Adds the names of the nodes to the errors of the graph.
+fn describe_resolve_error(snarl: &Snarl<MaterialNode>, err: &anyhow::Error) -> String {
+    let Some(err) = err.downcast_ref::<TypeCheckError>() else {
+        return err.to_string();
+    };
+    let messages: Vec<String> = err
+        .errors
+        .iter()
+        .map(|x| describe_node_error(snarl, x))
+        .collect();
+    messages.join("\n")
+}
+
+fn describe_node_error(snarl: &Snarl<MaterialNode>, err: &ResolveError) -> String {
+    let Some(node) = snarl.get_node(err.node_id) else {
+        return err.to_string();
+    };
+    let name = node.node_type.get_name();
+    match err.input {
+        Some(input) => format!(
+            "[{} {}] Input {}: {}",
+            err.node_id.0, name, input, err.message
+        ),
+        None => format!("[{} {}] {}", err.node_id.0, name, err.message),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{compile_project_materials, validate_shader_code};
+    use crate::{
+        project::Project,
+        ui::material_view::{Attribute, EMaterialNodeType, EValueType, MaterialNode},
+    };
+    use egui_snarl::{InPinId, NodeId, OutPinId, Snarl};
+    use rs_artifact::EEndianType;
+    use rs_content_manager::content_manager::ContentManager;
+    use rs_core_minimal::settings::Settings;
+    use rs_engine::{build_content_file_url, content::material::Material};
+    use rs_foundation::new::SingleThreadMut;
+    use rs_render_types::MaterialOptions;
+    use std::{cell::RefCell, rc::Rc};
+
+    #[test]
+    fn test_validate_shader_code() {
+        assert!(validate_shader_code("fn add(a: f32) -> f32 { return a + 1.0; }").is_ok());
+        assert!(
+            validate_shader_code("fn add(a: f32) -> f32 { return a + vec2<f32>(1.0); }").is_err()
+        );
+        assert!(validate_shader_code("fn add(a: f32) -> f32 { return a +; }").is_err());
+    }
+
+    /// A project with one material whose graph is `snarl`.
+    fn make_project(
+        dir: &std::path::Path,
+        name: &str,
+        snarl: Snarl<MaterialNode>,
+    ) -> (Project, ContentManager, url::Url) {
+        let _ = std::fs::remove_dir_all(dir);
+        std::fs::create_dir_all(dir).unwrap();
+
+        let content_url = build_content_file_url(name).unwrap();
+        let asset_url = crate::material::Material::make_url(&content_url);
+        let mut content_manager = ContentManager::from_path(dir.to_path_buf());
+        content_manager
+            .save(SingleThreadMut::new(Box::new(Material::new(
+                content_url.clone(),
+                asset_url.clone(),
+            ))))
+            .unwrap();
+        content_manager.load().unwrap();
+        let project = Project {
+            version_str: String::new(),
+            project_name: "Test".to_string(),
+            settings: Rc::new(RefCell::new(Settings::default())),
+            endian_type: EEndianType::Little,
+            materials: vec![Rc::new(RefCell::new(crate::material::Material::new(
+                asset_url, snarl,
+            )))],
+            material_functions: vec![],
+        };
+        (project, content_manager, content_url)
+    }
+
+    #[test]
+    fn test_compile_material() {
+        let dir = std::env::temp_dir().join("rs_editor_test_compile_material");
+        let mut snarl = Snarl::new();
+        let sink = snarl.insert_node(
+            egui::pos2(0.0, 0.0),
+            MaterialNode {
+                node_type: EMaterialNodeType::Sink(Attribute::default()),
+            },
+        );
+        let time = snarl.insert_node(
+            egui::pos2(0.0, 0.0),
+            MaterialNode {
+                node_type: EMaterialNodeType::Time,
+            },
+        );
+        let sin = snarl.insert_node(
+            egui::pos2(0.0, 0.0),
+            MaterialNode {
+                node_type: EMaterialNodeType::Sin(EValueType::F32(0.0)),
+            },
+        );
+        // Time -> Sin -> the metallic of the sink.
+        for (from, to, input) in [(time, sin, 0), (sin, sink, 1)] {
+            snarl.connect(
+                OutPinId {
+                    node: from,
+                    output: 0,
+                },
+                InPinId { node: to, input },
+            );
+        }
+        let (project, content_manager, _) = make_project(&dir, "Valid", snarl);
+
+        let report = compile_project_materials(&project, SingleThreadMut::new(content_manager));
+        assert!(report.is_success(), "{:?}", report.errors);
+        assert_eq!(report.num_compiled, MaterialOptions::all().len());
+        assert!(report.num_compiled > 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_compile_broken_material() {
+        let dir = std::env::temp_dir().join("rs_editor_test_compile_materials");
+        let mut snarl = Snarl::new();
+        snarl.insert_node(
+            egui::pos2(0.0, 0.0),
+            MaterialNode {
+                node_type: EMaterialNodeType::Sink(Attribute::default()),
+            },
+        );
+        let tex_coord = snarl.insert_node(
+            egui::pos2(0.0, 0.0),
+            MaterialNode {
+                node_type: EMaterialNodeType::TexCoord(0),
+            },
+        );
+        let cross = snarl.insert_node(
+            egui::pos2(0.0, 0.0),
+            MaterialNode {
+                node_type: EMaterialNodeType::Cross(
+                    EValueType::Vec3(glam::Vec3::ZERO),
+                    EValueType::Vec3(glam::Vec3::ZERO),
+                ),
+            },
+        );
+        for (from, to) in [(tex_coord, cross), (cross, NodeId(0))] {
+            snarl.connect(
+                OutPinId {
+                    node: from,
+                    output: 0,
+                },
+                InPinId { node: to, input: 0 },
+            );
+        }
+        let node_name = format!(
+            "[{} {}] Input 0",
+            cross.0,
+            snarl.get_node(cross).unwrap().node_type.get_name()
+        );
+        let (project, content_manager, content_url) = make_project(&dir, "Broken", snarl);
+
+        let report = compile_project_materials(&project, SingleThreadMut::new(content_manager));
+        assert!(!report.is_success());
+        assert_eq!(report.num_compiled, 0);
+        assert_eq!(report.errors.len(), MaterialOptions::all().len());
+        for error in &report.errors {
+            assert_eq!(error.url, content_url);
+            assert!(error.message.contains(&node_name), "{}", error.message);
+        }
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,4 +1,6 @@
-use crate::ui::material_view::{EMaterialNodeType, MaterialNode};
+use crate::ui::material_view::{
+    EMaterialNodeType, EPinType, EValueType, MaterialFunctionCall, MaterialNode,
+};
 use anyhow::anyhow;
 use egui_snarl::{InPinId, NodeId, OutPinId, Snarl};
 use rs_artifact::{
@@ -9,7 +11,7 @@ use rs_content_manager::content_manager::ContentManager;
 use rs_editor_core::types::CreationTask;
 use rs_engine::{
     content::{
-        content_file_type::collect_typed_contents,
+        content_file_type::collect_typed_contents, material_function::MaterialFunction,
         material_paramenters_collection::MaterialParamentersCollection,
     },
     url_extension::UrlExtension,
@@ -22,16 +24,79 @@ use std::{
     path::PathBuf,
 };
 
+/// The graphs of the material functions, keyed by the url of the content.
+pub type MaterialFunctionGraphs = HashMap<url::Url, SingleThreadMutType<crate::material::Material>>;
+
 struct ResolveResultInternal {
     lines: Vec<String>,
 }
 
 #[derive(Clone)]
 pub struct ResolveResult {
+    /// The WGSL code, material graphs are always resolved against `pbr_shading.wgsl`.
+    /// Emitting Slang is not supported yet.
     pub shader_code: String,
     pub material_info: MaterialInfo,
 }
 
+/// An error found at a node, `input` is the index of the input pin if the error is caused by a connection.
+#[derive(Debug, Clone)]
+pub struct ResolveError {
+    pub node_id: NodeId,
+    pub input: Option<usize>,
+    pub message: String,
+}
+
+impl std::fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.input {
+            Some(input) => write!(f, "[{}] Input {}: {}", self.node_id.0, input, self.message),
+            None => write!(f, "[{}] {}", self.node_id.0, self.message),
+        }
+    }
+}
+
+/// The connections of the graph don't type-check.
+#[derive(Debug, Clone)]
+pub struct TypeCheckError {
+    pub errors: Vec<ResolveError>,
+}
+
+impl std::fmt::Display for TypeCheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let messages: Vec<String> = self.errors.iter().map(|x| x.to_string()).collect();
+        f.write_str(&messages.join("\n"))
+    }
+}
+
+impl std::error::Error for TypeCheckError {}
+
+#[derive(Debug, Clone)]
+struct ResolvedValue {
+    expression: String,
+    pin_type: EPinType,
+    /// The type can not be inferred because of an error that has been reported.
+    is_unknown: bool,
+}
+
+impl ResolvedValue {
+    fn new(expression: impl Into<String>, pin_type: EPinType) -> ResolvedValue {
+        ResolvedValue {
+            expression: expression.into(),
+            pin_type,
+            is_unknown: false,
+        }
+    }
+
+    fn unknown() -> ResolvedValue {
+        ResolvedValue {
+            expression: "0.0".to_string(),
+            pin_type: EPinType::F32,
+            is_unknown: true,
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 struct NodeIOInfo {
     inputs: HashMap<usize, OutPinId>,
@@ -46,12 +111,25 @@ struct ResolveContext<'a> {
     current_binding: usize,
     used_material_paramenters_collection_urls: Vec<url::Url>,
     material_paramenters: &'a rs_editor_core::material::Paramenters,
+    collection_fields: HashMap<url::Url, Vec<StructField>>,
+    resolved_nodes: HashSet<NodeId>,
+    visiting_nodes: HashSet<NodeId>,
+    outputs: HashMap<OutPinId, ResolvedValue>,
+    errors: Vec<ResolveError>,
+    material_functions: &'a MaterialFunctionGraphs,
+    /// The urls of the material functions being resolved, used to detect recursive calls.
+    function_stack: Vec<url::Url>,
+    /// The values passed to the inputs of the material function being resolved.
+    function_arguments: Option<HashMap<String, ResolvedValue>>,
+    /// The prefix of the variable names, every inlined call has its own scope.
+    scope: String,
 }
 
 impl<'a> ResolveContext<'a> {
     fn new(
         snarl: &'a Snarl<MaterialNode>,
         material_paramenters: &'a rs_editor_core::material::Paramenters,
+        material_functions: &'a MaterialFunctionGraphs,
     ) -> ResolveContext<'a> {
         let mut node_io_infos: HashMap<NodeId, NodeIOInfo> = HashMap::new();
         for (out_pin_id, in_pin_id) in snarl.wires() {
@@ -85,6 +163,15 @@ impl<'a> ResolveContext<'a> {
             current_binding: 0,
             used_material_paramenters_collection_urls: vec![],
             material_paramenters,
+            collection_fields: HashMap::new(),
+            resolved_nodes: HashSet::new(),
+            visiting_nodes: HashSet::new(),
+            outputs: HashMap::new(),
+            errors: vec![],
+            material_functions,
+            function_stack: vec![],
+            function_arguments: None,
+            scope: String::new(),
         }
     }
 
@@ -148,27 +235,7 @@ impl<'a> ResolveContext<'a> {
         options: &MaterialOptions,
         content_manager: &ContentManager,
     ) -> anyhow::Result<ResolveResult> {
-        let mut material_info = MaterialInfo {
-            map_textures: HashSet::new(),
-            virtual_textures: HashSet::new(),
-            global_constants_binding: None,
-            base_color_sampler_binding: None,
-            physical_texture_binding: None,
-            page_table_texture_binding: None,
-            brdflut_texture_binding: None,
-            pre_filter_cube_map_texture_binding: None,
-            irradiance_texture_binding: None,
-            shadow_map_binding: None,
-            constants_binding: None,
-            point_lights_binding: None,
-            spot_lights_binding: None,
-            skin_constants_binding: None,
-            virtual_texture_constants_binding: None,
-            cluster_light_binding: None,
-            cluster_light_index_binding: None,
-            material_paramenters_collection_bindings: HashSet::new(),
-            paramenters: vec![],
-        };
+        let mut material_info = empty_material_info();
         let is_support_cluster_light = true;
         let mut definitions: Vec<String> = vec![
             "VIRTUAL_TEXTURE=1".to_string(),
@@ -190,19 +257,8 @@ impl<'a> ResolveContext<'a> {
             &mut material_info,
             is_support_cluster_light,
         );
-        // let resolve_context = ResolveContext::from_snarl(snarl);
-        let attribute_node_id = egui_snarl::NodeId(0);
-        let result = self.resolve_attribute_node(
-            attribute_node_id,
-            // &self,
-            // self.snarl,
-            &mut material_info,
-            // current_max_binding,
-        )?;
-        let mut lines: Vec<String> = vec![];
-        for resolve_result in result.iter() {
-            lines.extend_from_slice(&resolve_result.lines);
-        }
+        self.collection_fields = collect_collection_fields(content_manager);
+        let lines = self.resolve_material_code(&mut material_info)?;
         let material_shader_code = lines.join("\n");
         let shader_path = rs_render::get_buildin_shader_dir().join("pbr_shading.wgsl");
         let include_dirs: Vec<PathBuf> = vec![];
@@ -311,18 +367,32 @@ impl<'a> ResolveContext<'a> {
         })
     }
 
+    fn resolve_material_code(
+        &mut self,
+        material_info: &mut MaterialInfo,
+    ) -> anyhow::Result<Vec<String>> {
+        let attribute_node_id = egui_snarl::NodeId(0);
+        let result = self.resolve_attribute_node(attribute_node_id, material_info)?;
+        if !self.errors.is_empty() {
+            let errors = std::mem::take(&mut self.errors);
+            return Err(TypeCheckError { errors }.into());
+        }
+        let mut lines: Vec<String> = vec![];
+        for resolve_result in result.iter() {
+            lines.extend_from_slice(&resolve_result.lines);
+        }
+        Ok(lines)
+    }
+
     fn resolve_attribute_node(
         &mut self,
         attribute_node_id: NodeId,
-        // resolve_context: &ResolveContext,
-        // snarl: &Snarl<MaterialNode>,
         material_info: &mut MaterialInfo,
-        // user_texture_binding_start: usize,
     ) -> anyhow::Result<Vec<ResolveResultInternal>> {
         let mut result: Vec<ResolveResultInternal> = Vec::new();
 
         macro_rules! resolve_attribute {
-            ($name:ident, $input:literal, $convert_type:ident) => {{
+            ($name:ident, $input:literal, $convert_type:ident, $pin_type:expr) => {{
                 let attribute_node = self
                     .snarl
                     .get_node(attribute_node_id)
@@ -337,22 +407,20 @@ impl<'a> ResolveContext<'a> {
                     $input,
                     attribute_node_id,
                     &attribute_value_literal,
-                    // self,
-                    // self.snarl,
+                    $pin_type,
                     material_info,
-                    // user_texture_binding_start,
                 )?;
                 result.push(value);
             }};
         }
 
-        resolve_attribute!(base_color, 0, convert_to_vec3);
-        resolve_attribute!(metallic, 1, convert_to_f32);
-        resolve_attribute!(roughness, 2, convert_to_f32);
-        resolve_attribute!(normal, 3, convert_to_vec3);
-        resolve_attribute!(opacity, 4, convert_to_f32);
-        resolve_attribute!(clear_coat, 5, convert_to_f32);
-        resolve_attribute!(clear_coat_roughness, 6, convert_to_f32);
+        resolve_attribute!(base_color, 0, convert_to_vec3, EPinType::Vec3);
+        resolve_attribute!(metallic, 1, convert_to_f32, EPinType::F32);
+        resolve_attribute!(roughness, 2, convert_to_f32, EPinType::F32);
+        resolve_attribute!(normal, 3, convert_to_vec3, EPinType::Vec3);
+        resolve_attribute!(opacity, 4, convert_to_f32, EPinType::F32);
+        resolve_attribute!(clear_coat, 5, convert_to_f32, EPinType::F32);
+        resolve_attribute!(clear_coat_roughness, 6, convert_to_f32, EPinType::F32);
 
         Ok(result)
     }
@@ -363,183 +431,575 @@ impl<'a> ResolveContext<'a> {
         input: usize,
         attribute_node_id: NodeId,
         attribute_value_literal: &str,
-        // resolve_context: &ResolveContext,
-        // snarl: &Snarl<MaterialNode>,
+        pin_type: EPinType,
         material_info: &mut MaterialInfo,
-        // user_texture_binding_start: usize,
     ) -> anyhow::Result<ResolveResultInternal> {
-        let node_io_info = self
-            .nodes
-            .get(&attribute_node_id)
-            .expect("This node should not be null");
-
-        let right = {
-            node_io_info
-                .inputs
-                .get(&input)
-                .and_then(|x| {
-                    let var_name = node_var_name(x.node);
-                    Some(var_name)
-                })
-                .unwrap_or_else(|| attribute_value_literal.to_string())
+        let mut lines: Vec<String> = vec![];
+        let right = match self.resolve_input(attribute_node_id, input, &mut lines, material_info) {
+            Some(value) if value.is_unknown => attribute_value_literal.to_string(),
+            Some(value) => match convert(&value, pin_type) {
+                Some(expression) => expression,
+                None => {
+                    self.push_type_error(attribute_node_id, input, pin_type, value.pin_type);
+                    attribute_value_literal.to_string()
+                }
+            },
+            None => attribute_value_literal.to_string(),
         };
+        lines.push(format!("    user_attributes.{} = {};", name, right));
+        Ok(ResolveResultInternal { lines })
+    }
 
-        // let mut lines: Vec<String> = vec![];
+    fn push_error(&mut self, node_id: NodeId, input: Option<usize>, message: impl Into<String>) {
+        self.errors.push(ResolveError {
+            node_id,
+            input,
+            message: message.into(),
+        });
+    }
 
-        let mut lines: Vec<String> = vec![];
+    fn push_type_error(
+        &mut self,
+        node_id: NodeId,
+        input: usize,
+        expected: EPinType,
+        found: EPinType,
+    ) {
+        self.push_error(
+            node_id,
+            Some(input),
+            format!(
+                "Expects {}, found {}",
+                expected.get_type_name(),
+                found.get_type_name()
+            ),
+        );
+    }
 
-        if let Some(input) = node_io_info.inputs.get(&input) {
-            self.walk_resolve_node(
-                input.node,
-                // self,
-                // self.snarl,
-                &mut lines,
-                material_info,
-                // user_texture_binding_start,
-            );
-        }
-        // let Some(input) = node_io_info.inputs.get(&input) else {
-        //     panic!()
-        // };
-        lines.reverse();
-        // Some(lines)
+    fn set_output(&mut self, node_id: NodeId, output: usize, value: ResolvedValue) {
+        self.outputs.insert(
+            OutPinId {
+                node: node_id,
+                output,
+            },
+            value,
+        );
+    }
 
-        // let mut lines: Vec<String> = node_io_info
-        //     .inputs
-        //     .get(&input)
-        //     .and_then(|x| {
+    /// Marks all outputs of the node as unknown so the error is not reported again by the following nodes.
+    fn set_unknown_outputs(&mut self, node_id: NodeId, node: &MaterialNode) -> String {
+        let len = match &node.node_type {
+            EMaterialNodeType::ComponentSplit => 4,
+            EMaterialNodeType::MaterialFunction(call) => call.outputs.len(),
+            _ => 1,
+        };
+        for output in 0..len {
+            self.set_output(node_id, output, ResolvedValue::unknown());
+        }
+        String::new()
+    }
 
-        //     })
-        //     .unwrap_or_else(|| vec![]);
+    /// Resolves the node connected to the input.
+    fn resolve_input(
+        &mut self,
+        node_id: NodeId,
+        input: usize,
+        lines: &mut Vec<String>,
+        material_info: &mut MaterialInfo,
+    ) -> Option<ResolvedValue> {
+        let out_pin_id = self
+            .nodes
+            .get(&node_id)
+            .and_then(|x| x.inputs.get(&input))
+            .cloned()?;
+        self.walk_resolve_node(out_pin_id.node, lines, material_info);
+        if let Some(value) = self.outputs.get(&out_pin_id) {
+            return Some(value.clone());
+        }
+        if !self.visiting_nodes.contains(&out_pin_id.node) {
+            self.push_error(node_id, Some(input), "The connected output has no value");
+        }
+        Some(ResolvedValue::unknown())
+    }
 
-        // let Some(right) = node_io_info.inputs.get(&input) else {
-        //     panic!()
-        // };
+    /// Resolves the node connected to the input, or uses the value of the node if the input is not connected.
+    fn resolve_value_input(
+        &mut self,
+        node_id: NodeId,
+        node: &MaterialNode,
+        input: usize,
+        lines: &mut Vec<String>,
+        material_info: &mut MaterialInfo,
+    ) -> Option<ResolvedValue> {
+        if let Some(value) = self.resolve_input(node_id, input, lines, material_info) {
+            return Some(value);
+        }
+        node.node_type
+            .input_value(input)
+            .map(|value| ResolvedValue::new(value.literal(), value.pin_type()))
+    }
 
-        lines.push(format!("    user_attributes.{} = {};", name, right));
-        Ok(ResolveResultInternal { lines })
+    fn require_input(
+        &mut self,
+        node_id: NodeId,
+        node: &MaterialNode,
+        input: usize,
+        lines: &mut Vec<String>,
+        material_info: &mut MaterialInfo,
+    ) -> ResolvedValue {
+        match self.resolve_value_input(node_id, node, input, lines, material_info) {
+            Some(value) => value,
+            None => {
+                self.push_error(node_id, Some(input), "The input is not connected");
+                ResolvedValue::unknown()
+            }
+        }
     }
 
+    /// Emits the nodes in dependency order, each node is emitted only once.
     fn walk_resolve_node(
         &mut self,
         node_id: NodeId,
-        // resolve_context: &ResolveContext,
-        // snarl: &Snarl<MaterialNode>,
         lines: &mut Vec<String>,
         material_info: &mut MaterialInfo,
-        // user_texture_binding_start: usize,
     ) {
-        let node = self.snarl.get_node(node_id).expect("Not null");
-        let line = self.resolve_node(
-            node_id,
-            node,
-            // resolve_context,
-            material_info,
-            // snarl,
-            // user_texture_binding_start,
-        );
-        lines.push(line);
-        let out_pin_ids: Vec<_> = {
-            let node_io_info = self.nodes.get(&node_id).unwrap();
-            node_io_info.inputs.iter().map(|x| x.1.clone()).collect()
-        };
-        for out_pin_id in out_pin_ids {
-            self.walk_resolve_node(
-                out_pin_id.node,
-                // resolve_context,
-                // snarl,
-                lines,
-                material_info,
-                // user_texture_binding_start,
-            );
+        if self.resolved_nodes.contains(&node_id) {
+            return;
         }
-
-        // for (_, out_pin_id) in &node_io_info.inputs {
-        //     self.walk_resolve_node(
-        //         out_pin_id.node,
-        //         // resolve_context,
-        //         // snarl,
-        //         lines,
-        //         material_info,
-        //         // user_texture_binding_start,
-        //     );
-        // }
+        if !self.visiting_nodes.insert(node_id) {
+            self.push_error(node_id, None, "The node is part of a cycle");
+            return;
+        }
+        let snarl = self.snarl;
+        let node = snarl.get_node(node_id).expect("Not null");
+        let line = self.resolve_node(node_id, node, lines, material_info);
+        if !line.is_empty() {
+            lines.push(line);
+        }
+        self.visiting_nodes.remove(&node_id);
+        self.resolved_nodes.insert(node_id);
     }
 
     fn resolve_node(
         &mut self,
         node_id: NodeId,
         node: &MaterialNode,
-        // resolve_context: &ResolveContext,
+        lines: &mut Vec<String>,
         material_info: &mut MaterialInfo,
-        // snarl: &Snarl<MaterialNode>,
-        // user_texture_binding_start: usize,
     ) -> String {
-        // let _ = snarl;
-        let var_name = node_var_name(node_id);
+        let var_name = format!("{}{}", self.scope, node_var_name(node_id));
         match &node.node_type {
-            EMaterialNodeType::Add(v1, v2) => {
-                let inputs = &self
+            EMaterialNodeType::Add(..)
+            | EMaterialNodeType::Subtract(..)
+            | EMaterialNodeType::Multiply(..)
+            | EMaterialNodeType::Divide(..) => {
+                let operator = match &node.node_type {
+                    EMaterialNodeType::Add(..) => "+",
+                    EMaterialNodeType::Subtract(..) => "-",
+                    EMaterialNodeType::Multiply(..) => "*",
+                    _ => "/",
+                };
+                let a = self.require_input(node_id, node, 0, lines, material_info);
+                let b = self.require_input(node_id, node, 1, lines, material_info);
+                if a.is_unknown || b.is_unknown {
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                let Some(pin_type) = combine_types(a.pin_type, b.pin_type) else {
+                    self.push_error(
+                        node_id,
+                        None,
+                        format!(
+                            "Can not combine {} and {}",
+                            a.pin_type.get_type_name(),
+                            b.pin_type.get_type_name()
+                        ),
+                    );
+                    return self.set_unknown_outputs(node_id, node);
+                };
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, pin_type));
+                format!(
+                    "var {} = {} {} {};",
+                    var_name, a.expression, operator, b.expression
+                )
+            }
+            EMaterialNodeType::Pow(..) => {
+                let a = self.require_input(node_id, node, 0, lines, material_info);
+                let b = self.require_input(node_id, node, 1, lines, material_info);
+                if a.is_unknown || b.is_unknown {
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                let Some(pin_type) = combine_types(a.pin_type, b.pin_type) else {
+                    self.push_type_error(node_id, 1, a.pin_type, b.pin_type);
+                    return self.set_unknown_outputs(node_id, node);
+                };
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, pin_type));
+                format!(
+                    "var {} = pow({}, {});",
+                    var_name,
+                    splat(&a, pin_type),
+                    splat(&b, pin_type)
+                )
+            }
+            EMaterialNodeType::Lerp(..) => {
+                let a = self.require_input(node_id, node, 0, lines, material_info);
+                let b = self.require_input(node_id, node, 1, lines, material_info);
+                let alpha = self.require_input(node_id, node, 2, lines, material_info);
+                if a.is_unknown || b.is_unknown || alpha.is_unknown {
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                let Some(pin_type) = combine_types(a.pin_type, b.pin_type) else {
+                    self.push_type_error(node_id, 1, a.pin_type, b.pin_type);
+                    return self.set_unknown_outputs(node_id, node);
+                };
+                if alpha.pin_type != EPinType::F32 && alpha.pin_type != pin_type {
+                    self.push_type_error(node_id, 2, pin_type, alpha.pin_type);
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, pin_type));
+                format!(
+                    "var {} = mix({}, {}, {});",
+                    var_name,
+                    splat(&a, pin_type),
+                    splat(&b, pin_type),
+                    alpha.expression
+                )
+            }
+            EMaterialNodeType::Clamp(..) => {
+                let value = self.require_input(node_id, node, 0, lines, material_info);
+                let min = self.require_input(node_id, node, 1, lines, material_info);
+                let max = self.require_input(node_id, node, 2, lines, material_info);
+                if value.is_unknown || min.is_unknown || max.is_unknown {
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                let pin_type = value.pin_type;
+                for (input, bound) in [(1, &min), (2, &max)] {
+                    if bound.pin_type != EPinType::F32 && bound.pin_type != pin_type {
+                        self.push_type_error(node_id, input, pin_type, bound.pin_type);
+                        return self.set_unknown_outputs(node_id, node);
+                    }
+                }
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, pin_type));
+                format!(
+                    "var {} = clamp({}, {}, {});",
+                    var_name,
+                    value.expression,
+                    splat(&min, pin_type),
+                    splat(&max, pin_type)
+                )
+            }
+            EMaterialNodeType::Dot(..) => {
+                let a = self.require_input(node_id, node, 0, lines, material_info);
+                let b = self.require_input(node_id, node, 1, lines, material_info);
+                if a.is_unknown || b.is_unknown {
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                if !a.pin_type.is_vector() || a.pin_type != b.pin_type {
+                    self.push_error(
+                        node_id,
+                        None,
+                        format!(
+                            "Expects two vectors of the same type, found {} and {}",
+                            a.pin_type.get_type_name(),
+                            b.pin_type.get_type_name()
+                        ),
+                    );
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, EPinType::F32));
+                format!(
+                    "var {} = dot({}, {});",
+                    var_name, a.expression, b.expression
+                )
+            }
+            EMaterialNodeType::Cross(..) => {
+                let a = self.require_input(node_id, node, 0, lines, material_info);
+                let b = self.require_input(node_id, node, 1, lines, material_info);
+                if a.is_unknown || b.is_unknown {
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                for (input, value) in [(0, &a), (1, &b)] {
+                    if value.pin_type != EPinType::Vec3 {
+                        self.push_type_error(node_id, input, EPinType::Vec3, value.pin_type);
+                        return self.set_unknown_outputs(node_id, node);
+                    }
+                }
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, EPinType::Vec3));
+                format!(
+                    "var {} = cross({}, {});",
+                    var_name, a.expression, b.expression
+                )
+            }
+            EMaterialNodeType::Normalize(_) => {
+                let value = self.require_input(node_id, node, 0, lines, material_info);
+                if value.is_unknown {
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                if !value.pin_type.is_vector() {
+                    self.push_error(node_id, Some(0), "Expects a vector, found F32");
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, value.pin_type));
+                format!("var {} = normalize({});", var_name, value.expression)
+            }
+            EMaterialNodeType::Saturate(_) | EMaterialNodeType::Sin(_) => {
+                let function = match &node.node_type {
+                    EMaterialNodeType::Saturate(_) => "saturate",
+                    _ => "sin",
+                };
+                let value = self.require_input(node_id, node, 0, lines, material_info);
+                if value.is_unknown {
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, value.pin_type));
+                format!("var {} = {}({});", var_name, function, value.expression)
+            }
+            EMaterialNodeType::ComponentSplit => {
+                let value = self.require_input(node_id, node, 0, lines, material_info);
+                if value.is_unknown {
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                let used_outputs: Vec<usize> = self
                     .nodes
                     .get(&node_id)
-                    .expect("This node should not be null")
-                    .inputs;
-                let part_1 = inputs
-                    .get(&0)
-                    .and_then(|out_pin_id| Some(node_var_name(out_pin_id.node)))
-                    .unwrap_or_else(|| v1.literal());
-                let part_2 = inputs
-                    .get(&1)
-                    .and_then(|out_pin_id| Some(node_var_name(out_pin_id.node)))
-                    .unwrap_or_else(|| v2.literal());
-                format!("var {} = {} + {};", var_name, part_1, part_2)
+                    .map(|x| x.outputs.keys().cloned().collect())
+                    .unwrap_or_default();
+                let components = value.pin_type.components();
+                for output in 0..4 {
+                    if output < components {
+                        let expression = if value.pin_type == EPinType::F32 {
+                            var_name.clone()
+                        } else {
+                            format!("{}.{}", var_name, ["x", "y", "z", "w"][output])
+                        };
+                        self.set_output(
+                            node_id,
+                            output,
+                            ResolvedValue::new(expression, EPinType::F32),
+                        );
+                    } else {
+                        if used_outputs.contains(&output) {
+                            self.push_error(
+                                node_id,
+                                None,
+                                format!(
+                                    "{} has no component {}",
+                                    value.pin_type.get_type_name(),
+                                    ["X", "Y", "Z", "W"][output]
+                                ),
+                            );
+                        }
+                        self.set_output(node_id, output, ResolvedValue::unknown());
+                    }
+                }
+                format!("var {} = {};", var_name, value.expression)
+            }
+            EMaterialNodeType::Append(..) => {
+                let a = self.require_input(node_id, node, 0, lines, material_info);
+                let b = self.require_input(node_id, node, 1, lines, material_info);
+                if a.is_unknown || b.is_unknown {
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                let components = a.pin_type.components() + b.pin_type.components();
+                let Some(pin_type) = EPinType::from_components(components) else {
+                    self.push_error(
+                        node_id,
+                        None,
+                        format!(
+                            "Can not append {} to {}, the result has more than 4 components",
+                            b.pin_type.get_type_name(),
+                            a.pin_type.get_type_name()
+                        ),
+                    );
+                    return self.set_unknown_outputs(node_id, node);
+                };
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, pin_type));
+                format!(
+                    "var {} = {}({}, {});",
+                    var_name,
+                    pin_type.wgsl_type(),
+                    a.expression,
+                    b.expression
+                )
+            }
+            EMaterialNodeType::Swizzle(mask) => {
+                let value = self.require_input(node_id, node, 0, lines, material_info);
+                if value.is_unknown {
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                let Some(indices) = swizzle_indices(mask) else {
+                    self.push_error(node_id, None, format!("Invalid swizzle mask: {}", mask));
+                    return self.set_unknown_outputs(node_id, node);
+                };
+                if indices.iter().any(|x| *x >= value.pin_type.components()) {
+                    self.push_error(
+                        node_id,
+                        None,
+                        format!(
+                            "The swizzle mask {} is out of range for {}",
+                            mask,
+                            value.pin_type.get_type_name()
+                        ),
+                    );
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                let pin_type = EPinType::from_components(indices.len()).expect("Valid mask");
+                let expression = if value.pin_type == EPinType::F32 {
+                    splat(&value, pin_type)
+                } else {
+                    let mask: String = indices.iter().map(|x| ['x', 'y', 'z', 'w'][*x]).collect();
+                    format!("{}.{}", value.expression, mask)
+                };
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, pin_type));
+                format!("var {} = {};", var_name, expression)
+            }
+            EMaterialNodeType::WorldPosition => {
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, EPinType::Vec3));
+                format!("var {} = vertex_output.frag_position;", var_name)
+            }
+            EMaterialNodeType::WorldNormal => {
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, EPinType::Vec3));
+                format!("var {} = normalize(vertex_output.normal);", var_name)
+            }
+            EMaterialNodeType::ViewDirection => {
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, EPinType::Vec3));
+                format!(
+                    "var {} = normalize(global_constants.view_position - vertex_output.frag_position);",
+                    var_name
+                )
+            }
+            EMaterialNodeType::Time => {
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, EPinType::F32));
+                format!("var {} = global_constants.time;", var_name)
+            }
+            EMaterialNodeType::Fresnel(_) => {
+                let normal = self
+                    .resolve_input(node_id, 0, lines, material_info)
+                    .unwrap_or_else(|| {
+                        ResolvedValue::new("normalize(vertex_output.normal)", EPinType::Vec3)
+                    });
+                let exponent = self.require_input(node_id, node, 1, lines, material_info);
+                if normal.is_unknown || exponent.is_unknown {
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                if normal.pin_type != EPinType::Vec3 {
+                    self.push_type_error(node_id, 0, EPinType::Vec3, normal.pin_type);
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                if exponent.pin_type != EPinType::F32 {
+                    self.push_type_error(node_id, 1, EPinType::F32, exponent.pin_type);
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, EPinType::F32));
+                format!(
+                    "var {} = pow(1.0 - saturate(dot({}, normalize(global_constants.view_position - vertex_output.frag_position))), {});",
+                    var_name, normal.expression, exponent.expression
+                )
+            }
+            EMaterialNodeType::Panner(speed) => {
+                let uv = self
+                    .resolve_input(node_id, 0, lines, material_info)
+                    .unwrap_or_else(|| {
+                        ResolvedValue::new("vertex_output.tex_coord0", EPinType::Vec2)
+                    });
+                let time = self
+                    .resolve_input(node_id, 1, lines, material_info)
+                    .unwrap_or_else(|| ResolvedValue::new("global_constants.time", EPinType::F32));
+                if uv.is_unknown || time.is_unknown {
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                if uv.pin_type != EPinType::Vec2 {
+                    self.push_type_error(node_id, 0, EPinType::Vec2, uv.pin_type);
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                if time.pin_type != EPinType::F32 {
+                    self.push_type_error(node_id, 1, EPinType::F32, time.pin_type);
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, EPinType::Vec2));
+                format!(
+                    "var {} = {} + {} * {};",
+                    var_name,
+                    uv.expression,
+                    EValueType::Vec2(*speed).literal(),
+                    time.expression
+                )
+            }
+            EMaterialNodeType::NormalMapUnpack => {
+                let value = self.require_input(node_id, node, 0, lines, material_info);
+                if value.is_unknown {
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                if value.pin_type.components() < 2 {
+                    self.push_error(
+                        node_id,
+                        Some(0),
+                        "Expects at least two components, found F32",
+                    );
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, EPinType::Vec3));
+                format!(
+                    "var {var_name}_xy = {}.xy * 2.0 - 1.0;\nvar {var_name} = vec3<f32>({var_name}_xy, sqrt(saturate(1.0 - dot({var_name}_xy, {var_name}_xy)))) * 0.5 + 0.5;",
+                    value.expression
+                )
+            }
+            EMaterialNodeType::StaticSwitch(value) => {
+                let input = if *value { 0 } else { 1 };
+                let value = self.require_input(node_id, node, input, lines, material_info);
+                if value.is_unknown {
+                    return self.set_unknown_outputs(node_id, node);
+                }
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, value.pin_type));
+                format!("var {} = {};", var_name, value.expression)
             }
             EMaterialNodeType::Texture(texture_url) => {
-                if let Some(texture_url) = texture_url {
-                    let binding = self.next_binding();
-                    let inputs = &self
-                        .nodes
-                        .get(&node_id)
-                        .expect("This node should not be null")
-                        .inputs;
+                let uv = self.resolve_input(node_id, 0, lines, material_info);
+                let uv = match uv {
+                    Some(uv) if uv.is_unknown => {
+                        return self.set_unknown_outputs(node_id, node);
+                    }
+                    Some(uv) if uv.pin_type != EPinType::Vec2 => {
+                        self.push_type_error(node_id, 0, EPinType::Vec2, uv.pin_type);
+                        return self.set_unknown_outputs(node_id, node);
+                    }
+                    Some(uv) => uv.expression,
+                    None => "vertex_output.tex_coord0".to_string(),
+                };
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, EPinType::Vec3));
+                let Some(texture_url) = texture_url else {
+                    return format!("var {} = vec3<f32>(0.0);", var_name);
+                };
+                let texture_var_name: String;
+                if let Some(exist) = material_info
+                    .map_textures
+                    .iter()
+                    .find(|x| &x.texture_url == texture_url)
+                {
+                    texture_var_name = exist.get_texture_bind_name();
+                } else {
                     let binding = TextureBinding {
                         group: self.current_group,
-                        binding, //user_texture_binding_start + material_info.map_textures.len(),
+                        binding: self.next_binding(),
                         texture_url: texture_url.clone(),
                     };
-                    let texture_var_name: String;
-                    if let Some(exist) = material_info
-                        .map_textures
-                        .iter()
-                        .find(|x| &x.texture_url == texture_url)
-                    {
-                        texture_var_name = exist.get_texture_bind_name();
-                    } else {
-                        texture_var_name = binding.get_texture_bind_name();
-                        material_info.map_textures.insert(binding);
-                    }
-                    inputs.get(&0).and_then(|out_pin_id| {
-                    Some(format!(
-                        "var {} = textureSample({}, base_color_sampler, {}).xyz;",
-                        var_name,
-                        texture_var_name,
-                        node_var_name(out_pin_id.node)
-                    ))
-                }).unwrap_or_else(|| {
-                    format!("var {} = textureSample({}, base_color_sampler, vertex_output.tex_coord0).xyz;", var_name, texture_var_name)
-                })
-                } else {
-                    format!("var {} = vec4<f32>(0.0);", var_name)
+                    texture_var_name = binding.get_texture_bind_name();
+                    material_info.map_textures.insert(binding);
                 }
+                format!(
+                    "var {} = textureSample({}, base_color_sampler, {}).xyz;",
+                    var_name, texture_var_name, uv
+                )
             }
             EMaterialNodeType::TexCoord(tex_coord_index) => {
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, EPinType::Vec2));
                 format!(
                     "var {} = vertex_output.tex_coord{};",
                     var_name, tex_coord_index
                 )
             }
-            EMaterialNodeType::Sink(_) => unreachable!(),
+            EMaterialNodeType::Sink(_) | EMaterialNodeType::FunctionOutput(_) => unreachable!(),
             EMaterialNodeType::VirtualTexture(texture_url) => {
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, EPinType::Vec3));
                 if let Some(texture_url) = texture_url {
                     material_info.virtual_textures.insert(texture_url.clone());
                     format!(
@@ -550,51 +1010,216 @@ impl<'a> ResolveContext<'a> {
                     format!("var {} = vec3<f32>(0.0);", var_name)
                 }
             }
-            EMaterialNodeType::Time => todo!(),
-            EMaterialNodeType::Sin(v1) => {
-                let inputs = &self
-                    .nodes
-                    .get(&node_id)
-                    .expect("This node should not be null")
-                    .inputs;
-                let part_1 = inputs
-                    .get(&0)
-                    .and_then(|out_pin_id| Some(node_var_name(out_pin_id.node)))
-                    .unwrap_or_else(|| v1.literal());
-                format!("var {} = {}", var_name, part_1)
-            }
             EMaterialNodeType::MaterialParamentersCollection((
                 material_paramenters_collection,
                 name,
             )) => {
-                if let (Some(material_paramenters_collection), Some(name)) =
+                let (Some(material_paramenters_collection), Some(name)) =
                     (material_paramenters_collection.clone(), name)
-                {
-                    let is_contain = self
-                        .used_material_paramenters_collection_urls
-                        .iter()
-                        .find(|x| **x == material_paramenters_collection)
-                        .is_some();
-                    if !is_contain {
-                        self.used_material_paramenters_collection_urls
-                            .push(material_paramenters_collection.clone());
-                    }
-                    let type_text = material_paramenters_collection.get_name_in_editor();
-                    format!(
-                        "var {} = material_paramenters_collection_uniform_{}.{};",
-                        var_name, type_text, name
-                    )
-                } else {
-                    "".to_string()
+                else {
+                    self.push_error(node_id, None, "Select a collection and a field");
+                    return self.set_unknown_outputs(node_id, node);
+                };
+                let field = self
+                    .collection_fields
+                    .get(&material_paramenters_collection)
+                    .and_then(|fields| fields.iter().find(|x| &x.name == name));
+                let Some(field) = field else {
+                    self.push_error(node_id, None, format!("The field {} is not found", name));
+                    return self.set_unknown_outputs(node_id, node);
+                };
+                let pin_type = base_data_value_pin_type(&field.data_type);
+                let is_contain = self
+                    .used_material_paramenters_collection_urls
+                    .iter()
+                    .find(|x| **x == material_paramenters_collection)
+                    .is_some();
+                if !is_contain {
+                    self.used_material_paramenters_collection_urls
+                        .push(material_paramenters_collection.clone());
                 }
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, pin_type));
+                let type_text = material_paramenters_collection.get_name_in_editor();
+                format!(
+                    "var {} = material_paramenters_collection_uniform_{}.{};",
+                    var_name, type_text, name
+                )
+            }
+            EMaterialNodeType::Paramenter(_) if self.function_arguments.is_some() => {
+                self.push_error(
+                    node_id,
+                    None,
+                    "Paramenters are not supported in material functions",
+                );
+                self.set_unknown_outputs(node_id, node)
             }
             EMaterialNodeType::Paramenter(struct_field) => {
+                let pin_type = base_data_value_pin_type(&struct_field.data_type);
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, pin_type));
                 format!(
                     "var {} = user_material_paramenters_uniform.{};",
                     &var_name, struct_field.name
                 )
             }
+            EMaterialNodeType::FunctionInput(name, value) => {
+                let Some(function_arguments) = &self.function_arguments else {
+                    self.push_error(
+                        node_id,
+                        None,
+                        "Function inputs are only supported in material functions",
+                    );
+                    return self.set_unknown_outputs(node_id, node);
+                };
+                let argument = function_arguments
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| ResolvedValue::new(value.literal(), value.pin_type()));
+                self.set_output(node_id, 0, ResolvedValue::new(&var_name, argument.pin_type));
+                format!("var {} = {};", var_name, argument.expression)
+            }
+            EMaterialNodeType::MaterialFunction(call) => {
+                self.resolve_function_call(node_id, node, call, &var_name, lines, material_info)
+            }
+        }
+    }
+
+    /// Resolves the graph of the material function inline, the errors of the function are reported at the call.
+    fn resolve_function_call(
+        &mut self,
+        node_id: NodeId,
+        node: &MaterialNode,
+        call: &MaterialFunctionCall,
+        var_name: &str,
+        lines: &mut Vec<String>,
+        material_info: &mut MaterialInfo,
+    ) -> String {
+        let Some(url) = call.url.as_ref() else {
+            self.push_error(node_id, None, "Select a material function");
+            return self.set_unknown_outputs(node_id, node);
+        };
+        let name = url.get_name_in_editor();
+        let material_functions = self.material_functions;
+        let function = material_functions.get(url).map(|x| x.try_borrow());
+        let function = match function {
+            Some(Ok(function)) if !self.function_stack.contains(url) => function,
+            Some(_) => {
+                self.push_error(
+                    node_id,
+                    None,
+                    format!("The material function {} is called recursively", name),
+                );
+                return self.set_unknown_outputs(node_id, node);
+            }
+            None => {
+                self.push_error(
+                    node_id,
+                    None,
+                    format!("The material function {} is not found", name),
+                );
+                return self.set_unknown_outputs(node_id, node);
+            }
+        };
+        let (inputs, outputs) = function_signature(&function.snarl);
+
+        let mut is_valid = true;
+        let mut function_arguments = HashMap::new();
+        for (input, (input_name, _)) in call.inputs.iter().enumerate() {
+            let Some(value) = self.resolve_value_input(node_id, node, input, lines, material_info)
+            else {
+                continue;
+            };
+            let Some((_, input_value)) = inputs.iter().find(|x| &x.0 == input_name) else {
+                self.push_error(
+                    node_id,
+                    Some(input),
+                    format!("{} has no input {}", name, input_name),
+                );
+                is_valid = false;
+                continue;
+            };
+            if value.is_unknown {
+                is_valid = false;
+                continue;
+            }
+            let pin_type = input_value.pin_type();
+            match convert(&value, pin_type) {
+                Some(expression) => {
+                    function_arguments
+                        .insert(input_name.clone(), ResolvedValue::new(expression, pin_type));
+                }
+                None => {
+                    self.push_type_error(node_id, input, pin_type, value.pin_type);
+                    is_valid = false;
+                }
+            }
+        }
+        for output_name in call.outputs.iter() {
+            if !outputs.contains(output_name) {
+                self.push_error(
+                    node_id,
+                    None,
+                    format!("{} has no output {}", name, output_name),
+                );
+                is_valid = false;
+            }
+        }
+        if !is_valid {
+            return self.set_unknown_outputs(node_id, node);
+        }
+
+        let mut context = ResolveContext::new(
+            &function.snarl,
+            self.material_paramenters,
+            material_functions,
+        );
+        context.current_group = self.current_group;
+        context.current_binding = self.current_binding;
+        context.used_material_paramenters_collection_urls =
+            std::mem::take(&mut self.used_material_paramenters_collection_urls);
+        context.collection_fields = std::mem::take(&mut self.collection_fields);
+        context.function_stack = self.function_stack.clone();
+        context.function_stack.push(url.clone());
+        context.function_arguments = Some(function_arguments);
+        context.scope = format!("{}_", var_name);
+
+        let values: Vec<Option<ResolvedValue>> = call
+            .outputs
+            .iter()
+            .map(|output_name| context.resolve_function_output(output_name, lines, material_info))
+            .collect();
+
+        self.current_binding = context.current_binding;
+        self.used_material_paramenters_collection_urls =
+            context.used_material_paramenters_collection_urls;
+        self.collection_fields = context.collection_fields;
+        for error in context.errors {
+            self.push_error(node_id, None, format!("{}: {}", name, error));
+        }
+        for (output, value) in values.into_iter().enumerate() {
+            self.set_output(
+                node_id,
+                output,
+                value.unwrap_or_else(ResolvedValue::unknown),
+            );
+        }
+        String::new()
+    }
+
+    fn resolve_function_output(
+        &mut self,
+        output_name: &str,
+        lines: &mut Vec<String>,
+        material_info: &mut MaterialInfo,
+    ) -> Option<ResolvedValue> {
+        let snarl = self.snarl;
+        let (output_node_id, _) = snarl.node_ids().find(|(_, node)| {
+            matches!(&node.node_type, EMaterialNodeType::FunctionOutput(name) if name == output_name)
+        })?;
+        let value = self.resolve_input(output_node_id, 0, lines, material_info);
+        if value.is_none() {
+            self.push_error(output_node_id, Some(0), "The input is not connected");
         }
+        value
     }
 }
 
@@ -638,6 +1263,7 @@ pub struct ResolveTask {
     material_paramenters: rs_editor_core::material::Paramenters,
     tasks: HashMap<MaterialOptions, Box<dyn CreationTask>>,
     content_manager: SingleThreadMutType<ContentManager>,
+    material_functions: MaterialFunctionGraphs,
 }
 
 impl ResolveTask {
@@ -648,6 +1274,7 @@ impl ResolveTask {
         material_url: url::Url,
         options: Vec<MaterialOptions>,
         material_paramenters: rs_editor_core::material::Paramenters,
+        material_functions: MaterialFunctionGraphs,
     ) -> Self {
         type MCP = dyn rs_editor_core::types::MaterialCreationProxyModule;
         let mut module_manager = module_manager.borrow_mut();
@@ -675,6 +1302,7 @@ impl ResolveTask {
             material_paramenters,
             tasks,
             content_manager,
+            material_functions,
         }
     }
 
@@ -696,8 +1324,11 @@ impl ResolveTask {
             let mut results: HashMap<MaterialOptions, ResolveResult> = HashMap::new();
             let content_manager = self.content_manager.borrow();
             for option in &self.options {
-                let mut resolve_context =
-                    ResolveContext::new(&self.snarl, &self.material_paramenters);
+                let mut resolve_context = ResolveContext::new(
+                    &self.snarl,
+                    &self.material_paramenters,
+                    &self.material_functions,
+                );
                 let result = resolve_context.resolve(&option, &content_manager)?;
                 // let result = resolve_internal(snarl, &option)?;
                 results.insert(option.clone(), result);
@@ -714,6 +1345,7 @@ pub fn resolve_task(
     snarl: Snarl<MaterialNode>,
     options: Vec<MaterialOptions>,
     material_paramenters: rs_editor_core::material::Paramenters,
+    material_functions: MaterialFunctionGraphs,
 ) -> ResolveTask {
     ResolveTask::new(
         module_manager,
@@ -722,6 +1354,7 @@ pub fn resolve_task(
         material_url,
         options,
         material_paramenters,
+        material_functions,
     )
 }
 
@@ -732,6 +1365,7 @@ pub fn resolve(
     snarl: &Snarl<MaterialNode>,
     options: Vec<MaterialOptions>,
     material_paramenters: &rs_editor_core::material::Paramenters,
+    material_functions: &MaterialFunctionGraphs,
 ) -> anyhow::Result<HashMap<MaterialOptions, ResolveResult>> {
     if let Some(material_url) = &material_url {
         if let Ok(results) = resolve_by_proxy(
@@ -747,7 +1381,8 @@ pub fn resolve(
     let mut results: HashMap<MaterialOptions, ResolveResult> = HashMap::new();
     let content_manager = content_manager.borrow();
     for option in options {
-        let mut resolve_context = ResolveContext::new(snarl, material_paramenters);
+        let mut resolve_context =
+            ResolveContext::new(snarl, material_paramenters, material_functions);
         let result = resolve_context.resolve(&option, &content_manager)?;
         // let result = resolve_internal(snarl, &option)?;
         results.insert(option, result);
@@ -755,10 +1390,182 @@ pub fn resolve(
     Ok(results)
 }
 
+/// Type-checks the graph of a material function, the inputs use their default values.
+pub fn check_material_function(
+    content_manager: &ContentManager,
+    material_function_url: &url::Url,
+    snarl: &Snarl<MaterialNode>,
+    material_functions: &MaterialFunctionGraphs,
+) -> anyhow::Result<()> {
+    let material_paramenters = rs_editor_core::material::Paramenters::empty();
+    let mut context = ResolveContext::new(snarl, &material_paramenters, material_functions);
+    context.collection_fields = collect_collection_fields(content_manager);
+    context.function_stack = vec![material_function_url.clone()];
+    context.function_arguments = Some(HashMap::new());
+
+    let mut input_names: HashSet<&String> = HashSet::new();
+    let mut output_names: HashSet<&String> = HashSet::new();
+    for (node_id, node) in snarl.node_ids() {
+        let is_unique = match &node.node_type {
+            EMaterialNodeType::FunctionInput(name, _) => input_names.insert(name),
+            EMaterialNodeType::FunctionOutput(name) => output_names.insert(name),
+            _ => true,
+        };
+        if !is_unique {
+            context.push_error(node_id, None, "The name is already used");
+        }
+    }
+
+    let mut material_info = empty_material_info();
+    let mut lines = vec![];
+    let (_, outputs) = function_signature(snarl);
+    for output_name in outputs {
+        context.resolve_function_output(&output_name, &mut lines, &mut material_info);
+    }
+    if !context.errors.is_empty() {
+        let errors = std::mem::take(&mut context.errors);
+        return Err(TypeCheckError { errors }.into());
+    }
+    Ok(())
+}
+
+/// The inputs and the outputs of a material function, in the order of the creation of the nodes.
+pub fn function_signature(snarl: &Snarl<MaterialNode>) -> (Vec<(String, EValueType)>, Vec<String>) {
+    let mut inputs: Vec<(String, EValueType)> = vec![];
+    let mut outputs: Vec<String> = vec![];
+    for (_, node) in snarl.node_ids() {
+        match &node.node_type {
+            EMaterialNodeType::FunctionInput(name, value) => {
+                if !inputs.iter().any(|x| &x.0 == name) {
+                    inputs.push((name.clone(), *value));
+                }
+            }
+            EMaterialNodeType::FunctionOutput(name) => {
+                if !outputs.contains(name) {
+                    outputs.push(name.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+    (inputs, outputs)
+}
+
+pub fn collect_material_function_graphs(
+    content_manager: &ContentManager,
+    material_functions: &[SingleThreadMutType<crate::material::Material>],
+) -> MaterialFunctionGraphs {
+    collect_typed_contents::<MaterialFunction>(content_manager.content_files())
+        .iter()
+        .filter_map(|content| {
+            let content = content.borrow();
+            let graph = material_functions
+                .iter()
+                .find(|x| x.borrow().url == content.asset_url)?;
+            Some((content.url.clone(), graph.clone()))
+        })
+        .collect()
+}
+
+fn collect_collection_fields(
+    content_manager: &ContentManager,
+) -> HashMap<url::Url, Vec<StructField>> {
+    collect_typed_contents::<MaterialParamentersCollection>(content_manager.content_files())
+        .iter()
+        .map(|x| {
+            let x = x.borrow();
+            (x.url.clone(), x.fields.clone())
+        })
+        .collect()
+}
+
 fn node_var_name(node_id: NodeId) -> String {
     format!("v{}", node_id.0)
 }
 
+fn empty_material_info() -> MaterialInfo {
+    MaterialInfo {
+        map_textures: HashSet::new(),
+        virtual_textures: HashSet::new(),
+        global_constants_binding: None,
+        base_color_sampler_binding: None,
+        physical_texture_binding: None,
+        page_table_texture_binding: None,
+        brdflut_texture_binding: None,
+        pre_filter_cube_map_texture_binding: None,
+        irradiance_texture_binding: None,
+        shadow_map_binding: None,
+        constants_binding: None,
+        point_lights_binding: None,
+        spot_lights_binding: None,
+        skin_constants_binding: None,
+        virtual_texture_constants_binding: None,
+        cluster_light_binding: None,
+        cluster_light_index_binding: None,
+        material_paramenters_collection_bindings: HashSet::new(),
+        paramenters: vec![],
+    }
+}
+
+/// The scalar is broadcast if the types are different.
+fn combine_types(a: EPinType, b: EPinType) -> Option<EPinType> {
+    if a == b || b == EPinType::F32 {
+        Some(a)
+    } else if a == EPinType::F32 {
+        Some(b)
+    } else {
+        None
+    }
+}
+
+fn splat(value: &ResolvedValue, pin_type: EPinType) -> String {
+    if value.pin_type == pin_type {
+        value.expression.clone()
+    } else {
+        format!("{}({})", pin_type.wgsl_type(), value.expression)
+    }
+}
+
+/// The implicit conversions used when a value is connected to the sink.
+fn convert(value: &ResolvedValue, pin_type: EPinType) -> Option<String> {
+    match (value.pin_type, pin_type) {
+        (from, to) if from == to => Some(value.expression.clone()),
+        (EPinType::F32, _) => Some(splat(value, pin_type)),
+        (EPinType::Vec4, EPinType::Vec3) => Some(format!("{}.xyz", value.expression)),
+        _ => None,
+    }
+}
+
+fn swizzle_indices(mask: &str) -> Option<Vec<usize>> {
+    if mask.is_empty() || mask.len() > 4 {
+        return None;
+    }
+    let is_xyzw = mask.chars().all(|x| "xyzw".contains(x));
+    let is_rgba = mask.chars().all(|x| "rgba".contains(x));
+    if !is_xyzw && !is_rgba {
+        return None;
+    }
+    let indices = mask
+        .chars()
+        .map(|x| match x {
+            'x' | 'r' => 0,
+            'y' | 'g' => 1,
+            'z' | 'b' => 2,
+            _ => 3,
+        })
+        .collect();
+    Some(indices)
+}
+
+fn base_data_value_pin_type(data_type: &BaseDataValueType) -> EPinType {
+    match data_type {
+        BaseDataValueType::F32(_) => EPinType::F32,
+        BaseDataValueType::Vec2(_) => EPinType::Vec2,
+        BaseDataValueType::Vec3(_) => EPinType::Vec3,
+        BaseDataValueType::Vec4(_) => EPinType::Vec4,
+    }
+}
+
 fn material_paramenters_collection_to_struct_string(
     material_paramenters_collection: &MaterialParamentersCollection,
 ) -> String {
@@ -813,3 +1620,201 @@ struct {name} {{
 }}"
     );
 }
+
+#[cfg(test)]
+mod test {
+    use super::{MaterialFunctionGraphs, ResolveContext, TypeCheckError, empty_material_info};
+    use crate::ui::material_view::{
+        Attribute, EMaterialNodeType, EValueType, MaterialFunctionCall, MaterialNode,
+    };
+    use egui_snarl::{InPinId, NodeId, OutPinId, Snarl};
+    use rs_foundation::new::SingleThreadMut;
+
+    fn new_snarl() -> Snarl<MaterialNode> {
+        let mut snarl = Snarl::new();
+        snarl.insert_node(
+            egui::pos2(0.0, 0.0),
+            MaterialNode {
+                node_type: EMaterialNodeType::Sink(Attribute::default()),
+            },
+        );
+        snarl
+    }
+
+    fn insert(snarl: &mut Snarl<MaterialNode>, node_type: EMaterialNodeType) -> NodeId {
+        snarl.insert_node(egui::pos2(0.0, 0.0), MaterialNode { node_type })
+    }
+
+    fn connect(snarl: &mut Snarl<MaterialNode>, from: NodeId, to: NodeId, input: usize) {
+        snarl.connect(
+            OutPinId {
+                node: from,
+                output: 0,
+            },
+            InPinId { node: to, input },
+        );
+    }
+
+    fn resolve(snarl: &Snarl<MaterialNode>) -> anyhow::Result<Vec<String>> {
+        resolve_with_functions(snarl, &MaterialFunctionGraphs::new())
+    }
+
+    fn resolve_with_functions(
+        snarl: &Snarl<MaterialNode>,
+        material_functions: &MaterialFunctionGraphs,
+    ) -> anyhow::Result<Vec<String>> {
+        let paramenters = rs_editor_core::material::Paramenters::empty();
+        ResolveContext::new(snarl, &paramenters, material_functions)
+            .resolve_material_code(&mut empty_material_info())
+    }
+
+    fn function_call(url: &url::Url) -> EMaterialNodeType {
+        EMaterialNodeType::MaterialFunction(MaterialFunctionCall {
+            url: Some(url.clone()),
+            inputs: vec![("A".to_string(), EValueType::F32(3.0))],
+            outputs: vec!["Result".to_string()],
+        })
+    }
+
+    #[test]
+    fn test_shared_node() {
+        let mut snarl = new_snarl();
+        let tex_coord = insert(&mut snarl, EMaterialNodeType::TexCoord(0));
+        let split = insert(&mut snarl, EMaterialNodeType::ComponentSplit);
+        connect(&mut snarl, tex_coord, split, 0);
+        let add = insert(
+            &mut snarl,
+            EMaterialNodeType::Add(EValueType::F32(0.0), EValueType::F32(0.0)),
+        );
+        connect(&mut snarl, split, add, 0);
+        connect(&mut snarl, split, add, 1);
+        connect(&mut snarl, add, NodeId(0), 1);
+        connect(&mut snarl, add, NodeId(0), 2);
+
+        let lines = resolve(&snarl).unwrap();
+        let var_name = format!("var v{} =", tex_coord.0);
+        assert_eq!(lines.iter().filter(|x| x.starts_with(&var_name)).count(), 1);
+        let var_name = format!("var v{} =", add.0);
+        assert_eq!(lines.iter().filter(|x| x.starts_with(&var_name)).count(), 1);
+        assert!(lines.contains(&format!("    user_attributes.metallic = v{};", add.0)));
+    }
+
+    #[test]
+    fn test_type_error() {
+        let mut snarl = new_snarl();
+        let tex_coord = insert(&mut snarl, EMaterialNodeType::TexCoord(0));
+        let cross = insert(
+            &mut snarl,
+            EMaterialNodeType::Cross(
+                EValueType::Vec3(glam::Vec3::ZERO),
+                EValueType::Vec3(glam::Vec3::ZERO),
+            ),
+        );
+        connect(&mut snarl, tex_coord, cross, 0);
+        connect(&mut snarl, cross, NodeId(0), 0);
+        connect(&mut snarl, tex_coord, NodeId(0), 3);
+
+        let error = resolve(&snarl).unwrap_err();
+        let error = error.downcast_ref::<TypeCheckError>().unwrap();
+        assert_eq!(error.errors.len(), 2);
+        assert_eq!(error.errors[0].node_id, cross);
+        assert_eq!(error.errors[0].input, Some(0));
+        assert_eq!(error.errors[1].node_id, NodeId(0));
+        assert_eq!(error.errors[1].input, Some(3));
+    }
+
+    #[test]
+    fn test_static_switch() {
+        let mut snarl = new_snarl();
+        let time = insert(&mut snarl, EMaterialNodeType::Time);
+        let tex_coord = insert(&mut snarl, EMaterialNodeType::TexCoord(0));
+        let normalize = insert(
+            &mut snarl,
+            EMaterialNodeType::Normalize(EValueType::Vec3(glam::Vec3::ONE)),
+        );
+        connect(&mut snarl, time, normalize, 0);
+        let switch = insert(&mut snarl, EMaterialNodeType::StaticSwitch(false));
+        connect(&mut snarl, normalize, switch, 0);
+        connect(&mut snarl, tex_coord, switch, 1);
+        let split = insert(&mut snarl, EMaterialNodeType::ComponentSplit);
+        connect(&mut snarl, switch, split, 0);
+        connect(&mut snarl, split, NodeId(0), 2);
+
+        let lines = resolve(&snarl).unwrap();
+        assert!(lines.contains(&format!("var v{} = v{};", switch.0, tex_coord.0)));
+        assert!(!lines.iter().any(|x| x.contains("normalize")));
+
+        snarl[switch].node_type = EMaterialNodeType::StaticSwitch(true);
+        let error = resolve(&snarl).unwrap_err();
+        let error = error.downcast_ref::<TypeCheckError>().unwrap();
+        assert_eq!(error.errors.len(), 1);
+        assert_eq!(error.errors[0].node_id, normalize);
+    }
+
+    #[test]
+    fn test_material_function() {
+        let url = rs_engine::build_content_file_url("Double").unwrap();
+        let mut function = Snarl::new();
+        let input = insert(
+            &mut function,
+            EMaterialNodeType::FunctionInput("A".to_string(), EValueType::F32(1.0)),
+        );
+        let multiply = insert(
+            &mut function,
+            EMaterialNodeType::Multiply(EValueType::F32(1.0), EValueType::F32(2.0)),
+        );
+        let output = insert(
+            &mut function,
+            EMaterialNodeType::FunctionOutput("Result".to_string()),
+        );
+        connect(&mut function, input, multiply, 0);
+        connect(&mut function, multiply, output, 0);
+        let mut material_functions = MaterialFunctionGraphs::new();
+        material_functions.insert(
+            url.clone(),
+            SingleThreadMut::new(crate::material::Material::new(url.clone(), function)),
+        );
+
+        let mut snarl = new_snarl();
+        let call = insert(&mut snarl, function_call(&url));
+        connect(&mut snarl, call, NodeId(0), 1);
+
+        let lines = resolve_with_functions(&snarl, &material_functions).unwrap();
+        let var_name = format!("var v{}_v{} =", call.0, input.0);
+        assert_eq!(lines.iter().filter(|x| x.starts_with(&var_name)).count(), 1);
+        assert!(lines.contains(&format!(
+            "    user_attributes.metallic = v{}_v{};",
+            call.0, multiply.0
+        )));
+    }
+
+    #[test]
+    fn test_recursive_material_function() {
+        let url = rs_engine::build_content_file_url("Recursive").unwrap();
+        let mut function = Snarl::new();
+        let inner_call = insert(&mut function, function_call(&url));
+        let output = insert(
+            &mut function,
+            EMaterialNodeType::FunctionOutput("Result".to_string()),
+        );
+        connect(&mut function, inner_call, output, 0);
+        let mut material_functions = MaterialFunctionGraphs::new();
+        material_functions.insert(
+            url.clone(),
+            SingleThreadMut::new(crate::material::Material::new(url.clone(), function)),
+        );
+
+        let mut snarl = new_snarl();
+        let call = insert(&mut snarl, function_call(&url));
+        connect(&mut snarl, call, NodeId(0), 1);
+
+        let error = resolve_with_functions(&snarl, &material_functions).unwrap_err();
+        let error = error.downcast_ref::<TypeCheckError>().unwrap();
+        assert!(
+            error
+                .errors
+                .iter()
+                .any(|x| x.node_id == call && x.message.contains("recursively"))
+        );
+    }
+}
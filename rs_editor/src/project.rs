@@ -26,6 +26,8 @@ pub struct Project {
     pub settings: Rc<RefCell<Settings>>,
     pub endian_type: EEndianType,
     pub materials: Vec<Rc<RefCell<crate::material::Material>>>,
+    #[serde(default)]
+    pub material_functions: Vec<Rc<RefCell<crate::material::Material>>>,
 }
 
 impl Project {
@@ -68,6 +70,7 @@ impl Project {
             endian_type: EEndianType::Little,
             settings: Rc::new(RefCell::new(Settings::default())),
             materials: vec![],
+            material_functions: vec![],
         };
         let json_str = serde_json::ser::to_string_pretty(&empty_project)?;
         let mut file = std::fs::File::create(project_file_path)?;
@@ -1,23 +1,37 @@
 use crate::{
     build_config::{BuildConfig, EArchType, EBuildPlatformType, EBuildType},
     content_edit::ContentEdit,
+    cook::CookProfile,
     project::{ASSET_FOLDER_NAME, CONTENT_FOLDER_NAME, Project},
 };
 use anyhow::{Context, anyhow};
-use notify::ReadDirectoryChangesWatcher;
+use notify::RecommendedWatcher;
 use notify_debouncer_mini::{DebouncedEvent, Debouncer};
 use rs_artifact::{
-    EEndianType, artifact::ArtifactAssetEncoder, shader_source_code::ShaderSourceCode,
+    artifact::ArtifactAssetEncoder,
+    artifact_index::ArtifactIndex,
+    compiled_shader::{CompiledShader, EShaderTarget},
+    derive_data::compressed_texture::CompressedTexture,
+    shader_source_code::ShaderSourceCode,
 };
 use rs_content_manager::content_manager::ContentManager;
-use rs_engine::{ASSET_SCHEME, thread_pool::ThreadPool};
+use rs_engine::{
+    ASSET_SCHEME,
+    content::{
+        content_file_type::collect_typed_contents, material::Material,
+        material_function::MaterialFunction, texture::TextureFile,
+    },
+    thread_pool::ThreadPool,
+};
 use rs_foundation::new::{SingleThreadMut, SingleThreadMutType};
 use rs_hotreload_plugin::hot_reload::HotReload;
 use rs_model_loader::model_loader::ModelLoader;
 use rs_module::types::ModuleManager;
+use rs_render::shader_library::ShaderNagaModule;
+use rs_texpress::TextureFormatType;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     io::Write,
     path::{Path, PathBuf},
 };
@@ -60,16 +74,35 @@ pub struct ProjectContext {
     project_folder_path: PathBuf,
     project_file_path: PathBuf,
     _shader_folder_path: PathBuf,
-    pub hot_reload: rs_hotreload_plugin::hot_reload::HotReload,
+    /// None if the project is opened without the editor.
+    pub hot_reload: Option<rs_hotreload_plugin::hot_reload::HotReload>,
     folder_receiver:
         Option<std::sync::mpsc::Receiver<std::result::Result<Vec<DebouncedEvent>, notify::Error>>>,
-    folder_debouncer: Option<Debouncer<ReadDirectoryChangesWatcher>>,
+    folder_debouncer: Option<Debouncer<RecommendedWatcher>>,
     pub content_manager: SingleThreadMutType<ContentManager>,
     pub module_manager: SingleThreadMutType<ModuleManager>,
 }
 
 impl ProjectContext {
     pub fn open(project_file_path: &Path) -> anyhow::Result<ProjectContext> {
+        let mut context = Self::open_headless(project_file_path)?;
+        // #[cfg(debug_assertions)]
+        // let lib_folder = context.get_project_folder_path().join("target").join("debug");
+        // #[cfg(not(debug_assertions))]
+        // let lib_folder = context.get_project_folder_path().join("target").join("release");
+        let lib_folder = std::env::current_dir()?.join("deps");
+        context.hot_reload = Some(HotReload::new(
+            &lib_folder,
+            &lib_folder,
+            &context.project.project_name,
+        )?);
+        context.watch_project_folder()?;
+        Ok(context)
+    }
+
+    /// Opens the project without reloading the plugin and watching the project folder,
+    /// used by the commands which run without the editor, such as cooking.
+    pub fn open_headless(project_file_path: &Path) -> anyhow::Result<ProjectContext> {
         let project_folder_path =
             project_file_path
                 .parent()
@@ -81,18 +114,12 @@ impl ProjectContext {
         let reader = std::io::BufReader::new(file);
         let project: Project = serde_json::de::from_reader(reader)
             .context("Failed to deserialize JSON data to a project data structure.")?;
-        // #[cfg(debug_assertions)]
-        // let lib_folder = project_folder_path.join("target").join("debug");
-        // #[cfg(not(debug_assertions))]
-        // let lib_folder = project_folder_path.join("target").join("release");
-        let lib_folder = std::env::current_dir()?.join("deps");
-        let hot_reload = HotReload::new(&lib_folder, &lib_folder, &project.project_name)?;
         let module_manager = SingleThreadMut::new(ModuleManager::new());
         let mut context = ProjectContext {
             project,
             project_file_path: project_file_path.to_path_buf(),
             project_folder_path: project_folder_path.to_path_buf(),
-            hot_reload,
+            hot_reload: None,
             _shader_folder_path: project_folder_path.join("shader"),
             folder_receiver: None,
             folder_debouncer: None,
@@ -101,7 +128,17 @@ impl ProjectContext {
             )),
             module_manager,
         };
-        context.watch_project_folder()?;
+        let file_format = context
+            .project
+            .settings
+            .borrow()
+            .editor_settings
+            .content_file_format;
+        context
+            .content_manager
+            .borrow_mut()
+            .set_file_format(file_format);
+        context.update_material_dependencies();
         Ok(context)
     }
 
@@ -152,11 +189,15 @@ impl ProjectContext {
     }
 
     pub fn is_need_reload_plugin(&self) -> bool {
-        self.hot_reload.is_need_reload()
+        self.hot_reload.as_ref().is_some_and(|x| x.is_need_reload())
     }
 
     pub fn reload(&mut self) -> anyhow::Result<()> {
-        Ok(self.hot_reload.reload()?)
+        let hot_reload = self
+            .hot_reload
+            .as_mut()
+            .ok_or(anyhow!("The plugin is not reloaded"))?;
+        Ok(hot_reload.reload()?)
     }
 
     pub fn get_asset_folder_path(&self) -> PathBuf {
@@ -182,15 +223,62 @@ impl ProjectContext {
     }
 
     pub fn save(&self) -> anyhow::Result<()> {
+        let file_format = self
+            .project
+            .settings
+            .borrow()
+            .editor_settings
+            .content_file_format;
+        self.content_manager
+            .borrow_mut()
+            .set_file_format(file_format);
         let errors = self.content_manager.borrow().sync_disk();
         for (url, error) in errors {
             log::warn!("url: {}, error: {}", url, error);
         }
+        // The saved contents may refer to other contents now.
+        self.content_manager.borrow_mut().rebuild_dependency_graph();
+        self.update_material_dependencies();
         let json_str = serde_json::ser::to_string_pretty(&self.project)?;
         let mut file = std::fs::File::create(self.project_file_path.clone())?;
         Ok(file.write_fmt(format_args!("{}", json_str))?)
     }
 
+    /// The graphs of the materials are stored in the project instead of the contents,
+    /// the contents they refer to are added to the dependency graph of the content manager.
+    pub fn update_material_dependencies(&self) {
+        let mut content_manager = self.content_manager.borrow_mut();
+        let mut urls: Vec<(url::Url, url::Url)> =
+            collect_typed_contents::<Material>(content_manager.content_files())
+                .iter()
+                .map(|x| {
+                    let x = x.borrow();
+                    (x.url.clone(), x.asset_url.clone())
+                })
+                .collect();
+        urls.extend(
+            collect_typed_contents::<MaterialFunction>(content_manager.content_files())
+                .iter()
+                .map(|x| {
+                    let x = x.borrow();
+                    (x.url.clone(), x.asset_url.clone())
+                }),
+        );
+        let graphs = self
+            .project
+            .materials
+            .iter()
+            .chain(self.project.material_functions.iter());
+        for (url, asset_url) in urls {
+            let dependencies = graphs
+                .clone()
+                .find(|x| x.borrow().url == asset_url)
+                .map(|x| x.borrow().content_urls())
+                .unwrap_or_default();
+            content_manager.set_external_dependencies(&url, dependencies);
+        }
+    }
+
     pub fn get_project_folder_path(&self) -> PathBuf {
         self.project_folder_path.clone()
     }
@@ -239,6 +327,10 @@ impl ProjectContext {
         url::Url::parse(&format!("asset://shader/{}", name)).unwrap()
     }
 
+    pub fn build_compiled_shader_url(name: &str, target: EShaderTarget) -> url::Url {
+        url::Url::parse(&format!("asset://compiled_shader/{:?}/{}", target, name)).unwrap()
+    }
+
     pub fn get_build_dir(&self) -> PathBuf {
         self.project_folder_path.join("build")
     }
@@ -340,71 +432,170 @@ impl ProjectContext {
         model_loader: &mut ModelLoader,
         content_edit: &mut ContentEdit,
     ) -> anyhow::Result<PathBuf> {
-        let _span = tracy_client::span!();
-
         let output_folder_path = self.try_create_build_dir()?;
-        if !output_folder_path.exists() {
-            std::fs::create_dir(output_folder_path.clone())?;
-        }
-        let output_filename = "main.rs";
+        let artifact_file_path = output_folder_path.join("main.rs");
+        let _ = self.cook(
+            model_loader,
+            content_edit,
+            &CookProfile::default(),
+            &artifact_file_path,
+        )?;
+        Ok(artifact_file_path)
+    }
+
+    /// Cooks the content of the project for the platform of `cook_profile`. The assets are written
+    /// to disk as soon as they are exported, so the memory doesn't grow with the project.
+    pub fn cook(
+        &mut self,
+        model_loader: &mut ModelLoader,
+        content_edit: &mut ContentEdit,
+        cook_profile: &CookProfile,
+        artifact_file_path: &Path,
+    ) -> anyhow::Result<ArtifactIndex> {
+        let _span = tracy_client::span!();
 
+        cook_profile.validate()?;
+        let texture_format = cook_profile.get_texture_format()?;
         let mut artifact_asset_encoder = ArtifactAssetEncoder::new(
-            Some(EEndianType::Little),
+            Some(cook_profile.endian_type),
             self.project.settings.borrow().clone(),
-            &output_folder_path.join(output_filename),
-        );
-
-        let mut shader_source_codes: HashMap<
-            url::Url,
-            rs_artifact::shader_source_code::ShaderSourceCode,
-        > = HashMap::new();
-        let mut associated_assets: HashMap<url::Url, Box<dyn rs_artifact_types::asset::Asset>> =
-            HashMap::new();
+            artifact_file_path,
+        )
+        .with_max_shard_size(cook_profile.max_shard_size);
+        let mut encoded_urls: HashSet<url::Url> = HashSet::new();
 
         for content in self.content_manager.borrow().content_files() {
+            let url = {
+                let content = content.borrow();
+                if cook_profile.is_strip_editor_only_data
+                    && crate::cook::is_editor_only(content.as_ref())
+                {
+                    log::trace!("Strip editor only content: {}", content.get_url());
+                    continue;
+                }
+                content.get_url()
+            };
             let editable = content_edit.editable(content.borrow().as_ref());
-            if let Some(editable) = editable {
-                let _ = editable.export(
-                    content.clone(),
-                    &mut artifact_asset_encoder,
-                    &mut associated_assets,
-                    model_loader,
-                    self,
-                )?;
+            let Some(editable) = editable else {
+                continue;
+            };
+            artifact_asset_encoder.set_chunk(cook_profile.get_chunk(&url));
+            let mut associated_assets: HashMap<url::Url, Box<dyn rs_artifact_types::asset::Asset>> =
+                HashMap::new();
+            let _ = editable.export(
+                content.clone(),
+                &mut artifact_asset_encoder,
+                &mut associated_assets,
+                model_loader,
+                self,
+            )?;
+            if let Some(texture_format) = texture_format
+                && let Some(compressed_texture) =
+                    self.cook_compressed_texture(content.borrow().as_ref(), texture_format)?
+            {
+                associated_assets
+                    .insert(compressed_texture.url.clone(), Box::new(compressed_texture));
+            }
+            // Shared by several contents, such as the images of textures, but only written once.
+            for (url, associated_asset) in associated_assets {
+                if encoded_urls.insert(url) {
+                    artifact_asset_encoder.encode_asset(associated_asset.as_ref());
+                }
             }
         }
 
+        let shader_targets: HashSet<EShaderTarget> = match &cook_profile.shader_targets {
+            Some(shader_targets) => shader_targets.iter().copied().collect(),
+            None => {
+                let settings = self.project.settings.borrow();
+                let render_setting = &settings.render_setting;
+                rs_render::cross_compile::get_shader_targets(&render_setting.backends)
+                    .into_iter()
+                    .chain(rs_render::cross_compile::get_shader_targets(
+                        &render_setting.android_backends,
+                    ))
+                    .collect()
+            }
+        };
+
+        artifact_asset_encoder.set_chunk(None);
         for (name, code) in Self::pre_process_shaders() {
-            let url = Self::build_shader_url(&name);
-            let shader_source_code = ShaderSourceCode {
+            match naga::front::wgsl::parse_str(&code) {
+                Ok(module) => {
+                    for target in shader_targets.iter().copied() {
+                        match rs_render::cross_compile::cross_compile(&module, target) {
+                            Ok(compiled_code) => {
+                                artifact_asset_encoder.encode_asset(&CompiledShader {
+                                    name: name.clone(),
+                                    id: uuid::Uuid::new_v4(),
+                                    url: Self::build_compiled_shader_url(&name, target),
+                                    code: compiled_code,
+                                })
+                            }
+                            Err(err) => {
+                                log::warn!("Can not compile {} to {:?}, {}", name, target, err)
+                            }
+                        }
+                    }
+                }
+                Err(err) => log::warn!("{}, {}", name, err),
+            }
+
+            artifact_asset_encoder.encode_asset(&ShaderSourceCode {
                 name: name.clone(),
                 id: uuid::Uuid::new_v4(),
                 url: Self::build_shader_url(&name),
                 code,
-            };
-            shader_source_codes.insert(url, shader_source_code);
+            });
         }
 
-        // FIXME: Out of memory
-        for asset in shader_source_codes.values() {
-            artifact_asset_encoder.encode_asset(asset);
-        }
-        for (_, associated_asset) in associated_assets {
-            artifact_asset_encoder.encode_asset(associated_asset.as_ref());
+        Ok(artifact_asset_encoder.finish()?)
+    }
+
+    /// The texture compressed to `texture_format` if `content` is a texture marked as compressed.
+    fn cook_compressed_texture(
+        &self,
+        content: &dyn rs_content::Content,
+        texture_format: TextureFormatType,
+    ) -> anyhow::Result<Option<CompressedTexture>> {
+        let Some(texture) = content.downcast_ref::<TextureFile>() else {
+            return Ok(None);
+        };
+        if !texture.is_compressed {
+            return Ok(None);
         }
-        let _ = artifact_asset_encoder.finish()?;
-        Ok(output_folder_path.join(output_filename))
+        let image_reference = texture
+            .image_reference
+            .clone()
+            .ok_or(anyhow!("No image of {}", texture.url))?;
+        let path = self.get_asset_path_by_url(&image_reference);
+        let tmp_file = self.try_create_tmp_dir()?.join(format!(
+            "{}.{}",
+            uuid::Uuid::new_v4(),
+            crate::cook::get_texture_file_extension(texture_format)
+        ));
+        let compressed_texture = crate::cook::compress_texture(
+            texture_format,
+            &path,
+            &tmp_file,
+            image_reference,
+            texture.compressed_texture_url.clone(),
+        )?;
+        Ok(Some(compressed_texture))
     }
 
-    pub fn load_shader_naga_modules() -> HashMap<String, naga::Module> {
+    pub fn load_shader_naga_modules() -> HashMap<String, ShaderNagaModule> {
         let _span = tracy_client::span!();
         let mut shaders = HashMap::new();
         let buildin_shaders = rs_render::global_shaders::get_buildin_shaders();
         let read_folder_path: PathBuf =
             rs_core_minimal::file_manager::get_engine_output_target_dir().join("shaders");
 
-        for buildin_shader in buildin_shaders {
-            let name = buildin_shader.get_name();
+        let names = buildin_shaders
+            .iter()
+            .flat_map(|x| x.get_permutation_descriptions())
+            .map(|(name, _)| name);
+        for name in names {
             let read_path = read_folder_path.join(format!("{}.nagamodule", &name));
             let file = match std::fs::File::open(&read_path) {
                 Ok(file) => file,
@@ -434,7 +625,20 @@ impl ProjectContext {
                     continue;
                 }
             };
-            shaders.insert(name, module);
+            let reflection_path = read_folder_path.join(format!("{}.reflection.json", &name));
+            let reflection = match std::fs::File::open(&reflection_path) {
+                Ok(file) => serde_json::from_reader(std::io::BufReader::new(file))
+                    .inspect_err(|err| {
+                        log::warn!(
+                            "Failed to read shader reflection, {}. {}",
+                            &reflection_path.to_string_lossy(),
+                            err
+                        )
+                    })
+                    .ok(),
+                Err(_) => None,
+            };
+            shaders.insert(name, ShaderNagaModule { module, reflection });
         }
 
         shaders
@@ -449,11 +653,13 @@ impl ProjectContext {
             name: String,
             code: anyhow::Result<String>,
         }
-        let mut is_finish = buildin_shaders.len();
-        for buildin_shader in buildin_shaders {
+        let descriptions: Vec<_> = buildin_shaders
+            .iter()
+            .flat_map(|x| x.get_permutation_descriptions())
+            .collect();
+        let mut is_finish = descriptions.len();
+        for (name, description) in descriptions {
             ThreadPool::global().spawn({
-                let description = buildin_shader.get_shader_description();
-                let name = buildin_shader.get_name();
                 let sender = sender.clone();
                 move || {
                     let span = tracy_client::span!();
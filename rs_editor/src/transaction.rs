@@ -0,0 +1,308 @@
+use crate::error::{Error, Result};
+use rs_engine::content::level::Level;
+use std::collections::VecDeque;
+
+const DEFAULT_MAX_TRANSACTIONS: usize = 128;
+
+#[derive(Debug, Clone)]
+pub struct Transaction {
+    pub name: String,
+    before: String,
+    after: String,
+}
+
+#[derive(Debug, Clone)]
+struct Group {
+    name: String,
+    index: Option<usize>,
+}
+
+/// Records edits as pairs of serialized snapshots.
+pub struct TransactionHistory {
+    undo_stack: VecDeque<Transaction>,
+    redo_stack: Vec<Transaction>,
+    current: String,
+    max_transactions: usize,
+    group: Option<Group>,
+    pending: Option<String>,
+}
+
+impl TransactionHistory {
+    pub fn new(snapshot: String) -> TransactionHistory {
+        TransactionHistory {
+            undo_stack: VecDeque::new(),
+            redo_stack: vec![],
+            current: snapshot,
+            max_transactions: DEFAULT_MAX_TRANSACTIONS,
+            group: None,
+            pending: None,
+        }
+    }
+
+    pub fn set_max_transactions(&mut self, max_transactions: usize) {
+        self.max_transactions = max_transactions.max(1);
+        while self.undo_stack.len() > self.max_transactions {
+            self.undo_stack.pop_front();
+        }
+    }
+
+    /// Clears the history and uses the snapshot as the new initial state.
+    pub fn reset(&mut self, snapshot: String) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.current = snapshot;
+        self.group = None;
+        self.pending = None;
+    }
+
+    /// Records a transaction if the snapshot differs from the current state.
+    /// Returns true if a new transaction was committed or merged into the open group.
+    pub fn record(&mut self, name: impl AsRef<str>, snapshot: String) -> bool {
+        self.pending = None;
+        if snapshot == self.current {
+            return false;
+        }
+        let before = std::mem::replace(&mut self.current, snapshot.clone());
+        self.redo_stack.clear();
+
+        if let Some(group) = self.group.as_mut() {
+            if let Some(index) = group.index {
+                if let Some(transaction) = self.undo_stack.get_mut(index) {
+                    transaction.after = snapshot;
+                    return true;
+                }
+            }
+            self.undo_stack.push_back(Transaction {
+                name: group.name.clone(),
+                before,
+                after: snapshot,
+            });
+            if self.undo_stack.len() > self.max_transactions {
+                self.undo_stack.pop_front();
+            }
+            group.index = Some(self.undo_stack.len() - 1);
+            return true;
+        }
+
+        self.undo_stack.push_back(Transaction {
+            name: name.as_ref().to_string(),
+            before,
+            after: snapshot,
+        });
+        if self.undo_stack.len() > self.max_transactions {
+            self.undo_stack.pop_front();
+        }
+        true
+    }
+
+    /// All transactions recorded until `end_group` are merged into one.
+    pub fn begin_group(&mut self, name: impl AsRef<str>) {
+        if self.group.is_some() {
+            return;
+        }
+        self.group = Some(Group {
+            name: name.as_ref().to_string(),
+            index: None,
+        });
+    }
+
+    pub fn end_group(&mut self) {
+        self.group = None;
+    }
+
+    pub fn is_in_group(&self) -> bool {
+        self.group.is_some()
+    }
+
+    /// Marks that an edit happened, the caller records it once the edit is finished.
+    pub fn mark_pending(&mut self, name: impl AsRef<str>) {
+        if self.pending.is_none() {
+            self.pending = Some(name.as_ref().to_string());
+        }
+    }
+
+    pub fn take_pending(&mut self) -> Option<String> {
+        self.pending.take()
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Returns the snapshot to restore.
+    pub fn undo(&mut self) -> Option<String> {
+        self.group = None;
+        self.pending = None;
+        let transaction = self.undo_stack.pop_back()?;
+        self.current = transaction.before.clone();
+        let snapshot = transaction.before.clone();
+        self.redo_stack.push(transaction);
+        Some(snapshot)
+    }
+
+    /// Returns the snapshot to restore.
+    pub fn redo(&mut self) -> Option<String> {
+        self.group = None;
+        self.pending = None;
+        let transaction = self.redo_stack.pop()?;
+        self.current = transaction.after.clone();
+        let snapshot = transaction.after.clone();
+        self.undo_stack.push_back(transaction);
+        Some(snapshot)
+    }
+
+    /// Undoes or redoes until `undo_names().len() == position`.
+    pub fn jump_to(&mut self, position: usize) -> Option<String> {
+        let position = position.min(self.undo_stack.len() + self.redo_stack.len());
+        let mut snapshot = None;
+        while self.undo_stack.len() > position {
+            snapshot = self.undo();
+        }
+        while self.undo_stack.len() < position {
+            snapshot = self.redo();
+        }
+        snapshot
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// From the oldest to the newest.
+    pub fn undo_names(&self) -> Vec<String> {
+        self.undo_stack.iter().map(|x| x.name.clone()).collect()
+    }
+
+    /// From the next one to redo to the last one.
+    pub fn redo_names(&self) -> Vec<String> {
+        self.redo_stack
+            .iter()
+            .rev()
+            .map(|x| x.name.clone())
+            .collect()
+    }
+
+    pub fn current(&self) -> &str {
+        &self.current
+    }
+}
+
+impl Default for TransactionHistory {
+    fn default() -> Self {
+        Self::new(String::new())
+    }
+}
+
+pub fn level_snapshot(level: &Level) -> Result<String> {
+    serde_json::to_string(level).map_err(|err| Error::Other(err.to_string()))
+}
+
+/// The restored level is not initialized.
+pub fn restore_level(snapshot: &str) -> Result<Level> {
+    serde_json::from_str(snapshot).map_err(|err| Error::Other(err.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TransactionHistory, level_snapshot, restore_level};
+    use rs_engine::content::level::Level;
+
+    fn transformation(level: &Level, name: &str) -> glam::Mat4 {
+        let actor = level.find_actor(name).unwrap();
+        let actor = actor.borrow();
+        let scene_node = actor.scene_node.borrow();
+        let transformation = scene_node.component().get_transformation();
+        transformation
+    }
+
+    fn set_transformation(level: &Level, name: &str, transformation: glam::Mat4) {
+        let actor = level.find_actor(name).unwrap();
+        let actor = actor.borrow();
+        let mut scene_node = actor.scene_node.borrow_mut();
+        scene_node
+            .component_mut()
+            .set_transformation(transformation);
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let mut level = Level::new("Test");
+        let mut history = TransactionHistory::new(level_snapshot(&level).unwrap());
+        assert!(!history.can_undo());
+
+        level.create_and_insert_actor();
+        assert!(history.record("Create Actor", level_snapshot(&level).unwrap()));
+        assert!(!history.record("Nothing", level_snapshot(&level).unwrap()));
+
+        let name = level.actors[0].borrow().name.clone();
+        let moved = glam::Mat4::from_translation(glam::vec3(1.0, 2.0, 3.0));
+        set_transformation(&level, &name, moved);
+        assert!(history.record("Transform", level_snapshot(&level).unwrap()));
+        assert_eq!(history.undo_names(), vec!["Create Actor", "Transform"]);
+
+        level = restore_level(&history.undo().unwrap()).unwrap();
+        assert_eq!(transformation(&level, &name), glam::Mat4::IDENTITY);
+        assert_eq!(history.redo_names(), vec!["Transform"]);
+
+        level = restore_level(&history.undo().unwrap()).unwrap();
+        assert!(level.actors.is_empty());
+        assert!(history.undo().is_none());
+
+        level = restore_level(&history.redo().unwrap()).unwrap();
+        assert_eq!(level.actors.len(), 1);
+        level = restore_level(&history.redo().unwrap()).unwrap();
+        assert_eq!(transformation(&level, &name), moved);
+        assert!(!history.can_redo());
+
+        level = restore_level(&history.jump_to(0).unwrap()).unwrap();
+        assert!(level.actors.is_empty());
+        assert_eq!(history.redo_names().len(), 2);
+
+        level.create_and_insert_actor();
+        level.create_and_insert_actor();
+        assert!(history.record("Create Actors", level_snapshot(&level).unwrap()));
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn test_group() {
+        let mut level = Level::new("Test");
+        let mut history = TransactionHistory::new(level_snapshot(&level).unwrap());
+        level.create_and_insert_actor();
+        history.record("Create Actor", level_snapshot(&level).unwrap());
+        let name = level.actors[0].borrow().name.clone();
+
+        history.begin_group("Drag");
+        for i in 1..=10 {
+            let moved = glam::Mat4::from_translation(glam::vec3(i as f32, 0.0, 0.0));
+            set_transformation(&level, &name, moved);
+            history.record("Transform", level_snapshot(&level).unwrap());
+        }
+        history.end_group();
+        assert_eq!(history.undo_names(), vec!["Create Actor", "Drag"]);
+
+        level = restore_level(&history.undo().unwrap()).unwrap();
+        assert_eq!(transformation(&level, &name), glam::Mat4::IDENTITY);
+        level = restore_level(&history.redo().unwrap()).unwrap();
+        assert_eq!(
+            transformation(&level, &name),
+            glam::Mat4::from_translation(glam::vec3(10.0, 0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn test_max_transactions() {
+        let mut history = TransactionHistory::new("0".to_string());
+        history.set_max_transactions(3);
+        for i in 1..=5 {
+            history.record(i.to_string(), i.to_string());
+        }
+        assert_eq!(history.undo_names(), vec!["3", "4", "5"]);
+        assert_eq!(history.jump_to(0), Some("2".to_string()));
+        assert_eq!(history.current(), "2");
+    }
+}
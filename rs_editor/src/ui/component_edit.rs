@@ -1,5 +1,7 @@
 mod camera;
+mod character_controller;
 mod collision;
+mod joint;
 mod point_light;
 mod skeleton_mesh;
 mod spot_light;
@@ -9,7 +11,8 @@ mod text;
 use crate::ui::{
     UIEvent,
     component_edit::{
-        camera::CameraComponentEdit, collision::CollisionComponentEdit,
+        camera::CameraComponentEdit, character_controller::CharacterControllerComponentEdit,
+        collision::CollisionComponentEdit, joint::JointComponentEdit,
         point_light::PointLightComponentEdit, skeleton_mesh::SkeletonMeshComponentEdit,
         spot_light::SpotLightComponentEdit, static_mesh::StaticMeshComponentEdit,
         text::TextComponentEdit,
@@ -23,7 +26,8 @@ use rs_engine::{
     camera_component::CameraComponent,
     collision_componenet::CollisionComponent,
     components::{
-        component::Component, point_light_component::PointLightComponent,
+        character_controller_component::CharacterControllerComponent, component::Component,
+        joint_component::JointComponent, point_light_component::PointLightComponent,
         spot_light_component::SpotLightComponent, text_component::TextComponent,
     },
     engine::Engine,
@@ -115,6 +119,14 @@ impl ComponentEdit {
             TypeId::of::<SkeletonMeshComponent>(),
             Box::new(SkeletonMeshComponentEdit {}),
         );
+        let _ = editables.insert(
+            TypeId::of::<CharacterControllerComponent>(),
+            Box::new(CharacterControllerComponentEdit {}),
+        );
+        let _ = editables.insert(
+            TypeId::of::<JointComponent>(),
+            Box::new(JointComponentEdit {}),
+        );
         ComponentEdit { editables }
     }
 
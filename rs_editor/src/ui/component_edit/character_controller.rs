@@ -0,0 +1,180 @@
+use crate::ui::{
+    UIEvent,
+    component_edit::{ComponentEditable, UIComponentPropertyEvent},
+    object_property_view::ObjectPropertyView,
+};
+use egui::Ui;
+use rs_content_manager::content_manager::ContentManager;
+use rs_engine::{
+    components::{
+        character_controller_component::CharacterControllerComponent, component::Component,
+    },
+    engine::Engine,
+};
+use rust_i18n::t;
+use std::borrow::Cow;
+
+struct UpdateSettings {}
+
+impl UIEvent for UpdateSettings {}
+impl UIComponentPropertyEvent for UpdateSettings {}
+
+pub struct CharacterControllerComponentEdit {}
+
+impl ComponentEditable for CharacterControllerComponentEdit {
+    fn edit(
+        &mut self,
+        ui: &mut Ui,
+        component: &mut dyn Component,
+        engine: &mut Engine,
+        content_manager: &mut ContentManager,
+        object_property_view: &ObjectPropertyView,
+    ) -> Option<Box<dyn super::UIComponentPropertyEvent>> {
+        let _ = object_property_view;
+        let _ = content_manager;
+        let _ = engine;
+
+        let component = component
+            .downcast_mut::<CharacterControllerComponent>()
+            .expect("Matched type");
+        let settings = &mut component.settings;
+        let mut is_changed = false;
+
+        ui.vertical(|ui| {
+            is_changed |= ui
+                .add(
+                    egui::DragValue::new(&mut settings.capsule_half_height)
+                        .speed(0.01)
+                        .range(0.0..=f32::MAX)
+                        .prefix(t!("Half height: ").as_ref()),
+                )
+                .changed();
+            is_changed |= ui
+                .add(
+                    egui::DragValue::new(&mut settings.capsule_radius)
+                        .speed(0.01)
+                        .range(0.0..=f32::MAX)
+                        .prefix(t!("Radius: ").as_ref()),
+                )
+                .changed();
+            is_changed |= ui
+                .add(
+                    egui::DragValue::new(&mut settings.offset)
+                        .speed(0.001)
+                        .range(0.0..=f32::MAX)
+                        .prefix(t!("Offset: ").as_ref()),
+                )
+                .changed();
+            is_changed |= ui
+                .checkbox(&mut settings.is_slide, t!("Is slide").as_ref())
+                .changed();
+            is_changed |= ui
+                .add(
+                    egui::DragValue::new(&mut settings.max_slope_climb_angle)
+                        .speed(0.1)
+                        .range(0.0..=90.0)
+                        .prefix(t!("Max slope climb angle: ").as_ref()),
+                )
+                .changed();
+            is_changed |= ui
+                .add(
+                    egui::DragValue::new(&mut settings.min_slope_slide_angle)
+                        .speed(0.1)
+                        .range(0.0..=90.0)
+                        .prefix(t!("Min slope slide angle: ").as_ref()),
+                )
+                .changed();
+            is_changed |= ui
+                .checkbox(
+                    &mut settings.is_autostep_enable,
+                    t!("Is autostep enable").as_ref(),
+                )
+                .changed();
+            if settings.is_autostep_enable {
+                is_changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut settings.autostep_max_height)
+                            .speed(0.01)
+                            .range(0.0..=f32::MAX)
+                            .prefix(t!("Autostep max height: ").as_ref()),
+                    )
+                    .changed();
+                is_changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut settings.autostep_min_width)
+                            .speed(0.01)
+                            .range(0.0..=f32::MAX)
+                            .prefix(t!("Autostep min width: ").as_ref()),
+                    )
+                    .changed();
+                is_changed |= ui
+                    .checkbox(
+                        &mut settings.is_autostep_include_dynamic_bodies,
+                        t!("Is include dynamic bodies").as_ref(),
+                    )
+                    .changed();
+            }
+            is_changed |= ui
+                .checkbox(
+                    &mut settings.is_snap_to_ground_enable,
+                    t!("Is snap to ground enable").as_ref(),
+                )
+                .changed();
+            if settings.is_snap_to_ground_enable {
+                is_changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut settings.snap_to_ground_distance)
+                            .speed(0.01)
+                            .range(0.0..=f32::MAX)
+                            .prefix(t!("Snap to ground distance: ").as_ref()),
+                    )
+                    .changed();
+            }
+            ui.add(
+                egui::DragValue::new(&mut settings.gravity_scale)
+                    .speed(0.01)
+                    .prefix(t!("Gravity scale: ").as_ref()),
+            );
+            ui.add(
+                egui::DragValue::new(&mut settings.max_fall_speed)
+                    .speed(0.1)
+                    .range(0.0..=f32::MAX)
+                    .prefix(t!("Max fall speed: ").as_ref()),
+            );
+        });
+
+        if is_changed {
+            Some(Box::new(UpdateSettings {}))
+        } else {
+            None
+        }
+    }
+
+    fn display_type_name(&self) -> Cow<'static, str> {
+        t!("Type: CharacterControllerComponent")
+    }
+
+    fn on_process_event(
+        &self,
+        editor_context: &mut crate::editor_context::EditorContext,
+        component: &mut dyn Component,
+        event: Box<dyn UIComponentPropertyEvent>,
+    ) {
+        if event.downcast::<UpdateSettings>().is_err() {
+            return;
+        }
+        let component = component
+            .downcast_mut::<CharacterControllerComponent>()
+            .expect("Matched type");
+        let crate::editor_context::EditObjectContext { data_source, .. } =
+            editor_context.edit_object_context();
+        let Some(active_level) = data_source.level.as_mut() else {
+            return;
+        };
+        let mut active_level = active_level.borrow_mut();
+        let Some(level_physics) = active_level.get_physics_mut() else {
+            return;
+        };
+        component.recreate_physics(level_physics);
+    }
+}
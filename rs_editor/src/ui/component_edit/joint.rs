@@ -0,0 +1,172 @@
+use crate::ui::{
+    UIEvent,
+    component_edit::{ComponentEditable, UIComponentPropertyEvent},
+    misc::render_combo_box_not_null,
+    object_property_view::ObjectPropertyView,
+};
+use egui::Ui;
+use rs_content_manager::content_manager::ContentManager;
+use rs_engine::{
+    components::{
+        component::Component,
+        joint_component::{EJointType, JointComponent},
+    },
+    engine::Engine,
+};
+use rust_i18n::t;
+use std::borrow::Cow;
+
+struct UpdateJoint {}
+
+impl UIEvent for UpdateJoint {}
+impl UIComponentPropertyEvent for UpdateJoint {}
+
+pub struct JointComponentEdit {}
+
+impl ComponentEditable for JointComponentEdit {
+    fn edit(
+        &mut self,
+        ui: &mut Ui,
+        component: &mut dyn Component,
+        engine: &mut Engine,
+        content_manager: &mut ContentManager,
+        object_property_view: &ObjectPropertyView,
+    ) -> Option<Box<dyn super::UIComponentPropertyEvent>> {
+        let _ = object_property_view;
+        let _ = content_manager;
+        let _ = engine;
+
+        let component = component
+            .downcast_mut::<JointComponent>()
+            .expect("Matched type");
+        let settings = &mut component.settings;
+        let mut is_changed = false;
+
+        ui.vertical(|ui| {
+            is_changed |= render_combo_box_not_null(
+                ui,
+                t!("Joint type"),
+                "Joint type",
+                &mut settings.joint_type,
+                EJointType::all().to_vec(),
+            );
+
+            let mut connected_actor = settings.connected_actor.clone().unwrap_or_default();
+            ui.horizontal(|ui| {
+                ui.label(t!("Connected actor: ").as_ref());
+                if ui.text_edit_singleline(&mut connected_actor).changed() {
+                    settings.connected_actor = if connected_actor.is_empty() {
+                        None
+                    } else {
+                        Some(connected_actor)
+                    };
+                    is_changed = true;
+                }
+            });
+
+            is_changed |= ObjectPropertyView::vec3_widget_mut(
+                &mut settings.local_anchor1,
+                ui,
+                t!("Local anchor 1"),
+                true,
+            );
+            is_changed |= ObjectPropertyView::vec3_widget_mut(
+                &mut settings.local_anchor2,
+                ui,
+                t!("Local anchor 2"),
+                true,
+            );
+
+            if settings.joint_type.is_axis_used() {
+                is_changed |=
+                    ObjectPropertyView::vec3_widget_mut(&mut settings.axis, ui, t!("Axis"), false);
+                is_changed |= ui
+                    .checkbox(
+                        &mut settings.is_limit_enable,
+                        t!("Is limit enable").as_ref(),
+                    )
+                    .changed();
+                if settings.is_limit_enable {
+                    ui.horizontal(|ui| {
+                        ui.label(t!("Limits: ").as_ref());
+                        is_changed |= ui
+                            .add(egui::DragValue::new(&mut settings.limits.x).speed(0.1))
+                            .changed();
+                        is_changed |= ui
+                            .add(egui::DragValue::new(&mut settings.limits.y).speed(0.1))
+                            .changed();
+                    });
+                }
+            }
+
+            if settings.joint_type == EJointType::Spring {
+                is_changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut settings.rest_length)
+                            .speed(0.01)
+                            .range(0.0..=f32::MAX)
+                            .prefix(t!("Rest length: ").as_ref()),
+                    )
+                    .changed();
+                is_changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut settings.stiffness)
+                            .speed(0.1)
+                            .range(0.0..=f32::MAX)
+                            .prefix(t!("Stiffness: ").as_ref()),
+                    )
+                    .changed();
+                is_changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut settings.damping)
+                            .speed(0.01)
+                            .range(0.0..=f32::MAX)
+                            .prefix(t!("Damping: ").as_ref()),
+                    )
+                    .changed();
+            }
+
+            is_changed |= ui
+                .checkbox(
+                    &mut settings.is_contacts_enabled,
+                    t!("Is contacts enabled").as_ref(),
+                )
+                .changed();
+        });
+
+        if is_changed {
+            Some(Box::new(UpdateJoint {}))
+        } else {
+            None
+        }
+    }
+
+    fn display_type_name(&self) -> Cow<'static, str> {
+        t!("Type: JointComponent")
+    }
+
+    fn on_process_event(
+        &self,
+        editor_context: &mut crate::editor_context::EditorContext,
+        component: &mut dyn Component,
+        event: Box<dyn UIComponentPropertyEvent>,
+    ) {
+        if event.downcast::<UpdateJoint>().is_err() {
+            return;
+        }
+        let component = component
+            .downcast_mut::<JointComponent>()
+            .expect("Matched type");
+        let crate::editor_context::EditObjectContext { data_source, .. } =
+            editor_context.edit_object_context();
+        let Some(active_level) = data_source.level.as_mut() else {
+            return;
+        };
+        let mut active_level = active_level.borrow_mut();
+        let Some(level_physics) = active_level.get_physics_mut() else {
+            return;
+        };
+        // The level links the joint again with the new settings on the next simulated tick.
+        component.unlink(level_physics);
+    }
+}
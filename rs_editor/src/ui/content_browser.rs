@@ -43,6 +43,8 @@ pub enum EClickEventType {
     SingleClickFile(EContentFileType),
     Back,
     Rename(EContentFileType, String),
+    MoveFile(url::Url, PathBuf),
+    FixUpRedirectors,
     Detail(EContentFileType),
 }
 
@@ -103,6 +105,10 @@ pub fn draw(
                                 });
                             }
                         }
+                        if ui.button(t!("Fix Up Redirectors")).clicked() {
+                            click = Some(EClickEventType::FixUpRedirectors);
+                            ui.close_kind(egui::UiKind::Menu);
+                        }
                     });
                 });
                 if let Some(current_folder) = &current_folder {
@@ -168,6 +174,12 @@ fn draw_content(
                             if response.double_clicked() {
                                 click = Some(EClickEventType::OpenFolder(folder.clone()));
                             }
+                            if let Some(url) = response.dnd_release_payload::<url::Url>() {
+                                click = Some(EClickEventType::MoveFile(
+                                    url.as_ref().clone(),
+                                    folder.clone(),
+                                ));
+                            }
                         }
                     }
                     EItemType::File(file) => {
@@ -205,6 +217,7 @@ fn draw_content(
                                 })
                                 .response;
                             response = response.interact(Sense::click_and_drag());
+                            response.dnd_set_drag_payload(url.clone());
                             if response.clicked() {
                                 click = Some(EClickEventType::SingleClickFile(file.clone()));
                             }
@@ -226,9 +239,19 @@ fn draw_content(
                                     ui.close_kind(egui::UiKind::Menu);
                                 }
                             });
-                            let mut edit_name = name.clone();
-                            if ui.text_edit_multiline(&mut edit_name).changed() {
-                                click = Some(EClickEventType::Rename(file.clone(), edit_name));
+                            // Renames when the editing is finished, every rename leaves a redirector.
+                            let edit_id = ui.make_persistent_id(("content_name", &url));
+                            let mut edit_name = ui
+                                .data_mut(|data| data.get_temp::<String>(edit_id))
+                                .unwrap_or_else(|| name.clone());
+                            let response = ui.text_edit_multiline(&mut edit_name);
+                            if response.lost_focus() {
+                                ui.data_mut(|data| data.remove::<String>(edit_id));
+                                if edit_name != name {
+                                    click = Some(EClickEventType::Rename(file.clone(), edit_name));
+                                }
+                            } else if response.has_focus() {
+                                ui.data_mut(|data| data.insert_temp(edit_id, edit_name));
                             }
                         });
                     }
@@ -1,10 +1,14 @@
 use crate::content_edit::{ContentEdit, UIContentPropertyEvent};
 use rs_foundation::new::SingleThreadMutType;
+use rs_localization::t;
 use std::path::PathBuf;
 
 pub struct ContentItemPropertyView {
     pub content: Option<SingleThreadMutType<Box<dyn rs_content::Content>>>,
     pub image_asset_files: Vec<PathBuf>,
+    pub level_urls: Vec<url::Url>,
+    pub dependencies: Vec<url::Url>,
+    pub references: Vec<url::Url>,
 }
 
 impl ContentItemPropertyView {
@@ -12,6 +16,9 @@ impl ContentItemPropertyView {
         ContentItemPropertyView {
             content: None,
             image_asset_files: Vec::new(),
+            level_urls: Vec::new(),
+            dependencies: Vec::new(),
+            references: Vec::new(),
         }
     }
 
@@ -41,6 +48,16 @@ impl ContentItemPropertyView {
             ));
             ui.label(format!("Url:  {}", content.get_url().to_string()));
         }
+        ui.collapsing(t!("Dependencies"), |ui| {
+            for url in self.dependencies.iter() {
+                ui.label(url.to_string());
+            }
+        });
+        ui.collapsing(t!("References"), |ui| {
+            for url in self.references.iter() {
+                ui.label(url.to_string());
+            }
+        });
 
         let editable = content_edit.editable(content.borrow().as_ref())?;
         editable.render_detail(content.clone(), self, ui)
@@ -1,16 +1,23 @@
+use crate::ui::misc::render_combo_box_not_null;
 use egui::{Color32, Ui};
 use egui_plot::{Line, MarkerShape, Plot, PlotPoints, Points};
-use rs_engine::content::curve::{ControlPoint, Curve};
+use rs_engine::content::curve::{
+    ControlPoint, Curve, CurveChannel, ECurveExtrapolation, ECurveInterpolation, ECurveValueType,
+    ETangentMode,
+};
 use rs_localization::t;
-use uniform_cubic_splines::{basis::*, *};
 
 pub struct CurveViewDataSource {
     pub is_drag_enable: bool,
     pub hovered_plot_item: Option<egui::Id>,
     pub last_hover_plot_pos: Option<glam::DVec2>,
+    pub selected_channel: usize,
+    pub selected_control_point: Option<String>,
     point_radius: f32,
     point_color: Color32,
-    line_color: Color32,
+    selected_point_color: Color32,
+    channel_colors: [Color32; 4],
+    tangent_color: Color32,
     line_points: usize,
 }
 
@@ -20,15 +27,33 @@ impl Default for CurveViewDataSource {
             is_drag_enable: true,
             hovered_plot_item: None,
             last_hover_plot_pos: None,
+            selected_channel: 0,
+            selected_control_point: None,
             point_radius: 3.5,
             point_color: Color32::BLUE,
-            line_color: Color32::RED,
+            selected_point_color: Color32::YELLOW,
+            channel_colors: [
+                Color32::RED,
+                Color32::GREEN,
+                Color32::LIGHT_BLUE,
+                Color32::GRAY,
+            ],
+            tangent_color: Color32::LIGHT_YELLOW,
             line_points: 256,
         }
     }
 }
 
 pub fn draw(opend_curve: &mut Curve, ui: &mut Ui, data_source: &mut CurveViewDataSource) {
+    draw_toolbar(opend_curve, ui, data_source);
+    if opend_curve.channels.is_empty() {
+        return;
+    }
+    data_source.selected_channel = data_source
+        .selected_channel
+        .min(opend_curve.channels.len() - 1);
+    let selected_channel = data_source.selected_channel;
+
     let plot = Plot::new("Curve")
         .allow_drag(data_source.is_drag_enable)
         .allow_boxed_zoom(false)
@@ -37,17 +62,26 @@ pub fn draw(opend_curve: &mut Curve, ui: &mut Ui, data_source: &mut CurveViewDat
         .allow_double_click_reset(false)
         .data_aspect(1.0);
     let plot_response = plot.show(ui, |plot_ui| {
-        for line in lines(
-            &opend_curve.control_points,
-            data_source.line_color,
-            data_source.line_points,
+        for (index, channel) in opend_curve.channels.iter().enumerate() {
+            let color = data_source.channel_colors[index % data_source.channel_colors.len()];
+            if let Some(line) = line(channel, color, data_source.line_points) {
+                plot_ui.line(line);
+            }
+        }
+        let channel = &opend_curve.channels[selected_channel];
+        for tangent_line in tangent_lines(
+            channel,
+            data_source.selected_control_point.as_deref(),
+            data_source.tangent_color,
         ) {
-            plot_ui.line(line);
+            plot_ui.line(tangent_line);
         }
         for control_point in control_points_ui(
-            &opend_curve.control_points,
+            &channel.control_points,
             data_source.point_radius,
             data_source.point_color,
+            data_source.selected_control_point.as_deref(),
+            data_source.selected_point_color,
         ) {
             plot_ui.points(control_point);
         }
@@ -62,18 +96,36 @@ pub fn draw(opend_curve: &mut Curve, ui: &mut Ui, data_source: &mut CurveViewDat
 
     let is_pointer_button_down_on = plot_response.response.is_pointer_button_down_on();
 
+    let channel = &mut opend_curve.channels[selected_channel];
+
     plot_response.response.context_menu(|ui| {
         let response = ui.button(t!("Add"));
         if response.clicked() {
             if let Some(last_hover_plot_pos) = plot_pos.or(data_source.last_hover_plot_pos) {
-                let index = opend_curve.control_points.len();
-                opend_curve
+                let index = channel
                     .control_points
-                    .push(ControlPoint::new(index, last_hover_plot_pos));
-                opend_curve.sort_by_x();
+                    .iter()
+                    .filter_map(|x| x.id.strip_prefix("ControlPoint_")?.parse::<usize>().ok())
+                    .max()
+                    .map(|x| x + 1)
+                    .unwrap_or(0);
+                let control_point = ControlPoint::new(index, last_hover_plot_pos);
+                data_source.selected_control_point = Some(control_point.id.clone());
+                channel.control_points.push(control_point);
+                channel.sort_by_x();
             }
             ui.close_kind(egui::UiKind::Menu);
         }
+        if let Some(selected_control_point) = data_source.selected_control_point.clone() {
+            let response = ui.button(t!("Delete"));
+            if response.clicked() {
+                channel
+                    .control_points
+                    .retain(|x| x.id != selected_control_point);
+                data_source.selected_control_point = None;
+                ui.close_kind(egui::UiKind::Menu);
+            }
+        }
     });
 
     if is_pointer_button_down_on && plot_response.hovered_plot_item.is_some() {
@@ -83,8 +135,9 @@ pub fn draw(opend_curve: &mut Curve, ui: &mut Ui, data_source: &mut CurveViewDat
     if is_pointer_button_down_on {
         if let Some(hovered_plot_item_id) = data_source.hovered_plot_item {
             let mut is_need_sort = false;
-            for control_point in opend_curve.control_points.iter_mut() {
+            for control_point in channel.control_points.iter_mut() {
                 if egui::Id::new(control_point.id.clone()) == hovered_plot_item_id {
+                    data_source.selected_control_point = Some(control_point.id.clone());
                     if let Some(last_hover_plot_pos) = plot_pos.or(data_source.last_hover_plot_pos)
                     {
                         control_point.position = last_hover_plot_pos;
@@ -93,7 +146,7 @@ pub fn draw(opend_curve: &mut Curve, ui: &mut Ui, data_source: &mut CurveViewDat
                 }
             }
             if is_need_sort {
-                opend_curve.sort_by_x();
+                channel.sort_by_x();
             }
         }
     } else {
@@ -109,13 +162,114 @@ pub fn draw(opend_curve: &mut Curve, ui: &mut Ui, data_source: &mut CurveViewDat
     }
 }
 
-fn control_points_ui(
-    control_points: &[ControlPoint],
+fn draw_toolbar(opend_curve: &mut Curve, ui: &mut Ui, data_source: &mut CurveViewDataSource) {
+    ui.horizontal(|ui| {
+        let mut value_type = opend_curve.value_type;
+        if render_combo_box_not_null(
+            ui,
+            t!("Value Type"),
+            "Curve.ValueType",
+            &mut value_type,
+            ECurveValueType::all().to_vec(),
+        ) {
+            opend_curve.set_value_type(value_type);
+        }
+
+        let channel_names = opend_curve.value_type.channel_names();
+        if channel_names.len() > 1 {
+            let mut selected_channel = data_source.selected_channel;
+            egui::ComboBox::new("Curve.Channel", t!("Channel"))
+                .selected_text(channel_names.get(selected_channel).copied().unwrap_or(""))
+                .show_ui(ui, |ui| {
+                    for (index, name) in channel_names.iter().enumerate() {
+                        ui.selectable_value(&mut selected_channel, index, *name);
+                    }
+                });
+            if selected_channel != data_source.selected_channel {
+                data_source.selected_channel = selected_channel;
+                data_source.selected_control_point = None;
+            }
+        }
+    });
+
+    let Some(channel) = opend_curve.channels.get_mut(data_source.selected_channel) else {
+        return;
+    };
+
+    ui.horizontal(|ui| {
+        render_combo_box_not_null(
+            ui,
+            t!("Pre Infinity"),
+            "Curve.PreInfinity",
+            &mut channel.pre_infinity,
+            ECurveExtrapolation::all().to_vec(),
+        );
+        render_combo_box_not_null(
+            ui,
+            t!("Post Infinity"),
+            "Curve.PostInfinity",
+            &mut channel.post_infinity,
+            ECurveExtrapolation::all().to_vec(),
+        );
+    });
+
+    let Some(selected_control_point) = data_source.selected_control_point.as_ref() else {
+        return;
+    };
+    let Some(index) = channel
+        .control_points
+        .iter()
+        .position(|x| &x.id == selected_control_point)
+    else {
+        return;
+    };
+    let arrive_tangent = channel.arrive_tangent(index);
+    let leave_tangent = channel.leave_tangent(index);
+    let control_point = &mut channel.control_points[index];
+
+    ui.horizontal(|ui| {
+        render_combo_box_not_null(
+            ui,
+            t!("Interpolation"),
+            "Curve.Interpolation",
+            &mut control_point.interpolation,
+            ECurveInterpolation::all().to_vec(),
+        );
+        let is_changed = render_combo_box_not_null(
+            ui,
+            t!("Tangent Mode"),
+            "Curve.TangentMode",
+            &mut control_point.tangent_mode,
+            vec![ETangentMode::Auto, ETangentMode::User],
+        );
+        if is_changed && control_point.tangent_mode == ETangentMode::User {
+            control_point.arrive_tangent = arrive_tangent;
+            control_point.leave_tangent = leave_tangent;
+        }
+    });
+
+    if control_point.tangent_mode == ETangentMode::User {
+        ui.horizontal(|ui| {
+            ui.label(t!("Arrive Tangent"));
+            ui.add(egui::DragValue::new(&mut control_point.arrive_tangent.x).speed(0.01));
+            ui.add(egui::DragValue::new(&mut control_point.arrive_tangent.y).speed(0.01));
+            ui.label(t!("Leave Tangent"));
+            ui.add(egui::DragValue::new(&mut control_point.leave_tangent.x).speed(0.01));
+            ui.add(egui::DragValue::new(&mut control_point.leave_tangent.y).speed(0.01));
+        });
+    }
+}
+
+fn control_points_ui<'a>(
+    control_points: &'a [ControlPoint],
     radius: f32,
     color: Color32,
-) -> Vec<Points<'_>> {
+    selected_control_point: Option<&str>,
+    selected_color: Color32,
+) -> Vec<Points<'a>> {
     let mut points = Vec::with_capacity(control_points.len());
     for control_point in control_points {
+        let is_selected = selected_control_point == Some(control_point.id.as_str());
         points.push(
             Points::new(
                 "",
@@ -123,48 +277,59 @@ fn control_points_ui(
             )
             .shape(MarkerShape::Circle)
             .radius(radius)
-            .color(color)
+            .color(if is_selected { selected_color } else { color })
             .id(control_point.id.clone()),
         );
     }
     points
 }
 
-fn lines(control_points: &[ControlPoint], color: Color32, points: usize) -> Vec<Line<'_>> {
-    if control_points.len() >= 2 {
-        let mut knot_spacing: Vec<f64> = Vec::with_capacity(2 + control_points.len());
-        knot_spacing.push(control_points[0].position.x);
-        for item in control_points {
-            knot_spacing.push(item.position.x);
-        }
-        knot_spacing.push(control_points.last().unwrap().position.x);
-
-        let mut knots: Vec<f64> = Vec::with_capacity(2 + control_points.len());
-        knots.push(control_points[0].position.y);
-        for item in control_points {
-            knots.push(item.position.y);
-        }
-        knots.push(control_points.last().unwrap().position.y);
-
-        let mut lines = Vec::with_capacity(1);
+fn tangent_lines(
+    channel: &CurveChannel,
+    selected_control_point: Option<&str>,
+    color: Color32,
+) -> Vec<Line<'static>> {
+    let Some(selected_control_point) = selected_control_point else {
+        return vec![];
+    };
+    let Some(index) = channel
+        .control_points
+        .iter()
+        .position(|x| x.id == selected_control_point)
+    else {
+        return vec![];
+    };
+    let position = channel.control_points[index].position;
+    let mut lines = Vec::with_capacity(2);
+    for tangent in [channel.arrive_tangent(index), channel.leave_tangent(index)] {
+        let end = position + tangent;
         lines.push(
             Line::new(
                 "",
-                PlotPoints::from_explicit_callback(
-                    move |x| {
-                        let v = spline_inverse::<CatmullRom, _>(x, &knot_spacing).unwrap();
-                        let y = spline::<CatmullRom, _, _>(v, &knots);
-                        y
-                    },
-                    ..,
-                    points,
-                ),
+                PlotPoints::new(vec![[position.x, position.y], [end.x, end.y]]),
             )
             .allow_hover(false)
             .color(color),
         );
-        lines
-    } else {
-        vec![]
     }
+    lines
+}
+
+fn line(channel: &CurveChannel, color: Color32, points: usize) -> Option<Line<'static>> {
+    if channel.control_points.is_empty() {
+        return None;
+    }
+    let channel = channel.clone();
+    Some(
+        Line::new(
+            "",
+            PlotPoints::from_explicit_callback(
+                move |x| channel.evaluate(x).unwrap_or(0.0),
+                ..,
+                points,
+            ),
+        )
+        .allow_hover(false)
+        .color(color),
+    )
 }
@@ -0,0 +1,62 @@
+use crate::transaction::TransactionHistory;
+use egui::{Button, Context};
+use rs_localization::t;
+
+#[derive(Debug)]
+pub enum EClickEventType {
+    Undo,
+    Redo,
+    /// Undo or redo until the number of done transactions equals the value.
+    Jump(usize),
+}
+
+pub fn draw(
+    window: egui::Window,
+    context: &Context,
+    open: &mut bool,
+    transaction_history: &TransactionHistory,
+) -> Option<EClickEventType> {
+    let mut event = None;
+    window
+        .open(open)
+        .vscroll(true)
+        .hscroll(true)
+        .resizable(true)
+        .default_size([250.0, 500.0])
+        .show(context, |ui| {
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(transaction_history.can_undo(), Button::new(t!("Undo")))
+                    .clicked()
+                {
+                    event = Some(EClickEventType::Undo);
+                }
+                if ui
+                    .add_enabled(transaction_history.can_redo(), Button::new(t!("Redo")))
+                    .clicked()
+                {
+                    event = Some(EClickEventType::Redo);
+                }
+            });
+            ui.separator();
+
+            let undo_names = transaction_history.undo_names();
+            let redo_names = transaction_history.redo_names();
+            let position = undo_names.len();
+            if ui.selectable_label(position == 0, t!("Initial")).clicked() {
+                event = Some(EClickEventType::Jump(0));
+            }
+            for (index, name) in undo_names.iter().enumerate() {
+                if ui.selectable_label(index + 1 == position, name).clicked() {
+                    event = Some(EClickEventType::Jump(index + 1));
+                }
+            }
+            for (index, name) in redo_names.iter().enumerate() {
+                let text = egui::RichText::new(name).weak();
+                if ui.selectable_label(false, text).clicked() {
+                    event = Some(EClickEventType::Jump(position + index + 1));
+                }
+            }
+        });
+    event
+}
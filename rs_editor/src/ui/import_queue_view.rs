@@ -0,0 +1,71 @@
+use crate::import_queue::{EImportTaskState, ImportQueue, ImportTaskId};
+use egui::{Button, Color32, Context, ProgressBar};
+use rs_localization::t;
+
+#[derive(Debug)]
+pub enum EClickEventType {
+    Cancel(ImportTaskId),
+    CancelAll,
+    ClearDone,
+}
+
+pub fn draw(
+    window: egui::Window,
+    context: &Context,
+    open: &mut bool,
+    import_queue: &ImportQueue,
+) -> Option<EClickEventType> {
+    let mut event = None;
+    window
+        .open(open)
+        .vscroll(true)
+        .hscroll(true)
+        .resizable(true)
+        .default_size([300.0, 250.0])
+        .show(context, |ui| {
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(import_queue.is_busy(), Button::new(t!("Cancel All")))
+                    .clicked()
+                {
+                    event = Some(EClickEventType::CancelAll);
+                }
+                if ui.button(t!("Clear Done")).clicked() {
+                    event = Some(EClickEventType::ClearDone);
+                }
+            });
+            ui.separator();
+
+            for task in import_queue.tasks() {
+                ui.horizontal(|ui| {
+                    ui.label(&task.name);
+                    if !task.state.is_done() && ui.button(t!("Cancel")).clicked() {
+                        event = Some(EClickEventType::Cancel(task.id));
+                    }
+                });
+                match &task.state {
+                    EImportTaskState::Pending => {
+                        ui.label(t!("Pending"));
+                    }
+                    EImportTaskState::Running => {
+                        ui.add(
+                            ProgressBar::new(task.progress)
+                                .text(&task.stage)
+                                .animate(true),
+                        );
+                    }
+                    EImportTaskState::Finished => {
+                        ui.label(t!("Finished"));
+                    }
+                    EImportTaskState::Failed(err) => {
+                        ui.colored_label(Color32::RED, err);
+                    }
+                    EImportTaskState::Canceled => {
+                        ui.label(t!("Canceled"));
+                    }
+                }
+                ui.separator();
+            }
+        });
+    event
+}
@@ -11,6 +11,8 @@ pub enum EClickEventType {
     CreateCameraHere,
     DeleteActor(SingleThreadMutType<Actor>),
     DuplicateActor(SingleThreadMutType<Actor>),
+    CreatePrefab(SingleThreadMutType<Actor>),
+    ApplyPrefab(SingleThreadMutType<Actor>),
     SingleClickSceneNode(SingleThreadMutType<SceneNode>),
     CreateDirectionalLight,
     DirectionalLight(SingleThreadMutType<DirectionalLight>),
@@ -103,6 +105,21 @@ fn level_node(
                         ui.close_kind(egui::UiKind::Menu);
                     }
 
+                    let response = ui.button(t!("Create Prefab"));
+                    if response.clicked() {
+                        *event = Some(EClickEventType::CreatePrefab(actor.clone()));
+                        ui.close_kind(egui::UiKind::Menu);
+                    }
+
+                    let is_prefab_instance = _actor.scene_node.borrow().prefab_instance().is_some();
+                    if is_prefab_instance {
+                        let response = ui.button(t!("Apply to Prefab"));
+                        if response.clicked() {
+                            *event = Some(EClickEventType::ApplyPrefab(actor.clone()));
+                            ui.close_kind(egui::UiKind::Menu);
+                        }
+                    }
+
                     let response = ui.button(t!("Delete"));
                     if response.clicked() {
                         *event = Some(EClickEventType::DeleteActor(actor.clone()));
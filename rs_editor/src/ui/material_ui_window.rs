@@ -31,6 +31,7 @@ impl MaterialUIWindow {
         content_manager: SingleThreadMutType<ContentManager>,
         module_manager: SingleThreadMutType<ModuleManager>,
         material_url: url::Url,
+        material: SingleThreadMutType<crate::material::Material>,
     ) -> anyhow::Result<MaterialUIWindow> {
         let window_context = window_manager.spwan_new_window(
             EWindowType::Material,
@@ -62,9 +63,14 @@ impl MaterialUIWindow {
         egui_winit_state.egui_input_mut().viewport_id = viewport_id;
         egui_winit_state.egui_input_mut().viewports =
             std::iter::once((viewport_id, Default::default())).collect();
-        let material_view = MaterialView::new(content_manager, module_manager, material_url);
+        let material_view = MaterialView::new(
+            content_manager,
+            module_manager,
+            material_url,
+            &material.borrow(),
+        );
         let data_source = DataSource {
-            current_open_material: None,
+            current_open_material: Some(material),
             is_shader_code_window_open: false,
         };
         Ok(MaterialUIWindow {
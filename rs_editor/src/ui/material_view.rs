@@ -1,6 +1,7 @@
 use crate::{
     editor_ui,
-    material_resolve::{self, ResolveResult},
+    material_resolve::{self, MaterialFunctionGraphs, ResolveResult},
+    transaction::TransactionHistory,
     ui::misc::{
         f32_widget_mut, render_combo_box_not_null, vec2_widget_mut, vec3_widget_mut,
         vec4_widget_mut,
@@ -14,9 +15,12 @@ use egui_snarl::{
 use rs_artifact::material_paramenters::{BaseDataValueType, StructField};
 use rs_content_manager::content_manager::ContentManager;
 use rs_core_minimal::types::HasUrl;
-use rs_engine::content::{
-    content_file_type::collect_typed_contents,
-    material_paramenters_collection::MaterialParamentersCollection,
+use rs_engine::{
+    content::{
+        content_file_type::collect_typed_contents,
+        material_paramenters_collection::MaterialParamentersCollection,
+    },
+    url_extension::UrlExtension,
 };
 use rs_foundation::new::SingleThreadMutType;
 use rs_localization::t;
@@ -33,6 +37,9 @@ pub struct GraphViewer {
     pub is_updated: bool,
     pub content_manager: SingleThreadMutType<ContentManager>,
     pub paramenters: rs_editor_core::material::Paramenters,
+    /// The errors of the last resolve.
+    pub errors: HashMap<NodeId, Vec<String>>,
+    pub material_functions: MaterialFunctionGraphs,
 }
 
 impl GraphViewer {
@@ -109,6 +116,52 @@ impl GraphViewer {
             }
         }
     }
+
+    fn material_function_combo_box(&mut self, call: &mut MaterialFunctionCall, ui: &mut egui::Ui) {
+        let text = call
+            .url
+            .as_ref()
+            .map(|x| x.get_name_in_editor())
+            .unwrap_or(t!("None").to_string());
+        let mut urls: Vec<url::Url> = self.material_functions.keys().cloned().collect();
+        urls.sort_by_key(|x| x.to_string());
+        egui::ComboBox::from_id_salt("MaterialFunction")
+            .selected_text(text)
+            .show_ui(ui, |ui| {
+                if ui
+                    .selectable_value(&mut call.url, None, t!("None"))
+                    .clicked()
+                {
+                    self.is_updated = true;
+                }
+                for url in urls {
+                    let name = url.get_name_in_editor();
+                    if ui
+                        .selectable_value(&mut call.url, Some(url), name)
+                        .clicked()
+                    {
+                        self.is_updated = true;
+                    }
+                }
+            });
+    }
+
+    /// Keeps the pins of the node in sync with the graph of the material function.
+    fn update_function_signature(&mut self, call: &mut MaterialFunctionCall) {
+        let function = call
+            .url
+            .as_ref()
+            .and_then(|url| self.material_functions.get(url));
+        let (inputs, outputs) = match function.map(|x| x.try_borrow()) {
+            Some(Ok(function)) => material_resolve::function_signature(&function.snarl),
+            // The function is being edited.
+            Some(Err(_)) => return,
+            None => (vec![], vec![]),
+        };
+        if call.update_signature(inputs, outputs) {
+            self.is_updated = true;
+        }
+    }
 }
 
 impl SnarlViewer<MaterialNode> for GraphViewer {
@@ -127,6 +180,29 @@ impl SnarlViewer<MaterialNode> for GraphViewer {
             EMaterialNodeType::Sin(_) => 1,
             EMaterialNodeType::MaterialParamentersCollection(_) => 1,
             EMaterialNodeType::Paramenter(_) => 1,
+            EMaterialNodeType::ComponentSplit => 4,
+            EMaterialNodeType::Subtract(..)
+            | EMaterialNodeType::Multiply(..)
+            | EMaterialNodeType::Divide(..)
+            | EMaterialNodeType::Lerp(..)
+            | EMaterialNodeType::Clamp(..)
+            | EMaterialNodeType::Dot(..)
+            | EMaterialNodeType::Cross(..)
+            | EMaterialNodeType::Normalize(_)
+            | EMaterialNodeType::Pow(..)
+            | EMaterialNodeType::Saturate(_)
+            | EMaterialNodeType::Append(..)
+            | EMaterialNodeType::Swizzle(_)
+            | EMaterialNodeType::WorldPosition
+            | EMaterialNodeType::WorldNormal
+            | EMaterialNodeType::ViewDirection
+            | EMaterialNodeType::Fresnel(_)
+            | EMaterialNodeType::Panner(_)
+            | EMaterialNodeType::NormalMapUnpack
+            | EMaterialNodeType::StaticSwitch(_) => 1,
+            EMaterialNodeType::FunctionInput(..) => 1,
+            EMaterialNodeType::FunctionOutput(_) => 0,
+            EMaterialNodeType::MaterialFunction(ref call) => call.outputs.len(),
         }
     }
 
@@ -141,6 +217,29 @@ impl SnarlViewer<MaterialNode> for GraphViewer {
             EMaterialNodeType::Sin(_) => 1,
             EMaterialNodeType::MaterialParamentersCollection(_) => 2,
             EMaterialNodeType::Paramenter(_) => 0,
+            EMaterialNodeType::WorldPosition
+            | EMaterialNodeType::WorldNormal
+            | EMaterialNodeType::ViewDirection => 0,
+            EMaterialNodeType::Subtract(..)
+            | EMaterialNodeType::Multiply(..)
+            | EMaterialNodeType::Divide(..)
+            | EMaterialNodeType::Lerp(..)
+            | EMaterialNodeType::Clamp(..)
+            | EMaterialNodeType::Dot(..)
+            | EMaterialNodeType::Cross(..)
+            | EMaterialNodeType::Normalize(_)
+            | EMaterialNodeType::Pow(..)
+            | EMaterialNodeType::Saturate(_)
+            | EMaterialNodeType::ComponentSplit
+            | EMaterialNodeType::Append(..)
+            | EMaterialNodeType::Swizzle(_)
+            | EMaterialNodeType::Fresnel(_)
+            | EMaterialNodeType::Panner(_)
+            | EMaterialNodeType::NormalMapUnpack
+            | EMaterialNodeType::StaticSwitch(_) => node.node_type.value_input_names().len(),
+            EMaterialNodeType::FunctionInput(..) => 0,
+            EMaterialNodeType::FunctionOutput(_) => 1,
+            EMaterialNodeType::MaterialFunction(ref call) => call.inputs.len(),
         }
     }
 
@@ -152,33 +251,33 @@ impl SnarlViewer<MaterialNode> for GraphViewer {
     ) -> impl egui_snarl::ui::SnarlPin + 'static {
         let node = &mut snarl[pin.id.node];
         match &mut node.node_type {
-            EMaterialNodeType::Add(v1, v2) => {
+            node_type @ (EMaterialNodeType::Add(..)
+            | EMaterialNodeType::Subtract(..)
+            | EMaterialNodeType::Multiply(..)
+            | EMaterialNodeType::Divide(..)
+            | EMaterialNodeType::Lerp(..)
+            | EMaterialNodeType::Clamp(..)
+            | EMaterialNodeType::Dot(..)
+            | EMaterialNodeType::Cross(..)
+            | EMaterialNodeType::Normalize(_)
+            | EMaterialNodeType::Pow(..)
+            | EMaterialNodeType::Saturate(_)
+            | EMaterialNodeType::Sin(_)
+            | EMaterialNodeType::ComponentSplit
+            | EMaterialNodeType::Append(..)
+            | EMaterialNodeType::Swizzle(_)
+            | EMaterialNodeType::Fresnel(_)
+            | EMaterialNodeType::Panner(_)
+            | EMaterialNodeType::NormalMapUnpack
+            | EMaterialNodeType::StaticSwitch(_)) => {
+                // The types of the inputs are checked when the graph is resolved.
+                let name = node_type.value_input_names()[pin.id.input];
+                ui.label(name);
                 if !pin.remotes.is_empty() {
                     return PinInfo::square().with_fill(NODE_IO_COLOR);
                 }
-                match (&v1, &v2) {
-                    (EValueType::F32(_), EValueType::F32(_)) => {}
-                    (EValueType::F32(_), EValueType::Vec2(_)) => {}
-                    (EValueType::F32(_), EValueType::Vec3(_)) => {}
-                    (EValueType::F32(_), EValueType::Vec4(_)) => {}
-                    (EValueType::Vec2(_), EValueType::F32(_)) => {}
-                    (EValueType::Vec2(_), EValueType::Vec2(_)) => {}
-                    (EValueType::Vec3(_), EValueType::F32(_)) => {}
-                    (EValueType::Vec3(_), EValueType::Vec3(_)) => {}
-                    (EValueType::Vec4(_), EValueType::F32(_)) => {}
-                    (EValueType::Vec4(_), EValueType::Vec4(_)) => {}
-                    _ => panic!(),
-                }
-                match pin.id.input {
-                    0 => {
-                        // ui.add(egui::DragValue::new(v1));
-                        self.value_type_combo_box("v1", v1, ui);
-                    }
-                    1 => {
-                        self.value_type_combo_box("v2", v2, ui);
-                        // ui.add(egui::DragValue::new(v2));
-                    }
-                    _ => unreachable!(),
+                if let Some(value) = node_type.input_value_mut(pin.id.input) {
+                    self.value_type_combo_box(name, value, ui);
                 }
                 PinInfo::square().with_fill(NODE_IO_COLOR)
             }
@@ -301,18 +400,6 @@ impl SnarlViewer<MaterialNode> for GraphViewer {
                 PinInfo::default()
             }
             EMaterialNodeType::Time => todo!(),
-            EMaterialNodeType::Sin(v1) => {
-                if !pin.remotes.is_empty() {
-                    return PinInfo::square().with_fill(NODE_IO_COLOR);
-                }
-                match pin.id.input {
-                    0 => {
-                        self.value_type_combo_box("v1", v1, ui);
-                    }
-                    _ => unreachable!(),
-                }
-                PinInfo::square().with_fill(NODE_IO_COLOR)
-            }
             EMaterialNodeType::MaterialParamentersCollection((current_value, current_name)) => {
                 //
                 match pin.id.input {
@@ -429,6 +516,25 @@ impl SnarlViewer<MaterialNode> for GraphViewer {
                 PinInfo::default()
             }
             EMaterialNodeType::Paramenter(_) => PinInfo::default(),
+            EMaterialNodeType::FunctionInput(..) => PinInfo::default(),
+            EMaterialNodeType::FunctionOutput(name) => {
+                let response = ui.add(egui::TextEdit::singleline(name).desired_width(80.0));
+                if response.changed() {
+                    self.is_updated = true;
+                }
+                PinInfo::square().with_fill(NODE_IO_COLOR)
+            }
+            EMaterialNodeType::MaterialFunction(call) => {
+                let Some((name, value)) = call.inputs.get_mut(pin.id.input) else {
+                    return PinInfo::square().with_fill(NODE_IO_COLOR);
+                };
+                ui.label(name.as_str());
+                if pin.remotes.is_empty() {
+                    let id_source = format!("{}{}", name, pin.id.input);
+                    self.value_type_combo_box(id_source, value, ui);
+                }
+                PinInfo::square().with_fill(NODE_IO_COLOR)
+            }
         }
     }
 
@@ -465,12 +571,70 @@ impl SnarlViewer<MaterialNode> for GraphViewer {
                 PinInfo::square().with_fill(NODE_IO_COLOR)
             }
             EMaterialNodeType::VirtualTexture(_) => PinInfo::square().with_fill(NODE_IO_COLOR),
-            EMaterialNodeType::Time => todo!(),
+            EMaterialNodeType::Time => PinInfo::square().with_fill(NODE_IO_COLOR),
             EMaterialNodeType::Sin(_) => PinInfo::square().with_fill(NODE_IO_COLOR),
             EMaterialNodeType::MaterialParamentersCollection(_) => {
                 PinInfo::square().with_fill(NODE_IO_COLOR)
             }
             EMaterialNodeType::Paramenter(_) => PinInfo::square().with_fill(NODE_IO_COLOR),
+            EMaterialNodeType::ComponentSplit => {
+                ui.label(["X", "Y", "Z", "W"][pin.id.output]);
+                PinInfo::square().with_fill(NODE_IO_COLOR)
+            }
+            EMaterialNodeType::Swizzle(mask) => {
+                let response = ui.add(egui::TextEdit::singleline(mask).desired_width(48.0));
+                if response.changed() {
+                    self.is_updated = true;
+                }
+                PinInfo::square().with_fill(NODE_IO_COLOR)
+            }
+            EMaterialNodeType::Panner(speed) => {
+                ui.label("Speed");
+                let response = ui.add(egui::DragValue::new(&mut speed.x).speed(0.01).prefix("x:"));
+                let response =
+                    response | ui.add(egui::DragValue::new(&mut speed.y).speed(0.01).prefix("y:"));
+                if response.changed() {
+                    self.is_updated = true;
+                }
+                PinInfo::square().with_fill(NODE_IO_COLOR)
+            }
+            EMaterialNodeType::StaticSwitch(value) => {
+                if ui.checkbox(value, "Value").changed() {
+                    self.is_updated = true;
+                }
+                PinInfo::square().with_fill(NODE_IO_COLOR)
+            }
+            EMaterialNodeType::Subtract(..)
+            | EMaterialNodeType::Multiply(..)
+            | EMaterialNodeType::Divide(..)
+            | EMaterialNodeType::Lerp(..)
+            | EMaterialNodeType::Clamp(..)
+            | EMaterialNodeType::Dot(..)
+            | EMaterialNodeType::Cross(..)
+            | EMaterialNodeType::Normalize(_)
+            | EMaterialNodeType::Pow(..)
+            | EMaterialNodeType::Saturate(_)
+            | EMaterialNodeType::Append(..)
+            | EMaterialNodeType::WorldPosition
+            | EMaterialNodeType::WorldNormal
+            | EMaterialNodeType::ViewDirection
+            | EMaterialNodeType::Fresnel(_)
+            | EMaterialNodeType::NormalMapUnpack => PinInfo::square().with_fill(NODE_IO_COLOR),
+            EMaterialNodeType::FunctionInput(name, value) => {
+                let response = ui.add(egui::TextEdit::singleline(name).desired_width(80.0));
+                if response.changed() {
+                    self.is_updated = true;
+                }
+                self.value_type_combo_box("Default", value, ui);
+                PinInfo::square().with_fill(NODE_IO_COLOR)
+            }
+            EMaterialNodeType::FunctionOutput(_) => PinInfo::default(),
+            EMaterialNodeType::MaterialFunction(call) => {
+                if let Some(name) = call.outputs.get(pin.id.output) {
+                    ui.label(name.as_str());
+                }
+                PinInfo::square().with_fill(NODE_IO_COLOR)
+            }
         }
     }
 
@@ -484,6 +648,14 @@ impl SnarlViewer<MaterialNode> for GraphViewer {
     ) {
         ui.horizontal(|ui| {
             ui.label(format!("[{}] {}", node.0, self.title(&snarl[node])));
+            if let Some(errors) = self.errors.get(&node) {
+                ui.colored_label(Color32::RED, "!")
+                    .on_hover_text(errors.join("\n"));
+            }
+            if let EMaterialNodeType::MaterialFunction(call) = &mut snarl[node].node_type {
+                self.material_function_combo_box(call, ui);
+                self.update_function_signature(call);
+            }
             let is_remove = if let EMaterialNodeType::Sink(..) = snarl[node].node_type {
                 false
             } else {
@@ -527,22 +699,16 @@ impl SnarlViewer<MaterialNode> for GraphViewer {
     }
 
     fn show_graph_menu(&mut self, pos: egui::Pos2, ui: &mut Ui, snarl: &mut Snarl<MaterialNode>) {
-        let node_types = vec![
-            EMaterialNodeType::Add(EValueType::F32(0.0), EValueType::F32(0.0)),
-            EMaterialNodeType::Texture(None),
-            EMaterialNodeType::TexCoord(0),
-            EMaterialNodeType::VirtualTexture(None),
-            EMaterialNodeType::Time,
-            EMaterialNodeType::Sin(EValueType::F32(0.0)),
-            EMaterialNodeType::MaterialParamentersCollection((None, None)),
-        ];
-
-        for node_type in node_types {
-            if ui.button(node_type.get_name()).clicked() {
-                let node = MaterialNode { node_type };
-                snarl.insert_node(pos, node);
-                ui.close_kind(egui::UiKind::Menu);
-            }
+        for (category, node_types) in EMaterialNodeType::library() {
+            ui.menu_button(t!(category), |ui| {
+                for node_type in node_types {
+                    if ui.button(node_type.get_name()).clicked() {
+                        let node = MaterialNode { node_type };
+                        snarl.insert_node(pos, node);
+                        ui.close_kind(egui::UiKind::Menu);
+                    }
+                }
+            });
         }
         ui.menu_button(t!("Paramenter"), |ui| {
             for field in self.paramenters.fields() {
@@ -651,6 +817,66 @@ impl EValueType {
             EValueType::Vec4(value) => EValueType::F32(value.x),
         }
     }
+
+    pub fn pin_type(&self) -> EPinType {
+        match self {
+            EValueType::F32(_) => EPinType::F32,
+            EValueType::Vec2(_) => EPinType::Vec2,
+            EValueType::Vec3(_) => EPinType::Vec3,
+            EValueType::Vec4(_) => EPinType::Vec4,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum EPinType {
+    F32,
+    Vec2,
+    Vec3,
+    Vec4,
+}
+
+impl EPinType {
+    pub fn from_components(components: usize) -> Option<EPinType> {
+        match components {
+            1 => Some(EPinType::F32),
+            2 => Some(EPinType::Vec2),
+            3 => Some(EPinType::Vec3),
+            4 => Some(EPinType::Vec4),
+            _ => None,
+        }
+    }
+
+    pub fn components(&self) -> usize {
+        match self {
+            EPinType::F32 => 1,
+            EPinType::Vec2 => 2,
+            EPinType::Vec3 => 3,
+            EPinType::Vec4 => 4,
+        }
+    }
+
+    pub fn is_vector(&self) -> bool {
+        *self != EPinType::F32
+    }
+
+    pub fn get_type_name(&self) -> &str {
+        match self {
+            EPinType::F32 => "F32",
+            EPinType::Vec2 => "Vec2",
+            EPinType::Vec3 => "Vec3",
+            EPinType::Vec4 => "Vec4",
+        }
+    }
+
+    pub fn wgsl_type(&self) -> &str {
+        match self {
+            EPinType::F32 => "f32",
+            EPinType::Vec2 => "vec2<f32>",
+            EPinType::Vec3 => "vec3<f32>",
+            EPinType::Vec4 => "vec4<f32>",
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -670,9 +896,279 @@ pub enum EMaterialNodeType {
         ),
     ),
     Paramenter(StructField),
+    Subtract(EValueType, EValueType),
+    Multiply(EValueType, EValueType),
+    Divide(EValueType, EValueType),
+    Lerp(EValueType, EValueType, EValueType),
+    Clamp(EValueType, EValueType, EValueType),
+    Dot(EValueType, EValueType),
+    Cross(EValueType, EValueType),
+    Normalize(EValueType),
+    Pow(EValueType, EValueType),
+    Saturate(EValueType),
+    /// Outputs the x, y, z and w components of the input.
+    ComponentSplit,
+    Append(EValueType, EValueType),
+    /// A mask such as `xy` or `bgr`.
+    Swizzle(String),
+    WorldPosition,
+    WorldNormal,
+    ViewDirection,
+    /// The exponent.
+    Fresnel(EValueType),
+    /// The speed of the texture coordinate.
+    Panner(glam::Vec2),
+    /// Rebuilds the z component of a two channel normal map,
+    /// the result has the same encoding as the normal of the sink.
+    NormalMapUnpack,
+    /// Only the selected input is compiled.
+    StaticSwitch(bool),
+    /// The name and the default value of an input of a material function.
+    FunctionInput(String, EValueType),
+    /// The name of an output of a material function.
+    FunctionOutput(String),
+    /// Calls a material function, the function is resolved inline.
+    MaterialFunction(MaterialFunctionCall),
+}
+
+/// The function to call and the cached signature of it, which is used to create the pins.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct MaterialFunctionCall {
+    pub url: Option<url::Url>,
+    /// The names of the inputs and the values used when the inputs are not connected.
+    pub inputs: Vec<(String, EValueType)>,
+    pub outputs: Vec<String>,
+}
+
+impl MaterialFunctionCall {
+    /// Keeps the values of the inputs whose names and types are not changed.
+    /// Returns true if the signature is changed.
+    pub fn update_signature(
+        &mut self,
+        inputs: Vec<(String, EValueType)>,
+        outputs: Vec<String>,
+    ) -> bool {
+        let is_same_inputs = inputs.len() == self.inputs.len()
+            && inputs
+                .iter()
+                .zip(self.inputs.iter())
+                .all(|(new, old)| new.0 == old.0 && new.1.pin_type() == old.1.pin_type());
+        if is_same_inputs && outputs == self.outputs {
+            return false;
+        }
+        self.inputs = inputs
+            .into_iter()
+            .map(|(name, value)| {
+                let old = self
+                    .inputs
+                    .iter()
+                    .find(|x| x.0 == name && x.1.pin_type() == value.pin_type());
+                match old {
+                    Some(old) => (name, old.1),
+                    None => (name, value),
+                }
+            })
+            .collect();
+        self.outputs = outputs;
+        true
+    }
 }
 
 impl EMaterialNodeType {
+    /// The nodes that can be created from the graph menu, grouped by category.
+    pub fn library() -> Vec<(&'static str, Vec<EMaterialNodeType>)> {
+        let zero = EValueType::F32(0.0);
+        let one = EValueType::F32(1.0);
+        vec![
+            (
+                "Math",
+                vec![
+                    EMaterialNodeType::Add(zero, zero),
+                    EMaterialNodeType::Subtract(zero, zero),
+                    EMaterialNodeType::Multiply(one, one),
+                    EMaterialNodeType::Divide(one, one),
+                    EMaterialNodeType::Lerp(zero, one, EValueType::F32(0.5)),
+                    EMaterialNodeType::Clamp(zero, zero, one),
+                    EMaterialNodeType::Pow(one, one),
+                    EMaterialNodeType::Saturate(zero),
+                    EMaterialNodeType::Sin(zero),
+                ],
+            ),
+            (
+                "Vector",
+                vec![
+                    EMaterialNodeType::Dot(
+                        EValueType::Vec3(glam::Vec3::ZERO),
+                        EValueType::Vec3(glam::Vec3::ZERO),
+                    ),
+                    EMaterialNodeType::Cross(
+                        EValueType::Vec3(glam::Vec3::X),
+                        EValueType::Vec3(glam::Vec3::Y),
+                    ),
+                    EMaterialNodeType::Normalize(EValueType::Vec3(glam::Vec3::Z)),
+                    EMaterialNodeType::ComponentSplit,
+                    EMaterialNodeType::Append(zero, zero),
+                    EMaterialNodeType::Swizzle("xyz".to_string()),
+                    EMaterialNodeType::NormalMapUnpack,
+                ],
+            ),
+            (
+                "Input",
+                vec![
+                    EMaterialNodeType::TexCoord(0),
+                    EMaterialNodeType::Time,
+                    EMaterialNodeType::WorldPosition,
+                    EMaterialNodeType::WorldNormal,
+                    EMaterialNodeType::ViewDirection,
+                    EMaterialNodeType::MaterialParamentersCollection((None, None)),
+                ],
+            ),
+            (
+                "Texture",
+                vec![
+                    EMaterialNodeType::Texture(None),
+                    EMaterialNodeType::VirtualTexture(None),
+                    EMaterialNodeType::Panner(glam::vec2(0.1, 0.0)),
+                ],
+            ),
+            (
+                "Utility",
+                vec![
+                    EMaterialNodeType::Fresnel(EValueType::F32(5.0)),
+                    EMaterialNodeType::StaticSwitch(true),
+                ],
+            ),
+            (
+                "Function",
+                vec![
+                    EMaterialNodeType::MaterialFunction(MaterialFunctionCall::default()),
+                    EMaterialNodeType::FunctionInput("Input".to_string(), zero),
+                    EMaterialNodeType::FunctionOutput("Output".to_string()),
+                ],
+            ),
+        ]
+    }
+
+    /// The labels of the input pins of the nodes whose inputs are all values.
+    pub fn value_input_names(&self) -> &'static [&'static str] {
+        match self {
+            EMaterialNodeType::Add(..)
+            | EMaterialNodeType::Subtract(..)
+            | EMaterialNodeType::Multiply(..)
+            | EMaterialNodeType::Divide(..)
+            | EMaterialNodeType::Dot(..)
+            | EMaterialNodeType::Cross(..)
+            | EMaterialNodeType::Append(..) => &["A", "B"],
+            EMaterialNodeType::Lerp(..) => &["A", "B", "Alpha"],
+            EMaterialNodeType::Clamp(..) => &["Value", "Min", "Max"],
+            EMaterialNodeType::Pow(..) => &["Base", "Exponent"],
+            EMaterialNodeType::Sin(..)
+            | EMaterialNodeType::Normalize(..)
+            | EMaterialNodeType::Saturate(..)
+            | EMaterialNodeType::ComponentSplit
+            | EMaterialNodeType::Swizzle(..)
+            | EMaterialNodeType::NormalMapUnpack => &["Value"],
+            EMaterialNodeType::Fresnel(..) => &["Normal", "Exponent"],
+            EMaterialNodeType::Panner(..) => &["UV", "Time"],
+            EMaterialNodeType::StaticSwitch(..) => &["True", "False"],
+            _ => &[],
+        }
+    }
+
+    /// The value used when the input pin is not connected.
+    pub fn input_value(&self, input: usize) -> Option<&EValueType> {
+        match (self, input) {
+            (EMaterialNodeType::Add(v1, _), 0)
+            | (EMaterialNodeType::Subtract(v1, _), 0)
+            | (EMaterialNodeType::Multiply(v1, _), 0)
+            | (EMaterialNodeType::Divide(v1, _), 0)
+            | (EMaterialNodeType::Dot(v1, _), 0)
+            | (EMaterialNodeType::Cross(v1, _), 0)
+            | (EMaterialNodeType::Append(v1, _), 0)
+            | (EMaterialNodeType::Pow(v1, _), 0)
+            | (EMaterialNodeType::Lerp(v1, _, _), 0)
+            | (EMaterialNodeType::Clamp(v1, _, _), 0)
+            | (EMaterialNodeType::Sin(v1), 0)
+            | (EMaterialNodeType::Normalize(v1), 0)
+            | (EMaterialNodeType::Saturate(v1), 0) => Some(v1),
+            (EMaterialNodeType::Add(_, v2), 1)
+            | (EMaterialNodeType::Subtract(_, v2), 1)
+            | (EMaterialNodeType::Multiply(_, v2), 1)
+            | (EMaterialNodeType::Divide(_, v2), 1)
+            | (EMaterialNodeType::Dot(_, v2), 1)
+            | (EMaterialNodeType::Cross(_, v2), 1)
+            | (EMaterialNodeType::Append(_, v2), 1)
+            | (EMaterialNodeType::Pow(_, v2), 1)
+            | (EMaterialNodeType::Lerp(_, v2, _), 1)
+            | (EMaterialNodeType::Clamp(_, v2, _), 1)
+            | (EMaterialNodeType::Fresnel(v2), 1) => Some(v2),
+            (EMaterialNodeType::Lerp(_, _, v3), 2) | (EMaterialNodeType::Clamp(_, _, v3), 2) => {
+                Some(v3)
+            }
+            (EMaterialNodeType::MaterialFunction(call), input) => {
+                call.inputs.get(input).map(|x| &x.1)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn input_value_mut(&mut self, input: usize) -> Option<&mut EValueType> {
+        match (self, input) {
+            (EMaterialNodeType::Add(v1, _), 0)
+            | (EMaterialNodeType::Subtract(v1, _), 0)
+            | (EMaterialNodeType::Multiply(v1, _), 0)
+            | (EMaterialNodeType::Divide(v1, _), 0)
+            | (EMaterialNodeType::Dot(v1, _), 0)
+            | (EMaterialNodeType::Cross(v1, _), 0)
+            | (EMaterialNodeType::Append(v1, _), 0)
+            | (EMaterialNodeType::Pow(v1, _), 0)
+            | (EMaterialNodeType::Lerp(v1, _, _), 0)
+            | (EMaterialNodeType::Clamp(v1, _, _), 0)
+            | (EMaterialNodeType::Sin(v1), 0)
+            | (EMaterialNodeType::Normalize(v1), 0)
+            | (EMaterialNodeType::Saturate(v1), 0) => Some(v1),
+            (EMaterialNodeType::Add(_, v2), 1)
+            | (EMaterialNodeType::Subtract(_, v2), 1)
+            | (EMaterialNodeType::Multiply(_, v2), 1)
+            | (EMaterialNodeType::Divide(_, v2), 1)
+            | (EMaterialNodeType::Dot(_, v2), 1)
+            | (EMaterialNodeType::Cross(_, v2), 1)
+            | (EMaterialNodeType::Append(_, v2), 1)
+            | (EMaterialNodeType::Pow(_, v2), 1)
+            | (EMaterialNodeType::Lerp(_, v2, _), 1)
+            | (EMaterialNodeType::Clamp(_, v2, _), 1)
+            | (EMaterialNodeType::Fresnel(v2), 1) => Some(v2),
+            (EMaterialNodeType::Lerp(_, _, v3), 2) | (EMaterialNodeType::Clamp(_, _, v3), 2) => {
+                Some(v3)
+            }
+            (EMaterialNodeType::MaterialFunction(call), input) => {
+                call.inputs.get_mut(input).map(|x| &mut x.1)
+            }
+            _ => None,
+        }
+    }
+
+    /// The content the node refers to, such as a texture or a material function.
+    pub fn content_url(&self) -> Option<&url::Url> {
+        match self {
+            EMaterialNodeType::Texture(url)
+            | EMaterialNodeType::VirtualTexture(url)
+            | EMaterialNodeType::MaterialParamentersCollection((url, _)) => url.as_ref(),
+            EMaterialNodeType::MaterialFunction(call) => call.url.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn content_url_mut(&mut self) -> Option<&mut url::Url> {
+        match self {
+            EMaterialNodeType::Texture(url)
+            | EMaterialNodeType::VirtualTexture(url)
+            | EMaterialNodeType::MaterialParamentersCollection((url, _)) => url.as_mut(),
+            EMaterialNodeType::MaterialFunction(call) => call.url.as_mut(),
+            _ => None,
+        }
+    }
+
     pub fn get_name(&self) -> String {
         match self {
             EMaterialNodeType::Add(_, _) => format!("Add"),
@@ -688,6 +1184,32 @@ impl EMaterialNodeType {
             EMaterialNodeType::Paramenter(struct_field) => {
                 format!("Paramenter: {}", &struct_field.name)
             }
+            EMaterialNodeType::Subtract(_, _) => format!("Subtract"),
+            EMaterialNodeType::Multiply(_, _) => format!("Multiply"),
+            EMaterialNodeType::Divide(_, _) => format!("Divide"),
+            EMaterialNodeType::Lerp(_, _, _) => format!("Lerp"),
+            EMaterialNodeType::Clamp(_, _, _) => format!("Clamp"),
+            EMaterialNodeType::Dot(_, _) => format!("Dot"),
+            EMaterialNodeType::Cross(_, _) => format!("Cross"),
+            EMaterialNodeType::Normalize(_) => format!("Normalize"),
+            EMaterialNodeType::Pow(_, _) => format!("Pow"),
+            EMaterialNodeType::Saturate(_) => format!("Saturate"),
+            EMaterialNodeType::ComponentSplit => format!("ComponentSplit"),
+            EMaterialNodeType::Append(_, _) => format!("Append"),
+            EMaterialNodeType::Swizzle(_) => format!("Swizzle"),
+            EMaterialNodeType::WorldPosition => format!("WorldPosition"),
+            EMaterialNodeType::WorldNormal => format!("WorldNormal"),
+            EMaterialNodeType::ViewDirection => format!("ViewDirection"),
+            EMaterialNodeType::Fresnel(_) => format!("Fresnel"),
+            EMaterialNodeType::Panner(_) => format!("Panner"),
+            EMaterialNodeType::NormalMapUnpack => format!("NormalMapUnpack"),
+            EMaterialNodeType::StaticSwitch(_) => format!("StaticSwitch"),
+            EMaterialNodeType::FunctionInput(name, _) => format!("Input: {}", name),
+            EMaterialNodeType::FunctionOutput(name) => format!("Output: {}", name),
+            EMaterialNodeType::MaterialFunction(call) => match &call.url {
+                Some(url) => format!("MaterialFunction: {}", url.get_name_in_editor()),
+                None => format!("MaterialFunction"),
+            },
         }
     }
 }
@@ -710,6 +1232,8 @@ pub enum EEventType {
     ),
     ChangeParamenterName(Rc<RefCell<crate::material::Material>>, String, String),
     ChangeParamenterDefaultValue(Rc<RefCell<crate::material::Material>>),
+    /// The graph of a material function is changed, the materials need to be resolved again.
+    UpdateFunction(Rc<RefCell<crate::material::Material>>),
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -725,9 +1249,18 @@ pub struct MaterialView {
     pub event: Option<EEventType>,
     pub current_resolve_result: Option<HashMap<MaterialOptions, ResolveResult>>,
     pub validate: Option<HashMap<MaterialOptions, rs_render::error::Result<()>>>,
+    pub resolve_error: Option<String>,
+    /// The opened graph is the graph of a material function.
+    pub is_material_function: bool,
     material_url: url::Url,
     module_manager: SingleThreadMutType<ModuleManager>,
     content_manager: SingleThreadMutType<ContentManager>,
+    transaction_history: Option<TransactionHistory>,
+}
+
+enum ETransactionAction {
+    Undo,
+    Redo,
 }
 
 impl MaterialView {
@@ -735,6 +1268,7 @@ impl MaterialView {
         content_manager: SingleThreadMutType<ContentManager>,
         module_manager: SingleThreadMutType<ModuleManager>,
         material_url: url::Url,
+        material: &crate::material::Material,
     ) -> MaterialView {
         let mut snarl = Snarl::new();
         let mut style = SnarlStyle::new();
@@ -750,6 +1284,8 @@ impl MaterialView {
             is_updated: false,
             content_manager: content_manager.clone(),
             paramenters: rs_editor_core::material::Paramenters::empty(),
+            errors: HashMap::new(),
+            material_functions: HashMap::new(),
         };
 
         let node = MaterialNode {
@@ -765,9 +1301,12 @@ impl MaterialView {
             event: None,
             current_resolve_result: None,
             validate: None,
+            resolve_error: None,
+            is_material_function: false,
             material_url,
             module_manager,
             content_manager,
+            transaction_history: Self::snapshot(material).map(TransactionHistory::new),
         }
     }
 
@@ -787,6 +1326,7 @@ impl MaterialView {
             &snarl,
             MaterialOptions::all(),
             &rs_editor_core::material::Paramenters::empty(),
+            &HashMap::new(),
         )
     }
 
@@ -808,15 +1348,46 @@ impl MaterialView {
             Some(egui::Id::new("MaterialViewPanel")),
         );
 
+        let mut transaction_action = context.input_mut(|input| {
+            if input.consume_key(Modifiers::COMMAND | Modifiers::SHIFT, Key::Z)
+                || input.consume_key(Modifiers::COMMAND, Key::Y)
+            {
+                Some(ETransactionAction::Redo)
+            } else if input.consume_key(Modifiers::COMMAND, Key::Z) {
+                Some(ETransactionAction::Undo)
+            } else {
+                None
+            }
+        });
+
         Panel::top("material_menu_bar").show_inside(&mut panel_ui, |ui| {
             egui::MenuBar::new().ui(ui, |ui| {
+                ui.menu_button(t!("Edit"), |ui| {
+                    let (can_undo, can_redo) = self
+                        .transaction_history
+                        .as_ref()
+                        .map(|x| (x.can_undo(), x.can_redo()))
+                        .unwrap_or_default();
+                    if ui.add_enabled(can_undo, Button::new(t!("Undo"))).clicked() {
+                        transaction_action = Some(ETransactionAction::Undo);
+                    }
+                    if ui.add_enabled(can_redo, Button::new(t!("Redo"))).clicked() {
+                        transaction_action = Some(ETransactionAction::Redo);
+                    }
+                });
                 ui.menu_button(t!("Tool"), |ui| {
                     if ui.add(Button::new(t!("Debug Shader Code"))).clicked() {
                         data_source.is_shader_code_window_open = true;
                     }
                 });
                 if ui.button(t!("Apply")).clicked() {
-                    if let Some(current_resolve_result) = self.current_resolve_result.as_ref() {
+                    if self.is_material_function {
+                        if self.resolve_error.is_none() {
+                            self.event = Some(EEventType::UpdateFunction(material.clone()));
+                        }
+                    } else if let Some(current_resolve_result) =
+                        self.current_resolve_result.as_ref()
+                    {
                         self.event = Some(EEventType::Update(
                             material.clone(),
                             current_resolve_result.clone(),
@@ -908,12 +1479,24 @@ impl MaterialView {
         let material_clone = material.clone();
         let mut material = material.borrow_mut();
 
-        if let Some(event) = Self::render_detail(
-            &mut panel_ui,
-            material.paramenters.fields_iter_mut(),
-            material_clone,
-        ) {
-            self.event = Some(event);
+        self.apply_transaction_action(&mut material, transaction_action);
+
+        if !self.is_material_function {
+            if let Some(event) = Self::render_detail(
+                &mut panel_ui,
+                material.paramenters.fields_iter_mut(),
+                material_clone,
+            ) {
+                self.event = Some(event);
+            }
+        }
+
+        if let Some(resolve_error) = &self.resolve_error {
+            egui::Panel::bottom("ResolveError").show_inside(&mut panel_ui, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    ui.colored_label(Color32::RED, resolve_error);
+                });
+            });
         }
 
         let result = Self::do_draw(
@@ -922,26 +1505,118 @@ impl MaterialView {
             &mut panel_ui,
             &mut material,
             &self.material_url,
+            self.is_material_function,
             self.module_manager.clone(),
             self.content_manager.clone(),
         );
         if let Some(result) = result {
-            if let Ok(result) = result {
-                self.current_resolve_result = Some(result.clone());
+            self.viewer.errors.clear();
+            match result {
+                Ok(result) => {
+                    self.current_resolve_result = result;
+                    self.resolve_error = None;
+                }
+                Err(err) => {
+                    if let Some(type_check_error) =
+                        err.downcast_ref::<material_resolve::TypeCheckError>()
+                    {
+                        for error in &type_check_error.errors {
+                            self.viewer
+                                .errors
+                                .entry(error.node_id)
+                                .or_default()
+                                .push(error.to_string());
+                        }
+                    }
+                    self.resolve_error = Some(err.to_string());
+                }
             }
         }
+        self.record_transaction(context, &material);
         self.viewer.is_updated = false;
     }
 
+    /// The graph and the paramenters of the material.
+    fn snapshot(material: &crate::material::Material) -> Option<String> {
+        match serde_json::to_string(&(&material.snarl, &material.paramenters)) {
+            Ok(snapshot) => Some(snapshot),
+            Err(err) => {
+                log::warn!("{}", err);
+                None
+            }
+        }
+    }
+
+    fn apply_transaction_action(
+        &mut self,
+        material: &mut crate::material::Material,
+        transaction_action: Option<ETransactionAction>,
+    ) {
+        let Some(transaction_action) = transaction_action else {
+            return;
+        };
+        let Some(transaction_history) = self.transaction_history.as_mut() else {
+            return;
+        };
+        let snapshot = match transaction_action {
+            ETransactionAction::Undo => transaction_history.undo(),
+            ETransactionAction::Redo => transaction_history.redo(),
+        };
+        let Some(snapshot) = snapshot else {
+            return;
+        };
+        match serde_json::from_str(&snapshot) {
+            Ok((snarl, paramenters)) => {
+                material.snarl = snarl;
+                material.paramenters = paramenters;
+                self.viewer.is_updated = true;
+            }
+            Err(err) => {
+                log::warn!("{}", err);
+            }
+        }
+    }
+
+    /// Records the graph once the pointer is released or a key is typed.
+    fn record_transaction(
+        &mut self,
+        context: &egui::Context,
+        material: &crate::material::Material,
+    ) {
+        let is_edit_finished = context.input(|input| {
+            !input.pointer.any_down()
+                && (input.pointer.any_released()
+                    || input
+                        .events
+                        .iter()
+                        .any(|x| matches!(x, egui::Event::Key { .. } | egui::Event::Text(_))))
+        });
+        if !is_edit_finished {
+            return;
+        }
+        self.record_snapshot(material);
+    }
+
+    fn record_snapshot(&mut self, material: &crate::material::Material) -> bool {
+        let Some(snapshot) = Self::snapshot(material) else {
+            return false;
+        };
+        let Some(transaction_history) = self.transaction_history.as_mut() else {
+            return false;
+        };
+        transaction_history.record("Edit Material", snapshot)
+    }
+
     fn do_draw(
         viewer: &mut GraphViewer,
         style: &SnarlStyle,
         panel_ui: &mut Ui,
         material: &mut crate::material::Material,
         material_url: &url::Url,
+        is_material_function: bool,
         module_manager: SingleThreadMutType<ModuleManager>,
         content_manager: SingleThreadMutType<ContentManager>,
-    ) -> Option<anyhow::Result<HashMap<MaterialOptions, ResolveResult>>> {
+    ) -> Option<anyhow::Result<Option<HashMap<MaterialOptions, ResolveResult>>>> {
         {
             let snarl = &mut material.snarl;
 
@@ -954,15 +1629,30 @@ impl MaterialView {
             return None;
         }
         let snarl = &mut material.snarl;
+        if is_material_function {
+            return Some(
+                material_resolve::check_material_function(
+                    &content_manager.borrow(),
+                    material_url,
+                    snarl,
+                    &viewer.material_functions,
+                )
+                .map(|_| None),
+            );
+        }
         let material_paramenters = &material.paramenters;
-        Some(material_resolve::resolve(
-            module_manager,
-            content_manager,
-            Some(material_url),
-            snarl,
-            MaterialOptions::all(),
-            material_paramenters,
-        ))
+        Some(
+            material_resolve::resolve(
+                module_manager,
+                content_manager,
+                Some(material_url),
+                snarl,
+                MaterialOptions::all(),
+                material_paramenters,
+                &viewer.material_functions,
+            )
+            .map(Some),
+        )
     }
 
     fn render_detail(
@@ -1037,3 +1727,51 @@ impl MaterialView {
         event
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{EMaterialNodeType, ETransactionAction, MaterialNode, MaterialView};
+    use rs_artifact::material_paramenters::BaseDataValueType;
+    use rs_content_manager::content_manager::ContentManager;
+    use rs_foundation::new::SingleThreadMut;
+    use rs_module::types::ModuleManager;
+
+    #[test]
+    fn test_undo_first_edit() {
+        let url = url::Url::parse("asset://asset/material").unwrap();
+        let mut snarl = egui_snarl::Snarl::new();
+        snarl.insert_node(
+            egui::pos2(0.0, 0.0),
+            MaterialNode {
+                node_type: EMaterialNodeType::Sink(Default::default()),
+            },
+        );
+        let mut material = crate::material::Material::new(url.clone(), snarl);
+        let mut material_view = MaterialView::new(
+            SingleThreadMut::new(ContentManager::new()),
+            SingleThreadMut::new(ModuleManager::new()),
+            url,
+            &material,
+        );
+
+        material.snarl.insert_node(
+            egui::pos2(0.0, 0.0),
+            MaterialNode {
+                node_type: EMaterialNodeType::TexCoord(0),
+            },
+        );
+        assert!(material_view.record_snapshot(&material));
+        material
+            .paramenters
+            .add("Roughness".to_string(), BaseDataValueType::F32(0.5));
+        assert!(material_view.record_snapshot(&material));
+
+        material_view.apply_transaction_action(&mut material, Some(ETransactionAction::Undo));
+        assert_eq!(material.snarl.nodes().count(), 2);
+        assert!(material.paramenters.fields().is_empty());
+        material_view.apply_transaction_action(&mut material, Some(ETransactionAction::Undo));
+        assert_eq!(material.snarl.nodes().count(), 1);
+        material_view.apply_transaction_action(&mut material, Some(ETransactionAction::Redo));
+        assert_eq!(material.snarl.nodes().count(), 2);
+    }
+}
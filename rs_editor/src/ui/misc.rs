@@ -3,7 +3,13 @@ use egui_winit::State;
 use rapier3d::prelude::RigidBodyType;
 use rs_artifact::material_paramenters::BaseDataValueType;
 use rs_egui_ext::egui_render::EGUIRenderOutput;
-use rs_engine::{engine::Engine, frame_sync::FrameSync, input_mode::EInputMode};
+use rs_engine::{
+    components::joint_component::EJointType,
+    content::curve::{ECurveExtrapolation, ECurveInterpolation, ECurveValueType, ETangentMode},
+    engine::Engine,
+    frame_sync::FrameSync,
+    input_mode::EInputMode,
+};
 use rs_localization::t;
 use rs_render_types::EBlendModeType;
 use std::collections::HashMap;
@@ -404,3 +410,57 @@ impl ToUIString for EBlendModeType {
         }
     }
 }
+
+impl ToUIString for ECurveInterpolation {
+    fn to_ui_string(&self) -> String {
+        match self {
+            ECurveInterpolation::Constant => t!("Constant").to_string(),
+            ECurveInterpolation::Linear => t!("Linear").to_string(),
+            ECurveInterpolation::Cubic => t!("Cubic").to_string(),
+            ECurveInterpolation::Bezier => t!("Bezier").to_string(),
+        }
+    }
+}
+
+impl ToUIString for ETangentMode {
+    fn to_ui_string(&self) -> String {
+        match self {
+            ETangentMode::Auto => t!("Auto").to_string(),
+            ETangentMode::User => t!("User").to_string(),
+        }
+    }
+}
+
+impl ToUIString for ECurveExtrapolation {
+    fn to_ui_string(&self) -> String {
+        match self {
+            ECurveExtrapolation::Clamp => t!("Clamp").to_string(),
+            ECurveExtrapolation::Loop => t!("Loop").to_string(),
+            ECurveExtrapolation::PingPong => t!("Ping Pong").to_string(),
+        }
+    }
+}
+
+impl ToUIString for ECurveValueType {
+    fn to_ui_string(&self) -> String {
+        match self {
+            ECurveValueType::Scalar => t!("Scalar").to_string(),
+            ECurveValueType::Vector2 => t!("Vector2").to_string(),
+            ECurveValueType::Vector3 => t!("Vector3").to_string(),
+            ECurveValueType::Vector4 => t!("Vector4").to_string(),
+            ECurveValueType::Color => t!("Color").to_string(),
+        }
+    }
+}
+
+impl ToUIString for EJointType {
+    fn to_ui_string(&self) -> String {
+        match self {
+            EJointType::Fixed => t!("Fixed").to_string(),
+            EJointType::Revolute => t!("Revolute").to_string(),
+            EJointType::Prismatic => t!("Prismatic").to_string(),
+            EJointType::Spherical => t!("Spherical").to_string(),
+            EJointType::Spring => t!("Spring").to_string(),
+        }
+    }
+}
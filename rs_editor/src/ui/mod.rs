@@ -9,6 +9,8 @@ pub mod curve_view;
 pub mod debug_textures_view;
 pub mod gizmo_settings;
 pub mod gizmo_view;
+pub mod history_view;
+pub mod import_queue_view;
 pub mod level_view;
 pub mod material_ui_window;
 pub mod material_view;
@@ -1,7 +1,7 @@
 use crate::ui::misc::ToUIString;
 use egui::{Context, Ui};
 use rs_core_minimal::{
-    settings::{Backends, EAntialiasType, PowerPreference, Settings},
+    settings::{Backends, EAntialiasType, EContentFileFormat, PowerPreference, Settings},
     types::HasUrl,
 };
 use rs_engine::content::content_file_type::EContentFileType;
@@ -83,6 +83,18 @@ fn draw_content(
                     }
                 }
             });
+
+        let content_file_format = &mut project_settings.editor_settings.content_file_format;
+        egui::ComboBox::from_label(t!("Content File Format"))
+            .selected_text(format!("{:?}", content_file_format))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(content_file_format, EContentFileFormat::Json, "Json");
+                ui.selectable_value(
+                    content_file_format,
+                    EContentFileFormat::MergeFriendly,
+                    t!("Merge Friendly"),
+                );
+            });
     });
     ui.collapsing(t!("Engine"), |ui| {
         let mut project_settings = project_settings.borrow_mut();
@@ -120,6 +132,26 @@ fn draw_content(
                 }
             });
     });
+    ui.collapsing(t!("Physics"), |ui| {
+        let mut project_settings = project_settings.borrow_mut();
+        let physics_settings = &mut project_settings.physics_settings;
+        ui.add(
+            egui::DragValue::new(&mut physics_settings.fixed_time_step)
+                .speed(0.001)
+                .range(0.001..=1.0)
+                .prefix(t!("Fixed Time Step: ")),
+        );
+        ui.add(
+            egui::DragValue::new(&mut physics_settings.max_substeps)
+                .speed(1)
+                .range(1..=64)
+                .prefix(t!("Max Substeps: ")),
+        );
+        ui.checkbox(
+            &mut physics_settings.is_enable_interpolation,
+            t!("Is Enable Interpolation"),
+        );
+    });
     ui.collapsing(t!("Render"), |ui| {
         let mut project_settings = project_settings.borrow_mut();
         let render_setting = &mut project_settings.render_setting;
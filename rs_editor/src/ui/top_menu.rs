@@ -18,6 +18,8 @@ pub enum EWindowType {
     ComsoleCmds,
     MultipleDrawUi,
     DebugTexture,
+    History,
+    ImportQueue,
 }
 
 #[derive(Debug)]
@@ -164,6 +166,14 @@ impl TopMenu {
                         click = Some(EClickEventType::OpenWindow(EWindowType::DebugTexture));
                         ui.close_kind(egui::UiKind::Menu);
                     }
+                    if ui.add(Button::new(t!("History"))).clicked() {
+                        click = Some(EClickEventType::OpenWindow(EWindowType::History));
+                        ui.close_kind(egui::UiKind::Menu);
+                    }
+                    if ui.add(Button::new(t!("Import Queue"))).clicked() {
+                        click = Some(EClickEventType::OpenWindow(EWindowType::ImportQueue));
+                        ui.close_kind(egui::UiKind::Menu);
+                    }
                 });
                 ui.menu_button(t!("Tool"), |ui| {
                     if ui.add(Button::new(t!("Debug Shader"))).clicked() {
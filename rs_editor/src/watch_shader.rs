@@ -1,5 +1,5 @@
 use anyhow::Result;
-use notify::ReadDirectoryChangesWatcher;
+use notify::RecommendedWatcher;
 use notify_debouncer_mini::{DebouncedEvent, Debouncer, new_debouncer};
 use rs_render::{command::BuiltinShaderChanged, global_shaders::global_shader::GlobalShader};
 use std::{path::Path, sync::mpsc::Receiver};
@@ -11,7 +11,7 @@ pub enum ShaderSourceChangedType {
 
 pub struct WatchShader {
     receiver: Receiver<std::result::Result<Vec<DebouncedEvent>, notify::Error>>,
-    _debouncer: Debouncer<ReadDirectoryChangesWatcher>,
+    _debouncer: Debouncer<RecommendedWatcher>,
     buildin_shaders: Vec<Box<dyn GlobalShader>>,
 }
 
@@ -41,24 +41,24 @@ impl WatchShader {
         let events: Vec<DebouncedEvent> = self.receiver.try_iter().flatten().flatten().collect();
         for event in events {
             for buildin_shader in &self.buildin_shaders {
-                let description = buildin_shader.get_shader_description();
-                let name = buildin_shader.get_name();
-                if description.shader_path != event.path {
+                if buildin_shader.get_shader_description().shader_path != event.path {
                     continue;
                 }
-                let pre_process_code = rs_shader_compiler_core::pre_process::pre_process(
-                    &description.shader_path,
-                    description.include_dirs.iter(),
-                    description.definitions.iter(),
-                );
-                match pre_process_code {
-                    Ok(source) => {
-                        builtin_shader_changeds.push(ShaderSourceChangedType::Builtin(
-                            BuiltinShaderChanged { name, source },
-                        ));
-                    }
-                    Err(err) => {
-                        log::trace!("{err}");
+                for (name, description) in buildin_shader.get_permutation_descriptions() {
+                    let pre_process_code = rs_shader_compiler_core::pre_process::pre_process(
+                        &description.shader_path,
+                        description.include_dirs.iter(),
+                        description.definitions.iter(),
+                    );
+                    match pre_process_code {
+                        Ok(source) => {
+                            builtin_shader_changeds.push(ShaderSourceChangedType::Builtin(
+                                BuiltinShaderChanged { name, source },
+                            ));
+                        }
+                        Err(err) => {
+                            log::trace!("{err}");
+                        }
                     }
                 }
             }
@@ -402,7 +402,8 @@ impl Actor {
     }
 
     pub fn copy_without_initialization(&self, name: String) -> Actor {
-        let copy_root_scene_node = Self::copy_recursion(&self.scene_node.borrow());
+        let mut copy_root_scene_node = Self::copy_recursion(&self.scene_node.borrow());
+        copy_root_scene_node.regenerate_ids();
         let copy_actor = Actor {
             name,
             tags: self.tags.clone(),
@@ -422,6 +423,8 @@ impl Actor {
         let component = scene_node.underlying_component();
         let component = component.borrow().clone();
         let mut copy_scene_node = SceneNode::from_component_box(component);
+        copy_scene_node.set_prefab_instance(scene_node.prefab_instance().cloned());
+        copy_scene_node.set_prefab_node_id(scene_node.prefab_node_id().copied());
         let mut new_childs = vec![];
         for child in scene_node.childs() {
             let copy_node = Self::copy_recursion(&child.borrow());
@@ -1,4 +1,10 @@
-use crate::{camera::Camera, input_mode::EInputMode, rotator::Rotator};
+use crate::{
+    camera::Camera,
+    gamepad::{EGamepadEvent, GamepadId},
+    input_mode::EInputMode,
+    rotator::Rotator,
+};
+use rs_core_minimal::input_binding::EGamepadAxis;
 use winit::event::ElementState;
 
 pub trait CameraInputEventHandle {
@@ -116,3 +122,160 @@ impl CameraInputEventHandle for DefaultCameraInputEventHandle {
         }
     }
 }
+
+pub struct GamepadCameraController {
+    pub dead_zone: f32,
+    pub look_speed: f32,
+    pub is_invert_y: bool,
+    left_stick: glam::Vec2,
+    right_stick: glam::Vec2,
+    left_trigger: f32,
+    right_trigger: f32,
+    /// The gamepad which sent the last input.
+    active_gamepad: Option<GamepadId>,
+}
+
+impl GamepadCameraController {
+    pub fn new() -> GamepadCameraController {
+        GamepadCameraController {
+            dead_zone: 0.15,
+            look_speed: 2.0,
+            is_invert_y: false,
+            left_stick: glam::Vec2::ZERO,
+            right_stick: glam::Vec2::ZERO,
+            left_trigger: 0.0,
+            right_trigger: 0.0,
+            active_gamepad: None,
+        }
+    }
+
+    /// Follows the gamepad which sent the last input, the state is reset when that gamepad is disconnected.
+    pub fn on_gamepad_event(&mut self, event: &EGamepadEvent) {
+        match event {
+            EGamepadEvent::Axis { id, .. } | EGamepadEvent::Button { id, .. }
+                if self.active_gamepad != Some(*id) =>
+            {
+                self.reset();
+                self.active_gamepad = Some(*id);
+            }
+            _ => {}
+        }
+        match event {
+            EGamepadEvent::Axis { axis, value, .. } => match axis {
+                EGamepadAxis::LeftStickX => self.left_stick.x = *value,
+                EGamepadAxis::LeftStickY => self.left_stick.y = *value,
+                EGamepadAxis::RightStickX => self.right_stick.x = *value,
+                EGamepadAxis::RightStickY => self.right_stick.y = *value,
+                EGamepadAxis::LeftTrigger => self.left_trigger = *value,
+                EGamepadAxis::RightTrigger => self.right_trigger = *value,
+            },
+            EGamepadEvent::Disconnected(id) if self.active_gamepad == Some(*id) => {
+                self.reset();
+                self.active_gamepad = None;
+            }
+            _ => {}
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.left_stick = glam::Vec2::ZERO;
+        self.right_stick = glam::Vec2::ZERO;
+        self.left_trigger = 0.0;
+        self.right_trigger = 0.0;
+    }
+
+    fn apply_dead_zone(&self, stick: glam::Vec2) -> glam::Vec2 {
+        let length = stick.length();
+        if length <= self.dead_zone {
+            return glam::Vec2::ZERO;
+        }
+        let scale = ((length - self.dead_zone) / (1.0 - self.dead_zone)).min(1.0);
+        stick / length * scale
+    }
+
+    /// Moves the camera with the left stick and triggers, rotates it with the right stick. Should be called once per frame.
+    pub fn update(&self, camera: &mut Camera, input_mode: EInputMode, movement_speed: f32) {
+        if !matches!(input_mode, EInputMode::Game | EInputMode::GameUI) {
+            return;
+        }
+        let movement = self.apply_dead_zone(self.left_stick);
+        let vertical = self.right_trigger - self.left_trigger;
+        if movement != glam::Vec2::ZERO || vertical.abs() > self.dead_zone {
+            camera
+                .add_local_location(glam::vec3(movement.x, vertical, movement.y) * movement_speed);
+        }
+        let look = self.apply_dead_zone(self.right_stick);
+        if look != glam::Vec2::ZERO {
+            let pitch = if self.is_invert_y { -look.y } else { look.y };
+            camera.add_world_rotation_relative(&Rotator {
+                yaw: (look.x * self.look_speed).to_radians(),
+                roll: 0.0,
+                pitch: (pitch * self.look_speed).to_radians(),
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::GamepadCameraController;
+    use crate::{
+        camera::Camera,
+        gamepad::{GamepadManager, VirtualGamepadBackend},
+        input_mode::EInputMode,
+    };
+    use rs_core_minimal::input_binding::EGamepadAxis;
+    use rs_foundation::new::SingleThreadMut;
+
+    #[test]
+    fn test_gamepad_camera_controller() {
+        let backend = SingleThreadMut::new(VirtualGamepadBackend::new());
+        let mut manager = GamepadManager::new(Box::new(backend.clone()));
+        let mut controller = GamepadCameraController::new();
+        let mut camera = Camera::default(100, 100);
+        let location = camera.get_world_location();
+        let forward_vector = camera.get_forward_vector();
+
+        let id = backend.borrow_mut().connect("Virtual");
+        backend
+            .borrow_mut()
+            .set_axis(id, EGamepadAxis::LeftStickY, 0.1);
+        for event in manager.poll() {
+            controller.on_gamepad_event(&event);
+        }
+        controller.update(&mut camera, EInputMode::Game, 1.0);
+        assert_eq!(camera.get_world_location(), location);
+
+        backend
+            .borrow_mut()
+            .set_axis(id, EGamepadAxis::LeftStickY, 1.0);
+        backend
+            .borrow_mut()
+            .set_axis(id, EGamepadAxis::RightStickX, 1.0);
+        for event in manager.poll() {
+            controller.on_gamepad_event(&event);
+        }
+        controller.update(&mut camera, EInputMode::UI, 1.0);
+        assert_eq!(camera.get_world_location(), location);
+        controller.update(&mut camera, EInputMode::Game, 1.0);
+        assert_ne!(camera.get_world_location(), location);
+        assert_ne!(camera.get_forward_vector(), forward_vector);
+
+        let other_id = backend.borrow_mut().connect("Other");
+        backend.borrow_mut().disconnect(other_id);
+        for event in manager.poll() {
+            controller.on_gamepad_event(&event);
+        }
+        let location = camera.get_world_location();
+        controller.update(&mut camera, EInputMode::Game, 1.0);
+        assert_ne!(camera.get_world_location(), location);
+
+        backend.borrow_mut().disconnect(id);
+        for event in manager.poll() {
+            controller.on_gamepad_event(&event);
+        }
+        let location = camera.get_world_location();
+        controller.update(&mut camera, EInputMode::Game, 1.0);
+        assert_eq!(camera.get_world_location(), location);
+    }
+}
@@ -0,0 +1,599 @@
+use crate::{
+    content::{content_file_type::EContentFileType, level::LevelPhysics},
+    engine::Engine,
+    scene_node::SceneNode,
+};
+use rapier3d::{
+    control::{CharacterAutostep, CharacterLength, KinematicCharacterController},
+    prelude::*,
+};
+use rs_foundation::new::{SingleThreadMut, SingleThreadMutType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CharacterControllerSettings {
+    pub capsule_half_height: f32,
+    pub capsule_radius: f32,
+    /// A small gap kept between the character and the environment.
+    pub offset: f32,
+    pub is_slide: bool,
+    /// In degrees.
+    pub max_slope_climb_angle: f32,
+    /// In degrees.
+    pub min_slope_slide_angle: f32,
+    pub is_autostep_enable: bool,
+    pub autostep_max_height: f32,
+    pub autostep_min_width: f32,
+    pub is_autostep_include_dynamic_bodies: bool,
+    pub is_snap_to_ground_enable: bool,
+    pub snap_to_ground_distance: f32,
+    pub gravity_scale: f32,
+    pub max_fall_speed: f32,
+}
+
+impl Default for CharacterControllerSettings {
+    fn default() -> Self {
+        Self {
+            capsule_half_height: 0.9,
+            capsule_radius: 0.4,
+            offset: 0.01,
+            is_slide: true,
+            max_slope_climb_angle: 45.0,
+            min_slope_slide_angle: 30.0,
+            is_autostep_enable: true,
+            autostep_max_height: 0.3,
+            autostep_min_width: 0.2,
+            is_autostep_include_dynamic_bodies: false,
+            is_snap_to_ground_enable: true,
+            snap_to_ground_distance: 0.2,
+            gravity_scale: 1.0,
+            max_fall_speed: 50.0,
+        }
+    }
+}
+
+impl CharacterControllerSettings {
+    pub fn to_controller(&self) -> KinematicCharacterController {
+        let autostep = if self.is_autostep_enable {
+            Some(CharacterAutostep {
+                max_height: CharacterLength::Absolute(self.autostep_max_height),
+                min_width: CharacterLength::Absolute(self.autostep_min_width),
+                include_dynamic_bodies: self.is_autostep_include_dynamic_bodies,
+            })
+        } else {
+            None
+        };
+        let snap_to_ground = if self.is_snap_to_ground_enable {
+            Some(CharacterLength::Absolute(self.snap_to_ground_distance))
+        } else {
+            None
+        };
+        KinematicCharacterController {
+            offset: CharacterLength::Absolute(self.offset),
+            slide: self.is_slide,
+            autostep,
+            max_slope_climb_angle: self.max_slope_climb_angle.to_radians(),
+            min_slope_slide_angle: self.min_slope_slide_angle.to_radians(),
+            snap_to_ground,
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct CharacterControllerComponentRuntime {
+    pub parent_final_transformation: glam::Mat4,
+    pub final_transformation: glam::Mat4,
+    pub rigid_body_handle: RigidBodyHandle,
+    pub collider_handle: ColliderHandle,
+    pub controller: KinematicCharacterController,
+    pending_movement: glam::Vec3,
+    vertical_speed: f32,
+    is_grounded: bool,
+    is_sliding_down_slope: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CharacterControllerComponent {
+    pub name: String,
+    pub transformation: glam::Mat4,
+    #[serde(default)]
+    pub settings: CharacterControllerSettings,
+    #[serde(skip)]
+    pub run_time: Option<CharacterControllerComponentRuntime>,
+}
+
+impl CharacterControllerComponent {
+    pub fn new(name: String, transformation: glam::Mat4) -> CharacterControllerComponent {
+        CharacterControllerComponent {
+            name,
+            transformation,
+            settings: CharacterControllerSettings::default(),
+            run_time: None,
+        }
+    }
+
+    pub fn new_scene_node(
+        name: String,
+        transformation: glam::Mat4,
+    ) -> SingleThreadMutType<SceneNode> {
+        let component = Self::new(name, transformation);
+        SingleThreadMut::new(SceneNode::from_component(component))
+    }
+
+    /// Accumulates a movement which is consumed by the next tick.
+    pub fn add_movement_input(&mut self, movement: glam::Vec3) {
+        let Some(run_time) = self.run_time.as_mut() else {
+            return;
+        };
+        run_time.pending_movement += movement;
+    }
+
+    /// Starts a jump if the character is on the ground.
+    pub fn jump(&mut self, speed: f32) -> bool {
+        let Some(run_time) = self.run_time.as_mut() else {
+            return false;
+        };
+        if !run_time.is_grounded {
+            return false;
+        }
+        run_time.vertical_speed = speed;
+        run_time.is_grounded = false;
+        true
+    }
+
+    pub fn is_grounded(&self) -> bool {
+        self.run_time
+            .as_ref()
+            .map(|x| x.is_grounded)
+            .unwrap_or(false)
+    }
+
+    pub fn is_sliding_down_slope(&self) -> bool {
+        self.run_time
+            .as_ref()
+            .map(|x| x.is_sliding_down_slope)
+            .unwrap_or(false)
+    }
+
+    /// Applies the modified settings to the controller, the collider is rebuilt.
+    pub fn recreate_physics(&mut self, level_physics: &mut LevelPhysics) {
+        self.remove_physics(level_physics);
+        self.create_physics(level_physics);
+    }
+
+    pub fn remove_physics(&mut self, level_physics: &mut LevelPhysics) {
+        let Some(run_time) = self.run_time.as_mut() else {
+            return;
+        };
+        level_physics.remove_rigid_body(run_time.rigid_body_handle);
+        run_time.rigid_body_handle = RigidBodyHandle::invalid();
+        run_time.collider_handle = ColliderHandle::invalid();
+    }
+
+    fn create_physics(&mut self, level_physics: &mut LevelPhysics) {
+        let Some(run_time) = self.run_time.as_mut() else {
+            return;
+        };
+        let (_, rotation, translation) = run_time
+            .final_transformation
+            .to_scale_rotation_translation();
+        let mut rigid_body = RigidBodyBuilder::kinematic_position_based()
+            .translation(translation)
+            .build();
+        rigid_body.set_rotation(rotation, false);
+        let collider = ColliderBuilder::capsule_y(
+            self.settings.capsule_half_height,
+            self.settings.capsule_radius,
+        )
+        .active_events(ActiveEvents::COLLISION_EVENTS)
+        .build();
+        let rigid_body_handle = level_physics.insert_rigid_body(rigid_body);
+        let collider_handle = level_physics.collider_set.insert_with_parent(
+            collider,
+            rigid_body_handle,
+            &mut level_physics.rigid_body_set,
+        );
+        run_time.rigid_body_handle = rigid_body_handle;
+        run_time.collider_handle = collider_handle;
+        run_time.controller = self.settings.to_controller();
+        run_time.vertical_speed = 0.0;
+        run_time.is_grounded = false;
+        run_time.is_sliding_down_slope = false;
+    }
+
+    pub(crate) fn initialize_runtime(&mut self) {
+        self.run_time = Some(CharacterControllerComponentRuntime {
+            parent_final_transformation: glam::Mat4::IDENTITY,
+            final_transformation: glam::Mat4::IDENTITY,
+            rigid_body_handle: RigidBodyHandle::invalid(),
+            collider_handle: ColliderHandle::invalid(),
+            controller: self.settings.to_controller(),
+            pending_movement: glam::Vec3::ZERO,
+            vertical_speed: 0.0,
+            is_grounded: false,
+            is_sliding_down_slope: false,
+        });
+    }
+
+    fn is_physics_valid(run_time: &CharacterControllerComponentRuntime) -> bool {
+        run_time.rigid_body_handle != RigidBodyHandle::invalid()
+            && run_time.collider_handle != ColliderHandle::invalid()
+    }
+
+    /// Moves the character by the pending movement and the gravity over the steps taken by the last `LevelPhysics::advance`.
+    pub fn move_character(&mut self, level_physics: &mut LevelPhysics) {
+        let Some(run_time) = self.run_time.as_mut() else {
+            return;
+        };
+        if !Self::is_physics_valid(run_time) {
+            return;
+        }
+        // Only the simulated time is consumed, so the movement does not depend on the frame rate.
+        let delta_seconds = level_physics.fixed_time_step() * level_physics.advanced_steps() as f32;
+        if delta_seconds <= 0.0 {
+            return;
+        }
+
+        if run_time.is_grounded && run_time.vertical_speed <= 0.0 {
+            run_time.vertical_speed = 0.0;
+        } else {
+            run_time.vertical_speed +=
+                level_physics.gravity.y * self.settings.gravity_scale * delta_seconds;
+            run_time.vertical_speed = run_time.vertical_speed.max(-self.settings.max_fall_speed);
+        }
+        let mut desired_movement = std::mem::take(&mut run_time.pending_movement);
+        desired_movement.y += run_time.vertical_speed * delta_seconds;
+
+        let character_collider = &level_physics.collider_set[run_time.collider_handle];
+        let character_pose = *character_collider.position();
+        let character_shape = character_collider.shared_shape().clone();
+        let character_mass = level_physics.rigid_body_set[run_time.rigid_body_handle].mass();
+
+        let mut collisions = vec![];
+        let mut query_pipeline = level_physics.query_pipeline_mut(Some(
+            QueryFilter::new().exclude_rigid_body(run_time.rigid_body_handle),
+        ));
+        let movement = run_time.controller.move_shape(
+            delta_seconds,
+            &query_pipeline.as_ref(),
+            &*character_shape,
+            &character_pose,
+            desired_movement,
+            |c| collisions.push(c),
+        );
+        run_time.controller.solve_character_collision_impulses(
+            delta_seconds,
+            &mut query_pipeline,
+            &*character_shape,
+            character_mass,
+            &*collisions,
+        );
+        run_time.is_grounded = movement.grounded;
+        run_time.is_sliding_down_slope = movement.is_sliding_down_slope;
+
+        let character_body = &mut level_physics.rigid_body_set[run_time.rigid_body_handle];
+        let pose = *character_body.position();
+        character_body.set_next_kinematic_translation(pose.translation + movement.translation);
+
+        let (scale, _, _) = run_time
+            .final_transformation
+            .to_scale_rotation_translation();
+        run_time.final_transformation =
+            glam::Mat4::from_scale_rotation_translation(scale, pose.rotation, pose.translation);
+    }
+}
+
+#[typetag::serde]
+impl super::component::Component for CharacterControllerComponent {
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn set_name(&mut self, new_name: String) {
+        self.name = new_name;
+    }
+
+    fn get_final_transformation(&self) -> glam::Mat4 {
+        let Some(run_time) = self.run_time.as_ref() else {
+            return glam::Mat4::IDENTITY;
+        };
+        run_time.final_transformation
+    }
+
+    fn set_transformation(&mut self, transformation: glam::Mat4) {
+        self.transformation = transformation;
+    }
+
+    fn get_transformation(&self) -> glam::Mat4 {
+        self.transformation
+    }
+
+    fn on_post_update_transformation(
+        &mut self,
+        engine: &mut Engine,
+        level_physics: Option<&mut LevelPhysics>,
+        files: &HashMap<url::Url, EContentFileType>,
+    ) {
+        let _ = files;
+        let _ = engine;
+        let Some(level_physics) = level_physics else {
+            return;
+        };
+        let Some(run_time) = self.run_time.as_mut() else {
+            return;
+        };
+        if !Self::is_physics_valid(run_time) {
+            return;
+        }
+        let (_, rotation, translation) = run_time
+            .final_transformation
+            .to_scale_rotation_translation();
+        let Some(rigid_body) = level_physics
+            .rigid_body_set
+            .get_mut(run_time.rigid_body_handle)
+        else {
+            return;
+        };
+        rigid_body.set_translation(translation, true);
+        rigid_body.set_rotation(rotation, true);
+        run_time.vertical_speed = 0.0;
+    }
+
+    fn set_final_transformation(&mut self, final_transformation: glam::Mat4) {
+        let Some(run_time) = self.run_time.as_mut() else {
+            return;
+        };
+        run_time.final_transformation = final_transformation;
+    }
+
+    fn set_parent_final_transformation(&mut self, parent_final_transformation: glam::Mat4) {
+        let Some(run_time) = self.run_time.as_mut() else {
+            return;
+        };
+        run_time.parent_final_transformation = parent_final_transformation;
+    }
+
+    fn get_parent_final_transformation(&self) -> glam::Mat4 {
+        let Some(run_time) = self.run_time.as_ref() else {
+            return glam::Mat4::IDENTITY;
+        };
+        run_time.parent_final_transformation
+    }
+
+    fn initialize(
+        &mut self,
+        engine: &mut Engine,
+        files: &HashMap<url::Url, EContentFileType>,
+        player_viewport: &mut crate::player_viewport::PlayerViewport,
+    ) {
+        let _ = player_viewport;
+        let _ = files;
+        let _ = engine;
+        self.initialize_runtime();
+    }
+
+    fn initialize_physics(
+        &mut self,
+        engine: &mut Engine,
+        level_physics: &mut LevelPhysics,
+        files: &HashMap<url::Url, EContentFileType>,
+    ) {
+        let _ = files;
+        let _ = engine;
+        self.recreate_physics(level_physics);
+    }
+
+    fn tick(&mut self, time: f32, engine: &mut Engine, level_physics: &mut LevelPhysics) {
+        let _ = engine;
+        let _ = time;
+        self.move_character(level_physics);
+    }
+
+    fn collider_handles(&self) -> Vec<ColliderHandle> {
+        let Some(run_time) = self.run_time.as_ref() else {
+            return vec![];
+        };
+        if run_time.collider_handle == ColliderHandle::invalid() {
+            return vec![];
+        }
+        vec![run_time.collider_handle]
+    }
+
+    fn rigid_body_handle(&self) -> Option<RigidBodyHandle> {
+        let Some(run_time) = self.run_time.as_ref() else {
+            return None;
+        };
+        if run_time.rigid_body_handle == RigidBodyHandle::invalid() {
+            return None;
+        }
+        Some(run_time.rigid_body_handle)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CharacterControllerComponent, CharacterControllerSettings};
+    use crate::components::component::Component;
+    use crate::content::level::{Level, LevelPhysics};
+    use rapier3d::prelude::*;
+
+    const TIME_STEP: f32 = 1.0 / 60.0;
+    /// The height of the center of the default capsule standing on the ground.
+    const STAND_HEIGHT: f32 = 0.9 + 0.4 + 0.01;
+
+    /// A physics whose ground is at y = 0.
+    fn make_physics() -> LevelPhysics {
+        let mut physics = Level::default_physics();
+        physics.apply_settings(&rs_core_minimal::settings::PhysicsSettings {
+            fixed_time_step: TIME_STEP,
+            max_substeps: 1,
+            is_enable_interpolation: false,
+        });
+        add_box(
+            &mut physics,
+            glam::vec3(50.0, 0.5, 50.0),
+            glam::vec3(0.0, -0.5, 0.0),
+            glam::Quat::IDENTITY,
+        );
+        physics
+    }
+
+    fn add_box(
+        physics: &mut LevelPhysics,
+        half_extents: glam::Vec3,
+        translation: glam::Vec3,
+        rotation: glam::Quat,
+    ) {
+        let mut rigid_body = RigidBodyBuilder::fixed().translation(translation).build();
+        rigid_body.set_rotation(rotation, false);
+        let handle = physics.insert_rigid_body(rigid_body);
+        physics.collider_set.insert_with_parent(
+            ColliderBuilder::cuboid(half_extents.x, half_extents.y, half_extents.z).build(),
+            handle,
+            &mut physics.rigid_body_set,
+        );
+    }
+
+    fn make_character(
+        physics: &mut LevelPhysics,
+        translation: glam::Vec3,
+        settings: CharacterControllerSettings,
+    ) -> CharacterControllerComponent {
+        let mut character =
+            CharacterControllerComponent::new("Character".to_string(), glam::Mat4::IDENTITY);
+        character.settings = settings;
+        character.initialize_runtime();
+        character.set_final_transformation(glam::Mat4::from_translation(translation));
+        character.recreate_physics(physics);
+        character
+    }
+
+    /// Walks with the velocity and returns the number of frames which are not grounded.
+    fn walk(
+        physics: &mut LevelPhysics,
+        character: &mut CharacterControllerComponent,
+        velocity: glam::Vec3,
+        frames: usize,
+    ) -> usize {
+        let mut airborne_frames = 0;
+        for _ in 0..frames {
+            physics.advance(TIME_STEP);
+            character.add_movement_input(velocity * TIME_STEP);
+            character.move_character(physics);
+            if !character.is_grounded() {
+                airborne_frames += 1;
+            }
+        }
+        airborne_frames
+    }
+
+    fn location(physics: &LevelPhysics, character: &CharacterControllerComponent) -> glam::Vec3 {
+        physics.rigid_body_set[character.rigid_body_handle().unwrap()].translation()
+    }
+
+    #[test]
+    fn test_fall_to_ground() {
+        let mut physics = make_physics();
+        let mut character = make_character(
+            &mut physics,
+            glam::vec3(0.0, STAND_HEIGHT + 2.0, 0.0),
+            CharacterControllerSettings::default(),
+        );
+        assert!(!character.is_grounded());
+        walk(&mut physics, &mut character, glam::Vec3::ZERO, 120);
+        assert!(character.is_grounded());
+        assert!((location(&physics, &character).y - STAND_HEIGHT).abs() < 0.05);
+        assert!(character.jump(5.0));
+        assert!(!character.jump(5.0));
+    }
+
+    #[test]
+    fn test_step_height() {
+        let climb = |step_height: f32| {
+            let mut physics = make_physics();
+            add_box(
+                &mut physics,
+                glam::vec3(2.0, step_height / 2.0, 2.0),
+                glam::vec3(3.0, step_height / 2.0, 0.0),
+                glam::Quat::IDENTITY,
+            );
+            let mut character = make_character(
+                &mut physics,
+                glam::vec3(0.0, STAND_HEIGHT, 0.0),
+                CharacterControllerSettings::default(),
+            );
+            walk(&mut physics, &mut character, glam::Vec3::ZERO, 30);
+            walk(&mut physics, &mut character, glam::vec3(2.0, 0.0, 0.0), 90);
+            location(&physics, &character)
+        };
+        // Lower than `autostep_max_height`.
+        let location = climb(0.2);
+        assert!(location.x > 1.5, "{location}");
+        assert!(location.y > STAND_HEIGHT + 0.15, "{location}");
+        let location = climb(0.5);
+        assert!(location.x < 1.0, "{location}");
+        assert!(location.y < STAND_HEIGHT + 0.1, "{location}");
+    }
+
+    #[test]
+    fn test_slope_limit() {
+        let climb = |max_slope_climb_angle: f32| {
+            let mut physics = make_physics();
+            // A ramp of 35 degrees which starts at x = 2.
+            let angle = 35.0_f32.to_radians();
+            let rotation = glam::Quat::from_rotation_z(angle);
+            add_box(
+                &mut physics,
+                glam::vec3(10.0, 0.5, 3.0),
+                glam::vec3(2.0, 0.0, 0.0) + rotation * glam::vec3(10.0, -0.5, 0.0),
+                rotation,
+            );
+            let mut character = make_character(
+                &mut physics,
+                glam::vec3(0.0, STAND_HEIGHT, 0.0),
+                CharacterControllerSettings {
+                    max_slope_climb_angle,
+                    ..Default::default()
+                },
+            );
+            walk(&mut physics, &mut character, glam::Vec3::ZERO, 30);
+            walk(&mut physics, &mut character, glam::vec3(2.0, 0.0, 0.0), 120);
+            location(&physics, &character)
+        };
+        let location = climb(60.0);
+        assert!(location.y > STAND_HEIGHT + 0.3, "{location}");
+        let location = climb(20.0);
+        assert!(location.y < STAND_HEIGHT + 0.25, "{location}");
+    }
+
+    #[test]
+    fn test_snap_to_ground() {
+        let walk_off_ledge = |is_snap_to_ground_enable: bool| {
+            let mut physics = make_physics();
+            // A platform of 0.1 which ends at x = 0, lower than `snap_to_ground_distance`.
+            add_box(
+                &mut physics,
+                glam::vec3(5.0, 0.05, 3.0),
+                glam::vec3(-5.0, 0.05, 0.0),
+                glam::Quat::IDENTITY,
+            );
+            let mut character = make_character(
+                &mut physics,
+                glam::vec3(-1.0, STAND_HEIGHT + 0.1, 0.0),
+                CharacterControllerSettings {
+                    is_snap_to_ground_enable,
+                    ..Default::default()
+                },
+            );
+            walk(&mut physics, &mut character, glam::Vec3::ZERO, 30);
+            let airborne_frames = walk(&mut physics, &mut character, glam::vec3(2.0, 0.0, 0.0), 60);
+            let location = location(&physics, &character);
+            assert!(location.x > 0.5, "{location}");
+            assert!((location.y - STAND_HEIGHT).abs() < 0.05, "{location}");
+            airborne_frames
+        };
+        assert!(walk_off_ledge(true) < walk_off_ledge(false));
+    }
+}
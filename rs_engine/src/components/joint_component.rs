@@ -0,0 +1,422 @@
+use crate::{
+    content::{content_file_type::EContentFileType, level::LevelPhysics},
+    engine::Engine,
+    scene_node::SceneNode,
+};
+use rapier3d::prelude::*;
+use rs_foundation::new::{SingleThreadMut, SingleThreadMutType};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, Default)]
+pub enum EJointType {
+    #[default]
+    Fixed,
+    Revolute,
+    Prismatic,
+    Spherical,
+    Spring,
+}
+
+impl EJointType {
+    pub fn all() -> [EJointType; 5] {
+        [
+            EJointType::Fixed,
+            EJointType::Revolute,
+            EJointType::Prismatic,
+            EJointType::Spherical,
+            EJointType::Spring,
+        ]
+    }
+
+    pub fn is_axis_used(&self) -> bool {
+        matches!(self, EJointType::Revolute | EJointType::Prismatic)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JointSettings {
+    pub joint_type: EJointType,
+    /// The first rigid body found in this actor is attached to the first rigid body found in the connected actor.
+    pub connected_actor: Option<String>,
+    pub local_anchor1: glam::Vec3,
+    pub local_anchor2: glam::Vec3,
+    /// Used by revolute and prismatic joints.
+    pub axis: glam::Vec3,
+    pub is_limit_enable: bool,
+    /// Angles in degrees for revolute joints, distances for prismatic joints.
+    pub limits: glam::Vec2,
+    /// Used by spring joints.
+    pub rest_length: f32,
+    pub stiffness: f32,
+    pub damping: f32,
+    pub is_contacts_enabled: bool,
+}
+
+impl Default for JointSettings {
+    fn default() -> Self {
+        Self {
+            joint_type: EJointType::Fixed,
+            connected_actor: None,
+            local_anchor1: glam::Vec3::ZERO,
+            local_anchor2: glam::Vec3::ZERO,
+            axis: glam::Vec3::Y,
+            is_limit_enable: false,
+            limits: glam::vec2(-45.0, 45.0),
+            rest_length: 1.0,
+            stiffness: 100.0,
+            damping: 1.0,
+            is_contacts_enabled: true,
+        }
+    }
+}
+
+impl JointSettings {
+    pub fn build_joint(&self) -> GenericJoint {
+        let axis = self.axis.try_normalize().unwrap_or(glam::Vec3::Y);
+        let joint: GenericJoint = match self.joint_type {
+            EJointType::Fixed => FixedJointBuilder::new()
+                .local_anchor1(self.local_anchor1)
+                .local_anchor2(self.local_anchor2)
+                .contacts_enabled(self.is_contacts_enabled)
+                .into(),
+            EJointType::Revolute => {
+                let mut builder = RevoluteJointBuilder::new(axis)
+                    .local_anchor1(self.local_anchor1)
+                    .local_anchor2(self.local_anchor2)
+                    .contacts_enabled(self.is_contacts_enabled);
+                if self.is_limit_enable {
+                    builder =
+                        builder.limits([self.limits.x.to_radians(), self.limits.y.to_radians()]);
+                }
+                builder.into()
+            }
+            EJointType::Prismatic => {
+                let mut builder = PrismaticJointBuilder::new(axis)
+                    .local_anchor1(self.local_anchor1)
+                    .local_anchor2(self.local_anchor2)
+                    .contacts_enabled(self.is_contacts_enabled);
+                if self.is_limit_enable {
+                    builder = builder.limits([self.limits.x, self.limits.y]);
+                }
+                builder.into()
+            }
+            EJointType::Spherical => SphericalJointBuilder::new()
+                .local_anchor1(self.local_anchor1)
+                .local_anchor2(self.local_anchor2)
+                .contacts_enabled(self.is_contacts_enabled)
+                .into(),
+            EJointType::Spring => {
+                SpringJointBuilder::new(self.rest_length, self.stiffness, self.damping)
+                    .local_anchor1(self.local_anchor1)
+                    .local_anchor2(self.local_anchor2)
+                    .contacts_enabled(self.is_contacts_enabled)
+                    .into()
+            }
+        };
+        joint
+    }
+}
+
+#[derive(Clone)]
+pub struct JointComponentRuntime {
+    pub parent_final_transformation: glam::Mat4,
+    pub final_transformation: glam::Mat4,
+    pub joint_handle: Option<ImpulseJointHandle>,
+    pub bodies: Option<(RigidBodyHandle, RigidBodyHandle)>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct JointComponent {
+    pub name: String,
+    pub transformation: glam::Mat4,
+    #[serde(default)]
+    pub settings: JointSettings,
+    #[serde(skip)]
+    pub run_time: Option<JointComponentRuntime>,
+}
+
+impl JointComponent {
+    pub fn new(name: String, transformation: glam::Mat4) -> JointComponent {
+        JointComponent {
+            name,
+            transformation,
+            settings: JointSettings::default(),
+            run_time: None,
+        }
+    }
+
+    pub fn new_scene_node(
+        name: String,
+        transformation: glam::Mat4,
+    ) -> SingleThreadMutType<SceneNode> {
+        let component = Self::new(name, transformation);
+        SingleThreadMut::new(SceneNode::from_component(component))
+    }
+
+    pub(crate) fn initialize_runtime(&mut self) {
+        self.run_time = Some(JointComponentRuntime {
+            parent_final_transformation: glam::Mat4::IDENTITY,
+            final_transformation: glam::Mat4::IDENTITY,
+            joint_handle: None,
+            bodies: None,
+        });
+    }
+
+    /// Whether the joint exists and still connects the given bodies.
+    pub fn is_linked(
+        &self,
+        level_physics: &LevelPhysics,
+        body1: RigidBodyHandle,
+        body2: RigidBodyHandle,
+    ) -> bool {
+        let Some(run_time) = self.run_time.as_ref() else {
+            return false;
+        };
+        let Some(joint_handle) = run_time.joint_handle else {
+            return false;
+        };
+        if run_time.bodies != Some((body1, body2)) {
+            return false;
+        }
+        level_physics.impulse_joint_set.get(joint_handle).is_some()
+    }
+
+    pub fn link(
+        &mut self,
+        level_physics: &mut LevelPhysics,
+        body1: RigidBodyHandle,
+        body2: RigidBodyHandle,
+    ) {
+        self.unlink(level_physics);
+        let joint = self.settings.build_joint();
+        let Some(run_time) = self.run_time.as_mut() else {
+            return;
+        };
+        let joint_handle = level_physics
+            .impulse_joint_set
+            .insert(body1, body2, joint, true);
+        run_time.joint_handle = Some(joint_handle);
+        run_time.bodies = Some((body1, body2));
+    }
+
+    pub fn unlink(&mut self, level_physics: &mut LevelPhysics) {
+        let Some(run_time) = self.run_time.as_mut() else {
+            return;
+        };
+        run_time.bodies = None;
+        let Some(joint_handle) = run_time.joint_handle.take() else {
+            return;
+        };
+        level_physics.impulse_joint_set.remove(joint_handle, true);
+    }
+}
+
+#[typetag::serde]
+impl super::component::Component for JointComponent {
+    fn get_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn set_name(&mut self, new_name: String) {
+        self.name = new_name;
+    }
+
+    fn get_final_transformation(&self) -> glam::Mat4 {
+        let Some(run_time) = self.run_time.as_ref() else {
+            return glam::Mat4::IDENTITY;
+        };
+        run_time.final_transformation
+    }
+
+    fn set_transformation(&mut self, transformation: glam::Mat4) {
+        self.transformation = transformation;
+    }
+
+    fn get_transformation(&self) -> glam::Mat4 {
+        self.transformation
+    }
+
+    fn on_post_update_transformation(
+        &mut self,
+        engine: &mut Engine,
+        level_physics: Option<&mut LevelPhysics>,
+        files: &HashMap<url::Url, EContentFileType>,
+    ) {
+        let _ = files;
+        let _ = engine;
+        let _ = level_physics;
+    }
+
+    fn set_final_transformation(&mut self, final_transformation: glam::Mat4) {
+        let Some(run_time) = self.run_time.as_mut() else {
+            return;
+        };
+        run_time.final_transformation = final_transformation;
+    }
+
+    fn set_parent_final_transformation(&mut self, parent_final_transformation: glam::Mat4) {
+        let Some(run_time) = self.run_time.as_mut() else {
+            return;
+        };
+        run_time.parent_final_transformation = parent_final_transformation;
+    }
+
+    fn get_parent_final_transformation(&self) -> glam::Mat4 {
+        let Some(run_time) = self.run_time.as_ref() else {
+            return glam::Mat4::IDENTITY;
+        };
+        run_time.parent_final_transformation
+    }
+
+    fn initialize(
+        &mut self,
+        engine: &mut Engine,
+        files: &HashMap<url::Url, EContentFileType>,
+        player_viewport: &mut crate::player_viewport::PlayerViewport,
+    ) {
+        let _ = player_viewport;
+        let _ = files;
+        let _ = engine;
+        self.initialize_runtime();
+    }
+
+    fn initialize_physics(
+        &mut self,
+        engine: &mut Engine,
+        level_physics: &mut LevelPhysics,
+        files: &HashMap<url::Url, EContentFileType>,
+    ) {
+        let _ = files;
+        let _ = engine;
+        // The bodies of the connected actor may not exist yet, the level links joints after all actors are initialized.
+        self.unlink(level_physics);
+    }
+
+    fn tick(&mut self, time: f32, engine: &mut Engine, level_physics: &mut LevelPhysics) {
+        let _ = level_physics;
+        let _ = engine;
+        let _ = time;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{EJointType, JointComponent, JointSettings};
+    use crate::content::level::{Level, LevelPhysics};
+    use rapier3d::prelude::*;
+
+    const TIME_STEP: f32 = 1.0 / 60.0;
+
+    /// A physics with a fixed body at the origin and a dynamic ball one meter below it.
+    fn make_physics() -> (LevelPhysics, RigidBodyHandle, RigidBodyHandle) {
+        let mut physics = Level::default_physics();
+        physics.apply_settings(&rs_core_minimal::settings::PhysicsSettings {
+            fixed_time_step: TIME_STEP,
+            max_substeps: 1,
+            is_enable_interpolation: false,
+        });
+        let anchor = physics.insert_rigid_body(RigidBodyBuilder::fixed().build());
+        let ball = physics.insert_rigid_body(
+            RigidBodyBuilder::dynamic()
+                .translation(glam::vec3(0.0, -1.0, 0.0))
+                .build(),
+        );
+        physics.collider_set.insert_with_parent(
+            ColliderBuilder::ball(0.2).build(),
+            ball,
+            &mut physics.rigid_body_set,
+        );
+        (physics, anchor, ball)
+    }
+
+    fn make_joint(settings: JointSettings) -> JointComponent {
+        let mut joint = JointComponent::new("Joint".to_string(), glam::Mat4::IDENTITY);
+        joint.settings = settings;
+        joint.initialize_runtime();
+        joint
+    }
+
+    fn simulate(physics: &mut LevelPhysics, seconds: f32) {
+        for _ in 0..(seconds / TIME_STEP) as usize {
+            physics.advance(TIME_STEP);
+        }
+    }
+
+    #[test]
+    fn test_build_joint() {
+        let mut settings = JointSettings::default();
+        for joint_type in EJointType::all() {
+            settings.joint_type = joint_type;
+            settings.is_limit_enable = true;
+            let joint = settings.build_joint();
+            assert_eq!(joint.contacts_enabled, settings.is_contacts_enabled);
+        }
+    }
+
+    #[test]
+    fn test_link() {
+        let (mut physics, anchor, ball) = make_physics();
+        let mut joint = make_joint(JointSettings::default());
+        assert!(!joint.is_linked(&physics, anchor, ball));
+        joint.link(&mut physics, anchor, ball);
+        assert!(joint.is_linked(&physics, anchor, ball));
+        assert!(!joint.is_linked(&physics, ball, anchor));
+        assert_eq!(physics.impulse_joint_set.len(), 1);
+
+        joint.link(&mut physics, ball, anchor);
+        assert!(joint.is_linked(&physics, ball, anchor));
+        assert_eq!(physics.impulse_joint_set.len(), 1);
+
+        joint.unlink(&mut physics);
+        assert!(!joint.is_linked(&physics, ball, anchor));
+        assert_eq!(physics.impulse_joint_set.len(), 0);
+
+        // The joint is removed with its body.
+        joint.link(&mut physics, anchor, ball);
+        physics.remove_rigid_body(ball);
+        assert!(!joint.is_linked(&physics, anchor, ball));
+        assert_eq!(physics.impulse_joint_set.len(), 0);
+    }
+
+    #[test]
+    fn test_spherical_joint() {
+        let (mut physics, anchor, ball) = make_physics();
+        // Pushed sideways, the ball swings around the anchor at a distance of one meter.
+        let mut joint = make_joint(JointSettings {
+            joint_type: EJointType::Spherical,
+            local_anchor2: glam::vec3(0.0, 1.0, 0.0),
+            ..Default::default()
+        });
+        joint.link(&mut physics, anchor, ball);
+        physics.rigid_body_set[ball].set_linvel(glam::vec3(2.0, 0.0, 0.0), true);
+        for _ in 0..4 {
+            simulate(&mut physics, 0.5);
+            let translation = physics.rigid_body_set[ball].translation();
+            assert!((translation.length() - 1.0).abs() < 0.05, "{translation}");
+        }
+
+        let (mut physics, _, ball) = make_physics();
+        simulate(&mut physics, 2.0);
+        assert!(physics.rigid_body_set[ball].translation().y < -5.0);
+    }
+
+    #[test]
+    fn test_prismatic_joint_limits() {
+        let (mut physics, anchor, ball) = make_physics();
+        // The ball slides down along the axis until the lower limit.
+        let mut joint = make_joint(JointSettings {
+            joint_type: EJointType::Prismatic,
+            axis: glam::Vec3::Y,
+            local_anchor2: glam::vec3(0.0, 1.0, 0.0),
+            is_limit_enable: true,
+            limits: glam::vec2(-0.5, 0.5),
+            ..Default::default()
+        });
+        joint.link(&mut physics, anchor, ball);
+        simulate(&mut physics, 2.0);
+        let translation = physics.rigid_body_set[ball].translation();
+        assert!((translation.y + 1.5).abs() < 0.05, "{translation}");
+        assert!(translation.x.abs() < 0.01, "{translation}");
+    }
+}
@@ -1,4 +1,6 @@
+pub mod character_controller_component;
 pub mod component;
+pub mod joint_component;
 pub mod point_light_component;
 pub mod spot_light_component;
 pub mod text_component;
@@ -1,11 +1,101 @@
 use crate::url_extension::UrlExtension;
 use serde::{Deserialize, Serialize};
-use uniform_cubic_splines::{basis::CatmullRom, spline, spline_inverse};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ECurveInterpolation {
+    Constant,
+    Linear,
+    #[default]
+    Cubic,
+    Bezier,
+}
+
+impl ECurveInterpolation {
+    pub fn all() -> [ECurveInterpolation; 4] {
+        [
+            ECurveInterpolation::Constant,
+            ECurveInterpolation::Linear,
+            ECurveInterpolation::Cubic,
+            ECurveInterpolation::Bezier,
+        ]
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ETangentMode {
+    #[default]
+    Auto,
+    User,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ECurveExtrapolation {
+    #[default]
+    Clamp,
+    Loop,
+    PingPong,
+}
+
+impl ECurveExtrapolation {
+    pub fn all() -> [ECurveExtrapolation; 3] {
+        [
+            ECurveExtrapolation::Clamp,
+            ECurveExtrapolation::Loop,
+            ECurveExtrapolation::PingPong,
+        ]
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ECurveValueType {
+    #[default]
+    Scalar,
+    Vector2,
+    Vector3,
+    Vector4,
+    Color,
+}
+
+impl ECurveValueType {
+    pub fn all() -> [ECurveValueType; 5] {
+        [
+            ECurveValueType::Scalar,
+            ECurveValueType::Vector2,
+            ECurveValueType::Vector3,
+            ECurveValueType::Vector4,
+            ECurveValueType::Color,
+        ]
+    }
+
+    pub fn channel_names(&self) -> &'static [&'static str] {
+        match self {
+            ECurveValueType::Scalar => &["Value"],
+            ECurveValueType::Vector2 => &["X", "Y"],
+            ECurveValueType::Vector3 => &["X", "Y", "Z"],
+            ECurveValueType::Vector4 => &["X", "Y", "Z", "W"],
+            ECurveValueType::Color => &["R", "G", "B", "A"],
+        }
+    }
+
+    pub fn channel_count(&self) -> usize {
+        self.channel_names().len()
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ControlPoint {
     pub position: glam::DVec2,
     pub id: String,
+    #[serde(default)]
+    pub interpolation: ECurveInterpolation,
+    #[serde(default)]
+    pub tangent_mode: ETangentMode,
+    /// Handle pointing to the previous key, relative to `position`. Only used when `tangent_mode` is `User`.
+    #[serde(default)]
+    pub arrive_tangent: glam::DVec2,
+    /// Handle pointing to the next key, relative to `position`. Only used when `tangent_mode` is `User`.
+    #[serde(default)]
+    pub leave_tangent: glam::DVec2,
 }
 
 impl ControlPoint {
@@ -13,23 +103,323 @@ impl ControlPoint {
         ControlPoint {
             position: pos,
             id: format!("ControlPoint_{}", index),
+            interpolation: ECurveInterpolation::default(),
+            tangent_mode: ETangentMode::default(),
+            arrive_tangent: glam::dvec2(-1.0, 0.0),
+            leave_tangent: glam::dvec2(1.0, 0.0),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CurveChannel {
+    pub control_points: Vec<ControlPoint>,
+    #[serde(default)]
+    pub pre_infinity: ECurveExtrapolation,
+    #[serde(default)]
+    pub post_infinity: ECurveExtrapolation,
+}
+
+impl CurveChannel {
+    pub fn new() -> CurveChannel {
+        CurveChannel::default()
+    }
+
+    pub fn sort_by_x(&mut self) {
+        self.control_points
+            .sort_by(|a, b| a.position.x.total_cmp(&b.position.x));
+    }
+
+    pub fn get_x_range(&self) -> Option<std::ops::RangeInclusive<f64>> {
+        let first = self.control_points.first()?;
+        let last = self.control_points.last()?;
+        Some(first.position.x..=last.position.x)
+    }
+
+    pub fn get_y_range(&self) -> Option<std::ops::RangeInclusive<f64>> {
+        let mut iter = self.control_points.iter().map(|x| x.position.y);
+        let first = iter.next()?;
+        let (min_value, max_value) = iter.fold((first, first), |(min_value, max_value), y| {
+            (min_value.min(y), max_value.max(y))
+        });
+        Some(min_value..=max_value)
+    }
+
+    /// Returns `None` if the channel has no control points. Control points must be sorted by x.
+    pub fn evaluate(&self, x: f64) -> Option<f64> {
+        let (x, _) = self.remap_x(x)?;
+        let index = self.segment_index(x)?;
+        Some(self.evaluate_segment(index, x).0)
+    }
+
+    /// Returns the analytic first derivative dy/dx.
+    pub fn derivative(&self, x: f64) -> Option<f64> {
+        let (remap_x, sign) = self.remap_x(x)?;
+        let range = self.get_x_range()?;
+        let is_out_of_range = x < *range.start() || x > *range.end();
+        let is_clamped = (x < *range.start() && self.pre_infinity == ECurveExtrapolation::Clamp)
+            || (x > *range.end() && self.post_infinity == ECurveExtrapolation::Clamp);
+        if is_clamped || (is_out_of_range && range.start() == range.end()) {
+            return Some(0.0);
+        }
+        let index = self.segment_index(remap_x)?;
+        Some(self.evaluate_segment(index, remap_x).1 * sign)
+    }
+
+    fn remap_x(&self, x: f64) -> Option<(f64, f64)> {
+        let range = self.get_x_range()?;
+        let (start, end) = (*range.start(), *range.end());
+        let length = end - start;
+        if length <= 0.0 {
+            return Some((start, 1.0));
+        }
+        let mode = if x < start {
+            self.pre_infinity
+        } else if x > end {
+            self.post_infinity
+        } else {
+            return Some((x, 1.0));
+        };
+        let remap = match mode {
+            ECurveExtrapolation::Clamp => (x.clamp(start, end), 1.0),
+            ECurveExtrapolation::Loop => (start + (x - start).rem_euclid(length), 1.0),
+            ECurveExtrapolation::PingPong => {
+                let cycle = ((x - start) / length).floor();
+                let offset = (x - start).rem_euclid(length);
+                if (cycle as i64).rem_euclid(2) == 0 {
+                    (start + offset, 1.0)
+                } else {
+                    (end - offset, -1.0)
+                }
+            }
+        };
+        Some(remap)
+    }
+
+    fn segment_index(&self, x: f64) -> Option<usize> {
+        if self.control_points.is_empty() {
+            return None;
+        }
+        let index = self
+            .control_points
+            .partition_point(|control_point| control_point.position.x <= x);
+        Some(index.saturating_sub(1).min(self.control_points.len() - 1))
+    }
+
+    fn auto_slope(&self, index: usize) -> f64 {
+        let control_points = &self.control_points;
+        let prev = &control_points[index.saturating_sub(1)].position;
+        let next = &control_points[(index + 1).min(control_points.len() - 1)].position;
+        let dx = next.x - prev.x;
+        if dx.abs() <= f64::EPSILON {
+            0.0
+        } else {
+            (next.y - prev.y) / dx
+        }
+    }
+
+    pub fn arrive_tangent(&self, index: usize) -> glam::DVec2 {
+        let control_point = &self.control_points[index];
+        match control_point.tangent_mode {
+            ETangentMode::User => control_point.arrive_tangent,
+            ETangentMode::Auto => {
+                let length = if index == 0 {
+                    1.0
+                } else {
+                    (control_point.position.x - self.control_points[index - 1].position.x) / 3.0
+                };
+                let slope = self.auto_slope(index);
+                glam::dvec2(-length, -length * slope)
+            }
+        }
+    }
+
+    pub fn leave_tangent(&self, index: usize) -> glam::DVec2 {
+        let control_point = &self.control_points[index];
+        match control_point.tangent_mode {
+            ETangentMode::User => control_point.leave_tangent,
+            ETangentMode::Auto => {
+                let length = match self.control_points.get(index + 1) {
+                    Some(next) => (next.position.x - control_point.position.x) / 3.0,
+                    None => 1.0,
+                };
+                let slope = self.auto_slope(index);
+                glam::dvec2(length, length * slope)
+            }
+        }
+    }
+
+    fn slope_of(tangent: glam::DVec2) -> f64 {
+        if tangent.x.abs() <= f64::EPSILON {
+            0.0
+        } else {
+            tangent.y / tangent.x
+        }
+    }
+
+    /// Returns the value and dy/dx of the segment starting at `index`.
+    fn evaluate_segment(&self, index: usize, x: f64) -> (f64, f64) {
+        let start = &self.control_points[index];
+        let Some(end) = self.control_points.get(index + 1) else {
+            return (start.position.y, 0.0);
+        };
+        let p0 = start.position;
+        let p3 = end.position;
+        let dx = p3.x - p0.x;
+        if dx <= f64::EPSILON {
+            return (p3.y, 0.0);
+        }
+        let t = ((x - p0.x) / dx).clamp(0.0, 1.0);
+        match start.interpolation {
+            ECurveInterpolation::Constant => (p0.y, 0.0),
+            ECurveInterpolation::Linear => {
+                let slope = (p3.y - p0.y) / dx;
+                (p0.y + slope * (x - p0.x), slope)
+            }
+            ECurveInterpolation::Cubic => {
+                let m0 = Self::slope_of(self.leave_tangent(index)) * dx;
+                let m1 = Self::slope_of(self.arrive_tangent(index + 1)) * dx;
+                let t2 = t * t;
+                let t3 = t2 * t;
+                let h00 = 2.0 * t3 - 3.0 * t2 + 1.0;
+                let h10 = t3 - 2.0 * t2 + t;
+                let h01 = -2.0 * t3 + 3.0 * t2;
+                let h11 = t3 - t2;
+                let y = h00 * p0.y + h10 * m0 + h01 * p3.y + h11 * m1;
+                let dh00 = 6.0 * t2 - 6.0 * t;
+                let dh10 = 3.0 * t2 - 4.0 * t + 1.0;
+                let dh01 = -6.0 * t2 + 6.0 * t;
+                let dh11 = 3.0 * t2 - 2.0 * t;
+                let dy = (dh00 * p0.y + dh10 * m0 + dh01 * p3.y + dh11 * m1) / dx;
+                (y, dy)
+            }
+            ECurveInterpolation::Bezier => {
+                let mut p1 = p0 + self.leave_tangent(index);
+                let mut p2 = p3 + self.arrive_tangent(index + 1);
+                // Keep x monotonic so that every x maps to a single point.
+                p1.x = p1.x.clamp(p0.x, p3.x);
+                p2.x = p2.x.clamp(p0.x, p3.x);
+                let s = solve_bezier_parameter(x, p0.x, p1.x, p2.x, p3.x);
+                let y = bezier(s, p0.y, p1.y, p2.y, p3.y);
+                let dx_ds = bezier_derivative(s, p0.x, p1.x, p2.x, p3.x);
+                let dy_ds = bezier_derivative(s, p0.y, p1.y, p2.y, p3.y);
+                let dy = if dx_ds.abs() <= f64::EPSILON {
+                    0.0
+                } else {
+                    dy_ds / dx_ds
+                };
+                (y, dy)
+            }
+        }
+    }
+}
+
+fn bezier(s: f64, p0: f64, p1: f64, p2: f64, p3: f64) -> f64 {
+    let u = 1.0 - s;
+    u * u * u * p0 + 3.0 * u * u * s * p1 + 3.0 * u * s * s * p2 + s * s * s * p3
+}
+
+fn bezier_derivative(s: f64, p0: f64, p1: f64, p2: f64, p3: f64) -> f64 {
+    let u = 1.0 - s;
+    3.0 * u * u * (p1 - p0) + 6.0 * u * s * (p2 - p1) + 3.0 * s * s * (p3 - p2)
+}
+
+fn solve_bezier_parameter(x: f64, p0: f64, p1: f64, p2: f64, p3: f64) -> f64 {
+    let mut low = 0.0_f64;
+    let mut high = 1.0_f64;
+    let mut s = ((x - p0) / (p3 - p0)).clamp(0.0, 1.0);
+    for _ in 0..32 {
+        let error = bezier(s, p0, p1, p2, p3) - x;
+        if error.abs() < 1e-9 {
+            return s;
+        }
+        if error > 0.0 {
+            high = s;
+        } else {
+            low = s;
+        }
+        let derivative = bezier_derivative(s, p0, p1, p2, p3);
+        let newton = s - error / derivative;
+        s = if derivative.abs() > f64::EPSILON && newton > low && newton < high {
+            newton
+        } else {
+            (low + high) * 0.5
+        };
+    }
+    s
+}
+
+#[derive(Serialize, Debug, Clone)]
 pub struct Curve {
     pub url: url::Url,
-    pub control_points: Vec<ControlPoint>,
+    pub value_type: ECurveValueType,
+    pub channels: Vec<CurveChannel>,
+}
+
+/// The layout written by [`Curve`], used by formats which are not self-describing.
+#[derive(Deserialize)]
+struct CurveFields {
+    url: url::Url,
+    value_type: ECurveValueType,
+    channels: Vec<CurveChannel>,
+}
+
+/// Accepts both the current layout and the old single channel layout, which stored `control_points` on the curve itself.
+#[derive(Deserialize)]
+struct CurveData {
+    url: url::Url,
+    #[serde(default)]
+    value_type: ECurveValueType,
+    #[serde(default)]
+    channels: Option<Vec<CurveChannel>>,
+    #[serde(default)]
+    control_points: Option<Vec<ControlPoint>>,
+}
+
+impl<'de> Deserialize<'de> for Curve {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        if !deserializer.is_human_readable() {
+            let fields = CurveFields::deserialize(deserializer)?;
+            return Ok(Curve {
+                url: fields.url,
+                value_type: fields.value_type,
+                channels: fields.channels,
+            });
+        }
+        let data = CurveData::deserialize(deserializer)?;
+        let channels = match (data.channels, data.control_points) {
+            (Some(channels), _) => channels,
+            (None, Some(control_points)) => vec![CurveChannel {
+                control_points,
+                ..Default::default()
+            }],
+            (None, None) => return Err(serde::de::Error::missing_field("channels")),
+        };
+        let mut curve = Curve {
+            url: data.url,
+            value_type: data.value_type,
+            channels,
+        };
+        curve.set_value_type(data.value_type);
+        Ok(curve)
+    }
 }
 
 crate::impl_content!(Curve);
 
 impl Curve {
     pub fn new(url: url::Url) -> Curve {
+        Self::new_with_value_type(url, ECurveValueType::Scalar)
+    }
+
+    pub fn new_with_value_type(url: url::Url, value_type: ECurveValueType) -> Curve {
         Curve {
             url,
-            control_points: vec![],
+            value_type,
+            channels: vec![CurveChannel::new(); value_type.channel_count()],
         }
     }
 
@@ -37,52 +427,194 @@ impl Curve {
         self.url.get_name_in_editor()
     }
 
+    pub fn set_value_type(&mut self, value_type: ECurveValueType) {
+        self.value_type = value_type;
+        self.channels
+            .resize(value_type.channel_count(), CurveChannel::new());
+    }
+
     pub fn sort_by_x(&mut self) {
-        self.control_points
-            .sort_by(|a, b| a.position.x.total_cmp(&b.position.x));
+        for channel in self.channels.iter_mut() {
+            channel.sort_by_x();
+        }
     }
 
+    /// Evaluates the first channel.
     pub fn evaluate(&self, x: f64) -> Option<f64> {
-        let control_points = &self.control_points;
-        if control_points.len() < 2 {
-            return None;
+        self.channels.first()?.evaluate(x)
+    }
+
+    /// Evaluates the first derivative of the first channel.
+    pub fn derivative(&self, x: f64) -> Option<f64> {
+        self.channels.first()?.derivative(x)
+    }
+
+    /// Evaluates every channel, channels without control points evaluate to 0.
+    pub fn evaluate_channels(&self, x: f64) -> Vec<f64> {
+        self.channels
+            .iter()
+            .map(|channel| channel.evaluate(x).unwrap_or(0.0))
+            .collect()
+    }
+
+    pub fn derivative_channels(&self, x: f64) -> Vec<f64> {
+        self.channels
+            .iter()
+            .map(|channel| channel.derivative(x).unwrap_or(0.0))
+            .collect()
+    }
+
+    /// Evaluates up to four channels, missing channels are filled with 0, except the alpha of a color which is filled with 1.
+    pub fn evaluate_vec4(&self, x: f64) -> glam::DVec4 {
+        let values = self.evaluate_channels(x);
+        let mut vector = glam::DVec4::ZERO;
+        if self.value_type == ECurveValueType::Color {
+            vector.w = 1.0;
         }
-        let mut knot_spacing: Vec<f64> = Vec::with_capacity(2 + control_points.len());
-        knot_spacing.push(control_points[0].position.x);
-        for item in control_points {
-            knot_spacing.push(item.position.x);
+        for (index, channel) in self.channels.iter().enumerate().take(4) {
+            if !channel.control_points.is_empty() {
+                vector[index] = values[index];
+            }
         }
-        knot_spacing.push(control_points.last().unwrap().position.x);
+        vector
+    }
+
+    pub fn get_x_range(&self) -> Option<std::ops::RangeInclusive<f64>> {
+        self.channels
+            .iter()
+            .filter_map(|channel| channel.get_x_range())
+            .reduce(|lhs, rhs| lhs.start().min(*rhs.start())..=lhs.end().max(*rhs.end()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        ControlPoint, Curve, CurveChannel, ECurveExtrapolation, ECurveInterpolation,
+        ECurveValueType,
+    };
 
-        let mut knots: Vec<f64> = Vec::with_capacity(2 + control_points.len());
-        knots.push(control_points[0].position.y);
-        for item in control_points {
-            knots.push(item.position.y);
+    fn make_channel(interpolation: ECurveInterpolation) -> CurveChannel {
+        let mut channel = CurveChannel::new();
+        for (index, position) in [
+            glam::dvec2(0.0, 0.0),
+            glam::dvec2(1.0, 1.0),
+            glam::dvec2(2.0, 0.0),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let mut control_point = ControlPoint::new(index, position);
+            control_point.interpolation = interpolation;
+            channel.control_points.push(control_point);
         }
-        knots.push(control_points.last().unwrap().position.y);
-        let v = spline_inverse::<CatmullRom, _>(x, &knot_spacing).unwrap();
-        let y = spline::<CatmullRom, _, _>(v, &knots);
-        Some(y)
+        channel
     }
 
-    pub fn get_x_range(&self) -> Option<std::ops::RangeInclusive<f64>> {
-        let control_points = &self.control_points;
-        if control_points.len() < 2 {
-            return None;
+    fn numeric_derivative(channel: &CurveChannel, x: f64) -> f64 {
+        let h = 1e-5;
+        (channel.evaluate(x + h).unwrap() - channel.evaluate(x - h).unwrap()) / (2.0 * h)
+    }
+
+    #[test]
+    fn test_constant_and_linear() {
+        let channel = make_channel(ECurveInterpolation::Constant);
+        assert_eq!(channel.evaluate(0.5), Some(0.0));
+        assert_eq!(channel.evaluate(1.5), Some(1.0));
+        assert_eq!(channel.derivative(0.5), Some(0.0));
+
+        let channel = make_channel(ECurveInterpolation::Linear);
+        assert_eq!(channel.evaluate(0.5), Some(0.5));
+        assert_eq!(channel.evaluate(1.5), Some(0.5));
+        assert_eq!(channel.derivative(1.5), Some(-1.0));
+    }
+
+    #[test]
+    fn test_cubic_and_bezier_pass_through_keys() {
+        for interpolation in [ECurveInterpolation::Cubic, ECurveInterpolation::Bezier] {
+            let channel = make_channel(interpolation);
+            for control_point in &channel.control_points {
+                let y = channel.evaluate(control_point.position.x).unwrap();
+                assert!((y - control_point.position.y).abs() < 1e-6);
+            }
+            for x in [0.25, 0.5, 0.75, 1.25, 1.6] {
+                let derivative = channel.derivative(x).unwrap();
+                assert!(
+                    (derivative - numeric_derivative(&channel, x)).abs() < 1e-3,
+                    "{interpolation:?} {x}"
+                );
+            }
         }
-        let min_value = control_points
-            .iter()
-            .min_by(|lhs, rhs| lhs.position.x.total_cmp(&rhs.position.x))
-            .map(|x| x.position.x);
-        let max_value = control_points
-            .iter()
-            .max_by(|lhs, rhs| lhs.position.x.total_cmp(&rhs.position.x))
-            .map(|x| x.position.x);
+    }
 
-        if let (Some(min_value), Some(max_value)) = (min_value, max_value) {
-            Some(min_value..=max_value)
-        } else {
-            None
+    #[test]
+    fn test_cubic_and_bezier_auto_tangents_match() {
+        let cubic = make_channel(ECurveInterpolation::Cubic);
+        let bezier = make_channel(ECurveInterpolation::Bezier);
+        for x in [0.1, 0.4, 0.9, 1.3, 1.7] {
+            let lhs = cubic.evaluate(x).unwrap();
+            let rhs = bezier.evaluate(x).unwrap();
+            assert!((lhs - rhs).abs() < 1e-6, "{x} {lhs} {rhs}");
         }
     }
+
+    #[test]
+    fn test_extrapolation() {
+        let mut channel = make_channel(ECurveInterpolation::Linear);
+        assert_eq!(channel.evaluate(-1.0), Some(0.0));
+        assert_eq!(channel.evaluate(3.0), Some(0.0));
+        assert_eq!(channel.derivative(3.0), Some(0.0));
+
+        channel.post_infinity = ECurveExtrapolation::Loop;
+        assert_eq!(channel.evaluate(2.5), Some(0.5));
+        assert_eq!(channel.derivative(2.5), Some(1.0));
+
+        channel.pre_infinity = ECurveExtrapolation::PingPong;
+        assert_eq!(channel.evaluate(-0.5), Some(0.5));
+        assert_eq!(channel.derivative(-0.5), Some(-1.0));
+    }
+
+    #[test]
+    fn test_single_and_empty() {
+        let mut channel = CurveChannel::new();
+        assert_eq!(channel.evaluate(0.0), None);
+        channel
+            .control_points
+            .push(ControlPoint::new(0, glam::dvec2(1.0, 2.0)));
+        assert_eq!(channel.evaluate(-5.0), Some(2.0));
+        assert_eq!(channel.evaluate(5.0), Some(2.0));
+        assert_eq!(channel.derivative(5.0), Some(0.0));
+    }
+
+    #[test]
+    fn test_load_single_channel_format() {
+        let json = r#"{
+            "url": "asset://asset/curve",
+            "control_points": [
+                { "position": [0.0, 0.0], "id": "ControlPoint_0" },
+                { "position": [1.0, 1.0], "id": "ControlPoint_1" },
+                { "position": [2.0, 0.0], "id": "ControlPoint_2" }
+            ]
+        }"#;
+        let curve: Curve = serde_json::from_str(json).unwrap();
+        assert_eq!(curve.value_type, ECurveValueType::Scalar);
+        assert_eq!(curve.channels.len(), 1);
+        assert_eq!(curve.channels[0].control_points.len(), 3);
+        assert_eq!(
+            curve.channels[0].control_points[1].interpolation,
+            ECurveInterpolation::Cubic
+        );
+        assert!((curve.evaluate(1.0).unwrap() - 1.0).abs() < 1e-6);
+
+        let json = serde_json::to_string(&curve).unwrap();
+        let reloaded: Curve = serde_json::from_str(&json).unwrap();
+        assert_eq!(reloaded.channels[0].control_points.len(), 3);
+
+        let data = rs_artifact::bincode_legacy::serialize(&curve, None).unwrap();
+        let decoded: Curve = rs_artifact::bincode_legacy::deserialize(&data, None).unwrap();
+        assert_eq!(decoded.channels[0].control_points.len(), 3);
+
+        let json = r#"{ "url": "asset://asset/curve" }"#;
+        assert!(serde_json::from_str::<Curve>(json).is_err());
+    }
 }
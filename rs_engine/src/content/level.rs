@@ -1,7 +1,9 @@
 use super::content_file_type::EContentFileType;
+use super::level_streaming::{StreamedSubLevel, SubLevel};
 use crate::actor::Actor;
 use crate::camera_component::CameraComponent;
 use crate::components::component::Component;
+use crate::components::joint_component::JointComponent;
 use crate::components::point_light_component::PointLightComponent;
 use crate::components::spot_light_component::SpotLightComponent;
 use crate::directional_light::DirectionalLight;
@@ -14,10 +16,10 @@ use crate::player_viewport::PlayerViewport;
 use crate::scene_node::SceneNode;
 use crate::{build_content_file_url, url_extension::UrlExtension};
 use rapier3d::prelude::*;
-use rs_core_minimal::name_generator::make_unique_name;
+use rs_core_minimal::name_generator::{NameGenerator, make_unique_name};
 use rs_foundation::new::{SingleThreadMut, SingleThreadMutType};
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::ops::Deref;
 use std::rc::Rc;
 
@@ -39,9 +41,113 @@ pub struct LevelPhysics {
     pub contact_force_recv: std::sync::mpsc::Receiver<ContactForceEvent>,
     pub collision_events: VecDeque<CollisionEvent>,
     pub contact_force_events: VecDeque<ContactForceEvent>,
+    pub max_substeps: u32,
+    pub is_enable_interpolation: bool,
+    accumulator: f32,
+    step_count: u64,
+    advanced_steps: u32,
+    previous_poses: HashMap<RigidBodyHandle, (glam::Vec3, glam::Quat)>,
+    body_generation: u64,
 }
 
 impl LevelPhysics {
+    pub fn apply_settings(&mut self, settings: &rs_core_minimal::settings::PhysicsSettings) {
+        self.integration_parameters.dt = settings.fixed_time_step.max(f32::EPSILON);
+        self.max_substeps = settings.max_substeps.max(1);
+        self.is_enable_interpolation = settings.is_enable_interpolation;
+    }
+
+    pub fn fixed_time_step(&self) -> f32 {
+        self.integration_parameters.dt
+    }
+
+    /// The number of fixed steps simulated since the physics was created.
+    pub fn step_count(&self) -> u64 {
+        self.step_count
+    }
+
+    /// Changed whenever a rigid body is inserted or removed through `insert_rigid_body` or `remove_rigid_body`.
+    pub fn body_generation(&self) -> u64 {
+        self.body_generation
+    }
+
+    /// The number of steps taken by the last call to `advance`.
+    pub fn advanced_steps(&self) -> u32 {
+        self.advanced_steps
+    }
+
+    /// Advances the simulation by whole fixed steps and keeps the remaining time for the next frame.
+    /// Returns the number of steps taken.
+    pub fn advance(&mut self, delta_seconds: f32) -> u32 {
+        let fixed_time_step = self.fixed_time_step();
+        self.accumulator += delta_seconds.max(0.0);
+        let mut steps = 0;
+        while self.accumulator >= fixed_time_step && steps < self.max_substeps {
+            self.store_previous_poses();
+            self.step();
+            self.accumulator -= fixed_time_step;
+            steps += 1;
+        }
+        if self.accumulator >= fixed_time_step {
+            log::trace!(
+                "Physics is falling behind, drop {} seconds",
+                self.accumulator - fixed_time_step
+            );
+            self.accumulator = self.accumulator % fixed_time_step;
+        }
+        self.advanced_steps = steps;
+        steps
+    }
+
+    /// Drops the pending time and the previous poses, used while the simulation is paused.
+    pub fn reset_accumulator(&mut self) {
+        self.accumulator = 0.0;
+        self.advanced_steps = 0;
+        self.previous_poses.clear();
+    }
+
+    /// How far the current time is between the last step and the next step, in the range [0, 1).
+    pub fn interpolation_alpha(&self) -> f32 {
+        (self.accumulator / self.fixed_time_step()).clamp(0.0, 1.0)
+    }
+
+    /// The pose of the rigid body blended between the last two steps.
+    pub fn interpolated_pose(
+        &self,
+        rigid_body_handle: RigidBodyHandle,
+    ) -> Option<(glam::Vec3, glam::Quat)> {
+        let rigid_body = self.rigid_body_set.get(rigid_body_handle)?;
+        let translation = rigid_body.translation();
+        let rotation = *rigid_body.rotation();
+        if !self.is_enable_interpolation {
+            return Some((translation, rotation));
+        }
+        let Some((previous_translation, previous_rotation)) =
+            self.previous_poses.get(&rigid_body_handle)
+        else {
+            return Some((translation, rotation));
+        };
+        let alpha = self.interpolation_alpha();
+        Some((
+            previous_translation.lerp(translation, alpha),
+            previous_rotation.slerp(rotation, alpha),
+        ))
+    }
+
+    fn store_previous_poses(&mut self) {
+        self.previous_poses.clear();
+        if !self.is_enable_interpolation {
+            return;
+        }
+        for (handle, rigid_body) in self.rigid_body_set.iter() {
+            if rigid_body.is_fixed() {
+                continue;
+            }
+            self.previous_poses
+                .insert(handle, (rigid_body.translation(), *rigid_body.rotation()));
+        }
+    }
+
     pub fn step(&mut self) {
         let span = tracy_client::span!();
         span.emit_text(&format!(
@@ -49,6 +155,7 @@ impl LevelPhysics {
             self.collider_set.len(),
             self.rigid_body_set.len()
         ));
+        self.step_count += 1;
         self.physics_pipeline.step(
             self.gravity,
             &self.integration_parameters,
@@ -160,10 +267,16 @@ impl LevelPhysics {
         );
     }
 
+    pub fn insert_rigid_body(&mut self, rigid_body: impl Into<RigidBody>) -> RigidBodyHandle {
+        self.body_generation += 1;
+        self.rigid_body_set.insert(rigid_body)
+    }
+
     pub fn remove_rigid_body(&mut self, rigid_body_handle: RigidBodyHandle) {
         if rigid_body_handle == RigidBodyHandle::invalid() {
             return;
         }
+        self.body_generation += 1;
         self.rigid_body_set.remove(
             rigid_body_handle,
             &mut self.island_manager,
@@ -178,6 +291,10 @@ impl LevelPhysics {
 pub struct Runtime {
     pub physics: LevelPhysics,
     pub is_simulate: bool,
+    last_tick_time: Option<f32>,
+    streamed_sub_levels: HashMap<url::Url, StreamedSubLevel>,
+    /// The body generation of the physics when the joints were linked.
+    linked_body_generation: Option<u64>,
 }
 
 #[cfg(feature = "network")]
@@ -303,11 +420,13 @@ impl NetworkFields {
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Deserialize)]
 pub struct Level {
     pub url: url::Url,
     pub actors: Vec<SingleThreadMutType<Actor>>,
     pub directional_lights: Vec<SingleThreadMutType<DirectionalLight>>,
+    #[serde(default)]
+    pub sub_levels: Vec<SubLevel>,
     #[cfg(feature = "network")]
     #[serde(default)]
     pub network_fields: NetworkFields,
@@ -317,6 +436,55 @@ pub struct Level {
 
 crate::impl_content!(Level);
 
+/// The actors and the lights of the loaded sub-levels are left out, they are saved in their own levels.
+impl Serialize for Level {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        #[derive(Serialize)]
+        #[serde(rename = "Level")]
+        struct LevelFields<'a> {
+            url: &'a url::Url,
+            actors: Vec<&'a SingleThreadMutType<Actor>>,
+            directional_lights: Vec<&'a SingleThreadMutType<DirectionalLight>>,
+            sub_levels: &'a Vec<SubLevel>,
+            #[cfg(feature = "network")]
+            network_fields: &'a NetworkFields,
+        }
+        let streamed_sub_levels = self
+            .runtime
+            .as_ref()
+            .map(|x| x.streamed_sub_levels.values().collect::<Vec<_>>())
+            .unwrap_or_default();
+        let fields = LevelFields {
+            url: &self.url,
+            actors: self
+                .actors
+                .iter()
+                .filter(|x| {
+                    !streamed_sub_levels
+                        .iter()
+                        .any(|y| y.actors.iter().any(|y| Rc::ptr_eq(x, y)))
+                })
+                .collect(),
+            directional_lights: self
+                .directional_lights
+                .iter()
+                .filter(|x| {
+                    !streamed_sub_levels
+                        .iter()
+                        .any(|y| y.directional_lights.iter().any(|y| Rc::ptr_eq(x, y)))
+                })
+                .collect(),
+            sub_levels: &self.sub_levels,
+            #[cfg(feature = "network")]
+            network_fields: &self.network_fields,
+        };
+        fields.serialize(serializer)
+    }
+}
+
 #[cfg(feature = "network")]
 impl crate::network::NetworkReplicated for Level {
     fn get_network_id(&self) -> &uuid::Uuid {
@@ -433,9 +601,13 @@ impl Level {
             actors: vec![],
             url: build_content_file_url(name).unwrap(),
             directional_lights: vec![],
+            sub_levels: vec![],
             runtime: Some(Runtime {
                 physics: Self::default_physics(),
                 is_simulate: false,
+                last_tick_time: None,
+                streamed_sub_levels: HashMap::new(),
+                linked_body_generation: None,
             }),
             #[cfg(feature = "network")]
             network_fields: NetworkFields::new(),
@@ -446,7 +618,7 @@ impl Level {
         self.url.get_name_in_editor()
     }
 
-    fn default_physics() -> LevelPhysics {
+    pub(crate) fn default_physics() -> LevelPhysics {
         let rigid_body_set: RigidBodySet = RigidBodySet::new();
         let collider_set: ColliderSet = ColliderSet::new();
 
@@ -464,7 +636,7 @@ impl Level {
         let (contact_force_send, contact_force_recv) = std::sync::mpsc::channel();
         let event_handler = ChannelEventCollector::new(collision_send, contact_force_send);
 
-        let physics = LevelPhysics {
+        let mut physics = LevelPhysics {
             rigid_body_set,
             collider_set,
             gravity,
@@ -482,7 +654,15 @@ impl Level {
             contact_force_recv,
             collision_events: VecDeque::new(),
             contact_force_events: VecDeque::new(),
+            max_substeps: 8,
+            is_enable_interpolation: true,
+            accumulator: 0.0,
+            step_count: 0,
+            advanced_steps: 0,
+            previous_poses: HashMap::new(),
+            body_generation: 0,
         };
+        physics.apply_settings(&rs_core_minimal::settings::PhysicsSettings::default());
         physics
     }
 
@@ -540,16 +720,36 @@ impl Level {
         //     collision_events: VecDeque::new(),
         //     contact_force_events: VecDeque::new(),
         // };
+        // The objects of the loaded sub-levels are not part of this level.
+        if let Some(runtime) = self.runtime.take() {
+            for streamed_sub_level in runtime.streamed_sub_levels.into_values() {
+                self.actors
+                    .retain(|x| !streamed_sub_level.actors.iter().any(|y| Rc::ptr_eq(x, y)));
+                self.directional_lights.retain(|x| {
+                    !streamed_sub_level
+                        .directional_lights
+                        .iter()
+                        .any(|y| Rc::ptr_eq(x, y))
+                });
+            }
+        }
+        let mut physics = Self::default_physics();
+        physics.apply_settings(&engine.get_settings().physics_settings);
         self.runtime = Some(Runtime {
-            physics: Self::default_physics(),
+            physics,
             is_simulate: false,
+            last_tick_time: None,
+            streamed_sub_levels: HashMap::new(),
+            linked_body_generation: None,
         });
+        self.resolve_prefab_instances(files);
         let actors = self.actors.clone();
         self.init_actors(engine, actors, files, player_viewport);
         let actors = self.actors.clone();
         for actor in actors {
             self.init_actor_physics(actor.clone(), engine, files);
         }
+        self.link_joints();
         let name = self.get_name();
         log::trace!("initialize level: {}", name);
     }
@@ -613,9 +813,16 @@ impl Level {
         let Some(runtime) = self.runtime.as_mut() else {
             return;
         };
-        if runtime.is_simulate {
-            runtime.physics.step();
+        let delta_seconds = runtime
+            .last_tick_time
+            .map(|last_tick_time| time - last_tick_time)
+            .unwrap_or(0.0);
+        runtime.last_tick_time = Some(time);
+        let is_simulate = runtime.is_simulate;
+        if is_simulate {
+            runtime.physics.advance(delta_seconds);
         } else {
+            runtime.physics.reset_accumulator();
             runtime.physics.query_update();
         }
         let level_physics = &mut runtime.physics;
@@ -624,6 +831,9 @@ impl Level {
             actor.tick(time, engine, level_physics);
             // actor.tick_physics(rigid_body_set, collider_set);
         }
+        if is_simulate {
+            self.link_joints_if_bodies_changed();
+        }
 
         let light_components = self.collect_point_light_components();
 
@@ -729,6 +939,109 @@ impl Level {
         }
 
         self.actors.append(&mut actors);
+        self.link_joints();
+    }
+
+    fn find_actor_rigid_body(actor: &Actor) -> Option<RigidBodyHandle> {
+        let mut rigid_body_handle: Option<RigidBodyHandle> = None;
+        Actor::walk_node_mut(actor.scene_node.clone(), &mut |node| {
+            if rigid_body_handle.is_some() {
+                return;
+            }
+            let node = node.borrow();
+            rigid_body_handle = node
+                .component()
+                .rigid_body_handle()
+                .filter(|x| *x != RigidBodyHandle::invalid());
+        });
+        rigid_body_handle
+    }
+
+    /// Links the joints again only if a rigid body was inserted or removed since the last `link_joints`.
+    pub fn link_joints_if_bodies_changed(&mut self) {
+        let Some(runtime) = self.runtime.as_ref() else {
+            return;
+        };
+        if runtime.linked_body_generation != Some(runtime.physics.body_generation()) {
+            self.link_joints();
+        }
+    }
+
+    /// Connects the joint components to the bodies of their actors, joints whose bodies were recreated are linked again.
+    /// The joints of a loaded sub-level look up the actors by their names in the sub-level first.
+    pub fn link_joints(&mut self) {
+        self.link_joints_by_name();
+        if let Some(runtime) = self.runtime.as_mut() {
+            runtime.linked_body_generation = Some(runtime.physics.body_generation());
+        }
+    }
+
+    fn link_joints_by_name(&mut self) {
+        let mut body_map: HashMap<String, RigidBodyHandle> = HashMap::new();
+        let mut owners: Vec<(SingleThreadMutType<Box<dyn Component>>, String)> = vec![];
+        for actor in self.actors.clone() {
+            let actor = actor.borrow();
+            if let Some(rigid_body_handle) = Self::find_actor_rigid_body(&actor) {
+                body_map.insert(actor.name.clone(), rigid_body_handle);
+            }
+            Actor::walk_node_mut(actor.scene_node.clone(), &mut |node| {
+                let node = node.borrow();
+                if node.is_typed_component::<JointComponent>() {
+                    owners.push((node.underlying_component(), actor.name.clone()));
+                }
+            });
+        }
+        if owners.is_empty() {
+            return;
+        }
+        let mut sub_level_names: HashMap<String, &HashMap<String, String>> = HashMap::new();
+        if let Some(runtime) = self.runtime.as_ref() {
+            for streamed_sub_level in runtime.streamed_sub_levels.values() {
+                for actor in streamed_sub_level.actors.iter() {
+                    sub_level_names.insert(
+                        actor.borrow().name.clone(),
+                        &streamed_sub_level.original_names,
+                    );
+                }
+            }
+        }
+        let links = owners
+            .into_iter()
+            .map(|(component, owner)| {
+                let body1 = body_map.get(&owner).copied();
+                let body2 = component
+                    .borrow()
+                    .downcast_ref::<JointComponent>()
+                    .and_then(|x| x.settings.connected_actor.clone())
+                    .and_then(|connected_actor| {
+                        let name = sub_level_names
+                            .get(&owner)
+                            .and_then(|names| names.get(&connected_actor))
+                            .unwrap_or(&connected_actor);
+                        body_map.get(name).copied()
+                    });
+                (component, body1, body2)
+            })
+            .collect::<Vec<_>>();
+        let Some(level_physics) = self.get_physics_mut() else {
+            return;
+        };
+        for (component, body1, body2) in links {
+            let mut component = component.borrow_mut();
+            let Some(joint_component) = component.downcast_mut::<JointComponent>() else {
+                continue;
+            };
+            match (body1, body2) {
+                (Some(body1), Some(body2)) if body1 != body2 => {
+                    if !joint_component.is_linked(level_physics, body1, body2) {
+                        joint_component.link(level_physics, body1, body2);
+                    }
+                }
+                _ => {
+                    joint_component.unlink(level_physics);
+                }
+            }
+        }
     }
 
     pub fn ray_cast_find_node(
@@ -917,6 +1230,226 @@ impl Level {
         self.add_new_actors(engine, vec![duplicated_actor], files, player_viewport);
     }
 
+    /// Rebuilds the prefab instances from the latest prefabs, the overrides are kept.
+    pub fn resolve_prefab_instances(&mut self, files: &HashMap<url::Url, EContentFileType>) {
+        for actor in self.actors.iter() {
+            let actor = actor.borrow();
+            if let Err(err) = super::prefab::resolve_instances(&actor.scene_node, files) {
+                log::warn!("{}: {}", actor.name, err);
+            }
+        }
+    }
+
+    /// Records the changes of the prefab instances, should be called before the level is saved.
+    pub fn update_prefab_overrides(&self, files: &HashMap<url::Url, EContentFileType>) {
+        for actor in self.actors.iter() {
+            let actor = actor.borrow();
+            if let Err(err) = super::prefab::update_overrides(&actor.scene_node, files) {
+                log::warn!("{}: {}", actor.name, err);
+            }
+        }
+    }
+
+    pub fn instantiate_prefab(
+        &mut self,
+        url: &url::Url,
+        engine: &mut crate::engine::Engine,
+        files: &HashMap<url::Url, EContentFileType>,
+        player_viewport: &mut PlayerViewport,
+    ) -> crate::error::Result<SingleThreadMutType<Actor>> {
+        let scene_node = super::prefab::instantiate(url, files)?;
+        let name = self.make_actor_name(&url.get_name_in_editor());
+        let actor = SingleThreadMut::new(Actor::new_with_node(name, scene_node));
+        self.add_new_actors(engine, vec![actor.clone()], files, player_viewport);
+        Ok(actor)
+    }
+
+    pub fn is_sub_level_loaded(&self, url: &url::Url) -> bool {
+        self.runtime
+            .as_ref()
+            .map(|x| x.streamed_sub_levels.contains_key(url))
+            .unwrap_or(false)
+    }
+
+    /// Loads the sub-levels that are marked to be loaded with the level.
+    pub fn load_initial_sub_levels(
+        &mut self,
+        engine: &mut crate::engine::Engine,
+        files: &HashMap<url::Url, EContentFileType>,
+        player_viewport: &mut PlayerViewport,
+    ) {
+        let urls: Vec<url::Url> = self
+            .sub_levels
+            .iter()
+            .filter(|x| x.is_initially_loaded)
+            .map(|x| x.url.clone())
+            .collect();
+        for url in urls {
+            if let Err(err) = self.load_sub_level(&url, engine, files, player_viewport) {
+                log::warn!("{}", err);
+            }
+        }
+    }
+
+    /// Loads the sub-levels whose streaming volumes the viewer enters and unloads the ones the viewer leaves.
+    pub fn update_streaming(
+        &mut self,
+        viewer_location: glam::Vec3,
+        engine: &mut crate::engine::Engine,
+        files: &HashMap<url::Url, EContentFileType>,
+        player_viewport: &mut PlayerViewport,
+    ) {
+        let mut load_urls: Vec<url::Url> = vec![];
+        let mut unload_urls: Vec<url::Url> = vec![];
+        for sub_level in self.sub_levels.iter() {
+            let Some(streaming_volume) = &sub_level.streaming_volume else {
+                continue;
+            };
+            let is_loaded = self.is_sub_level_loaded(&sub_level.url);
+            let should_load = streaming_volume.should_load(viewer_location, is_loaded);
+            if should_load && !is_loaded {
+                load_urls.push(sub_level.url.clone());
+            } else if !should_load && is_loaded {
+                unload_urls.push(sub_level.url.clone());
+            }
+        }
+        for url in unload_urls {
+            self.unload_sub_level(&url);
+        }
+        for url in load_urls {
+            if let Err(err) = self.load_sub_level(&url, engine, files, player_viewport) {
+                log::warn!("{}", err);
+            }
+        }
+    }
+
+    /// Adds a copy of the actors and the lights of the sub-level to this level,
+    /// the sub-levels of the sub-level are not loaded.
+    pub fn load_sub_level(
+        &mut self,
+        url: &url::Url,
+        engine: &mut crate::engine::Engine,
+        files: &HashMap<url::Url, EContentFileType>,
+        player_viewport: &mut PlayerViewport,
+    ) -> crate::error::Result<()> {
+        self.load_sub_level_with(url, files, |level, sub_level| {
+            for light in sub_level.directional_lights.iter() {
+                let mut light = light.borrow_mut();
+                light.initialize(engine, player_viewport);
+            }
+            level.init_actors(engine, sub_level.actors.clone(), files, player_viewport);
+            for actor in sub_level.actors.clone() {
+                level.init_actor_physics(actor, engine, files);
+            }
+        })
+    }
+
+    fn load_sub_level_with(
+        &mut self,
+        url: &url::Url,
+        files: &HashMap<url::Url, EContentFileType>,
+        initialize: impl FnOnce(&mut Level, &Level),
+    ) -> crate::error::Result<()> {
+        if self.runtime.is_none() {
+            return Err(crate::error::Error::Other(Some(format!(
+                "The level {} is not initialized",
+                self.get_name()
+            ))));
+        }
+        if self.is_sub_level_loaded(url) {
+            return Ok(());
+        }
+        if *url == self.url {
+            return Err(crate::error::Error::Other(Some(format!(
+                "The level {} can not be a sub-level of itself",
+                self.get_name()
+            ))));
+        }
+        let mut sub_level = {
+            let file = files
+                .get(url)
+                .ok_or(crate::error::Error::NullReference(Some(format!(
+                    "Can not find level {}",
+                    url
+                ))))?;
+            let content = file.borrow();
+            let level = content.as_ref().as_any().downcast_ref::<Level>().ok_or(
+                crate::error::Error::DowncastFail {
+                    expected: "Level",
+                    url: url.clone(),
+                },
+            )?;
+            let ser_level = serde_json::to_string(level)?;
+            serde_json::from_str::<Level>(&ser_level)?
+        };
+        sub_level.resolve_prefab_instances(files);
+        // The copy must not share the ids of the nodes with the sub-level.
+        for actor in sub_level.actors.iter() {
+            actor.borrow().scene_node.borrow_mut().regenerate_ids();
+        }
+
+        let names: HashSet<String> = self
+            .actors
+            .iter()
+            .map(|x| x.borrow().name.clone())
+            .collect();
+        let mut name_generator = NameGenerator::new(
+            names
+                .iter()
+                .cloned()
+                .chain(sub_level.actors.iter().map(|x| x.borrow().name.clone()))
+                .collect(),
+        );
+        let mut original_names: HashMap<String, String> = HashMap::new();
+        for actor in sub_level.actors.iter() {
+            let mut actor = actor.borrow_mut();
+            let original_name = actor.name.clone();
+            if names.contains(&actor.name) {
+                actor.name = name_generator.next(&actor.name);
+            }
+            original_names.insert(original_name, actor.name.clone());
+        }
+
+        initialize(self, &sub_level);
+        self.directional_lights
+            .extend(sub_level.directional_lights.iter().cloned());
+        self.actors.extend(sub_level.actors.iter().cloned());
+
+        if let Some(runtime) = self.runtime.as_mut() {
+            runtime.streamed_sub_levels.insert(
+                url.clone(),
+                StreamedSubLevel {
+                    actors: sub_level.actors,
+                    directional_lights: sub_level.directional_lights,
+                    original_names,
+                },
+            );
+        }
+        self.link_joints();
+        log::trace!("Load sub-level: {}", url.get_name_in_editor());
+        Ok(())
+    }
+
+    /// Removes the actors and the lights of the sub-level, returns false if it is not loaded.
+    pub fn unload_sub_level(&mut self, url: &url::Url) -> bool {
+        let Some(streamed_sub_level) = self
+            .runtime
+            .as_mut()
+            .and_then(|x| x.streamed_sub_levels.remove(url))
+        else {
+            return false;
+        };
+        for actor in streamed_sub_level.actors {
+            self.delete_actor_client(actor);
+        }
+        for light in streamed_sub_level.directional_lights {
+            self.delete_light(light);
+        }
+        self.link_joints();
+        log::trace!("Unload sub-level: {}", url.get_name_in_editor());
+        true
+    }
+
     pub fn collect_point_light_components(&self) -> Vec<SingleThreadMutType<Box<dyn Component>>> {
         self.collect_typed_componenets::<PointLightComponent>()
     }
@@ -1030,6 +1563,7 @@ impl Level {
             self.init_actor_physics(actor.clone(), engine, files);
         }
         self.actors.append(&mut actors);
+        self.link_joints();
     }
 
     pub fn net_delete_actor(&mut self, actor: SingleThreadMutType<Actor>) {
@@ -1041,3 +1575,256 @@ impl Level {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Level, LevelPhysics};
+    use crate::actor::Actor;
+    use crate::components::character_controller_component::CharacterControllerComponent;
+    use crate::components::joint_component::JointComponent;
+    use rapier3d::prelude::*;
+    use rs_foundation::new::{SingleThreadMut, SingleThreadMutType};
+    use std::collections::HashMap;
+
+    fn make_physics() -> LevelPhysics {
+        let mut physics = Level::default_physics();
+        physics.apply_settings(&rs_core_minimal::settings::PhysicsSettings {
+            fixed_time_step: 1.0 / 64.0,
+            max_substeps: 8,
+            is_enable_interpolation: true,
+        });
+        let ground = physics
+            .rigid_body_set
+            .insert(RigidBodyBuilder::fixed().build());
+        physics.collider_set.insert_with_parent(
+            ColliderBuilder::cuboid(50.0, 0.5, 50.0).build(),
+            ground,
+            &mut physics.rigid_body_set,
+        );
+        for i in 0..10 {
+            let rigid_body = RigidBodyBuilder::dynamic()
+                .translation(glam::vec3(i as f32 * 0.3, 2.0 + i as f32 * 1.1, 0.0))
+                .build();
+            let handle = physics.rigid_body_set.insert(rigid_body);
+            physics.collider_set.insert_with_parent(
+                ColliderBuilder::ball(0.5).restitution(0.3).build(),
+                handle,
+                &mut physics.rigid_body_set,
+            );
+        }
+        physics
+    }
+
+    fn snapshot(physics: &LevelPhysics) -> String {
+        serde_json::to_string(&physics.rigid_body_set).unwrap()
+    }
+
+    #[test]
+    fn test_determinism() {
+        let mut lhs = make_physics();
+        let mut rhs = make_physics();
+        // One second at 64 fps against one second with uneven frames.
+        for _ in 0..64 {
+            lhs.advance(1.0 / 64.0);
+        }
+        for _ in 0..16 {
+            rhs.advance(1.0 / 128.0);
+            rhs.advance(3.0 / 128.0);
+            rhs.advance(2.0 / 128.0);
+            rhs.advance(2.0 / 128.0);
+        }
+        assert_eq!(lhs.step_count(), 64);
+        assert_eq!(rhs.step_count(), 64);
+        assert_eq!(snapshot(&lhs), snapshot(&rhs));
+    }
+
+    #[test]
+    fn test_replay() {
+        let run = || {
+            let mut physics = make_physics();
+            let handles: Vec<RigidBodyHandle> = physics
+                .rigid_body_set
+                .iter()
+                .filter(|(_, x)| x.is_dynamic())
+                .map(|(handle, _)| handle)
+                .collect();
+            for frame in 0..120 {
+                if frame % 10 == 0 {
+                    let rigid_body = &mut physics.rigid_body_set[handles[frame % handles.len()]];
+                    rigid_body.apply_impulse(glam::vec3(1.0, 2.0, 0.5), true);
+                }
+                physics.advance(1.0 / 60.0);
+            }
+            (physics.step_count(), snapshot(&physics))
+        };
+        assert_eq!(run(), run());
+    }
+
+    #[test]
+    fn test_max_substeps() {
+        let mut physics = make_physics();
+        assert_eq!(physics.advance(1.0), 8);
+        assert_eq!(physics.advanced_steps(), 8);
+        assert!(physics.interpolation_alpha() < 1.0);
+        assert_eq!(physics.advance(0.0), 0);
+    }
+
+    #[test]
+    fn test_interpolation() {
+        let mut physics = make_physics();
+        let handle = physics
+            .rigid_body_set
+            .iter()
+            .find(|(_, x)| x.is_dynamic())
+            .map(|(handle, _)| handle)
+            .unwrap();
+        physics.advance(1.0 / 64.0 * 10.0);
+        let previous = physics.rigid_body_set[handle].translation();
+        physics.advance(1.0 / 64.0);
+        let current = physics.rigid_body_set[handle].translation();
+        physics.advance(1.0 / 128.0);
+        assert_eq!(physics.interpolation_alpha(), 0.5);
+        let (translation, _) = physics.interpolated_pose(handle).unwrap();
+        assert!(translation.abs_diff_eq(previous.lerp(current, 0.5), 1e-5));
+    }
+
+    fn make_actor(name: &str, connected_actor: Option<&str>) -> SingleThreadMutType<Actor> {
+        let scene_node =
+            CharacterControllerComponent::new_scene_node(name.to_string(), glam::Mat4::IDENTITY);
+        if let Some(connected_actor) = connected_actor {
+            let joint = JointComponent::new_scene_node("Joint".to_string(), glam::Mat4::IDENTITY);
+            joint
+                .borrow_mut()
+                .typed_component_mut::<JointComponent>()
+                .unwrap()
+                .settings
+                .connected_actor = Some(connected_actor.to_string());
+            scene_node.borrow_mut().add_child(joint);
+        }
+        SingleThreadMut::new(Actor::new_with_node(name.to_string(), scene_node))
+    }
+
+    fn initialize_physics(level: &mut Level, actors: &[SingleThreadMutType<Actor>]) {
+        let level_physics = level.get_physics_mut().unwrap();
+        for actor in actors {
+            Actor::walk_node_mut(actor.borrow().scene_node.clone(), &mut |node| {
+                let mut node = node.borrow_mut();
+                if let Some(mut component) =
+                    node.typed_component_mut::<CharacterControllerComponent>()
+                {
+                    component.initialize_runtime();
+                    component.recreate_physics(level_physics);
+                }
+                if let Some(mut component) = node.typed_component_mut::<JointComponent>() {
+                    component.initialize_runtime();
+                }
+            });
+        }
+    }
+
+    fn find_body(level: &Level, name: &str) -> RigidBodyHandle {
+        let actor = level
+            .actors
+            .iter()
+            .find(|x| x.borrow().name == name)
+            .unwrap();
+        Level::find_actor_rigid_body(&actor.borrow()).unwrap()
+    }
+
+    #[test]
+    fn test_load_and_unload_sub_level() {
+        let mut level = Level::new("Main");
+        level.actors = vec![make_actor("Box", None)];
+        let actors = level.actors.clone();
+        initialize_physics(&mut level, &actors);
+        let box_body = find_body(&level, "Box");
+
+        let mut sub_level = Level::new("Sub");
+        sub_level.actors = vec![make_actor("Box", None), make_actor("Hinge", Some("Box"))];
+        let url = sub_level.url.clone();
+        let files = HashMap::from([(
+            url.clone(),
+            SingleThreadMut::new(Box::new(sub_level) as Box<dyn rs_content::Content>),
+        )]);
+
+        level
+            .load_sub_level_with(&url, &files, |level, sub_level| {
+                initialize_physics(level, &sub_level.actors);
+            })
+            .unwrap();
+        assert!(level.is_sub_level_loaded(&url));
+        let names = level
+            .actors
+            .iter()
+            .map(|x| x.borrow().name.clone())
+            .collect::<Vec<String>>();
+        assert_eq!(names.len(), 3);
+        assert_eq!(names[0], "Box");
+        assert_ne!(names[1], "Box");
+        assert_eq!(names[2], "Hinge");
+        let physics = level.get_physics_mut().unwrap();
+        assert_eq!(physics.rigid_body_set.len(), 3);
+        assert_eq!(physics.impulse_joint_set.len(), 1);
+        // The joint of the sub-level connects to the box of the sub-level, not the box of the level.
+        let (_, joint) = physics.impulse_joint_set.iter().next().unwrap();
+        let (body1, body2) = (joint.body1, joint.body2);
+        assert_eq!(body1, find_body(&level, "Hinge"));
+        assert_eq!(body2, find_body(&level, &names[1]));
+        assert_ne!(body2, box_body);
+        // The sub-level is saved in its own file.
+        let ser_level = serde_json::to_value(&level).unwrap();
+        assert_eq!(ser_level["actors"].as_array().unwrap().len(), 1);
+
+        assert!(level.unload_sub_level(&url));
+        assert!(!level.is_sub_level_loaded(&url));
+        assert_eq!(level.actors.len(), 1);
+        assert_eq!(level.actors[0].borrow().name, "Box");
+        let physics = level.get_physics_mut().unwrap();
+        assert_eq!(physics.rigid_body_set.len(), 1);
+        assert!(physics.rigid_body_set.contains(box_body));
+        assert_eq!(physics.impulse_joint_set.len(), 0);
+        assert!(!level.unload_sub_level(&url));
+    }
+
+    #[test]
+    fn test_link_joints_if_bodies_changed() {
+        let mut level = Level::new("Main");
+        level.actors = vec![make_actor("Box", None), make_actor("Hinge", Some("Box"))];
+        let actors = level.actors.clone();
+        initialize_physics(&mut level, &actors);
+        level.link_joints();
+        let body_generation = level.get_physics_mut().unwrap().body_generation();
+        assert_eq!(
+            level.runtime.as_ref().unwrap().linked_body_generation,
+            Some(body_generation)
+        );
+        let box_body = find_body(&level, "Box");
+
+        // Nothing changed, the joints are not linked again.
+        level.link_joints_if_bodies_changed();
+        assert_eq!(
+            level.get_physics_mut().unwrap().body_generation(),
+            body_generation
+        );
+
+        // Recreating the body of the box removes the joint, which is linked to the new body.
+        let box_actor = level.find_actor("Box").unwrap();
+        let level_physics = level.get_physics_mut().unwrap();
+        Actor::walk_node_mut(box_actor.borrow().scene_node.clone(), &mut |node| {
+            let mut node = node.borrow_mut();
+            if let Some(mut component) = node.typed_component_mut::<CharacterControllerComponent>()
+            {
+                component.recreate_physics(level_physics);
+            }
+        });
+        assert_ne!(level_physics.body_generation(), body_generation);
+        assert_eq!(level_physics.impulse_joint_set.len(), 0);
+        level.link_joints_if_bodies_changed();
+        let new_box_body = find_body(&level, "Box");
+        assert_ne!(new_box_body, box_body);
+        let physics = level.get_physics_mut().unwrap();
+        assert_eq!(physics.impulse_joint_set.len(), 1);
+        let (_, joint) = physics.impulse_joint_set.iter().next().unwrap();
+        assert_eq!(joint.body2, new_box_body);
+    }
+}
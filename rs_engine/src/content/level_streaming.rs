@@ -0,0 +1,99 @@
+use crate::actor::Actor;
+use crate::directional_light::DirectionalLight;
+use rs_foundation::new::SingleThreadMutType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A box in the persistent level, the sub-level is loaded while the viewer is inside it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StreamingVolume {
+    pub center: glam::Vec3,
+    pub half_extents: glam::Vec3,
+    /// How far the viewer can leave the box before the sub-level is unloaded,
+    /// keeps the sub-level from being loaded and unloaded repeatedly at the border.
+    pub unload_distance: f32,
+}
+
+impl StreamingVolume {
+    pub fn new(center: glam::Vec3, half_extents: glam::Vec3) -> StreamingVolume {
+        StreamingVolume {
+            center,
+            half_extents,
+            unload_distance: 10.0,
+        }
+    }
+
+    /// The distance from the location to the box, zero if the location is inside.
+    pub fn distance(&self, location: glam::Vec3) -> f32 {
+        let outside = (location - self.center).abs() - self.half_extents.abs();
+        outside.max(glam::Vec3::ZERO).length()
+    }
+
+    pub fn should_load(&self, location: glam::Vec3, is_loaded: bool) -> bool {
+        let distance = self.distance(location);
+        if is_loaded {
+            distance <= self.unload_distance.max(0.0)
+        } else {
+            distance <= 0.0
+        }
+    }
+}
+
+impl Default for StreamingVolume {
+    fn default() -> Self {
+        Self::new(glam::Vec3::ZERO, glam::Vec3::splat(50.0))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SubLevel {
+    pub url: url::Url,
+    /// Streams the sub-level by the location of the viewer, none if it is only loaded by calls.
+    #[serde(default)]
+    pub streaming_volume: Option<StreamingVolume>,
+    #[serde(default)]
+    pub is_initially_loaded: bool,
+}
+
+impl SubLevel {
+    pub fn new(url: url::Url) -> SubLevel {
+        SubLevel {
+            url,
+            streaming_volume: None,
+            is_initially_loaded: false,
+        }
+    }
+}
+
+/// The objects a loaded sub-level added to the persistent level.
+pub(crate) struct StreamedSubLevel {
+    pub(crate) actors: Vec<SingleThreadMutType<Actor>>,
+    pub(crate) directional_lights: Vec<SingleThreadMutType<DirectionalLight>>,
+    /// The names of the actors in the sub-level to their names in the persistent level,
+    /// the names in the sub-level keep referring to its own actors after they are renamed.
+    pub(crate) original_names: HashMap<String, String>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::StreamingVolume;
+
+    #[test]
+    fn test_distance() {
+        let volume = StreamingVolume::new(glam::vec3(10.0, 0.0, 0.0), glam::Vec3::splat(5.0));
+        assert_eq!(volume.distance(glam::vec3(12.0, 3.0, -4.0)), 0.0);
+        assert_eq!(volume.distance(glam::vec3(18.0, 0.0, 0.0)), 3.0);
+        assert_eq!(volume.distance(glam::vec3(18.0, 9.0, 0.0)), 5.0);
+    }
+
+    #[test]
+    fn test_should_load() {
+        let mut volume = StreamingVolume::new(glam::Vec3::ZERO, glam::Vec3::splat(5.0));
+        volume.unload_distance = 2.0;
+        let border = glam::vec3(6.0, 0.0, 0.0);
+        assert!(!volume.should_load(border, false));
+        assert!(volume.should_load(border, true));
+        assert!(volume.should_load(glam::Vec3::ZERO, false));
+        assert!(!volume.should_load(glam::vec3(8.0, 0.0, 0.0), true));
+    }
+}
@@ -0,0 +1,22 @@
+use crate::url_extension::UrlExtension;
+use serde::{Deserialize, Serialize};
+
+/// A reusable graph that can be called from the graphs of materials,
+/// the graph is stored in the project and resolved inline by the editor.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MaterialFunction {
+    pub url: url::Url,
+    pub asset_url: url::Url,
+}
+
+crate::impl_content!(MaterialFunction);
+
+impl MaterialFunction {
+    pub fn new(url: url::Url, asset_url: url::Url) -> MaterialFunction {
+        MaterialFunction { url, asset_url }
+    }
+
+    pub fn get_name(&self) -> String {
+        self.url.get_name_in_editor()
+    }
+}
@@ -3,10 +3,13 @@ pub mod content_file_type;
 pub mod curve;
 pub mod ibl;
 pub mod level;
+pub mod level_streaming;
 pub mod material;
+pub mod material_function;
 pub mod material_paramenters_collection;
 pub mod media_source;
 pub mod particle_system;
+pub mod prefab;
 pub mod render_target_2d;
 pub mod skeleton;
 pub mod skeleton_animation;
@@ -34,6 +37,10 @@ macro_rules! impl_content {
                 crate::url_extension::UrlExtension::set_name_in_editor(&mut self.url, new_name);
             }
 
+            fn set_url(&mut self, url: url::Url) {
+                self.url = url;
+            }
+
             fn get_type_text(&self) -> &'static str {
                 ::core::stringify!($type_name)
             }
@@ -0,0 +1,716 @@
+use super::content_file_type::EContentFileType;
+use crate::{components::component::Component, scene_node::SceneNode};
+use crate::{error::Result, url_extension::UrlExtension};
+use rs_foundation::new::{SingleThreadMut, SingleThreadMutType};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// The fields of the components that are changed in an instance, keyed by the id of the node in the prefab,
+/// the key of the root is empty. The values are stored as JSON text, so the level can still be encoded with bincode.
+pub type PrefabOverrides = BTreeMap<String, BTreeMap<String, String>>;
+
+/// A reusable tree of scene nodes that can be instanced into any level.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Prefab {
+    pub url: url::Url,
+    pub scene_node: SingleThreadMutType<SceneNode>,
+}
+
+crate::impl_content!(Prefab);
+
+impl Prefab {
+    pub fn new(url: url::Url, scene_node: SingleThreadMutType<SceneNode>) -> Prefab {
+        Prefab { url, scene_node }
+    }
+
+    pub fn get_name(&self) -> String {
+        self.url.get_name_in_editor()
+    }
+}
+
+/// Marks a scene node as the root of an instance of a prefab.
+/// The tree under the node is rebuilt from the prefab when the level is loaded,
+/// so only the overridden fields of the components and the added and removed nodes are kept.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PrefabInstance {
+    pub url: url::Url,
+    #[serde(default)]
+    pub overrides: PrefabOverrides,
+    /// The trees added to the instance as JSON text, keyed by the id of the parent in the prefab.
+    #[serde(default)]
+    pub added_nodes: BTreeMap<String, Vec<String>>,
+    /// The ids of the nodes of the prefab that are removed from the instance.
+    #[serde(default)]
+    pub removed_nodes: BTreeSet<String>,
+}
+
+impl PrefabInstance {
+    pub fn new(url: url::Url) -> PrefabInstance {
+        PrefabInstance {
+            url,
+            overrides: PrefabOverrides::new(),
+            added_nodes: BTreeMap::new(),
+            removed_nodes: BTreeSet::new(),
+        }
+    }
+}
+
+/// Creates a new instance of the prefab, the components are not initialized.
+pub fn instantiate(
+    url: &url::Url,
+    files: &HashMap<url::Url, EContentFileType>,
+) -> Result<SingleThreadMutType<SceneNode>> {
+    let mut scene_node = build_prefab_tree(url, files, &mut vec![])?;
+    scene_node.set_id(uuid::Uuid::new_v4());
+    assign_instance_ids(&mut scene_node);
+    scene_node.set_prefab_instance(Some(PrefabInstance::new(url.clone())));
+    Ok(SingleThreadMut::new(scene_node))
+}
+
+/// Rebuilds the instances under the node from the latest prefabs and applies the overrides.
+pub fn resolve_instances(
+    scene_node: &SingleThreadMutType<SceneNode>,
+    files: &HashMap<url::Url, EContentFileType>,
+) -> Result<()> {
+    resolve_instances_internal(&mut scene_node.borrow_mut(), files, &mut vec![])
+}
+
+/// Compares the instances under the node with their prefabs and stores the differences as overrides.
+pub fn update_overrides(
+    scene_node: &SingleThreadMutType<SceneNode>,
+    files: &HashMap<url::Url, EContentFileType>,
+) -> Result<()> {
+    let mut scene_node = scene_node.borrow_mut();
+    let Some(mut instance) = scene_node.prefab_instance().cloned() else {
+        for child in scene_node.childs() {
+            update_overrides(child, files)?;
+        }
+        return Ok(());
+    };
+    // The changes of the nested instances are the overrides of the outer instance.
+    let prefab_tree = build_prefab_tree(&instance.url, files, &mut vec![])?;
+    diff_tree(&prefab_tree, &scene_node, &mut instance)?;
+    scene_node.set_prefab_instance(Some(instance));
+    Ok(())
+}
+
+/// Copies the tree as the tree of a prefab, the nodes built from a prefab get back their ids in the prefab.
+pub fn to_prefab_tree(scene_node: &SceneNode) -> Result<SceneNode> {
+    let prefab_tree = copy_tree(scene_node)?;
+    visit_prefab_ids(
+        &prefab_tree,
+        prefab_tree.prefab_instance().is_some(),
+        &mut |child, id| {
+            child.set_id(id);
+            child.set_prefab_node_id(None);
+        },
+    );
+    Ok(prefab_tree)
+}
+
+/// Turns the tree into an instance of the prefab made from it by [`to_prefab_tree`], the components are kept.
+pub fn make_instance(scene_node: &mut SceneNode, url: url::Url) {
+    visit_prefab_ids(
+        scene_node,
+        scene_node.prefab_instance().is_some(),
+        &mut |child, id| child.set_prefab_node_id(Some(id)),
+    );
+    scene_node.set_prefab_instance(Some(PrefabInstance::new(url)));
+    assign_instance_ids(scene_node);
+}
+
+/// Visits the descendants with the ids they have in a prefab made from the tree.
+/// Inside an instance the nodes built from the prefab use their ids in the prefab,
+/// the ids of the other nodes are derived from the ids of the nested instances they belong to, so they are kept.
+fn visit_prefab_ids(
+    scene_node: &SceneNode,
+    is_in_instance: bool,
+    visit: &mut impl FnMut(&mut SceneNode, uuid::Uuid),
+) {
+    for child in scene_node.childs() {
+        let mut child = child.borrow_mut();
+        let (id, is_in_instance) = match child.prefab_node_id().copied() {
+            Some(prefab_node_id) if is_in_instance => (prefab_node_id, true),
+            _ => (*child.id(), false),
+        };
+        visit(&mut child, id);
+        visit_prefab_ids(&child, is_in_instance, visit);
+    }
+}
+
+/// Derives the ids of the nodes in the instance from the id of the root and their ids in the prefab,
+/// so every instance has its own ids and the ids stay the same when the instance is rebuilt.
+pub(crate) fn assign_instance_ids(scene_node: &mut SceneNode) {
+    let root_id = *scene_node.id();
+    assign_instance_ids_internal(&root_id, scene_node);
+}
+
+fn assign_instance_ids_internal(root_id: &uuid::Uuid, scene_node: &SceneNode) {
+    for child in scene_node.childs() {
+        let mut child = child.borrow_mut();
+        match child.prefab_node_id().copied() {
+            Some(prefab_node_id) => {
+                child.set_id(uuid::Uuid::new_v5(root_id, prefab_node_id.as_bytes()));
+                assign_instance_ids_internal(root_id, &child);
+            }
+            // An added node, which may be the root of another instance.
+            None => child.regenerate_ids(),
+        }
+    }
+}
+
+fn resolve_instances_internal(
+    scene_node: &mut SceneNode,
+    files: &HashMap<url::Url, EContentFileType>,
+    stack: &mut Vec<url::Url>,
+) -> Result<()> {
+    let Some(instance) = scene_node.prefab_instance().cloned() else {
+        for child in scene_node.childs() {
+            resolve_instances_internal(&mut child.borrow_mut(), files, stack)?;
+        }
+        return Ok(());
+    };
+    let mut prefab_tree = build_prefab_tree(&instance.url, files, stack)?;
+    apply_overrides(&mut prefab_tree, &instance)?;
+    prefab_tree.set_id(*scene_node.id());
+    assign_instance_ids(&mut prefab_tree);
+    add_nodes(&mut prefab_tree, &instance, files, stack)?;
+    scene_node.replace_with(prefab_tree);
+    scene_node.set_prefab_instance(Some(instance));
+    Ok(())
+}
+
+/// Copies the tree of the prefab, the nested instances are resolved.
+/// Every node except the root remembers its id in the prefab.
+fn build_prefab_tree(
+    url: &url::Url,
+    files: &HashMap<url::Url, EContentFileType>,
+    stack: &mut Vec<url::Url>,
+) -> Result<SceneNode> {
+    if stack.contains(url) {
+        return Err(crate::error::Error::Other(Some(format!(
+            "The prefab {} contains itself",
+            url.get_name_in_editor()
+        ))));
+    }
+    let file = files
+        .get(url)
+        .ok_or(crate::error::Error::NullReference(Some(format!(
+            "Can not find prefab {}",
+            url
+        ))))?;
+    let mut scene_node = {
+        let content = file.borrow();
+        let prefab = content.as_ref().as_any().downcast_ref::<Prefab>().ok_or(
+            crate::error::Error::DowncastFail {
+                expected: "Prefab",
+                url: url.clone(),
+            },
+        )?;
+        copy_tree(&prefab.scene_node.borrow())?
+    };
+    // The root of a prefab can be an instance of another prefab.
+    stack.push(url.clone());
+    let result = resolve_instances_internal(&mut scene_node, files, stack);
+    stack.pop();
+    result?;
+    mark_prefab_ids(&scene_node);
+    Ok(scene_node)
+}
+
+fn mark_prefab_ids(scene_node: &SceneNode) {
+    for child in scene_node.childs() {
+        let mut child = child.borrow_mut();
+        let id = *child.id();
+        child.set_prefab_node_id(Some(id));
+        mark_prefab_ids(&child);
+    }
+}
+
+fn copy_tree(scene_node: &SceneNode) -> Result<SceneNode> {
+    let value = serde_json::to_value(scene_node)?;
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Applies the overrides of the components and removes the nodes, the tree still has the ids of the prefab.
+fn apply_overrides(scene_node: &mut SceneNode, instance: &PrefabInstance) -> Result<()> {
+    let components = collect_components(scene_node);
+    for (key, fields) in &instance.overrides {
+        let component = match components.get(key) {
+            Some(component) => component.clone(),
+            None => match find_component_by_path(scene_node, key) {
+                Some(component) => component,
+                None => {
+                    log::warn!("The node {} is not found in the prefab", key);
+                    continue;
+                }
+            },
+        };
+        let (type_name, mut component_fields) = component_fields(component.borrow().as_ref())?;
+        for (field, value) in fields {
+            component_fields.insert(field.clone(), serde_json::from_str(value)?);
+        }
+        let mut value = Map::new();
+        value.insert(type_name, Value::Object(component_fields));
+        let new_component: Box<dyn Component> = serde_json::from_value(Value::Object(value))?;
+        *component.borrow_mut() = new_component;
+    }
+    if !instance.removed_nodes.is_empty() {
+        remove_nodes(scene_node, &instance.removed_nodes);
+    }
+    Ok(())
+}
+
+fn remove_nodes(scene_node: &mut SceneNode, removed_nodes: &BTreeSet<String>) {
+    let childs: Vec<SingleThreadMutType<SceneNode>> = scene_node
+        .childs()
+        .iter()
+        .filter(|x| !removed_nodes.contains(&x.borrow().id().to_string()))
+        .cloned()
+        .collect();
+    for child in childs.iter() {
+        remove_nodes(&mut child.borrow_mut(), removed_nodes);
+    }
+    scene_node.set_childs(childs);
+}
+
+/// Adds the trees added to the instance, the tree has the ids of the instance.
+fn add_nodes(
+    scene_node: &mut SceneNode,
+    instance: &PrefabInstance,
+    files: &HashMap<url::Url, EContentFileType>,
+    stack: &mut Vec<url::Url>,
+) -> Result<()> {
+    for (parent_key, nodes) in &instance.added_nodes {
+        let Some(parent) = find_instance_node(scene_node, parent_key) else {
+            log::warn!("The parent {} is not found in the prefab", parent_key);
+            continue;
+        };
+        for node in nodes {
+            let mut node: SceneNode = serde_json::from_str(node)?;
+            resolve_instances_internal(&mut node, files, stack)?;
+            match &parent {
+                Some(parent) => parent.borrow_mut().add_child(SingleThreadMut::new(node)),
+                None => scene_node.add_child(SingleThreadMut::new(node)),
+            };
+        }
+    }
+    Ok(())
+}
+
+/// Finds the node by its id in the prefab, `Some(None)` is the root.
+fn find_instance_node(
+    scene_node: &SceneNode,
+    key: &str,
+) -> Option<Option<SingleThreadMutType<SceneNode>>> {
+    if key.is_empty() {
+        return Some(None);
+    }
+    fn find(scene_node: &SceneNode, key: &str) -> Option<SingleThreadMutType<SceneNode>> {
+        for child in scene_node.childs() {
+            let is_matched = child
+                .borrow()
+                .prefab_node_id()
+                .map(|x| x.to_string() == key)
+                .unwrap_or(false);
+            if is_matched {
+                return Some(child.clone());
+            }
+            if let Some(found) = find(&child.borrow(), key) {
+                return Some(found);
+            }
+        }
+        None
+    }
+    find(scene_node, key).map(Some)
+}
+
+fn diff_tree(
+    prefab_tree: &SceneNode,
+    scene_node: &SceneNode,
+    instance: &mut PrefabInstance,
+) -> Result<()> {
+    let prefab_components = collect_components(prefab_tree);
+    let instance_components = collect_instance_components(scene_node);
+    let mut overrides = PrefabOverrides::new();
+    for (key, component) in instance_components.iter() {
+        let Some(prefab_component) = prefab_components.get(key) else {
+            continue;
+        };
+        let (prefab_type_name, prefab_fields) =
+            component_fields(prefab_component.borrow().as_ref())?;
+        let (type_name, fields) = component_fields(component.borrow().as_ref())?;
+        if type_name != prefab_type_name {
+            log::warn!("The type of the node {} is changed", key);
+            continue;
+        }
+        let mut changed_fields = BTreeMap::new();
+        for (field, value) in fields {
+            if prefab_fields.get(&field) != Some(&value) {
+                changed_fields.insert(field, serde_json::to_string(&value)?);
+            }
+        }
+        if !changed_fields.is_empty() {
+            overrides.insert(key.clone(), changed_fields);
+        }
+    }
+    instance.overrides = overrides;
+    instance.removed_nodes = prefab_components
+        .keys()
+        .filter(|x| !instance_components.contains_key(*x))
+        .cloned()
+        .collect();
+    instance.added_nodes = BTreeMap::new();
+    collect_added_nodes("", scene_node, &mut instance.added_nodes)?;
+    Ok(())
+}
+
+/// The nodes without an id in the prefab are added to the instance, only the top of each added tree is collected.
+fn collect_added_nodes(
+    parent_key: &str,
+    scene_node: &SceneNode,
+    added_nodes: &mut BTreeMap<String, Vec<String>>,
+) -> Result<()> {
+    for child in scene_node.childs() {
+        let child = child.borrow();
+        match child.prefab_node_id() {
+            Some(prefab_node_id) => {
+                collect_added_nodes(&prefab_node_id.to_string(), &child, added_nodes)?;
+            }
+            None => {
+                added_nodes
+                    .entry(parent_key.to_string())
+                    .or_default()
+                    .push(serde_json::to_string(&*child)?);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The serialized component is tagged with the name of the type.
+fn component_fields(component: &dyn Component) -> Result<(String, Map<String, Value>)> {
+    let value = serde_json::to_value(component)?;
+    let Value::Object(value) = value else {
+        return Err(crate::error::Error::Other(Some(
+            "The component is not serialized as an object".to_string(),
+        )));
+    };
+    let Some((type_name, Value::Object(fields))) = value.into_iter().next() else {
+        return Err(crate::error::Error::Other(Some(
+            "The component is not serialized as an object".to_string(),
+        )));
+    };
+    Ok((type_name, fields))
+}
+
+/// The components of the tree of a prefab keyed by the ids of the nodes, the key of the root is empty.
+fn collect_components(
+    scene_node: &SceneNode,
+) -> HashMap<String, SingleThreadMutType<Box<dyn Component>>> {
+    let mut components = HashMap::new();
+    components.insert(String::new(), scene_node.underlying_component());
+    walk_childs(scene_node, &mut |child| {
+        components.insert(child.id().to_string(), child.underlying_component());
+        true
+    });
+    components
+}
+
+/// The components of an instance keyed by the ids of the nodes in the prefab, the added nodes are skipped.
+fn collect_instance_components(
+    scene_node: &SceneNode,
+) -> HashMap<String, SingleThreadMutType<Box<dyn Component>>> {
+    let mut components = HashMap::new();
+    components.insert(String::new(), scene_node.underlying_component());
+    walk_childs(scene_node, &mut |child| {
+        let Some(prefab_node_id) = child.prefab_node_id() else {
+            return false;
+        };
+        components.insert(prefab_node_id.to_string(), child.underlying_component());
+        true
+    });
+    components
+}
+
+/// Visits the descendants, the childs of a node are skipped if the visitor returns false.
+fn walk_childs(scene_node: &SceneNode, visit: &mut impl FnMut(&SceneNode) -> bool) {
+    for child in scene_node.childs() {
+        let child = child.borrow();
+        if visit(&child) {
+            walk_childs(&child, visit);
+        }
+    }
+}
+
+/// Overrides saved before the nodes had ids are keyed by the names of the components joined by `/`.
+fn find_component_by_path(
+    scene_node: &SceneNode,
+    path: &str,
+) -> Option<SingleThreadMutType<Box<dyn Component>>> {
+    let mut names = path.split('/');
+    let name = names.next()?;
+    let child = scene_node
+        .childs()
+        .iter()
+        .find(|x| x.borrow().component().get_name() == name)?
+        .clone();
+    let child = child.borrow();
+    let rest: Vec<&str> = names.collect();
+    if rest.is_empty() {
+        Some(child.underlying_component())
+    } else {
+        find_component_by_path(&child, &rest.join("/"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        Prefab, PrefabInstance, instantiate, make_instance, resolve_instances, to_prefab_tree,
+        update_overrides,
+    };
+    use crate::{
+        build_content_file_url, content::content_file_type::EContentFileType, scene_node::SceneNode,
+    };
+    use rs_foundation::new::{SingleThreadMut, SingleThreadMutType};
+    use std::collections::HashMap;
+
+    fn add_prefab(
+        files: &mut HashMap<url::Url, EContentFileType>,
+        name: &str,
+        scene_node: SingleThreadMutType<SceneNode>,
+    ) -> url::Url {
+        let url = build_content_file_url(name).unwrap();
+        let prefab: Box<dyn rs_content::Content> = Box::new(Prefab::new(url.clone(), scene_node));
+        files.insert(url.clone(), SingleThreadMut::new(prefab));
+        url
+    }
+
+    fn prefab_root(
+        files: &HashMap<url::Url, EContentFileType>,
+        url: &url::Url,
+    ) -> SingleThreadMutType<SceneNode> {
+        let file = files.get(url).unwrap().borrow();
+        let prefab = file.as_ref().as_any().downcast_ref::<Prefab>().unwrap();
+        prefab.scene_node.clone()
+    }
+
+    fn translation(scene_node: &SingleThreadMutType<SceneNode>) -> glam::Vec3 {
+        let transformation = scene_node.borrow().component().get_transformation();
+        transformation.to_scale_rotation_translation().2
+    }
+
+    fn set_translation(scene_node: &SingleThreadMutType<SceneNode>, translation: glam::Vec3) {
+        scene_node
+            .borrow_mut()
+            .component_mut()
+            .set_transformation(glam::Mat4::from_translation(translation));
+    }
+
+    #[test]
+    fn test_overrides() {
+        let mut files = HashMap::new();
+        let root = SceneNode::new_sp("Scene".to_string());
+        root.borrow_mut()
+            .add_child(SceneNode::new_sp("Child".to_string()));
+        let url = add_prefab(&mut files, "Box", root);
+
+        let instance = instantiate(&url, &files).unwrap();
+        let child = instance.borrow().childs()[0].clone();
+        set_translation(&child, glam::vec3(2.0, 0.0, 0.0));
+        update_overrides(&instance, &files).unwrap();
+        let prefab_root = prefab_root(&files, &url);
+        let child_key = prefab_root.borrow().childs()[0].borrow().id().to_string();
+        {
+            let instance = instance.borrow();
+            let overrides = &instance.prefab_instance().unwrap().overrides;
+            assert_eq!(overrides.len(), 1);
+            assert_eq!(overrides[&child_key].len(), 1);
+            assert!(overrides[&child_key].contains_key("transformation"));
+        }
+
+        prefab_root
+            .borrow_mut()
+            .add_child(SceneNode::new_sp("Extra".to_string()));
+        set_translation(&prefab_root.borrow().childs()[0], glam::vec3(0.0, 5.0, 0.0));
+        resolve_instances(&instance, &files).unwrap();
+
+        assert_eq!(instance.borrow().childs().len(), 2);
+        let child = instance.borrow().childs()[0].clone();
+        assert_eq!(translation(&child), glam::vec3(2.0, 0.0, 0.0));
+        assert_eq!(
+            instance.borrow().prefab_instance().map(|x| x.url.clone()),
+            Some(url)
+        );
+    }
+
+    #[test]
+    fn test_nested_prefab() {
+        let mut files = HashMap::new();
+        let inner_root = SceneNode::new_sp("Inner".to_string());
+        let inner_url = add_prefab(&mut files, "Inner", inner_root.clone());
+        let outer_root = SceneNode::new_sp("Scene".to_string());
+        outer_root
+            .borrow_mut()
+            .add_child(instantiate(&inner_url, &files).unwrap());
+        let outer_url = add_prefab(&mut files, "Outer", outer_root);
+
+        let instance = instantiate(&outer_url, &files).unwrap();
+        inner_root
+            .borrow_mut()
+            .add_child(SceneNode::new_sp("Leaf".to_string()));
+        resolve_instances(&instance, &files).unwrap();
+
+        let nested = instance.borrow().childs()[0].clone();
+        assert!(nested.borrow().prefab_instance().is_some());
+        assert_eq!(nested.borrow().childs().len(), 1);
+    }
+
+    #[test]
+    fn test_recursive_prefab() {
+        let mut files = HashMap::new();
+        let url = build_content_file_url("Recursive").unwrap();
+        let root = SceneNode::new_sp("Scene".to_string());
+        let child = SceneNode::new_sp("Child".to_string());
+        child
+            .borrow_mut()
+            .set_prefab_instance(Some(PrefabInstance::new(url.clone())));
+        root.borrow_mut().add_child(child);
+        add_prefab(&mut files, "Recursive", root);
+
+        assert!(instantiate(&url, &files).is_err());
+    }
+
+    fn collect_ids(scene_node: &SingleThreadMutType<SceneNode>, ids: &mut Vec<uuid::Uuid>) {
+        let scene_node = scene_node.borrow();
+        ids.push(*scene_node.id());
+        for child in scene_node.childs() {
+            collect_ids(child, ids);
+        }
+    }
+
+    #[test]
+    fn test_unique_stable_ids() {
+        let mut files = HashMap::new();
+        let inner_url = add_prefab(&mut files, "Inner", {
+            let root = SceneNode::new_sp("Inner".to_string());
+            root.borrow_mut()
+                .add_child(SceneNode::new_sp("Leaf".to_string()));
+            root
+        });
+        let root = SceneNode::new_sp("Scene".to_string());
+        root.borrow_mut()
+            .add_child(SceneNode::new_sp("Child".to_string()));
+        root.borrow_mut()
+            .add_child(instantiate(&inner_url, &files).unwrap());
+        let url = add_prefab(&mut files, "Box", root.clone());
+
+        let first = instantiate(&url, &files).unwrap();
+        let second = instantiate(&url, &files).unwrap();
+        let mut prefab_ids = vec![];
+        collect_ids(&root, &mut prefab_ids);
+        let mut first_ids = vec![];
+        collect_ids(&first, &mut first_ids);
+        let mut second_ids = vec![];
+        collect_ids(&second, &mut second_ids);
+        assert_eq!(first_ids.len(), 4);
+        let mut all_ids = first_ids
+            .iter()
+            .chain(second_ids.iter())
+            .chain(prefab_ids.iter())
+            .collect::<Vec<_>>();
+        all_ids.sort();
+        all_ids.dedup();
+        assert_eq!(all_ids.len(), first_ids.len() * 2 + prefab_ids.len());
+
+        resolve_instances(&first, &files).unwrap();
+        let mut resolved_ids = vec![];
+        collect_ids(&first, &mut resolved_ids);
+        assert_eq!(resolved_ids, first_ids);
+
+        let json = serde_json::to_string(&*first.borrow()).unwrap();
+        let loaded = SingleThreadMut::new(serde_json::from_str::<SceneNode>(&json).unwrap());
+        resolve_instances(&loaded, &files).unwrap();
+        let mut loaded_ids = vec![];
+        collect_ids(&loaded, &mut loaded_ids);
+        assert_eq!(loaded_ids, first_ids);
+
+        let mut copied_ids = vec![];
+        let copied = SingleThreadMut::new(serde_json::from_str::<SceneNode>(&json).unwrap());
+        copied.borrow_mut().regenerate_ids();
+        collect_ids(&copied, &mut copied_ids);
+        assert!(copied_ids.iter().all(|x| !first_ids.contains(x)));
+        resolve_instances(&copied, &files).unwrap();
+        let mut resolved_ids = vec![];
+        collect_ids(&copied, &mut resolved_ids);
+        assert_eq!(resolved_ids, copied_ids);
+    }
+
+    #[test]
+    fn test_same_names_and_structure_changes() {
+        let mut files = HashMap::new();
+        let root = SceneNode::new_sp("Scene".to_string());
+        root.borrow_mut()
+            .add_child(SceneNode::new_sp("Child".to_string()));
+        root.borrow_mut()
+            .add_child(SceneNode::new_sp("Child".to_string()));
+        root.borrow_mut()
+            .add_child(SceneNode::new_sp("Removed".to_string()));
+        let url = add_prefab(&mut files, "Box", root);
+
+        let instance = instantiate(&url, &files).unwrap();
+        {
+            let instance = instance.borrow();
+            set_translation(&instance.childs()[0], glam::vec3(1.0, 0.0, 0.0));
+            set_translation(&instance.childs()[1], glam::vec3(2.0, 0.0, 0.0));
+        }
+        let mut childs = instance.borrow().childs().to_vec();
+        childs.pop();
+        instance.borrow_mut().set_childs(childs);
+        let added = SceneNode::new_sp("Added".to_string());
+        added
+            .borrow_mut()
+            .add_child(SceneNode::new_sp("AddedLeaf".to_string()));
+        instance.borrow().childs()[1]
+            .borrow_mut()
+            .add_child(added.clone());
+        update_overrides(&instance, &files).unwrap();
+
+        let json = serde_json::to_string(&*instance.borrow()).unwrap();
+        let loaded = SingleThreadMut::new(serde_json::from_str::<SceneNode>(&json).unwrap());
+        loaded.borrow_mut().set_childs(vec![]);
+        resolve_instances(&loaded, &files).unwrap();
+
+        let loaded = loaded.borrow();
+        assert_eq!(loaded.childs().len(), 2);
+        assert_eq!(translation(&loaded.childs()[0]), glam::vec3(1.0, 0.0, 0.0));
+        assert_eq!(translation(&loaded.childs()[1]), glam::vec3(2.0, 0.0, 0.0));
+        let second = loaded.childs()[1].borrow();
+        assert_eq!(second.childs().len(), 1);
+        let loaded_added = second.childs()[0].borrow();
+        assert_eq!(loaded_added.component().get_name(), "Added");
+        assert_eq!(loaded_added.id(), added.borrow().id());
+        assert_eq!(loaded_added.childs().len(), 1);
+    }
+
+    #[test]
+    fn test_make_instance() {
+        let mut files = HashMap::new();
+        let root = SceneNode::new_sp("Scene".to_string());
+        root.borrow_mut()
+            .add_child(SceneNode::new_sp("Child".to_string()));
+        let url = build_content_file_url("Made").unwrap();
+        let prefab_tree = to_prefab_tree(&root.borrow()).unwrap();
+        add_prefab(&mut files, "Made", SingleThreadMut::new(prefab_tree));
+        make_instance(&mut root.borrow_mut(), url);
+
+        update_overrides(&root, &files).unwrap();
+        let root = root.borrow();
+        let instance = root.prefab_instance().unwrap();
+        assert!(instance.overrides.is_empty());
+        assert!(instance.added_nodes.is_empty());
+        assert!(instance.removed_nodes.is_empty());
+    }
+}
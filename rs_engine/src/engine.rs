@@ -14,12 +14,14 @@ use crate::player_viewport::PlayerViewport;
 use crate::render_thread_mode::ERenderThreadMode;
 use crate::{logger::Logger, resource_manager::ResourceManager};
 use rs_artifact::artifact::ArtifactReader;
+use rs_artifact::derive_data::compressed_texture::CompressedTexture;
 use rs_artifact::resource_info::ResourceInfo;
 use rs_artifact_types::asset::ASSET_KIND;
 use rs_artifact_types::asset::Asset;
 use rs_audio::audio_device::AudioDevice;
 use rs_content::CONTENT_ASSET_KIND;
 use rs_content::TypedContent;
+use rs_core_minimal::file_type::TextureFileType;
 use rs_core_minimal::settings::Settings;
 use rs_foundation::new::{
     MultipleThreadMut, MultipleThreadMutType, SingleThreadMut, SingleThreadMutType,
@@ -34,6 +36,8 @@ use rs_render::command::{
 use rs_render::global_uniform::{self};
 use rs_render::renderer::Renderer;
 use rs_render::sdf2d_generator;
+use rs_render::shader_library::ShaderNagaModule;
+use rs_render::texture_loader::TextureLoader;
 use rs_render::view_mode::EViewModeType;
 use rs_render::virtual_texture_source::TVirtualTextureSource;
 use rs_render_types::MaterialOptions;
@@ -84,6 +88,7 @@ pub struct Engine {
     virtual_pass_handle: Option<VirtualPassHandle>,
     _audio_device: Option<AudioDevice>,
     ctx: egui::Context,
+    device_features: wgpu::Features,
 }
 
 impl Engine {
@@ -95,7 +100,7 @@ impl Engine {
         mut logger: Logger,
         mut artifact_reader: Option<ArtifactReader>,
         mut shaders: HashMap<String, String>,
-        shader_naga_modules: HashMap<String, wgpu::naga::Module>,
+        shader_naga_modules: HashMap<String, ShaderNagaModule>,
         ctx: egui::Context,
     ) -> Result<Engine>
     where
@@ -138,6 +143,11 @@ impl Engine {
         for shader_source_code in resource_manager.get_all_shader_source_codes() {
             shaders.insert(shader_source_code.name, shader_source_code.code);
         }
+        let compiled_shaders = resource_manager
+            .get_all_compiled_shaders()
+            .into_iter()
+            .map(|x| *x)
+            .collect();
 
         let renderer = Renderer::from_window(
             window_id,
@@ -146,10 +156,12 @@ impl Engine {
             surface_height,
             shaders,
             shader_naga_modules,
+            compiled_shaders,
             settings.render_setting.clone(),
         )
         .map_err(|err| crate::error::Error::RendererError(err))?;
 
+        let device_features = renderer.get_device().features();
         let mut render_thread_mode = ERenderThreadMode::from(renderer, true);
         let mut virtual_pass_handle: Option<VirtualPassHandle> = None;
         if settings.render_setting.virtual_texture_setting.is_enable {
@@ -216,6 +228,7 @@ impl Engine {
             // shadow_depth_texture_handle: None,
             _audio_device: Some(audio_device),
             ctx,
+            device_features,
         };
 
         ResourceManager::default().create_builtin_resources(&mut engine);
@@ -296,6 +309,16 @@ impl Engine {
         let resource_map = self.resource_manager.get_resource_map()?;
 
         let resource_manager = self.resource_manager.clone();
+        // The textures cooked to the texture format of the platform, keyed by the urls of their images.
+        let mut compressed_textures: HashMap<url::Url, CompressedTexture> = HashMap::new();
+        for (url, resource_info) in resource_map.iter() {
+            if resource_info.resource_type == CompressedTexture::associated_resource_type() {
+                let compressed_texture = resource_manager.get_asset(url, None)?;
+                let compressed_texture = compressed_texture.downcast::<CompressedTexture>()?;
+                compressed_textures
+                    .insert(compressed_texture.source_url.clone(), *compressed_texture);
+            }
+        }
         for (url, resource_info) in resource_map {
             let kind: &str = resource_info.resource_type.kind();
             if kind == CONTENT_ASSET_KIND {
@@ -315,6 +338,18 @@ impl Engine {
                                 "No image reference".to_string(),
                             )))?;
                     log::trace!("Image reference: {}", image_reference.to_string());
+                    if texture.is_compressed
+                        && let Some(compressed_texture) = compressed_textures.get(&image_reference)
+                    {
+                        match self.create_texture_from_compressed_texture(&url, compressed_texture)
+                        {
+                            Ok(_) => {
+                                log::trace!("Load compressed texture: {}", url.to_string());
+                                continue;
+                            }
+                            Err(err) => log::warn!("{}, {}", url.to_string(), err),
+                        }
+                    }
                     let image = resource_manager.get_asset(&image_reference, None)?;
                     let image = image.downcast::<rs_artifact::image::Image>()?;
 
@@ -1226,6 +1261,56 @@ impl Engine {
         handle
     }
 
+    /// Creates the texture from the data cooked for the platform, only DDS is supported.
+    /// Fails if the device doesn't support the texture format.
+    pub fn create_texture_from_compressed_texture(
+        &mut self,
+        url: &url::Url,
+        compressed_texture: &CompressedTexture,
+    ) -> Result<crate::handle::TextureHandle> {
+        if compressed_texture.ty != TextureFileType::Dds {
+            return Err(crate::error::Error::Other(Some(format!(
+                "Not support {:?}",
+                compressed_texture.ty
+            ))));
+        }
+        let texture_data = TextureLoader::load_dds(&compressed_texture.data)?;
+        let required_features = texture_data.format.required_features();
+        if !self.device_features.contains(required_features) {
+            return Err(crate::error::Error::Other(Some(format!(
+                "{:?} requires {:?}",
+                texture_data.format, required_features
+            ))));
+        }
+        let (block_width, _) = texture_data.format.block_dimensions();
+        let block_copy_size =
+            texture_data
+                .format
+                .block_copy_size(None)
+                .ok_or(crate::error::Error::Other(Some(format!(
+                    "Not support {:?}",
+                    texture_data.format
+                ))))?;
+        let bytes_per_row = texture_data.width.div_ceil(block_width) * block_copy_size;
+        Ok(self.create_texture_from_data(
+            url,
+            TextureDescriptorCreateInfo::d2(
+                Some(url.to_string()),
+                texture_data.width,
+                texture_data.height,
+                Some(texture_data.format),
+            ),
+            InitTextureData {
+                data: texture_data.data,
+                data_layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+        ))
+    }
+
     pub fn create_texture(
         &mut self,
         url: &url::Url,
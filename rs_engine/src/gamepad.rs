@@ -0,0 +1,332 @@
+use rs_core_minimal::input_binding::{EGamepadAxis, EGamepadButton};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GamepadId(pub usize);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GamepadInfo {
+    pub id: GamepadId,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EGamepadEvent {
+    Connected(GamepadInfo),
+    Disconnected(GamepadId),
+    Button {
+        id: GamepadId,
+        button: EGamepadButton,
+        is_pressed: bool,
+    },
+    /// Sticks are in the range [-1, 1], triggers are in the range [0, 1].
+    Axis {
+        id: GamepadId,
+        axis: EGamepadAxis,
+        value: f32,
+    },
+}
+
+impl EGamepadEvent {
+    pub fn id(&self) -> GamepadId {
+        match self {
+            EGamepadEvent::Connected(info) => info.id,
+            EGamepadEvent::Disconnected(id) => *id,
+            EGamepadEvent::Button { id, .. } => *id,
+            EGamepadEvent::Axis { id, .. } => *id,
+        }
+    }
+}
+
+pub trait GamepadBackend {
+    /// Returns all events received since the last call.
+    fn poll_events(&mut self) -> Vec<EGamepadEvent>;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GamepadState {
+    pub info: Option<GamepadInfo>,
+    pub pressed_buttons: HashSet<EGamepadButton>,
+    pub axis_values: HashMap<EGamepadAxis, f32>,
+}
+
+impl GamepadState {
+    pub fn is_button_pressed(&self, button: EGamepadButton) -> bool {
+        self.pressed_buttons.contains(&button)
+    }
+
+    pub fn axis_value(&self, axis: EGamepadAxis) -> f32 {
+        self.axis_values.get(&axis).copied().unwrap_or(0.0)
+    }
+}
+
+pub struct GamepadManager {
+    backend: Box<dyn GamepadBackend>,
+    states: HashMap<GamepadId, GamepadState>,
+}
+
+impl GamepadManager {
+    pub fn new(backend: Box<dyn GamepadBackend>) -> GamepadManager {
+        GamepadManager {
+            backend,
+            states: HashMap::new(),
+        }
+    }
+
+    /// Uses the platform backend when the `gamepad` feature is enabled, otherwise a virtual backend without devices.
+    pub fn platform_default() -> GamepadManager {
+        #[cfg(feature = "gamepad")]
+        match GilrsGamepadBackend::new() {
+            Ok(backend) => return Self::new(Box::new(backend)),
+            Err(err) => log::warn!("{err}"),
+        }
+        Self::new(Box::new(VirtualGamepadBackend::new()))
+    }
+
+    /// Polls the backend, updates the cached gamepad states and returns the new events.
+    pub fn poll(&mut self) -> Vec<EGamepadEvent> {
+        let events = self.backend.poll_events();
+        for event in &events {
+            match event {
+                EGamepadEvent::Connected(info) => {
+                    self.states.entry(info.id).or_default().info = Some(info.clone());
+                }
+                EGamepadEvent::Disconnected(id) => {
+                    self.states.remove(id);
+                }
+                EGamepadEvent::Button {
+                    id,
+                    button,
+                    is_pressed,
+                } => {
+                    let state = self.states.entry(*id).or_default();
+                    if *is_pressed {
+                        state.pressed_buttons.insert(*button);
+                    } else {
+                        state.pressed_buttons.remove(button);
+                    }
+                }
+                EGamepadEvent::Axis { id, axis, value } => {
+                    self.states
+                        .entry(*id)
+                        .or_default()
+                        .axis_values
+                        .insert(*axis, *value);
+                }
+            }
+        }
+        events
+    }
+
+    pub fn gamepads(&self) -> Vec<GamepadInfo> {
+        let mut gamepads = self
+            .states
+            .values()
+            .filter_map(|x| x.info.clone())
+            .collect::<Vec<GamepadInfo>>();
+        gamepads.sort_by_key(|x| x.id);
+        gamepads
+    }
+
+    pub fn state(&self, id: GamepadId) -> Option<&GamepadState> {
+        self.states.get(&id)
+    }
+}
+
+/// A backend without hardware, events are queued manually. Useful for tests and replays.
+#[derive(Default)]
+pub struct VirtualGamepadBackend {
+    next_id: usize,
+    pending_events: Vec<EGamepadEvent>,
+}
+
+impl VirtualGamepadBackend {
+    pub fn new() -> VirtualGamepadBackend {
+        VirtualGamepadBackend::default()
+    }
+
+    pub fn connect(&mut self, name: impl AsRef<str>) -> GamepadId {
+        let id = GamepadId(self.next_id);
+        self.next_id += 1;
+        self.pending_events.push(EGamepadEvent::Connected(GamepadInfo {
+            id,
+            name: name.as_ref().to_string(),
+        }));
+        id
+    }
+
+    pub fn disconnect(&mut self, id: GamepadId) {
+        self.pending_events.push(EGamepadEvent::Disconnected(id));
+    }
+
+    pub fn set_button(&mut self, id: GamepadId, button: EGamepadButton, is_pressed: bool) {
+        self.pending_events.push(EGamepadEvent::Button {
+            id,
+            button,
+            is_pressed,
+        });
+    }
+
+    pub fn set_axis(&mut self, id: GamepadId, axis: EGamepadAxis, value: f32) {
+        self.pending_events
+            .push(EGamepadEvent::Axis { id, axis, value });
+    }
+}
+
+impl GamepadBackend for VirtualGamepadBackend {
+    fn poll_events(&mut self) -> Vec<EGamepadEvent> {
+        std::mem::take(&mut self.pending_events)
+    }
+}
+
+impl<T: GamepadBackend> GamepadBackend for rs_foundation::new::SingleThreadMutType<T> {
+    fn poll_events(&mut self) -> Vec<EGamepadEvent> {
+        self.borrow_mut().poll_events()
+    }
+}
+
+#[cfg(feature = "gamepad")]
+pub struct GilrsGamepadBackend {
+    gilrs: gilrs::Gilrs,
+    is_initial_enumerated: bool,
+}
+
+#[cfg(feature = "gamepad")]
+impl GilrsGamepadBackend {
+    pub fn new() -> crate::error::Result<GilrsGamepadBackend> {
+        let gilrs = gilrs::Gilrs::new()
+            .map_err(|err| crate::error::Error::Other(Some(format!("{err}"))))?;
+        Ok(GilrsGamepadBackend {
+            gilrs,
+            is_initial_enumerated: false,
+        })
+    }
+
+    fn map_button(button: gilrs::Button) -> Option<EGamepadButton> {
+        match button {
+            gilrs::Button::South => Some(EGamepadButton::South),
+            gilrs::Button::East => Some(EGamepadButton::East),
+            gilrs::Button::North => Some(EGamepadButton::North),
+            gilrs::Button::West => Some(EGamepadButton::West),
+            gilrs::Button::LeftTrigger => Some(EGamepadButton::LeftBumper),
+            gilrs::Button::RightTrigger => Some(EGamepadButton::RightBumper),
+            gilrs::Button::LeftTrigger2 => Some(EGamepadButton::LeftTrigger),
+            gilrs::Button::RightTrigger2 => Some(EGamepadButton::RightTrigger),
+            gilrs::Button::Select => Some(EGamepadButton::Select),
+            gilrs::Button::Start => Some(EGamepadButton::Start),
+            gilrs::Button::Mode => Some(EGamepadButton::Mode),
+            gilrs::Button::LeftThumb => Some(EGamepadButton::LeftThumb),
+            gilrs::Button::RightThumb => Some(EGamepadButton::RightThumb),
+            gilrs::Button::DPadUp => Some(EGamepadButton::DPadUp),
+            gilrs::Button::DPadDown => Some(EGamepadButton::DPadDown),
+            gilrs::Button::DPadLeft => Some(EGamepadButton::DPadLeft),
+            gilrs::Button::DPadRight => Some(EGamepadButton::DPadRight),
+            _ => None,
+        }
+    }
+
+    fn map_axis(axis: gilrs::Axis) -> Option<EGamepadAxis> {
+        match axis {
+            gilrs::Axis::LeftStickX => Some(EGamepadAxis::LeftStickX),
+            gilrs::Axis::LeftStickY => Some(EGamepadAxis::LeftStickY),
+            gilrs::Axis::RightStickX => Some(EGamepadAxis::RightStickX),
+            gilrs::Axis::RightStickY => Some(EGamepadAxis::RightStickY),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "gamepad")]
+impl GamepadBackend for GilrsGamepadBackend {
+    fn poll_events(&mut self) -> Vec<EGamepadEvent> {
+        let mut events = vec![];
+        if !self.is_initial_enumerated {
+            self.is_initial_enumerated = true;
+            for (id, gamepad) in self.gilrs.gamepads() {
+                events.push(EGamepadEvent::Connected(GamepadInfo {
+                    id: GamepadId(usize::from(id)),
+                    name: gamepad.name().to_string(),
+                }));
+            }
+        }
+        while let Some(gilrs::Event {
+            id: gilrs_id,
+            event,
+            ..
+        }) = self.gilrs.next_event()
+        {
+            let id = GamepadId(usize::from(gilrs_id));
+            match event {
+                gilrs::EventType::Connected => {
+                    let name = self.gilrs.gamepad(gilrs_id).name();
+                    events.push(EGamepadEvent::Connected(GamepadInfo {
+                        id,
+                        name: name.to_string(),
+                    }));
+                }
+                gilrs::EventType::Disconnected => {
+                    events.push(EGamepadEvent::Disconnected(id));
+                }
+                gilrs::EventType::ButtonPressed(button, _)
+                | gilrs::EventType::ButtonReleased(button, _) => {
+                    let is_pressed = matches!(event, gilrs::EventType::ButtonPressed(..));
+                    if let Some(button) = Self::map_button(button) {
+                        events.push(EGamepadEvent::Button {
+                            id,
+                            button,
+                            is_pressed,
+                        });
+                    }
+                }
+                gilrs::EventType::ButtonChanged(button, value, _) => {
+                    let axis = match button {
+                        gilrs::Button::LeftTrigger2 => EGamepadAxis::LeftTrigger,
+                        gilrs::Button::RightTrigger2 => EGamepadAxis::RightTrigger,
+                        _ => continue,
+                    };
+                    events.push(EGamepadEvent::Axis { id, axis, value });
+                }
+                gilrs::EventType::AxisChanged(axis, value, _) => {
+                    if let Some(axis) = Self::map_axis(axis) {
+                        events.push(EGamepadEvent::Axis { id, axis, value });
+                    }
+                }
+                _ => {}
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{EGamepadEvent, GamepadId, GamepadManager, VirtualGamepadBackend};
+    use rs_core_minimal::input_binding::{EGamepadAxis, EGamepadButton};
+    use rs_foundation::new::SingleThreadMut;
+
+    #[test]
+    fn test_virtual_backend() {
+        let backend = SingleThreadMut::new(VirtualGamepadBackend::new());
+        let mut manager = GamepadManager::new(Box::new(backend.clone()));
+        let id = backend.borrow_mut().connect("Virtual");
+        backend
+            .borrow_mut()
+            .set_button(id, EGamepadButton::South, true);
+        backend
+            .borrow_mut()
+            .set_axis(id, EGamepadAxis::LeftStickX, 0.5);
+        let events = manager.poll();
+        assert_eq!(events.len(), 3);
+        assert_eq!(manager.gamepads().len(), 1);
+        assert_eq!(manager.gamepads()[0].name, "Virtual");
+        let state = manager.state(id).unwrap();
+        assert!(state.is_button_pressed(EGamepadButton::South));
+        assert_eq!(state.axis_value(EGamepadAxis::LeftStickX), 0.5);
+
+        backend.borrow_mut().disconnect(id);
+        let events = manager.poll();
+        assert_eq!(events, vec![EGamepadEvent::Disconnected(GamepadId(0))]);
+        assert!(manager.gamepads().is_empty());
+        assert!(manager.poll().is_empty());
+    }
+}
@@ -0,0 +1,694 @@
+use crate::{
+    gamepad::{EGamepadEvent, GamepadId},
+    input_type::EInputType,
+};
+use rs_core_minimal::{
+    input_binding::{
+        EGamepadAxis, EGamepadButton, EInputKey, EMouseButton, InputActionBinding,
+        InputAxisBinding, InputContextDescription, InputModifiers,
+    },
+    settings::InputSettings,
+};
+use std::collections::{HashMap, HashSet};
+use winit::{
+    event::{ElementState, MouseButton, MouseScrollDelta},
+    keyboard::KeyCode,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EInputEvent {
+    Button { key: EInputKey, is_pressed: bool },
+    Axis { key: EInputKey, value: f32 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EActionState {
+    #[default]
+    Idle,
+    Pressed,
+    Held,
+    Released,
+}
+
+impl EActionState {
+    pub fn is_active(&self) -> bool {
+        match self {
+            EActionState::Pressed | EActionState::Held => true,
+            EActionState::Idle | EActionState::Released => false,
+        }
+    }
+}
+
+pub struct InputContext {
+    pub description: InputContextDescription,
+    pub is_enable: bool,
+}
+
+pub struct InputActionSystem {
+    contexts: Vec<InputContext>,
+    held_buttons: HashSet<EInputKey>,
+    pending_pressed_buttons: HashSet<EInputKey>,
+    absolute_axis_values: HashMap<EInputKey, f32>,
+    pending_relative_axis_values: HashMap<EInputKey, f32>,
+    relative_axis_values: HashMap<EInputKey, f32>,
+    action_states: HashMap<String, EActionState>,
+    axis_values: HashMap<String, f32>,
+    gamepad_buttons: HashMap<GamepadId, HashSet<EGamepadButton>>,
+    gamepad_axes: HashMap<GamepadId, HashMap<EGamepadAxis, f32>>,
+}
+
+impl InputActionSystem {
+    pub fn new() -> InputActionSystem {
+        InputActionSystem {
+            contexts: vec![],
+            held_buttons: HashSet::new(),
+            pending_pressed_buttons: HashSet::new(),
+            absolute_axis_values: HashMap::new(),
+            pending_relative_axis_values: HashMap::new(),
+            relative_axis_values: HashMap::new(),
+            action_states: HashMap::new(),
+            axis_values: HashMap::new(),
+            gamepad_buttons: HashMap::new(),
+            gamepad_axes: HashMap::new(),
+        }
+    }
+
+    pub fn from_settings(input_settings: &InputSettings) -> InputActionSystem {
+        let mut input_action_system = Self::new();
+        for context in &input_settings.contexts {
+            input_action_system.add_context(context.clone());
+        }
+        input_action_system
+    }
+
+    pub fn to_settings(&self) -> InputSettings {
+        InputSettings {
+            contexts: self
+                .contexts
+                .iter()
+                .map(|x| x.description.clone())
+                .collect(),
+        }
+    }
+
+    pub fn key_from_key_code(key_code: KeyCode) -> EInputKey {
+        EInputKey::keyboard(format!("{:?}", key_code))
+    }
+
+    pub fn key_from_mouse_button(mouse_button: MouseButton) -> EInputKey {
+        let mouse_button = match mouse_button {
+            MouseButton::Left => EMouseButton::Left,
+            MouseButton::Right => EMouseButton::Right,
+            MouseButton::Middle => EMouseButton::Middle,
+            MouseButton::Back => EMouseButton::Back,
+            MouseButton::Forward => EMouseButton::Forward,
+            MouseButton::Other(other) => EMouseButton::Other(other),
+        };
+        EInputKey::MouseButton(mouse_button)
+    }
+
+    /// Adds or replaces a context with the same name. New contexts are enabled.
+    pub fn add_context(&mut self, description: InputContextDescription) {
+        self.remove_context(&description.name);
+        self.contexts.push(InputContext {
+            description,
+            is_enable: true,
+        });
+        self.contexts
+            .sort_by_key(|x| std::cmp::Reverse(x.description.priority));
+    }
+
+    pub fn remove_context(&mut self, name: &str) -> Option<InputContextDescription> {
+        let index = self
+            .contexts
+            .iter()
+            .position(|x| x.description.name == name)?;
+        Some(self.contexts.remove(index).description)
+    }
+
+    pub fn set_context_enable(&mut self, name: &str, is_enable: bool) -> bool {
+        let Some(context) = self.find_context_mut(name) else {
+            return false;
+        };
+        context.is_enable = is_enable;
+        true
+    }
+
+    pub fn contexts(&self) -> &[InputContext] {
+        &self.contexts
+    }
+
+    fn find_context_mut(&mut self, name: &str) -> Option<&mut InputContext> {
+        self.contexts
+            .iter_mut()
+            .find(|x| x.description.name == name)
+    }
+
+    pub fn rebind_action(
+        &mut self,
+        context_name: &str,
+        action_name: &str,
+        bindings: Vec<InputActionBinding>,
+    ) -> bool {
+        let Some(context) = self.find_context_mut(context_name) else {
+            return false;
+        };
+        let Some(action) = context
+            .description
+            .actions
+            .iter_mut()
+            .find(|x| x.name == action_name)
+        else {
+            return false;
+        };
+        action.bindings = bindings;
+        true
+    }
+
+    pub fn rebind_axis(
+        &mut self,
+        context_name: &str,
+        axis_name: &str,
+        bindings: Vec<InputAxisBinding>,
+    ) -> bool {
+        let Some(context) = self.find_context_mut(context_name) else {
+            return false;
+        };
+        let Some(axis) = context
+            .description
+            .axes
+            .iter_mut()
+            .find(|x| x.name == axis_name)
+        else {
+            return false;
+        };
+        axis.bindings = bindings;
+        true
+    }
+
+    pub fn inject(&mut self, event: EInputEvent) {
+        match event {
+            EInputEvent::Button { key, is_pressed } => {
+                if is_pressed {
+                    if self.held_buttons.insert(key.clone()) {
+                        self.pending_pressed_buttons.insert(key);
+                    }
+                } else {
+                    self.held_buttons.remove(&key);
+                }
+            }
+            EInputEvent::Axis { key, value } => {
+                if key.is_relative_axis() {
+                    *self.pending_relative_axis_values.entry(key).or_default() += value;
+                } else {
+                    self.absolute_axis_values.insert(key, value);
+                }
+            }
+        }
+    }
+
+    pub fn on_key(&mut self, key_code: KeyCode, element_state: ElementState) {
+        self.inject(EInputEvent::Button {
+            key: Self::key_from_key_code(key_code),
+            is_pressed: element_state.is_pressed(),
+        });
+    }
+
+    pub fn on_window_input(&mut self, ty: &EInputType) {
+        match ty {
+            EInputType::MouseWheel(delta) => {
+                let value = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y,
+                    MouseScrollDelta::PixelDelta(position) => position.y as f32,
+                };
+                self.inject(EInputEvent::Axis {
+                    key: EInputKey::MouseWheel,
+                    value,
+                });
+            }
+            EInputType::MouseInput(element_state, mouse_button) => {
+                self.inject(EInputEvent::Button {
+                    key: Self::key_from_mouse_button(**mouse_button),
+                    is_pressed: element_state.is_pressed(),
+                });
+            }
+            EInputType::KeyboardInput(keys_detector) => {
+                for (key_code, element_state) in keys_detector.virtual_key_code_states() {
+                    self.on_key(*key_code, *element_state);
+                }
+            }
+            EInputType::Gamepad(event) => self.on_gamepad_event(event),
+            _ => {}
+        }
+    }
+
+    /// Input of all connected gamepads is merged, a button stays held while any gamepad holds it.
+    /// Buttons and axes of a disconnected gamepad are released.
+    pub fn on_gamepad_event(&mut self, event: &EGamepadEvent) {
+        match event {
+            EGamepadEvent::Button {
+                id,
+                button,
+                is_pressed,
+            } => {
+                let buttons = self.gamepad_buttons.entry(*id).or_default();
+                if *is_pressed {
+                    buttons.insert(*button);
+                } else {
+                    buttons.remove(button);
+                }
+                self.inject(EInputEvent::Button {
+                    key: EInputKey::GamepadButton(*button),
+                    is_pressed: self.is_gamepad_button_held(*button),
+                });
+            }
+            EGamepadEvent::Axis { id, axis, value } => {
+                self.gamepad_axes
+                    .entry(*id)
+                    .or_default()
+                    .insert(*axis, *value);
+                self.inject(EInputEvent::Axis {
+                    key: EInputKey::GamepadAxis(*axis),
+                    value: *value,
+                });
+            }
+            EGamepadEvent::Connected(_) => {}
+            EGamepadEvent::Disconnected(id) => {
+                for button in self.gamepad_buttons.remove(id).unwrap_or_default() {
+                    self.inject(EInputEvent::Button {
+                        key: EInputKey::GamepadButton(button),
+                        is_pressed: self.is_gamepad_button_held(button),
+                    });
+                }
+                for (axis, _) in self.gamepad_axes.remove(id).unwrap_or_default() {
+                    let value = self
+                        .gamepad_axes
+                        .values()
+                        .filter_map(|axes| axes.get(&axis).copied())
+                        .find(|value| *value != 0.0)
+                        .unwrap_or(0.0);
+                    self.inject(EInputEvent::Axis {
+                        key: EInputKey::GamepadAxis(axis),
+                        value,
+                    });
+                }
+            }
+        }
+    }
+
+    fn is_gamepad_button_held(&self, button: EGamepadButton) -> bool {
+        self.gamepad_buttons
+            .values()
+            .any(|buttons| buttons.contains(&button))
+    }
+
+    pub fn on_device_event(&mut self, device_event: &winit::event::DeviceEvent) {
+        if let winit::event::DeviceEvent::MouseMotion { delta } = device_event {
+            self.inject(EInputEvent::Axis {
+                key: EInputKey::MouseX,
+                value: delta.0 as f32,
+            });
+            self.inject(EInputEvent::Axis {
+                key: EInputKey::MouseY,
+                value: delta.1 as f32,
+            });
+        }
+    }
+
+    fn modifiers(&self) -> InputModifiers {
+        let is_held = |names: [&str; 2]| {
+            names
+                .iter()
+                .any(|name| self.held_buttons.contains(&EInputKey::keyboard(name)))
+        };
+        InputModifiers {
+            shift: is_held(["ShiftLeft", "ShiftRight"]),
+            ctrl: is_held(["ControlLeft", "ControlRight"]),
+            alt: is_held(["AltLeft", "AltRight"]),
+            logo: is_held(["SuperLeft", "SuperRight"]),
+        }
+    }
+
+    fn is_button_active(&self, key: &EInputKey) -> bool {
+        self.held_buttons.contains(key) || self.pending_pressed_buttons.contains(key)
+    }
+
+    fn raw_axis_value(&self, key: &EInputKey) -> f32 {
+        if key.is_relative_axis() {
+            self.relative_axis_values.get(key).copied().unwrap_or(0.0)
+        } else if key.is_axis() {
+            self.absolute_axis_values.get(key).copied().unwrap_or(0.0)
+        } else if self.is_button_active(key) {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+        let dead_zone = dead_zone.clamp(0.0, 0.99);
+        if value.abs() <= dead_zone {
+            0.0
+        } else {
+            value.signum() * (value.abs() - dead_zone) / (1.0 - dead_zone)
+        }
+    }
+
+    /// Resolves all actions and axes for the current frame. Should be called once per frame, after all input events of the frame have been delivered.
+    pub fn update(&mut self) {
+        self.relative_axis_values = std::mem::take(&mut self.pending_relative_axis_values);
+        let modifiers = self.modifiers();
+        let mut consumed_keys: HashSet<EInputKey> = HashSet::new();
+        let mut active_actions: HashSet<String> = HashSet::new();
+        let mut axis_values: HashMap<String, f32> = HashMap::new();
+
+        for context in self.contexts.iter().filter(|x| x.is_enable) {
+            let description = &context.description;
+            let mut used_keys: Vec<EInputKey> = vec![];
+
+            for action in &description.actions {
+                for binding in &action.bindings {
+                    let keys = std::iter::once(&binding.key).chain(binding.chord.iter());
+                    let is_matched = modifiers.contains(&binding.modifiers)
+                        && keys.clone().all(|key| {
+                            !consumed_keys.contains(key) && self.raw_axis_value(key) != 0.0
+                        });
+                    if is_matched {
+                        active_actions.insert(action.name.clone());
+                        used_keys.extend(keys.cloned());
+                    }
+                }
+            }
+
+            for axis in &description.axes {
+                for binding in &axis.bindings {
+                    if consumed_keys.contains(&binding.key)
+                        || !modifiers.contains(&binding.modifiers)
+                    {
+                        continue;
+                    }
+                    let value =
+                        Self::apply_dead_zone(self.raw_axis_value(&binding.key), binding.dead_zone);
+                    if value != 0.0 {
+                        *axis_values.entry(axis.name.clone()).or_default() += value * binding.scale;
+                        used_keys.push(binding.key.clone());
+                    }
+                }
+            }
+
+            if description.is_consume_input {
+                consumed_keys.extend(used_keys);
+            }
+        }
+
+        let mut action_states: HashMap<String, EActionState> = HashMap::new();
+        let names = active_actions
+            .iter()
+            .chain(self.action_states.keys())
+            .cloned()
+            .collect::<HashSet<String>>();
+        for name in names {
+            let was_active = self
+                .action_states
+                .get(&name)
+                .map(|x| x.is_active())
+                .unwrap_or(false);
+            let is_active = active_actions.contains(&name);
+            let state = match (was_active, is_active) {
+                (false, true) => EActionState::Pressed,
+                (true, true) => EActionState::Held,
+                (true, false) => EActionState::Released,
+                (false, false) => continue,
+            };
+            action_states.insert(name, state);
+        }
+        self.action_states = action_states;
+        self.axis_values = axis_values;
+        self.pending_pressed_buttons.clear();
+    }
+
+    pub fn action_state(&self, name: &str) -> EActionState {
+        self.action_states.get(name).copied().unwrap_or_default()
+    }
+
+    pub fn is_action_pressed(&self, name: &str) -> bool {
+        self.action_state(name) == EActionState::Pressed
+    }
+
+    pub fn is_action_held(&self, name: &str) -> bool {
+        self.action_state(name).is_active()
+    }
+
+    pub fn is_action_released(&self, name: &str) -> bool {
+        self.action_state(name) == EActionState::Released
+    }
+
+    pub fn axis_value(&self, name: &str) -> f32 {
+        self.axis_values.get(name).copied().unwrap_or(0.0)
+    }
+
+    /// Releases every button and resets all axes, for example when the window loses focus.
+    pub fn reset(&mut self) {
+        self.held_buttons.clear();
+        self.pending_pressed_buttons.clear();
+        self.absolute_axis_values.clear();
+        self.pending_relative_axis_values.clear();
+        self.gamepad_buttons.clear();
+        self.gamepad_axes.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{EActionState, EInputEvent, InputActionSystem};
+    use crate::gamepad::{EGamepadEvent, GamepadId};
+    use rs_core_minimal::{
+        input_binding::{
+            EGamepadAxis, EGamepadButton, EInputKey, InputActionBinding, InputActionMapping,
+            InputAxisBinding, InputAxisMapping, InputContextDescription, InputModifiers,
+        },
+        settings::InputSettings,
+    };
+
+    fn press(system: &mut InputActionSystem, name: &str, is_pressed: bool) {
+        system.inject(EInputEvent::Button {
+            key: EInputKey::keyboard(name),
+            is_pressed,
+        });
+    }
+
+    fn gameplay_context() -> InputContextDescription {
+        let mut context = InputContextDescription::new("Gameplay", 0);
+        context.actions.push(InputActionMapping {
+            name: "Jump".to_string(),
+            bindings: vec![InputActionBinding::new(EInputKey::keyboard("Space"))],
+        });
+        context.axes.push(InputAxisMapping {
+            name: "MoveForward".to_string(),
+            bindings: vec![
+                InputAxisBinding::new(EInputKey::keyboard("KeyW"), 1.0),
+                InputAxisBinding::new(EInputKey::keyboard("KeyS"), -1.0),
+                InputAxisBinding {
+                    dead_zone: 0.2,
+                    ..InputAxisBinding::new(EInputKey::GamepadAxis(EGamepadAxis::LeftStickY), 1.0)
+                },
+            ],
+        });
+        context
+    }
+
+    #[test]
+    fn test_action_state() {
+        let mut system = InputActionSystem::new();
+        system.add_context(gameplay_context());
+        press(&mut system, "Space", true);
+        system.update();
+        assert_eq!(system.action_state("Jump"), EActionState::Pressed);
+        system.update();
+        assert_eq!(system.action_state("Jump"), EActionState::Held);
+        press(&mut system, "Space", false);
+        system.update();
+        assert_eq!(system.action_state("Jump"), EActionState::Released);
+        system.update();
+        assert_eq!(system.action_state("Jump"), EActionState::Idle);
+
+        press(&mut system, "Space", true);
+        press(&mut system, "Space", false);
+        system.update();
+        assert!(system.is_action_pressed("Jump"));
+    }
+
+    #[test]
+    fn test_axis_and_dead_zone() {
+        let mut system = InputActionSystem::new();
+        system.add_context(gameplay_context());
+        press(&mut system, "KeyW", true);
+        system.update();
+        assert_eq!(system.axis_value("MoveForward"), 1.0);
+        press(&mut system, "KeyS", true);
+        system.update();
+        assert_eq!(system.axis_value("MoveForward"), 0.0);
+        press(&mut system, "KeyW", false);
+        press(&mut system, "KeyS", false);
+
+        system.inject(EInputEvent::Axis {
+            key: EInputKey::GamepadAxis(EGamepadAxis::LeftStickY),
+            value: 0.1,
+        });
+        system.update();
+        assert_eq!(system.axis_value("MoveForward"), 0.0);
+        system.inject(EInputEvent::Axis {
+            key: EInputKey::GamepadAxis(EGamepadAxis::LeftStickY),
+            value: 0.6,
+        });
+        system.update();
+        assert!((system.axis_value("MoveForward") - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_relative_axis_reset() {
+        let mut system = InputActionSystem::new();
+        let mut context = InputContextDescription::new("Camera", 0);
+        context.axes.push(InputAxisMapping {
+            name: "Turn".to_string(),
+            bindings: vec![InputAxisBinding::new(EInputKey::MouseX, 0.5)],
+        });
+        system.add_context(context);
+        system.inject(EInputEvent::Axis {
+            key: EInputKey::MouseX,
+            value: 4.0,
+        });
+        system.inject(EInputEvent::Axis {
+            key: EInputKey::MouseX,
+            value: 2.0,
+        });
+        system.update();
+        assert_eq!(system.axis_value("Turn"), 3.0);
+        system.update();
+        assert_eq!(system.axis_value("Turn"), 0.0);
+    }
+
+    #[test]
+    fn test_modifiers_and_chord() {
+        let mut system = InputActionSystem::new();
+        let mut context = InputContextDescription::new("Editor", 0);
+        context.actions.push(InputActionMapping {
+            name: "Save".to_string(),
+            bindings: vec![InputActionBinding {
+                modifiers: InputModifiers {
+                    ctrl: true,
+                    ..Default::default()
+                },
+                ..InputActionBinding::new(EInputKey::keyboard("KeyS"))
+            }],
+        });
+        context.actions.push(InputActionMapping {
+            name: "Combo".to_string(),
+            bindings: vec![InputActionBinding {
+                chord: vec![EInputKey::keyboard("KeyA")],
+                ..InputActionBinding::new(EInputKey::keyboard("KeyB"))
+            }],
+        });
+        system.add_context(context);
+
+        press(&mut system, "KeyS", true);
+        press(&mut system, "KeyB", true);
+        system.update();
+        assert!(!system.is_action_held("Save"));
+        assert!(!system.is_action_held("Combo"));
+
+        press(&mut system, "ControlLeft", true);
+        press(&mut system, "KeyA", true);
+        system.update();
+        assert!(system.is_action_pressed("Save"));
+        assert!(system.is_action_pressed("Combo"));
+    }
+
+    #[test]
+    fn test_context_priority() {
+        let mut system = InputActionSystem::new();
+        system.add_context(gameplay_context());
+        let mut menu = InputContextDescription::new("Menu", 10);
+        menu.is_consume_input = true;
+        menu.actions.push(InputActionMapping {
+            name: "Confirm".to_string(),
+            bindings: vec![InputActionBinding::new(EInputKey::keyboard("Space"))],
+        });
+        system.add_context(menu);
+
+        press(&mut system, "Space", true);
+        system.update();
+        assert!(system.is_action_pressed("Confirm"));
+        assert!(!system.is_action_held("Jump"));
+
+        system.set_context_enable("Menu", false);
+        system.update();
+        assert!(system.is_action_released("Confirm"));
+        assert!(system.is_action_pressed("Jump"));
+    }
+
+    #[test]
+    fn test_rebind_and_settings() {
+        let mut system = InputActionSystem::from_settings(&InputSettings {
+            contexts: vec![gameplay_context()],
+        });
+        assert!(system.rebind_action(
+            "Gameplay",
+            "Jump",
+            vec![InputActionBinding::new(EInputKey::keyboard("KeyJ"))]
+        ));
+        press(&mut system, "Space", true);
+        system.update();
+        assert!(!system.is_action_held("Jump"));
+        press(&mut system, "KeyJ", true);
+        system.update();
+        assert!(system.is_action_pressed("Jump"));
+
+        let settings = system.to_settings();
+        let json = serde_json::to_string(&settings).unwrap();
+        let settings: InputSettings = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            settings.contexts[0].actions[0].bindings[0].key,
+            EInputKey::keyboard("KeyJ")
+        );
+    }
+
+    #[test]
+    fn test_gamepad_disconnected() {
+        let mut system = InputActionSystem::new();
+        let mut context = gameplay_context();
+        context.actions.push(InputActionMapping {
+            name: "Fire".to_string(),
+            bindings: vec![InputActionBinding::new(EInputKey::GamepadButton(
+                EGamepadButton::South,
+            ))],
+        });
+        system.add_context(context);
+        for id in [GamepadId(0), GamepadId(1)] {
+            system.on_gamepad_event(&EGamepadEvent::Button {
+                id,
+                button: EGamepadButton::South,
+                is_pressed: true,
+            });
+        }
+        system.on_gamepad_event(&EGamepadEvent::Axis {
+            id: GamepadId(0),
+            axis: EGamepadAxis::LeftStickY,
+            value: 1.0,
+        });
+        system.update();
+        assert!(system.is_action_pressed("Fire"));
+        assert_eq!(system.axis_value("MoveForward"), 1.0);
+
+        system.on_gamepad_event(&EGamepadEvent::Disconnected(GamepadId(0)));
+        system.update();
+        assert!(system.is_action_held("Fire"));
+        assert_eq!(system.axis_value("MoveForward"), 0.0);
+
+        system.on_gamepad_event(&EGamepadEvent::Disconnected(GamepadId(1)));
+        system.update();
+        assert!(system.is_action_released("Fire"));
+        system.update();
+        assert_eq!(system.action_state("Fire"), EActionState::Idle);
+    }
+}
@@ -10,4 +10,5 @@ pub enum EInputType<'a> {
     CursorEntered,
     CursorLeft,
     CursorMoved(&'a winit::dpi::PhysicalPosition<f64>),
+    Gamepad(&'a crate::gamepad::EGamepadEvent),
 }
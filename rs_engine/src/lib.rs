@@ -15,7 +15,9 @@ pub mod engine;
 pub mod error;
 pub mod ffi;
 pub mod frame_sync;
+pub mod gamepad;
 pub mod handle;
+pub mod input_action;
 pub mod input_mode;
 pub mod input_type;
 pub mod keys_detector;
@@ -186,7 +186,7 @@ impl PhysicsAbility {
         let rigid_body = rigid_body_builder.build();
 
         let mut collider_handles: Vec<ColliderHandle> = Vec::with_capacity(colliders.len());
-        let rigid_body_handle = level_physics.rigid_body_set.insert(rigid_body.clone());
+        let rigid_body_handle = level_physics.insert_rigid_body(rigid_body.clone());
         for collider in colliders.clone() {
             let collider_handle = level_physics.collider_set.insert_with_parent(
                 collider,
@@ -347,9 +347,12 @@ impl PhysicsAbility {
             let _ = engine;
             let _ = delta_seconds;
 
-            let rigid_body = &level_physics.rigid_body_set[self.rigid_body_handle];
-            self.translation = rigid_body.translation();
-            self.rotation = *rigid_body.rotation();
+            if let Some((translation, rotation)) =
+                level_physics.interpolated_pose(self.rigid_body_handle)
+            {
+                self.translation = translation;
+                self.rotation = rotation;
+            }
         }
     }
 }
@@ -69,6 +69,7 @@ pub struct PlayerViewport {
     _input_mode: EInputMode,
     _camera_movement_speed: f32,
     _camera_motion_speed: f32,
+    gamepad_camera_controller: crate::camera_input_event_handle::GamepadCameraController,
     pub is_use_default_input_process: bool,
     pub is_grid_visible: bool,
     settings: rs_core_minimal::settings::Settings,
@@ -229,6 +230,8 @@ impl PlayerViewport {
             _input_mode: input_mode,
             _camera_movement_speed: 0.1,
             _camera_motion_speed: 0.1,
+            gamepad_camera_controller:
+                crate::camera_input_event_handle::GamepadCameraController::new(),
             is_use_default_input_process: true,
             is_grid_visible: true,
             point_lights_constants,
@@ -1414,10 +1417,24 @@ impl PlayerViewport {
                     );
                 }
             }
+            EInputType::Gamepad(event) => {
+                self.gamepad_camera_controller.on_gamepad_event(event);
+            }
             _ => {}
         }
     }
 
+    pub fn update_gamepad_camera(&mut self) {
+        if !self.is_use_default_input_process {
+            return;
+        }
+        self.gamepad_camera_controller.update(
+            &mut self.camera,
+            self._input_mode,
+            self._camera_movement_speed,
+        );
+    }
+
     pub fn on_antialias_type_changed(
         &mut self,
         antialias_type: rs_core_minimal::settings::EAntialiasType,
@@ -4,6 +4,7 @@ use crate::handle::SamplerHandle;
 use crate::thread_pool::ThreadPool;
 use crate::{error::Result, handle::HandleManager};
 use lazy_static::lazy_static;
+use rs_artifact::compiled_shader::CompiledShader;
 use rs_artifact::resource_info::ResourceInfo;
 use rs_artifact::sound::Sound;
 use rs_artifact::static_mesh::StaticMesh;
@@ -306,6 +307,30 @@ impl STResourceManager {
         codes
     }
 
+    fn get_all_compiled_shaders(&mut self) -> Vec<Box<CompiledShader>> {
+        let mut shaders: Vec<Box<CompiledShader>> = vec![];
+        let Some(reader) = self.artifact_reader.as_mut() else {
+            return shaders;
+        };
+        for (url, resource_info) in reader.get_artifact_file_header().resource_map.clone() {
+            if resource_info.resource_type != CompiledShader::associated_resource_type() {
+                continue;
+            }
+            let shader = reader
+                .asset(&url, Some(CompiledShader::associated_resource_type()))
+                .expect("Never");
+            match shader.downcast::<CompiledShader>() {
+                Ok(shader) => {
+                    shaders.push(shader);
+                }
+                Err(_) => {
+                    panic!("Never");
+                }
+            }
+        }
+        shaders
+    }
+
     fn set_artifact_reader(&mut self, reader: Option<ArtifactReader>) {
         self.artifact_reader = reader;
     }
@@ -4,7 +4,7 @@ use crate::network;
 use crate::network::NetworkReplicated;
 use crate::{
     components::component::Component,
-    content::{content_file_type::EContentFileType, level::LevelPhysics},
+    content::{content_file_type::EContentFileType, level::LevelPhysics, prefab::PrefabInstance},
     engine::Engine,
     player_viewport::PlayerViewport,
     static_mesh_component::StaticMeshComponent,
@@ -331,11 +331,48 @@ impl SceneComponent {
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct SceneNode {
+    /// Identifies the node across saves, so diffs of the content files can follow it.
+    #[serde(default = "uuid::Uuid::new_v4")]
+    id: uuid::Uuid,
     component: SingleThreadMutType<Box<dyn Component>>,
     childs: Vec<SingleThreadMutType<SceneNode>>,
+    #[serde(default)]
+    prefab: Option<PrefabInstance>,
+    /// The id of the node in the prefab, set on the nodes built from a prefab.
+    #[serde(default)]
+    prefab_node_id: Option<uuid::Uuid>,
 }
 
 impl SceneNode {
+    pub fn id(&self) -> &uuid::Uuid {
+        &self.id
+    }
+
+    pub(crate) fn set_id(&mut self, id: uuid::Uuid) {
+        self.id = id;
+    }
+
+    pub fn prefab_node_id(&self) -> Option<&uuid::Uuid> {
+        self.prefab_node_id.as_ref()
+    }
+
+    pub(crate) fn set_prefab_node_id(&mut self, prefab_node_id: Option<uuid::Uuid>) {
+        self.prefab_node_id = prefab_node_id;
+    }
+
+    /// Gives the node and its descendants new ids, should be called after the tree is copied.
+    /// The ids in an instance of a prefab are derived from the new id of the instance.
+    pub fn regenerate_ids(&mut self) {
+        self.id = uuid::Uuid::new_v4();
+        if self.prefab.is_some() {
+            crate::content::prefab::assign_instance_ids(self);
+            return;
+        }
+        for child in self.childs.iter() {
+            child.borrow_mut().regenerate_ids();
+        }
+    }
+
     pub fn component(&self) -> std::cell::Ref<'_, Box<dyn Component>> {
         let refe = self.component.borrow();
         refe
@@ -388,6 +425,23 @@ impl SceneNode {
         self.childs.push(child);
         return true;
     }
+
+    /// Returns the prefab if the node is the root of an instance of it.
+    pub fn prefab_instance(&self) -> Option<&PrefabInstance> {
+        self.prefab.as_ref()
+    }
+
+    pub fn set_prefab_instance(&mut self, prefab: Option<PrefabInstance>) {
+        self.prefab = prefab;
+    }
+
+    /// Takes the component and the childs of the other node, the references to this node stay valid.
+    /// The id of this node is kept.
+    pub(crate) fn replace_with(&mut self, other: SceneNode) {
+        *self.component.borrow_mut() = other.component.borrow().clone();
+        self.childs = other.childs;
+        self.prefab = other.prefab;
+    }
 }
 
 impl SceneNode {
@@ -397,8 +451,11 @@ impl SceneNode {
             glam::Mat4::IDENTITY,
         )) as Box<dyn Component>);
         SceneNode {
+            id: uuid::Uuid::new_v4(),
             component,
             childs: vec![],
+            prefab: None,
+            prefab_node_id: None,
         }
     }
 
@@ -408,15 +465,21 @@ impl SceneNode {
 
     pub fn from_component(component: impl Component) -> SceneNode {
         SceneNode {
+            id: uuid::Uuid::new_v4(),
             component: SingleThreadMut::new(Box::new(component)),
             childs: vec![],
+            prefab: None,
+            prefab_node_id: None,
         }
     }
 
     pub fn from_component_box(component: Box<dyn Component>) -> SceneNode {
         SceneNode {
+            id: uuid::Uuid::new_v4(),
             component: SingleThreadMut::new(component),
             childs: vec![],
+            prefab: None,
+            prefab_node_id: None,
         }
     }
 
@@ -5,6 +5,8 @@ use crate::plugin::plugin_crate::Plugin;
 use crate::{
     content::{content_file_type::EContentFileType, level::Level},
     engine::Engine,
+    gamepad::GamepadManager,
+    input_action::InputActionSystem,
     input_mode::EInputMode,
     player_viewport::PlayerViewport,
     scene_node::ChangedStateFlags,
@@ -155,6 +157,8 @@ enum ClientTickResultType {
 pub struct Application {
     _window_id: isize,
     player_view_port: PlayerViewport,
+    input_action_system: InputActionSystem,
+    gamepad_manager: GamepadManager,
     current_active_level: SingleThreadMutType<Level>,
     _contents: HashMap<url::Url, EContentFileType>,
     #[cfg(feature = "plugin_shared_crate")]
@@ -196,11 +200,16 @@ impl Application {
             current_active_level.make_copy_for_standalone(engine, &contents, &mut player_view_port);
 
         current_active_level.initialize(engine, &contents, &mut player_view_port);
+        current_active_level.load_initial_sub_levels(engine, &contents, &mut player_view_port);
         current_active_level.set_physics_simulate(true);
+        let input_action_system =
+            InputActionSystem::from_settings(&engine.get_settings().input_settings);
         #[allow(unused_mut)]
         let mut app = Application {
             _window_id: window_id,
             player_view_port,
+            input_action_system,
+            gamepad_manager: GamepadManager::platform_default(),
             #[cfg(feature = "plugin_shared_crate")]
             plugins: SingleThreadMut::new(plugins),
             current_active_level: SingleThreadMut::new(current_active_level),
@@ -240,6 +249,7 @@ impl Application {
     #[cfg(not(target_os = "android"))]
     pub fn on_device_event(&mut self, device_event: &winit::event::DeviceEvent) {
         self.player_view_port.on_device_event(device_event);
+        self.input_action_system.on_device_event(device_event);
         #[cfg(feature = "plugin_shared_crate")]
         {
             let mut plugins = self.plugins.borrow_mut();
@@ -259,6 +269,7 @@ impl Application {
         #[cfg(not(target_os = "android"))]
         let _ = window;
         self.player_view_port.on_window_input(ty);
+        self.input_action_system.on_window_input(ty);
         #[cfg(feature = "plugin_shared_crate")]
         let mut consume = vec![];
         #[cfg(not(feature = "plugin_shared_crate"))]
@@ -304,10 +315,20 @@ impl Application {
         self.net_tick(engine);
 
         #[cfg(not(target_os = "android"))]
-        self.player_view_port
-            .on_window_input(&mut crate::input_type::EInputType::KeyboardInput(
-                keys_detector,
-            ));
+        {
+            let mut input_type = crate::input_type::EInputType::KeyboardInput(keys_detector);
+            self.player_view_port.on_window_input(&mut input_type);
+            self.input_action_system.on_window_input(&input_type);
+        }
+        for event in self.gamepad_manager.poll() {
+            let mut input_type = crate::input_type::EInputType::Gamepad(&event);
+            #[cfg(not(target_os = "android"))]
+            self.on_window_input(window, &ctx, &mut input_type);
+            #[cfg(target_os = "android")]
+            self.on_window_input(&ctx, &mut input_type);
+        }
+        self.player_view_port.update_gamepad_camera();
+        self.input_action_system.update();
 
         let active_level = self.current_active_level.clone();
         {
@@ -355,6 +376,12 @@ impl Application {
         if let Some(physics) = active_level.get_physics_mut() {
             physics.collision_events.clear();
         }
+        active_level.update_streaming(
+            self.player_view_port.camera.get_world_location(),
+            engine,
+            &self._contents,
+            &mut self.player_view_port,
+        );
         active_level.tick(engine.get_game_time(), engine, &mut self.player_view_port);
         let mut draw_objects = active_level.collect_draw_objects();
         for draw_object in draw_objects.iter_mut() {
@@ -389,10 +416,40 @@ impl Application {
         self.current_active_level.clone()
     }
 
+    pub fn load_sub_level(
+        &mut self,
+        engine: &mut Engine,
+        url: &url::Url,
+    ) -> crate::error::Result<()> {
+        let mut active_level = self.current_active_level.borrow_mut();
+        active_level.load_sub_level(url, engine, &self._contents, &mut self.player_view_port)
+    }
+
+    pub fn unload_sub_level(&mut self, url: &url::Url) -> bool {
+        let mut active_level = self.current_active_level.borrow_mut();
+        active_level.unload_sub_level(url)
+    }
+
     pub fn player_view_port_mut(&mut self) -> &mut PlayerViewport {
         &mut self.player_view_port
     }
 
+    pub fn input_action_system(&self) -> &InputActionSystem {
+        &self.input_action_system
+    }
+
+    pub fn input_action_system_mut(&mut self) -> &mut InputActionSystem {
+        &mut self.input_action_system
+    }
+
+    pub fn gamepad_manager(&self) -> &GamepadManager {
+        &self.gamepad_manager
+    }
+
+    pub fn set_gamepad_manager(&mut self, gamepad_manager: GamepadManager) {
+        self.gamepad_manager = gamepad_manager;
+    }
+
     pub fn window_id(&self) -> isize {
         self._window_id
     }
@@ -659,6 +716,7 @@ impl Application {
             if let Some(mut remote_level) = server_net_data.level() {
                 log::trace!("To remote level: {}", &remote_level.get_name());
                 remote_level.initialize(engine, contents, player_viewport);
+                remote_level.load_initial_sub_levels(engine, contents, player_viewport);
                 result = ClientTickResultType::OpenLevel(remote_level);
             }
 
@@ -882,6 +940,7 @@ impl Application {
         server_net_data.serialize_level(&find_level)?;
         let data = server_net_data.serialize()?;
         server.broadcast(&data);
+        find_level.load_initial_sub_levels(engine, &self._contents, &mut self.player_view_port);
         self.current_active_level = SingleThreadMut::new(find_level);
         self.server_pending_open_level = Some(url);
         self.on_network_changed();
@@ -417,7 +417,7 @@ impl Component for StaticMeshComponent {
 
         if let Some(physics) = run_time.physics.as_mut() {
             if let Some(pending_rigid_body) = run_time.pending_rigid_body.take() {
-                let handle = level_physics.rigid_body_set.insert(pending_rigid_body);
+                let handle = level_physics.insert_rigid_body(pending_rigid_body);
                 physics.collider_handles.clear();
                 for collider in physics.colliders.clone() {
                     let collider_handle = level_physics.collider_set.insert_with_parent(
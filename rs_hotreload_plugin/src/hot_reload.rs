@@ -1,6 +1,6 @@
 use crate::error::Result;
 use crate::library_reload::LibraryReload;
-use notify::ReadDirectoryChangesWatcher;
+use notify::RecommendedWatcher;
 use notify_debouncer_mini::{DebouncedEvent, Debouncer, new_debouncer};
 use std::path::Path;
 use std::sync::mpsc::Receiver;
@@ -9,7 +9,7 @@ use std::sync::{Arc, Mutex};
 pub struct HotReload {
     library_reload: Arc<Mutex<LibraryReload>>,
     receiver: Receiver<std::result::Result<Vec<DebouncedEvent>, notify::Error>>,
-    _debouncer: Debouncer<ReadDirectoryChangesWatcher>,
+    _debouncer: Debouncer<RecommendedWatcher>,
 }
 
 impl HotReload {
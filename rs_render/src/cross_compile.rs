@@ -0,0 +1,192 @@
+use rs_artifact::compiled_shader::{ECompiledShaderCode, EShaderTarget, GlslEntryPoint};
+use rs_core_minimal::settings::Backends;
+
+/// The targets which can be used by `to_shader_source`.
+pub const RUNTIME_SHADER_TARGETS: [EShaderTarget; 1] = [EShaderTarget::SpirV];
+
+/// The targets which are consumed by the backends.
+pub fn get_shader_targets(backends: &Backends) -> Vec<EShaderTarget> {
+    match backends {
+        Backends::Primary | Backends::Vulkan => vec![EShaderTarget::SpirV],
+        // MSL and GLSL ES are not consumed, see `to_shader_source`.
+        // HLSL is not precompiled, DX12 always compiles the naga modules at runtime.
+        Backends::GL | Backends::DX12 => vec![],
+    }
+}
+
+/// Validates the module and translates it to the target.
+pub fn cross_compile(
+    module: &naga::Module,
+    target: EShaderTarget,
+) -> crate::error::Result<ECompiledShaderCode> {
+    let mut validator = naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    );
+    let module_info = validator
+        .validate(module)
+        .map_err(|err| crate::error::Error::ValidationError(err))?;
+    match target {
+        EShaderTarget::SpirV => {
+            let mut options = naga::back::spv::Options::default();
+            options
+                .flags
+                .remove(naga::back::spv::WriterFlags::ADJUST_COORDINATE_SPACE);
+            let spv = naga::back::spv::write_vec(module, &module_info, &options, None)
+                .map_err(|err| crate::error::Error::NagaBackSpirVError(err))?;
+            Ok(ECompiledShaderCode::SpirV(spv))
+        }
+        EShaderTarget::GlslEs => {
+            let options = naga::back::glsl::Options {
+                version: naga::back::glsl::Version::new_gles(310),
+                ..Default::default()
+            };
+            let mut entry_points = Vec::with_capacity(module.entry_points.len());
+            for entry_point in &module.entry_points {
+                let pipeline_options = naga::back::glsl::PipelineOptions {
+                    shader_stage: entry_point.stage,
+                    entry_point: entry_point.name.clone(),
+                    multiview: None,
+                };
+                let mut code = String::new();
+                naga::back::glsl::Writer::new(
+                    &mut code,
+                    module,
+                    &module_info,
+                    &options,
+                    &pipeline_options,
+                    naga::proc::BoundsCheckPolicies::default(),
+                )
+                .and_then(|mut writer| writer.write())
+                .map_err(|err| crate::error::Error::NagaBackGlslError(err))?;
+                entry_points.push(GlslEntryPoint {
+                    name: entry_point.name.clone(),
+                    code,
+                });
+            }
+            Ok(ECompiledShaderCode::GlslEs(entry_points))
+        }
+        EShaderTarget::Msl => {
+            let options = naga::back::msl::Options {
+                lang_version: (2, 4),
+                ..Default::default()
+            };
+            let (code, _) = naga::back::msl::write_string(
+                module,
+                &module_info,
+                &options,
+                &naga::back::msl::PipelineOptions::default(),
+            )
+            .map_err(|err| crate::error::Error::NagaBackMslError(err))?;
+            Ok(ECompiledShaderCode::Msl(code))
+        }
+    }
+}
+
+/// The source of the precompiled shader if the backend can consume it.
+/// MSL and GLSL ES are only accepted by the shader passthrough of wgpu, which is not enabled
+/// and doesn't support GLSL, so only SPIR-V is used on Vulkan.
+pub fn to_shader_source(
+    code: &ECompiledShaderCode,
+    backend: wgpu::Backend,
+) -> Option<wgpu::ShaderSource<'static>> {
+    match (code, backend) {
+        (ECompiledShaderCode::SpirV(spv), wgpu::Backend::Vulkan) => Some(
+            wgpu::ShaderSource::SpirV(std::borrow::Cow::Owned(spv.clone())),
+        ),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RUNTIME_SHADER_TARGETS, cross_compile, get_shader_targets, to_shader_source};
+    use crate::global_shaders::get_buildin_shaders;
+    use rs_artifact::compiled_shader::{ECompiledShaderCode, EShaderTarget, GlslEntryPoint};
+    use rs_core_minimal::settings::Backends;
+
+    /// `textureLoad` from depth textures is not supported in GLSL.
+    const GLSL_ES_UNSUPPORTED: [&str; 2] =
+        ["Depth32FloatConvertRGBA8Unorm.wgsl", "box_culling.wgsl"];
+
+    #[test]
+    fn test_cross_compile_builtin_shaders() {
+        for buildin_shader in get_buildin_shaders() {
+            for (name, description) in buildin_shader.get_permutation_descriptions() {
+                let code = rs_shader_compiler_core::pre_process::pre_process(
+                    &description.shader_path,
+                    description.include_dirs.iter(),
+                    description.definitions.iter(),
+                )
+                .unwrap();
+                let module = naga::front::wgsl::parse_str(&code).unwrap();
+                for target in [
+                    EShaderTarget::SpirV,
+                    EShaderTarget::GlslEs,
+                    EShaderTarget::Msl,
+                ] {
+                    let result = cross_compile(&module, target);
+                    if target == EShaderTarget::GlslEs
+                        && GLSL_ES_UNSUPPORTED.contains(&name.as_str())
+                    {
+                        assert!(result.is_err(), "{name} {target:?}");
+                        continue;
+                    }
+                    match result {
+                        Ok(ECompiledShaderCode::SpirV(spv)) => {
+                            assert_eq!(target, EShaderTarget::SpirV);
+                            assert_eq!(spv.first(), Some(&0x07230203));
+                        }
+                        Ok(ECompiledShaderCode::GlslEs(entry_points)) => {
+                            assert_eq!(target, EShaderTarget::GlslEs);
+                            assert_eq!(entry_points.len(), module.entry_points.len());
+                            assert!(
+                                entry_points
+                                    .iter()
+                                    .all(|x| x.code.starts_with("#version 310 es"))
+                            );
+                        }
+                        Ok(ECompiledShaderCode::Msl(code)) => {
+                            assert_eq!(target, EShaderTarget::Msl);
+                            assert!(!code.is_empty());
+                        }
+                        Err(err) => panic!("{name} {target:?}, {err}"),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_to_shader_source() {
+        let spv = ECompiledShaderCode::SpirV(vec![0x07230203]);
+        assert!(matches!(
+            to_shader_source(&spv, wgpu::Backend::Vulkan),
+            Some(wgpu::ShaderSource::SpirV(_))
+        ));
+        assert!(to_shader_source(&spv, wgpu::Backend::Metal).is_none());
+        assert!(to_shader_source(&spv, wgpu::Backend::Dx12).is_none());
+        assert!(to_shader_source(&spv, wgpu::Backend::Gl).is_none());
+
+        let msl = ECompiledShaderCode::Msl(String::new());
+        assert!(to_shader_source(&msl, wgpu::Backend::Metal).is_none());
+        assert!(to_shader_source(&msl, wgpu::Backend::Vulkan).is_none());
+
+        let glsl = ECompiledShaderCode::GlslEs(vec![GlslEntryPoint {
+            name: "main".to_string(),
+            code: String::new(),
+        }]);
+        assert!(to_shader_source(&glsl, wgpu::Backend::Gl).is_none());
+
+        for backends in [
+            Backends::Primary,
+            Backends::Vulkan,
+            Backends::GL,
+            Backends::DX12,
+        ] {
+            for target in get_shader_targets(&backends) {
+                assert!(RUNTIME_SHADER_TARGETS.contains(&target), "{target:?}");
+            }
+        }
+    }
+}
@@ -18,6 +18,8 @@ pub enum Error {
     Wgpu(Mutex<wgpu::Error>),
     ValidationError(naga::WithSpan<naga::valid::ValidationError>),
     NagaBackSpirVError(naga::back::spv::Error),
+    NagaBackGlslError(naga::back::glsl::Error),
+    NagaBackMslError(naga::back::msl::Error),
     RenderCore(rs_render_core::error::Error),
     Other(Option<String>),
 }
@@ -1,7 +1,13 @@
-use super::{global_shader::GlobalShader, skeleton_shading::NUM_MAX_BONE};
+use super::{
+    global_shader::GlobalShader,
+    permutation::{Permutation, PermutationDimension},
+    skeleton_shading::{SKELETON_MAX_BONES, skeleton_dimension},
+};
 use crate::get_buildin_shader_dir;
 use rs_shader_compiler_core::pre_process::ShaderDescription;
 
+pub const PLAYER_VIEW: &str = "PLAYER_VIEW";
+
 pub struct DepthShader {}
 
 impl GlobalShader for DepthShader {
@@ -17,21 +23,16 @@ impl GlobalShader for DepthShader {
     fn get_name(&self) -> String {
         "DepthShader.wgsl".to_string()
     }
-}
-
-pub struct DepthSkinShader {}
 
-impl GlobalShader for DepthSkinShader {
-    fn get_shader_description(&self) -> ShaderDescription {
-        let shader_description = ShaderDescription {
-            shader_path: get_buildin_shader_dir().join("depth.wgsl"),
-            include_dirs: vec![],
-            definitions: vec![format!("SKELETON_MAX_BONES={}", NUM_MAX_BONE)],
-        };
-        shader_description
+    fn get_permutation_dimensions(&self) -> Vec<PermutationDimension> {
+        vec![
+            skeleton_dimension(),
+            PermutationDimension::new_bool(PLAYER_VIEW),
+        ]
     }
 
-    fn get_name(&self) -> String {
-        "DepthSkinShader.wgsl".to_string()
+    fn should_compile_permutation(&self, permutation: &Permutation) -> bool {
+        // The depth of the player view is only drawn for the multiple resolution meshes.
+        !(permutation.get_bool(SKELETON_MAX_BONES) && permutation.get_bool(PLAYER_VIEW))
     }
 }
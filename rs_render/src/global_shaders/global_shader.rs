@@ -1,3 +1,4 @@
+use super::permutation::{Permutation, PermutationDimension};
 use rs_shader_compiler_core::pre_process::ShaderDescription;
 #[cfg(feature = "editor")]
 mod editor_mod {
@@ -19,6 +20,48 @@ pub trait GlobalShader {
         let _ = limits;
         true
     }
+    /// The features of the shader, each permutation of them is compiled into a separate shader.
+    fn get_permutation_dimensions(&self) -> Vec<PermutationDimension> {
+        vec![]
+    }
+    /// Returns false to skip compiling the permutation which is never used.
+    fn should_compile_permutation(&self, permutation: &Permutation) -> bool {
+        let _ = permutation;
+        true
+    }
+    fn get_permutations(&self) -> Vec<Permutation> {
+        Permutation::enumerate(&self.get_permutation_dimensions())
+            .into_iter()
+            .filter(|x| self.should_compile_permutation(x))
+            .collect()
+    }
+    fn get_permutation_name(&self, permutation: &Permutation) -> String {
+        permutation
+            .normalize(&self.get_permutation_dimensions())
+            .get_name(&self.get_name())
+    }
+    fn get_permutation_shader_description(&self, permutation: &Permutation) -> ShaderDescription {
+        let dimensions = self.get_permutation_dimensions();
+        let mut shader_description = self.get_shader_description();
+        shader_description.definitions.extend(
+            permutation
+                .normalize(&dimensions)
+                .get_definitions(&dimensions),
+        );
+        shader_description
+    }
+    /// The name and the description of every permutation to compile.
+    fn get_permutation_descriptions(&self) -> Vec<(String, ShaderDescription)> {
+        self.get_permutations()
+            .iter()
+            .map(|x| {
+                (
+                    self.get_permutation_name(x),
+                    self.get_permutation_shader_description(x),
+                )
+            })
+            .collect()
+    }
     #[cfg(feature = "editor")]
     fn to_compile_command(&self) -> CompileCommand {
         let shader_description = self.get_shader_description();
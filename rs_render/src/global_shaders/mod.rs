@@ -15,12 +15,12 @@ pub mod mesh_view;
 pub mod mesh_view_multiple_draw;
 pub mod panorama_to_cube;
 pub mod particle;
+pub mod permutation;
 pub mod pre_filter_environment_cube_map;
 pub mod primitive;
 pub mod sdf2d_preprocess;
 pub mod shading;
 pub mod skeleton_shading;
-pub mod view_depth;
 pub mod virtual_texture_clean;
 pub mod virtual_texture_feed_back;
 
@@ -29,7 +29,7 @@ use attachment::AttachmentShader;
 use blit::BlitShader;
 use box_culling::BoxCullingShader;
 use brdf_lut::BrdfLutShader;
-use depth::{DepthShader, DepthSkinShader};
+use depth::DepthShader;
 use format_conversion::Depth32FloatConvertRGBA8UnormShader;
 use fxaa::FXAAShader;
 use grid::GridShader;
@@ -45,12 +45,8 @@ use pre_filter_environment_cube_map::PreFilterEnvironmentCubeMapShader;
 use primitive::PrimitiveShader;
 use sdf2d_preprocess::Sdf2dPreprocessShader;
 use shading::ShadingShader;
-use skeleton_shading::SkeletonShadingShader;
-use view_depth::ViewDepthShader;
 use virtual_texture_clean::VirtualTextureCleanShader;
-use virtual_texture_feed_back::{
-    SkinMeshVirtualTextureFeedBackShader, StaticMeshVirtualTextureFeedBackShader,
-};
+use virtual_texture_feed_back::VirtualTextureFeedBackShader;
 
 pub fn get_buildin_shaders() -> Vec<Box<dyn GlobalShader>> {
     vec![
@@ -60,10 +56,8 @@ pub fn get_buildin_shaders() -> Vec<Box<dyn GlobalShader>> {
         Box::new(IrradianceCubeMapShader {}),
         Box::new(PreFilterEnvironmentCubeMapShader {}),
         Box::new(ShadingShader {}),
-        Box::new(SkeletonShadingShader {}),
         Box::new(GridShader {}),
-        Box::new(StaticMeshVirtualTextureFeedBackShader {}),
-        Box::new(SkinMeshVirtualTextureFeedBackShader {}),
+        Box::new(VirtualTextureFeedBackShader {}),
         Box::new(VirtualTextureCleanShader {}),
         Box::new(Sdf2dPreprocessShader {}),
         Box::new(JFAShader {}),
@@ -71,13 +65,11 @@ pub fn get_buildin_shaders() -> Vec<Box<dyn GlobalShader>> {
         Box::new(MeshViewShader {}),
         Box::new(MeshViewMultipleDrawShader {}),
         Box::new(DepthShader {}),
-        Box::new(DepthSkinShader {}),
         Box::new(Depth32FloatConvertRGBA8UnormShader {}),
         Box::new(FXAAShader {}),
         Box::new(ParticleShader {}),
         Box::new(PrimitiveShader {}),
         Box::new(LightCullingShader {}),
-        Box::new(ViewDepthShader {}),
         Box::new(BoxCullingShader {}),
         Box::new(BlitShader {}),
     ]
@@ -0,0 +1,282 @@
+use std::collections::BTreeMap;
+
+/// A feature of a shader which can be switched on or off, or set to one of the values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PermutationDimension {
+    /// `name` is defined as `value` when the feature is enabled, and is undefined otherwise.
+    Bool { name: String, value: Option<String> },
+    /// `name` is defined as one of `values`, the first one is the default.
+    Enum { name: String, values: Vec<String> },
+}
+
+impl PermutationDimension {
+    pub fn new_bool(name: impl ToString) -> PermutationDimension {
+        PermutationDimension::Bool {
+            name: name.to_string(),
+            value: None,
+        }
+    }
+
+    pub fn new_bool_with_value(name: impl ToString, value: impl ToString) -> PermutationDimension {
+        PermutationDimension::Bool {
+            name: name.to_string(),
+            value: Some(value.to_string()),
+        }
+    }
+
+    pub fn new_enum(
+        name: impl ToString,
+        values: impl IntoIterator<Item = impl ToString>,
+    ) -> PermutationDimension {
+        PermutationDimension::Enum {
+            name: name.to_string(),
+            values: values.into_iter().map(|x| x.to_string()).collect(),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            PermutationDimension::Bool { name, .. } => name,
+            PermutationDimension::Enum { name, .. } => name,
+        }
+    }
+
+    fn get_values(&self) -> Vec<PermutationValue> {
+        match self {
+            PermutationDimension::Bool { .. } => {
+                vec![PermutationValue::Bool(false), PermutationValue::Bool(true)]
+            }
+            PermutationDimension::Enum { values, .. } => values
+                .iter()
+                .map(|x| PermutationValue::Enum(x.clone()))
+                .collect(),
+        }
+    }
+
+    fn is_valid_value(&self, value: &PermutationValue) -> bool {
+        match (self, value) {
+            (PermutationDimension::Bool { .. }, PermutationValue::Bool(_)) => true,
+            (PermutationDimension::Enum { values, .. }, PermutationValue::Enum(value)) => {
+                values.contains(value)
+            }
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PermutationValue {
+    Bool(bool),
+    Enum(String),
+}
+
+/// The values of the dimensions of a shader, the missing dimensions take the default values.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Permutation {
+    values: BTreeMap<String, PermutationValue>,
+}
+
+impl Permutation {
+    pub fn new() -> Permutation {
+        Permutation::default()
+    }
+
+    pub fn with_bool(mut self, name: impl ToString, value: bool) -> Permutation {
+        self.values
+            .insert(name.to_string(), PermutationValue::Bool(value));
+        self
+    }
+
+    pub fn with_enum(mut self, name: impl ToString, value: impl ToString) -> Permutation {
+        self.values
+            .insert(name.to_string(), PermutationValue::Enum(value.to_string()));
+        self
+    }
+
+    pub fn get_bool(&self, name: &str) -> bool {
+        matches!(self.values.get(name), Some(PermutationValue::Bool(true)))
+    }
+
+    pub fn get_enum(&self, name: &str) -> Option<&str> {
+        match self.values.get(name) {
+            Some(PermutationValue::Enum(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Every dimension gets a valid value, the unknown or invalid values are replaced by the defaults.
+    pub fn normalize(&self, dimensions: &[PermutationDimension]) -> Permutation {
+        let mut values = BTreeMap::new();
+        for dimension in dimensions {
+            let value = self
+                .values
+                .get(dimension.get_name())
+                .filter(|x| dimension.is_valid_value(x))
+                .cloned()
+                .or_else(|| dimension.get_values().into_iter().next());
+            if let Some(value) = value {
+                values.insert(dimension.get_name().to_string(), value);
+            }
+        }
+        Permutation { values }
+    }
+
+    /// All combinations of the values of the dimensions.
+    pub fn enumerate(dimensions: &[PermutationDimension]) -> Vec<Permutation> {
+        let mut permutations = vec![Permutation::new()];
+        for dimension in dimensions {
+            let mut next = Vec::with_capacity(permutations.len() * dimension.get_values().len());
+            for permutation in &permutations {
+                for value in dimension.get_values() {
+                    let mut permutation = permutation.clone();
+                    permutation
+                        .values
+                        .insert(dimension.get_name().to_string(), value);
+                    next.push(permutation);
+                }
+            }
+            permutations = next;
+        }
+        permutations
+    }
+
+    /// The definitions passed to the preprocessor, such as `NAME` or `NAME=value`.
+    pub fn get_definitions(&self, dimensions: &[PermutationDimension]) -> Vec<String> {
+        let mut definitions = vec![];
+        for dimension in dimensions {
+            match (dimension, self.values.get(dimension.get_name())) {
+                (
+                    PermutationDimension::Bool { name, value },
+                    Some(PermutationValue::Bool(true)),
+                ) => {
+                    definitions.push(match value {
+                        Some(value) => format!("{name}={value}"),
+                        None => name.clone(),
+                    });
+                }
+                (PermutationDimension::Enum { name, .. }, Some(PermutationValue::Enum(value))) => {
+                    definitions.push(format!("{name}={value}"));
+                }
+                _ => {}
+            }
+        }
+        definitions
+    }
+
+    /// Appends the enabled features to the stem of `base_name`,
+    /// for example, `depth.wgsl` becomes `depth_PLAYER_VIEW_QUALITY-HIGH.wgsl`.
+    pub fn get_name(&self, base_name: &str) -> String {
+        let mut suffix = String::new();
+        for (name, value) in &self.values {
+            match value {
+                PermutationValue::Bool(true) => suffix += &format!("_{name}"),
+                PermutationValue::Bool(false) => {}
+                PermutationValue::Enum(value) => suffix += &format!("_{name}-{value}"),
+            }
+        }
+        match base_name.rsplit_once('.') {
+            Some((stem, extension)) => format!("{stem}{suffix}.{extension}"),
+            None => format!("{base_name}{suffix}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Permutation, PermutationDimension};
+    use crate::global_shaders::{
+        depth::{DepthShader, PLAYER_VIEW},
+        global_shader::GlobalShader,
+        skeleton_shading::{SKELETON_MAX_BONES, skeleton_permutation},
+    };
+
+    fn dimensions() -> Vec<PermutationDimension> {
+        vec![
+            PermutationDimension::new_bool("A"),
+            PermutationDimension::new_enum("QUALITY", ["LOW", "HIGH"]),
+        ]
+    }
+
+    #[test]
+    fn test_enumerate() {
+        let permutations = Permutation::enumerate(&dimensions());
+        assert_eq!(permutations.len(), 4);
+        for permutation in &permutations {
+            assert_eq!(permutation.normalize(&dimensions()), *permutation);
+        }
+        assert!(
+            permutations.contains(
+                &Permutation::new()
+                    .with_bool("A", true)
+                    .with_enum("QUALITY", "HIGH")
+            )
+        );
+        assert_eq!(Permutation::enumerate(&[]), vec![Permutation::new()]);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let permutation = Permutation::new()
+            .with_enum("QUALITY", "ULTRA")
+            .with_bool("UNKNOWN", true)
+            .normalize(&dimensions());
+        assert_eq!(
+            permutation,
+            Permutation::new()
+                .with_bool("A", false)
+                .with_enum("QUALITY", "LOW")
+        );
+        let permutation = Permutation::new()
+            .with_enum("A", "HIGH")
+            .normalize(&dimensions());
+        assert!(!permutation.get_bool("A"));
+        assert_eq!(permutation.get_enum("QUALITY"), Some("LOW"));
+    }
+
+    #[test]
+    fn test_get_name() {
+        let permutation = Permutation::new().normalize(&dimensions());
+        assert_eq!(permutation.get_name("depth.wgsl"), "depth_QUALITY-LOW.wgsl");
+        let permutation = Permutation::new()
+            .with_bool("A", true)
+            .with_enum("QUALITY", "HIGH")
+            .normalize(&dimensions());
+        assert_eq!(
+            permutation.get_name("depth.wgsl"),
+            "depth_A_QUALITY-HIGH.wgsl"
+        );
+        assert_eq!(permutation.get_name("depth"), "depth_A_QUALITY-HIGH");
+        assert_eq!(
+            permutation.get_definitions(&dimensions()),
+            vec!["A".to_string(), "QUALITY=HIGH".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_should_compile_permutation() {
+        let shader = DepthShader {};
+        let permutations = shader.get_permutations();
+        assert_eq!(permutations.len(), 3);
+        let pruned = skeleton_permutation(true).with_bool(PLAYER_VIEW, true);
+        assert!(!shader.should_compile_permutation(&pruned));
+        assert!(!permutations.contains(&pruned.normalize(&shader.get_permutation_dimensions())));
+        assert!(shader.should_compile_permutation(&skeleton_permutation(true)));
+        assert!(
+            shader.should_compile_permutation(&Permutation::new().with_bool(PLAYER_VIEW, true))
+        );
+
+        let names: Vec<String> = permutations
+            .iter()
+            .map(|x| shader.get_permutation_name(x))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "DepthShader.wgsl".to_string(),
+                "DepthShader_PLAYER_VIEW.wgsl".to_string(),
+                format!("DepthShader_{SKELETON_MAX_BONES}.wgsl"),
+            ]
+        );
+        assert!(!names.contains(&shader.get_permutation_name(&pruned)));
+    }
+}
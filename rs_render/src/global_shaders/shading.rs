@@ -1,4 +1,7 @@
-use super::global_shader::GlobalShader;
+use super::{
+    global_shader::GlobalShader, permutation::PermutationDimension,
+    skeleton_shading::skeleton_dimension,
+};
 use crate::get_buildin_shader_dir;
 use rs_shader_compiler_core::pre_process::ShaderDescription;
 
@@ -15,6 +18,10 @@ impl GlobalShader for ShadingShader {
     }
 
     fn get_name(&self) -> String {
-        "phong_static_shading.wgsl".to_string()
+        "phong_shading.wgsl".to_string()
+    }
+
+    fn get_permutation_dimensions(&self) -> Vec<PermutationDimension> {
+        vec![skeleton_dimension()]
     }
 }
@@ -1,22 +1,14 @@
-use super::global_shader::GlobalShader;
-use crate::get_buildin_shader_dir;
-use rs_shader_compiler_core::pre_process::ShaderDescription;
+use super::permutation::{Permutation, PermutationDimension};
 
 pub const NUM_MAX_BONE: usize = 255;
 
-pub struct SkeletonShadingShader {}
+pub const SKELETON_MAX_BONES: &str = "SKELETON_MAX_BONES";
 
-impl GlobalShader for SkeletonShadingShader {
-    fn get_shader_description(&self) -> ShaderDescription {
-        let shader_description = ShaderDescription {
-            shader_path: get_buildin_shader_dir().join("phong_shading.wgsl"),
-            include_dirs: vec![],
-            definitions: vec![format!("SKELETON_MAX_BONES={NUM_MAX_BONE}")],
-        };
-        shader_description
-    }
+/// Defines `SKELETON_MAX_BONES` for the shaders of the skin meshes.
+pub fn skeleton_dimension() -> PermutationDimension {
+    PermutationDimension::new_bool_with_value(SKELETON_MAX_BONES, NUM_MAX_BONE)
+}
 
-    fn get_name(&self) -> String {
-        "phong_skeleton_shading.wgsl".to_string()
-    }
+pub fn skeleton_permutation(is_skin: bool) -> Permutation {
+    Permutation::new().with_bool(SKELETON_MAX_BONES, is_skin)
 }
@@ -1,15 +1,18 @@
-use super::{global_shader::GlobalShader, skeleton_shading::NUM_MAX_BONE};
+use super::{
+    global_shader::GlobalShader, permutation::PermutationDimension,
+    skeleton_shading::skeleton_dimension,
+};
 use crate::get_buildin_shader_dir;
 use rs_shader_compiler_core::pre_process::ShaderDescription;
 
-pub struct SkinMeshVirtualTextureFeedBackShader {}
+pub struct VirtualTextureFeedBackShader {}
 
-impl GlobalShader for SkinMeshVirtualTextureFeedBackShader {
+impl GlobalShader for VirtualTextureFeedBackShader {
     fn get_shader_description(&self) -> ShaderDescription {
         let shader_description = ShaderDescription {
             shader_path: get_buildin_shader_dir().join("virtual_texture_feed_back.wgsl"),
             include_dirs: vec![],
-            definitions: vec![format!("SKELETON_MAX_BONES={NUM_MAX_BONE}")],
+            definitions: vec![],
         };
         shader_description
     }
@@ -17,21 +20,8 @@ impl GlobalShader for SkinMeshVirtualTextureFeedBackShader {
     fn get_name(&self) -> String {
         "virtual_texture_feed_back.wgsl".to_string()
     }
-}
 
-pub struct StaticMeshVirtualTextureFeedBackShader {}
-
-impl GlobalShader for StaticMeshVirtualTextureFeedBackShader {
-    fn get_shader_description(&self) -> ShaderDescription {
-        let shader_description = ShaderDescription {
-            shader_path: get_buildin_shader_dir().join("virtual_texture_feed_back.wgsl"),
-            include_dirs: vec![],
-            definitions: vec![],
-        };
-        shader_description
-    }
-
-    fn get_name(&self) -> String {
-        "static_mesh_virtual_texture_feed_back.wgsl".to_string()
+    fn get_permutation_dimensions(&self) -> Vec<PermutationDimension> {
+        vec![skeleton_dimension()]
     }
 }
@@ -9,6 +9,7 @@ pub mod bind_group_layout_entry_hook;
 pub mod command;
 pub mod compute_pipeline;
 pub mod constants;
+pub mod cross_compile;
 pub mod cube_map;
 pub mod default_textures;
 pub mod depth_texture;
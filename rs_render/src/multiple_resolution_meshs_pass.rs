@@ -2,7 +2,11 @@ use crate::{
     VertexBufferType,
     base_render_pipeline_pool::BaseRenderPipelinePool,
     compute_pipeline::box_culling::{AABB, BoxCullingPipeline},
-    global_shaders::{global_shader::GlobalShader, view_depth::ViewDepthShader},
+    global_shaders::{
+        depth::{DepthShader, PLAYER_VIEW},
+        global_shader::GlobalShader,
+        permutation::Permutation,
+    },
     gpu_vertex_buffer::{Draw, EDrawCallType, GpuVertexBufferImp},
     misc::find_or_insert_bind_groups,
     multi_res_mesh::MultipleResolutionMesh,
@@ -40,7 +44,7 @@ impl MultipleResolutionMeshsPass {
     ) -> Option<MultipleResolutionMeshsPass> {
         let cluster_collection_resources = HashMap::new();
         let depth_pipeline = GenericPipeline::standard_depth_only(
-            ViewDepthShader {}.get_name(),
+            DepthShader {}.get_permutation_name(&Permutation::new().with_bool(PLAYER_VIEW, true)),
             device,
             shader_library,
             pool,
@@ -1,12 +1,67 @@
 use crate::{VertexBufferType, shader_library::ShaderLibrary};
-use naga::*;
+use naga::{common::wgsl::TypeContext, *};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use wgpu::*;
 
-#[derive(Debug, Clone)]
+/// Increase it when `ReflectionDescription` changes, the descriptions of other versions are ignored.
+pub const REFLECTION_DESCRIPTION_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EntryPointDescription {
+    pub name: String,
+    pub workgroup_size: [u32; 3],
+}
+
+impl From<&naga::EntryPoint> for EntryPointDescription {
+    fn from(value: &naga::EntryPoint) -> Self {
+        EntryPointDescription {
+            name: value.name.clone(),
+            workgroup_size: value.workgroup_size,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum EPipelineType {
-    Render(EntryPoint, EntryPoint),
-    Compute(EntryPoint),
+    Render(EntryPointDescription, EntryPointDescription),
+    Compute(EntryPointDescription),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UniformMemberLayout {
+    pub name: Option<String>,
+    pub type_name: String,
+    pub offset: u32,
+    pub size: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UniformLayout {
+    pub group: u32,
+    pub binding: u32,
+    pub name: Option<String>,
+    pub type_name: String,
+    pub size: u32,
+    pub members: Vec<UniformMemberLayout>,
+}
+
+/// Everything needed to create the pipeline of a shader,
+/// which is written by the shader compiler, so that the shader is not reflected on every launch.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ReflectionDescription {
+    pub version: u32,
+    pub pipeline_type: EPipelineType,
+    pub vertex_attributes: Vec<wgpu::VertexAttribute>,
+    pub array_stride: u64,
+    pub bind_group_layout_entrys: Vec<Vec<BindGroupLayoutEntry>>,
+    pub uniform_layouts: Vec<UniformLayout>,
 }
 
 pub struct VertexBufferLayoutBuilder {
@@ -56,11 +111,9 @@ impl VertexBufferLayoutBuilder {
 }
 
 pub struct Reflection {
-    module: Module,
-    vertex_attributes: Vec<wgpu::VertexAttribute>,
-    array_stride: u64,
-    pipeline_type: EPipelineType,
-    bind_group_layout_entrys: Vec<Vec<BindGroupLayoutEntry>>,
+    /// None if the reflection is created from the description.
+    module: Option<Module>,
+    description: ReflectionDescription,
 }
 
 impl Reflection {
@@ -83,9 +136,12 @@ impl Reflection {
         let pipeline_type: EPipelineType;
 
         if let Some(render_entry_points) = render_entry_points {
-            pipeline_type = EPipelineType::Render(render_entry_points.0, render_entry_points.1);
+            pipeline_type = EPipelineType::Render(
+                EntryPointDescription::from(&render_entry_points.0),
+                EntryPointDescription::from(&render_entry_points.1),
+            );
         } else if let Some(cs_entry_point) = cs_entry_point {
-            pipeline_type = EPipelineType::Compute(cs_entry_point);
+            pipeline_type = EPipelineType::Compute(EntryPointDescription::from(&cs_entry_point));
         } else {
             return Err(crate::error::Error::ShaderNotSupported(None));
         }
@@ -93,18 +149,53 @@ impl Reflection {
         let (vertex_attributes, array_stride) = Self::extract_vertex_attributes(&module);
         let bind_group_layout_entrys =
             Self::extract_bind_group_layout_entrys(&module, &pipeline_type);
+        let uniform_layouts = Self::extract_uniform_layouts(&module);
 
         let reflection = Reflection {
-            module,
-            vertex_attributes,
-            array_stride,
-            pipeline_type,
-            bind_group_layout_entrys,
+            module: Some(module),
+            description: ReflectionDescription {
+                version: REFLECTION_DESCRIPTION_VERSION,
+                pipeline_type,
+                vertex_attributes,
+                array_stride,
+                bind_group_layout_entrys,
+                uniform_layouts,
+            },
         };
 
         Ok(reflection)
     }
 
+    /// Uses the cached description of the module unless it is missing or its version is outdated.
+    pub fn from_cache(
+        name: &str,
+        module: &naga::Module,
+        description: Option<ReflectionDescription>,
+    ) -> crate::error::Result<Reflection> {
+        match description {
+            Some(description) if description.version == REFLECTION_DESCRIPTION_VERSION => {
+                Ok(Self::from_description(description))
+            }
+            Some(description) => {
+                log::warn!(
+                    "{}, the version of the reflection {} is outdated, expected {}.",
+                    name,
+                    description.version,
+                    REFLECTION_DESCRIPTION_VERSION
+                );
+                Self::from_naga_module(module.clone(), false)
+            }
+            None => Self::from_naga_module(module.clone(), false),
+        }
+    }
+
+    pub fn from_description(description: ReflectionDescription) -> Reflection {
+        Reflection {
+            module: None,
+            description,
+        }
+    }
+
     pub fn make_vertex_buffer_layout_builder(
         &self,
         vertex_buffer_type: VertexBufferType,
@@ -114,7 +205,7 @@ impl Reflection {
                 let mut vertex_attributes_layout: Vec<Vec<VertexAttribute>> =
                     Vec::with_capacity(verifications.len());
                 let mut index: usize = 0;
-                let mut _vertex_attributes = self.vertex_attributes.clone();
+                let mut _vertex_attributes = self.description.vertex_attributes.clone();
 
                 for verification in verifications {
                     let mut vertex_attributes: Vec<VertexAttribute> =
@@ -150,7 +241,7 @@ impl Reflection {
             }
             VertexBufferType::Noninterleaved => {
                 let mut noninterleaved_vertex_attributes = Vec::<Vec<wgpu::VertexAttribute>>::new();
-                for mut vertex_attribute in self.vertex_attributes.to_vec() {
+                for mut vertex_attribute in self.description.vertex_attributes.to_vec() {
                     vertex_attribute.offset = 0;
                     noninterleaved_vertex_attributes.push(vec![vertex_attribute]);
                 }
@@ -513,6 +604,42 @@ impl Reflection {
         bind_group_layout_entrys
     }
 
+    fn extract_uniform_layouts(module: &naga::Module) -> Vec<UniformLayout> {
+        let gctx = module.to_ctx();
+        let mut uniform_layouts = Vec::new();
+        for (_, global_variable) in module.global_variables.iter() {
+            if !matches!(global_variable.space, AddressSpace::Uniform) {
+                continue;
+            }
+            let Some(binding) = &global_variable.binding else {
+                continue;
+            };
+            let ty = &module.types[global_variable.ty];
+            let members = match &ty.inner {
+                TypeInner::Struct { members, .. } => members
+                    .iter()
+                    .map(|member| UniformMemberLayout {
+                        name: member.name.clone(),
+                        type_name: gctx.type_to_string(member.ty),
+                        offset: member.offset,
+                        size: module.types[member.ty].inner.size(gctx),
+                    })
+                    .collect(),
+                _ => vec![],
+            };
+            uniform_layouts.push(UniformLayout {
+                group: binding.group,
+                binding: binding.binding,
+                name: global_variable.name.clone(),
+                type_name: gctx.type_to_string(global_variable.ty),
+                size: ty.inner.size(gctx),
+                members,
+            });
+        }
+        uniform_layouts.sort_by_key(|x| (x.group, x.binding));
+        uniform_layouts
+    }
+
     fn image_dimension2texture_dimension(
         image_dimension: ImageDimension,
         arrayed: bool,
@@ -591,18 +718,26 @@ impl Reflection {
     }
 
     pub fn get_array_stride(&self) -> u64 {
-        self.array_stride
+        self.description.array_stride
     }
 
-    pub fn get_module(&self) -> &Module {
-        &self.module
+    pub fn get_module(&self) -> Option<&Module> {
+        self.module.as_ref()
     }
 
     pub fn get_bind_group_layout_entrys(&self) -> &[Vec<BindGroupLayoutEntry>] {
-        self.bind_group_layout_entrys.as_ref()
+        self.description.bind_group_layout_entrys.as_ref()
     }
 
     pub fn get_pipeline_type(&self) -> &EPipelineType {
-        &self.pipeline_type
+        &self.description.pipeline_type
+    }
+
+    pub fn get_uniform_layouts(&self) -> &[UniformLayout] {
+        &self.description.uniform_layouts
+    }
+
+    pub fn get_description(&self) -> &ReflectionDescription {
+        &self.description
     }
 }
@@ -2,7 +2,9 @@ use crate::{
     VertexBufferType,
     base_render_pipeline::{BaseRenderPipeline, ColorAttachment},
     base_render_pipeline_pool::BaseRenderPipelineBuilder,
-    global_shaders::{global_shader::GlobalShader, shading::ShadingShader},
+    global_shaders::{
+        global_shader::GlobalShader, shading::ShadingShader, skeleton_shading::skeleton_permutation,
+    },
     gpu_vertex_buffer::GpuVertexBufferImp,
     shader_library::ShaderLibrary,
     vertex_data_type::mesh_vertex::*,
@@ -44,7 +46,7 @@ impl ShadingPipeline {
             blend: Some(BlendState::ALPHA_BLENDING),
             write_mask: ColorWrites::ALL,
         })];
-        builder.shader_name = ShadingShader {}.get_name();
+        builder.shader_name = ShadingShader {}.get_permutation_name(&skeleton_permutation(false));
         builder.depth_stencil = Some(DepthStencilState {
             depth_compare: Some(CompareFunction::Less),
             format: TextureFormat::Depth32Float,
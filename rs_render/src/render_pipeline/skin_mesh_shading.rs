@@ -4,7 +4,8 @@ use crate::{
     base_render_pipeline_pool::{BaseRenderPipelineBuilder, BaseRenderPipelinePool},
     global_shaders::{
         global_shader::GlobalShader,
-        skeleton_shading::{NUM_MAX_BONE, SkeletonShadingShader},
+        shading::ShadingShader,
+        skeleton_shading::{NUM_MAX_BONE, skeleton_permutation},
     },
     gpu_vertex_buffer::GpuVertexBufferImp,
     shader_library::ShaderLibrary,
@@ -69,7 +70,7 @@ impl SkinMeshShadingPipeline {
             blend: Some(BlendState::ALPHA_BLENDING),
             write_mask: ColorWrites::ALL,
         })];
-        builder.shader_name = SkeletonShadingShader {}.get_name();
+        builder.shader_name = ShadingShader {}.get_permutation_name(&skeleton_permutation(true));
         builder.depth_stencil = Some(DepthStencilState {
             depth_compare: Some(CompareFunction::Less),
             format: TextureFormat::Depth32Float,
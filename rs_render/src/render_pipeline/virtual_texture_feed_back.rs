@@ -3,8 +3,8 @@ use crate::{
     base_render_pipeline::{BaseRenderPipeline, ColorAttachment},
     base_render_pipeline_pool::BaseRenderPipelineBuilder,
     global_shaders::{
-        global_shader::GlobalShader,
-        virtual_texture_feed_back::StaticMeshVirtualTextureFeedBackShader,
+        global_shader::GlobalShader, skeleton_shading::skeleton_permutation,
+        virtual_texture_feed_back::VirtualTextureFeedBackShader,
     },
     gpu_vertex_buffer::GpuVertexBufferImp,
     shader_library::ShaderLibrary,
@@ -26,7 +26,8 @@ impl StaticMeshVirtualTextureFeedBackPipeline {
     ) -> StaticMeshVirtualTextureFeedBackPipeline {
         let mut builder = BaseRenderPipelineBuilder::default();
         builder.targets = vec![Some(texture_format.clone().into())];
-        builder.shader_name = StaticMeshVirtualTextureFeedBackShader {}.get_name();
+        builder.shader_name =
+            VirtualTextureFeedBackShader {}.get_permutation_name(&skeleton_permutation(false));
         builder.depth_stencil = Some(DepthStencilState {
             depth_compare: Some(CompareFunction::Less),
             format: TextureFormat::Depth32Float,
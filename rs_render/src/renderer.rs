@@ -21,11 +21,12 @@ use crate::render_pipeline::particle_pipeline::ParticlePipeline;
 use crate::render_pipeline::primitive::PrimitiveRenderPipeline;
 use crate::render_pipeline::shading::ShadingPipeline;
 use crate::render_pipeline::skin_mesh_shading::SkinMeshShadingPipeline;
-use crate::shader_library::ShaderLibrary;
+use crate::shader_library::{ShaderLibrary, ShaderNagaModule};
 use crate::shadow_pass::ShadowPipelines;
 use crate::virtual_texture_pass::VirtualTexturePass;
 use crate::virtual_texture_source::VirtualTextureSource;
 use crate::{command::*, shadow_pass};
+use rs_artifact::compiled_shader::CompiledShader;
 use rs_core_minimal::settings::{self, RenderSettings};
 use rs_core_minimal::thread_pool::ThreadPool;
 use rs_egui_ext::egui_render::EGUIRenderer;
@@ -130,7 +131,8 @@ impl Renderer {
         surface_width: u32,
         surface_height: u32,
         shaders: HashMap<String, String>,
-        shader_naga_modules: HashMap<String, naga::Module>,
+        shader_naga_modules: HashMap<String, ShaderNagaModule>,
+        compiled_shaders: Vec<CompiledShader>,
         settings: RenderSettings,
     ) -> Result<Renderer> {
         let _span = tracy_client::span!();
@@ -146,9 +148,22 @@ impl Renderer {
             egui_wgpu::RendererOptions::PREDICTABLE,
         );
 
+        let backend = wgpu_context.get_adapter_info().backend;
+        let mut precompiled_shaders: HashMap<String, ShaderSource<'static>> = HashMap::new();
+        for compiled_shader in compiled_shaders {
+            if let Some(shader_source) =
+                crate::cross_compile::to_shader_source(&compiled_shader.code, backend)
+            {
+                precompiled_shaders.insert(compiled_shader.name, shader_source);
+            }
+        }
+
         let mut shader_library = ShaderLibrary::new();
-        let load_shader_results =
-            shader_library.load_shaders_from(shaders, wgpu_context.get_device());
+        let load_shader_results = shader_library.load_shaders_from_precompiled(
+            shaders,
+            precompiled_shaders,
+            wgpu_context.get_device(),
+        );
         Self::dump_load_shader_results(load_shader_results);
         let load_shader_results = shader_library
             .load_shaders_from_naga_module(wgpu_context.get_device(), shader_naga_modules);
@@ -299,7 +314,8 @@ impl Renderer {
         surface_width: u32,
         surface_height: u32,
         shaders: HashMap<String, String>,
-        shader_naga_modules: HashMap<String, wgpu::naga::Module>,
+        shader_naga_modules: HashMap<String, ShaderNagaModule>,
+        compiled_shaders: Vec<CompiledShader>,
         settings: RenderSettings,
     ) -> Result<Renderer>
     where
@@ -354,6 +370,7 @@ impl Renderer {
             surface_height,
             shaders,
             shader_naga_modules,
+            compiled_shaders,
             settings,
         )
     }
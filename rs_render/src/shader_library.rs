@@ -1,5 +1,8 @@
-use super::reflection::Reflection;
-use crate::command::MaterialRenderPipelineHandle;
+use super::reflection::{Reflection, ReflectionDescription};
+use crate::{
+    command::MaterialRenderPipelineHandle,
+    global_shaders::{global_shader::GlobalShader, permutation::Permutation},
+};
 use pollster::FutureExt;
 use rs_core_minimal::thread_pool::ThreadPool;
 use rs_render_types::MaterialOptions;
@@ -9,6 +12,12 @@ use std::{
     sync::{Arc, Mutex},
 };
 
+/// A compiled shader, the reflection is computed from the module if it is not provided.
+pub struct ShaderNagaModule {
+    pub module: naga::Module,
+    pub reflection: Option<ReflectionDescription>,
+}
+
 pub struct ShaderLibrary {
     shader_dic: HashMap<String, Arc<wgpu::ShaderModule>>,
     reflection_dic: HashMap<String, Arc<Reflection>>,
@@ -27,6 +36,20 @@ impl ShaderLibrary {
         shaders: HashMap<K, String>,
         device: &wgpu::Device,
     ) -> HashMap<String, crate::error::Result<()>>
+    where
+        K: AsRef<str>,
+    {
+        self.load_shaders_from_precompiled(shaders, HashMap::new(), device)
+    }
+
+    /// The reflection is still extracted from `shaders`,
+    /// but the modules are created from `precompiled` when they exist.
+    pub fn load_shaders_from_precompiled<K>(
+        &mut self,
+        shaders: HashMap<K, String>,
+        mut precompiled: HashMap<String, wgpu::ShaderSource<'static>>,
+        device: &wgpu::Device,
+    ) -> HashMap<String, crate::error::Result<()>>
     where
         K: AsRef<str>,
     {
@@ -75,10 +98,11 @@ impl ShaderLibrary {
             let name = result.0;
             match result.1 {
                 Ok(result) => {
+                    let shader_source = precompiled.remove(&name).unwrap_or(result.shader_source);
                     let result = self.load_shader_from_source_reflection(
                         &name,
                         device,
-                        result.shader_source,
+                        shader_source,
                         result.reflection,
                     );
                     results.insert(name, result);
@@ -117,7 +141,7 @@ impl ShaderLibrary {
     pub fn load_shaders_from_naga_module(
         &mut self,
         device: &wgpu::Device,
-        modules: HashMap<String, naga::Module>,
+        modules: HashMap<String, ShaderNagaModule>,
     ) -> HashMap<String, crate::error::Result<()>> {
         let _ = tracy_client::span!();
         let mut results: HashMap<String, crate::error::Result<()>> = HashMap::new();
@@ -132,12 +156,13 @@ impl ShaderLibrary {
         &mut self,
         name: K,
         device: &wgpu::Device,
-        module: naga::Module,
+        module: ShaderNagaModule,
     ) -> crate::error::Result<()>
     where
         K: AsRef<str>,
     {
-        let reflection = Reflection::from_naga_module(module.clone(), false)?;
+        let ShaderNagaModule { module, reflection } = module;
+        let reflection = Reflection::from_cache(name.as_ref(), &module, reflection)?;
         let shader_module = Self::create_shader_module(
             name.as_ref(),
             device,
@@ -229,7 +254,9 @@ impl ShaderLibrary {
         K: AsRef<str>,
     {
         let reflection = Reflection::new(&code.as_ref(), false)?;
-        let module = reflection.get_module();
+        let module = reflection
+            .get_module()
+            .expect("The reflection is created from the code");
         let pipeline_options: Option<naga::back::spv::PipelineOptions> =
             match reflection.get_pipeline_type() {
                 crate::reflection::EPipelineType::Render(..) => None,
@@ -274,6 +301,28 @@ impl ShaderLibrary {
         )
     }
 
+    /// Looks up the permutation of the built-in shader, the missing features take the default values.
+    /// Returns `None` if the permutation is pruned by `GlobalShader::should_compile_permutation`.
+    pub fn get_global_shader(
+        &self,
+        shader: &dyn GlobalShader,
+        permutation: &Permutation,
+    ) -> Option<Arc<wgpu::ShaderModule>> {
+        self.shader_dic
+            .get(&shader.get_permutation_name(permutation))
+            .cloned()
+    }
+
+    pub fn get_global_shader_reflection(
+        &self,
+        shader: &dyn GlobalShader,
+        permutation: &Permutation,
+    ) -> Option<Arc<Reflection>> {
+        self.reflection_dic
+            .get(&shader.get_permutation_name(permutation))
+            .cloned()
+    }
+
     pub fn get_material_shader_name(
         handle: MaterialRenderPipelineHandle,
         options: &MaterialOptions,
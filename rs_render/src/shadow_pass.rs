@@ -2,8 +2,7 @@ use crate::{
     VertexBufferType,
     base_render_pipeline_pool::BaseRenderPipelinePool,
     global_shaders::{
-        depth::{DepthShader, DepthSkinShader},
-        global_shader::GlobalShader,
+        depth::DepthShader, global_shader::GlobalShader, skeleton_shading::skeleton_permutation,
     },
     render_pipeline::generic_pipeline::GenericPipeline,
     shader_library::ShaderLibrary,
@@ -24,7 +23,7 @@ impl ShadowPipelines {
         pool: &mut BaseRenderPipelinePool,
     ) -> ShadowPipelines {
         let depth_pipeline = GenericPipeline::standard_depth_only(
-            DepthShader {}.get_name(),
+            DepthShader {}.get_permutation_name(&skeleton_permutation(false)),
             device,
             shader_library,
             pool,
@@ -33,7 +32,7 @@ impl ShadowPipelines {
             ])),
         );
         let depth_skin_pipeline = GenericPipeline::standard_depth_only(
-            DepthSkinShader {}.get_name(),
+            DepthShader {}.get_permutation_name(&skeleton_permutation(true)),
             device,
             shader_library,
             pool,
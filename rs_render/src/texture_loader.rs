@@ -6,7 +6,45 @@ use crate::misc::find_most_compatible_texture_usages;
 
 pub struct TextureLoader {}
 
+/// The first mipmap of a compressed texture.
+pub struct CompressedTextureData {
+    pub width: u32,
+    pub height: u32,
+    pub format: TextureFormat,
+    pub data: Vec<u8>,
+}
+
 impl TextureLoader {
+    /// Reads the first mipmap of a DDS file, BC7 is the only supported format.
+    pub fn load_dds(data: &[u8]) -> crate::error::Result<CompressedTextureData> {
+        let dds = ddsfile::Dds::read(std::io::Cursor::new(data))
+            .map_err(|err| crate::error::Error::DdsFile(err))?;
+        let surface = image_dds::Surface::from_dds(&dds)
+            .map_err(|err| crate::error::Error::ImageDdsSurface(err))?;
+        let format = match surface.image_format {
+            image_dds::ImageFormat::BC7RgbaUnorm => TextureFormat::Bc7RgbaUnorm,
+            image_format => {
+                return Err(crate::error::Error::Other(Some(format!(
+                    "Not support format {:?}",
+                    image_format
+                ))));
+            }
+        };
+        let data = surface
+            .get(0, 0, 0)
+            .ok_or(crate::error::Error::Other(Some(
+                "No data corresponding to the specified layer: 0, depth_level: 0, mipmap: 0"
+                    .to_string(),
+            )))?
+            .to_vec();
+        Ok(CompressedTextureData {
+            width: surface.width,
+            height: surface.height,
+            format,
+            data,
+        })
+    }
+
     pub fn load_texture_2d_from_file(
         file_path: &Path,
         label: Option<&str>,
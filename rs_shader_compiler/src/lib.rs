@@ -1 +1,2 @@
-pub mod shader_time_stamp;
+pub mod reflection_cache;
+pub mod shader_dependency;
@@ -3,8 +3,11 @@ use anyhow::anyhow;
 use clap::Parser;
 use path_slash::PathBufExt;
 use pollster::FutureExt;
+use rs_artifact::compiled_shader::{ECompiledShaderCode, EShaderTarget};
 use rs_core_minimal::path_ext::CanonicalizeSlashExt;
-use std::path::PathBuf;
+use rs_shader_compiler::{reflection_cache, shader_dependency::ShaderDependencyGraph};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -33,45 +36,103 @@ fn prepreocess_builtin_shader() -> anyhow::Result<()> {
             .context(anyhow!("Can not create dir {:?}", &output_path))?;
     }
 
+    let mut dependency_graph = ShaderDependencyGraph::read_from_disk().unwrap_or_default();
+    let mut names = HashSet::new();
     let mut compile_commands = vec![];
     for buildin_shader in buildin_shaders {
-        let description = buildin_shader.get_shader_description();
-        let name = buildin_shader.get_name();
-        let processed_code = rs_shader_compiler_core::pre_process::pre_process(
-            &description.shader_path,
-            description.include_dirs.iter(),
-            description.definitions.iter(),
-        )?;
-        let wgsl_filepath = output_path.join(&name);
-        match wgsl_filepath.to_slash() {
-            Some(filepath) => log::trace!("Writing: {:?}", &filepath),
-            None => log::warn!(
-                "The path contains non-Unicode sequence: {:?}",
-                &wgsl_filepath
-            ),
-        }
-        std::fs::write(&wgsl_filepath, &processed_code)
-            .context(anyhow!("Can not write to file {:?}", &wgsl_filepath))?;
-
-        let module = naga::front::wgsl::parse_str(&processed_code)?;
-        let bin_data = rs_artifact::bincode_legacy::serialize(&module, None)?;
-        let bin_filepath = output_path.join(format!("{}.nagamodule", &name));
-        match bin_filepath.to_slash() {
-            Some(filepath) => log::trace!("Writing: {:?}", &filepath),
-            None => log::warn!(
-                "The path contains non-Unicode sequence: {:?}",
-                &bin_filepath
-            ),
-        }
-        std::fs::write(&bin_filepath, bin_data)
-            .context(anyhow!("Can not write to file {:?}", &bin_filepath))?;
-
         #[cfg(feature = "editor")]
         {
             let compile_command = buildin_shader.as_ref().to_compile_command();
             compile_commands.push(compile_command);
         }
+
+        for (name, description) in buildin_shader.get_permutation_descriptions() {
+            names.insert(name.clone());
+
+            let wgsl_filepath = output_path.join(&name);
+            let bin_filepath = reflection_cache::get_naga_module_path(&output_path, &name);
+            let reflection_filepath = reflection_cache::get_reflection_path(&output_path, &name);
+            if wgsl_filepath.exists()
+                && bin_filepath.exists()
+                && reflection_filepath.exists()
+                && !dependency_graph.is_shader_outdated(&name, &description)
+            {
+                log::trace!("Skip up to date shader: {}", &name);
+                continue;
+            }
+
+            let output = rs_shader_compiler_core::pre_process::pre_process_with_source_map(
+                &description.shader_path,
+                description.include_dirs.iter(),
+                description.definitions.iter(),
+            )?;
+            for warning in &output.warnings {
+                log::warn!("{}", warning);
+            }
+            let is_slang =
+                rs_shader_compiler_core::slang::is_slang_shader(&description.shader_path);
+            let processed_code = &match is_slang {
+                true => rs_shader_compiler_core::slang::compile_to_wgsl(
+                    &output,
+                    &description.shader_path,
+                    description.include_dirs.iter(),
+                )?,
+                false => output.code.clone(),
+            };
+            match wgsl_filepath.to_slash() {
+                Some(filepath) => log::trace!("Writing: {:?}", &filepath),
+                None => log::warn!(
+                    "The path contains non-Unicode sequence: {:?}",
+                    &wgsl_filepath
+                ),
+            }
+            std::fs::write(&wgsl_filepath, processed_code)
+                .context(anyhow!("Can not write to file {:?}", &wgsl_filepath))?;
+
+            let module = naga::front::wgsl::parse_str(processed_code)?;
+            reflection_cache::write_naga_module(&output_path, &name, &module)?;
+
+            if is_slang {
+                // SPIR-V is generated by slangc, which supports more features than naga.
+                let spv = rs_shader_compiler_core::slang::compile_to_spirv(
+                    &output,
+                    &description.shader_path,
+                    description.include_dirs.iter(),
+                )?;
+                let spv_filepath = output_path.join(format!("{}.spv", name));
+                log::trace!("Writing: {:?}", &spv_filepath);
+                std::fs::write(
+                    &spv_filepath,
+                    spv.iter()
+                        .flat_map(|x| x.to_le_bytes())
+                        .collect::<Vec<u8>>(),
+                )
+                .context(anyhow!("Can not write to file {:?}", &spv_filepath))?;
+                write_cross_compiled_shaders(
+                    &output_path,
+                    &name,
+                    &module,
+                    &[EShaderTarget::GlslEs, EShaderTarget::Msl],
+                )?;
+            } else {
+                write_cross_compiled_shaders(
+                    &output_path,
+                    &name,
+                    &module,
+                    &[
+                        EShaderTarget::SpirV,
+                        EShaderTarget::GlslEs,
+                        EShaderTarget::Msl,
+                    ],
+                )?;
+            }
+
+            dependency_graph.update(name, &description, output.includes)?;
+        }
     }
+    dependency_graph.retain(&names);
+    dependency_graph.save()?;
+
     let output_path = rs_core_minimal::file_manager::get_engine_root_dir().join(".vscode");
     if !output_path.exists() {
         std::fs::create_dir(output_path.clone())
@@ -86,6 +147,52 @@ fn prepreocess_builtin_shader() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Not every shader can be translated to every target, such as `textureLoad` from depth textures in GLSL,
+/// so the failures are only reported.
+fn write_cross_compiled_shaders(
+    output_path: &Path,
+    name: &str,
+    module: &naga::Module,
+    targets: &[EShaderTarget],
+) -> anyhow::Result<()> {
+    for target in targets.iter().copied() {
+        let code = match rs_render::cross_compile::cross_compile(module, target) {
+            Ok(code) => code,
+            Err(err) => {
+                log::warn!("Can not compile {} to {:?}, {}", name, target, err);
+                continue;
+            }
+        };
+        let outputs: Vec<(PathBuf, Vec<u8>)> = match code {
+            ECompiledShaderCode::SpirV(spv) => vec![(
+                output_path.join(format!("{}.spv", name)),
+                spv.iter().flat_map(|x| x.to_le_bytes()).collect(),
+            )],
+            ECompiledShaderCode::GlslEs(entry_points) => entry_points
+                .into_iter()
+                .map(|x| {
+                    (
+                        output_path.join(format!("{}.{}.glsl", name, x.name)),
+                        x.code.into_bytes(),
+                    )
+                })
+                .collect(),
+            ECompiledShaderCode::Msl(code) => {
+                vec![(
+                    output_path.join(format!("{}.metal", name)),
+                    code.into_bytes(),
+                )]
+            }
+        };
+        for (filepath, data) in outputs {
+            log::trace!("Writing: {:?}", &filepath);
+            std::fs::write(&filepath, data)
+                .context(anyhow!("Can not write to file {:?}", &filepath))?;
+        }
+    }
+    Ok(())
+}
+
 fn verify_shaders() -> anyhow::Result<()> {
     let ctx = rs_render_core::wgpu_context::WGPUContext::windowless(None, None, None)?;
 
@@ -140,12 +247,29 @@ fn main() -> anyhow::Result<()> {
             let result: anyhow::Result<String> = (|| {
                 let include_dirs = args.include_dirs.unwrap_or(vec![]);
                 let definitions = args.definitions.unwrap_or(vec![]);
-                let result = rs_shader_compiler_core::pre_process::pre_process(
+                let output = rs_shader_compiler_core::pre_process::pre_process_with_source_map(
                     &input_file,
                     include_dirs.iter(),
                     definitions.iter(),
                 )?;
-                let _ = naga::front::wgsl::parse_str(&result)?;
+                for warning in &output.warnings {
+                    log::warn!("{}", warning);
+                }
+                let result = match rs_shader_compiler_core::slang::is_slang_shader(&input_file) {
+                    true => {
+                        let code = rs_shader_compiler_core::slang::compile_to_wgsl(
+                            &output,
+                            &input_file,
+                            include_dirs.iter(),
+                        )?;
+                        let _ = naga::front::wgsl::parse_str(&code)?;
+                        code
+                    }
+                    false => {
+                        let _ = output.validate()?;
+                        output.code
+                    }
+                };
                 match args.output_file {
                     Some(output_file) => {
                         let _ = std::fs::write(output_file, result.clone())?;
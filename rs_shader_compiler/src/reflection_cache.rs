@@ -0,0 +1,117 @@
+use anyhow::{Context, anyhow};
+use rs_render::reflection::{Reflection, ReflectionDescription};
+use std::path::{Path, PathBuf};
+
+pub fn get_naga_module_path(output_path: &Path, name: &str) -> PathBuf {
+    output_path.join(format!("{}.nagamodule", name))
+}
+
+/// The reflection of the shader is stored next to `<name>.nagamodule`.
+pub fn get_reflection_path(output_path: &Path, name: &str) -> PathBuf {
+    output_path.join(format!("{}.reflection.json", name))
+}
+
+pub fn reflect(module: &naga::Module) -> anyhow::Result<ReflectionDescription> {
+    let reflection = Reflection::from_naga_module(module.clone(), false)
+        .map_err(|err| anyhow::anyhow!("{}", err))?;
+    Ok(reflection.get_description().clone())
+}
+
+pub fn write_reflection(path: &Path, description: &ReflectionDescription) -> anyhow::Result<()> {
+    let contents = serde_json::to_string_pretty(description)?;
+    std::fs::write(path, contents)?;
+    Ok(())
+}
+
+/// Writes `<name>.nagamodule` and the reflection of the module, which are loaded by the editor.
+pub fn write_naga_module(
+    output_path: &Path,
+    name: &str,
+    module: &naga::Module,
+) -> anyhow::Result<()> {
+    let bin_filepath = get_naga_module_path(output_path, name);
+    let bin_data = rs_artifact::bincode_legacy::serialize(module, None)?;
+    log::trace!("Writing: {:?}", &bin_filepath);
+    std::fs::write(&bin_filepath, bin_data)
+        .context(anyhow!("Can not write to file {:?}", &bin_filepath))?;
+
+    let reflection_filepath = get_reflection_path(output_path, name);
+    let reflection = reflect(module).context(anyhow!("Can not reflect shader {}", name))?;
+    write_reflection(&reflection_filepath, &reflection)
+        .context(anyhow!("Can not write to file {:?}", &reflection_filepath))?;
+    Ok(())
+}
+
+pub fn read_reflection(path: &Path) -> anyhow::Result<ReflectionDescription> {
+    let file = std::fs::File::open(path)?;
+    Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        get_naga_module_path, get_reflection_path, read_reflection, write_naga_module,
+        write_reflection,
+    };
+    use rs_render::reflection::{REFLECTION_DESCRIPTION_VERSION, Reflection};
+
+    fn compile_builtin_shaders(dir: &std::path::Path) -> Vec<String> {
+        let _ = std::fs::create_dir_all(dir);
+        let mut names = vec![];
+        for buildin_shader in rs_render::global_shaders::get_buildin_shaders() {
+            for (name, description) in buildin_shader.get_permutation_descriptions() {
+                let output = rs_shader_compiler_core::pre_process::pre_process_with_source_map(
+                    &description.shader_path,
+                    description.include_dirs.iter(),
+                    description.definitions.iter(),
+                )
+                .unwrap();
+                let module = naga::front::wgsl::parse_str(&output.code).unwrap();
+                write_naga_module(dir, &name, &module).unwrap();
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    fn read_naga_module(dir: &std::path::Path, name: &str) -> naga::Module {
+        let data = std::fs::read(get_naga_module_path(dir, name)).unwrap();
+        rs_artifact::bincode_legacy::deserialize(&data, None).unwrap()
+    }
+
+    #[test]
+    fn test_builtin_shaders() {
+        let dir = std::env::temp_dir().join("rs_shader_compiler_test_reflection");
+        for name in compile_builtin_shaders(&dir) {
+            let module = read_naga_module(&dir, &name);
+            let cached = read_reflection(&get_reflection_path(&dir, &name)).unwrap();
+            assert_eq!(cached.version, REFLECTION_DESCRIPTION_VERSION);
+            let live = Reflection::from_naga_module(module, false).unwrap();
+            assert_eq!(&cached, live.get_description(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_stale_version() {
+        let dir = std::env::temp_dir().join("rs_shader_compiler_test_stale_reflection");
+        let name = compile_builtin_shaders(&dir).remove(0);
+        let module = read_naga_module(&dir, &name);
+        let path = get_reflection_path(&dir, &name);
+        let live = read_reflection(&path).unwrap();
+
+        let mut stale = live.clone();
+        stale.version = REFLECTION_DESCRIPTION_VERSION + 1;
+        stale.array_stride += 1;
+        write_reflection(&path, &stale).unwrap();
+        let cached = read_reflection(&path).unwrap();
+        assert_eq!(cached, stale);
+
+        let reflection = Reflection::from_cache(&name, &module, Some(cached)).unwrap();
+        assert_eq!(reflection.get_description(), &live);
+        assert!(reflection.get_module().is_some());
+
+        let reflection = Reflection::from_cache(&name, &module, Some(live.clone())).unwrap();
+        assert_eq!(reflection.get_description(), &live);
+        assert!(reflection.get_module().is_none());
+    }
+}
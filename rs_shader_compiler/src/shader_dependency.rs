@@ -0,0 +1,181 @@
+use rs_shader_compiler_core::pre_process::ShaderDescription;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+/// The inputs of a processed shader.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShaderDependency {
+    pub shader_path: PathBuf,
+    /// All files included by the shader directly or indirectly.
+    pub includes: Vec<PathBuf>,
+    /// The hash of the contents of the inputs, the include directories, the definitions
+    /// and the version of the compiler.
+    pub hash: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct ShaderDependencyGraph {
+    /// Keyed by the name of the shader.
+    pub shaders: HashMap<String, ShaderDependency>,
+}
+
+impl ShaderDependencyGraph {
+    fn file_path() -> PathBuf {
+        rs_core_minimal::file_manager::get_engine_output_target_dir().join("shaders/dependency")
+    }
+
+    pub fn read_from_disk() -> anyhow::Result<ShaderDependencyGraph> {
+        let file = std::fs::File::open(Self::file_path())?;
+        let reader = std::io::BufReader::new(file);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let contents = serde_json::to_string(self)?;
+        std::fs::write(Self::file_path(), contents)?;
+        Ok(())
+    }
+
+    /// The version of the compiler and of the reflection it writes, bumping either makes all shaders outdated.
+    fn compiler_version() -> String {
+        format!(
+            "{}.{}",
+            env!("CARGO_PKG_VERSION"),
+            rs_render::reflection::REFLECTION_DESCRIPTION_VERSION
+        )
+    }
+
+    /// Returns none if any input is not readable.
+    fn compute_hash(description: &ShaderDescription, includes: &[PathBuf]) -> Option<String> {
+        Self::compute_hash_with_version(description, includes, &Self::compiler_version())
+    }
+
+    fn compute_hash_with_version(
+        description: &ShaderDescription,
+        includes: &[PathBuf],
+        compiler_version: &str,
+    ) -> Option<String> {
+        let mut buffer: Vec<u8> = vec![];
+        let mut push = |bytes: &[u8]| {
+            buffer.extend_from_slice(bytes);
+            buffer.push(0);
+        };
+        push(compiler_version.as_bytes());
+        push(description.shader_path.to_string_lossy().as_bytes());
+        for include_dir in &description.include_dirs {
+            push(include_dir.to_string_lossy().as_bytes());
+        }
+        for definition in &description.definitions {
+            push(definition.as_bytes());
+        }
+        for file_path in std::iter::once(&description.shader_path).chain(includes) {
+            push(file_path.to_string_lossy().as_bytes());
+            push(&std::fs::read(file_path).ok()?);
+        }
+        Some(rs_core_minimal::misc::get_sha256_from_buf(&buffer))
+    }
+
+    /// Whether the shader or any of its transitive inputs is changed since the last `update`.
+    pub fn is_shader_outdated(&self, name: &str, description: &ShaderDescription) -> bool {
+        let Some(dependency) = self.shaders.get(name) else {
+            return true;
+        };
+        dependency.shader_path != description.shader_path
+            || Self::compute_hash(description, &dependency.includes).as_ref()
+                != Some(&dependency.hash)
+    }
+
+    pub fn update(
+        &mut self,
+        name: String,
+        description: &ShaderDescription,
+        includes: Vec<PathBuf>,
+    ) -> anyhow::Result<()> {
+        let hash = Self::compute_hash(description, &includes).ok_or(anyhow::anyhow!(
+            "Can not read the inputs of {:?}",
+            description.shader_path
+        ))?;
+        self.shaders.insert(
+            name,
+            ShaderDependency {
+                shader_path: description.shader_path.clone(),
+                includes,
+                hash,
+            },
+        );
+        Ok(())
+    }
+
+    /// Removes the shaders which no longer exist.
+    pub fn retain(&mut self, names: &HashSet<String>) {
+        self.shaders.retain(|name, _| names.contains(name));
+    }
+
+    /// The names of the shaders which include the file or are the file.
+    pub fn get_dependents(&self, file_path: &Path) -> Vec<&str> {
+        self.shaders
+            .iter()
+            .filter(|(_, dependency)| {
+                dependency.shader_path == file_path
+                    || dependency.includes.iter().any(|x| x == file_path)
+            })
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ShaderDependencyGraph;
+    use rs_shader_compiler_core::pre_process::ShaderDescription;
+
+    #[test]
+    fn test_outdated() {
+        let dir = std::env::temp_dir().join("rs_shader_compiler_test_outdated");
+        let _ = std::fs::create_dir_all(&dir);
+        let shader_path = dir.join("main.wgsl");
+        let common_path = dir.join("common.wgsl");
+        std::fs::write(&shader_path, "#include \"common.wgsl\"").unwrap();
+        std::fs::write(&common_path, "const A = 1;").unwrap();
+        let mut description = ShaderDescription {
+            shader_path: shader_path.clone(),
+            include_dirs: vec![dir.clone()],
+            definitions: vec!["A=1".to_string()],
+        };
+
+        let mut graph = ShaderDependencyGraph::default();
+        assert!(graph.is_shader_outdated("main", &description));
+        graph
+            .update("main".to_string(), &description, vec![common_path.clone()])
+            .unwrap();
+        assert!(!graph.is_shader_outdated("main", &description));
+        assert_eq!(graph.get_dependents(&common_path), vec!["main"]);
+
+        std::fs::write(&common_path, "const A = 2;").unwrap();
+        assert!(graph.is_shader_outdated("main", &description));
+        graph
+            .update("main".to_string(), &description, vec![common_path.clone()])
+            .unwrap();
+        assert!(!graph.is_shader_outdated("main", &description));
+
+        description.definitions.push("B".to_string());
+        assert!(graph.is_shader_outdated("main", &description));
+
+        let includes = vec![common_path.clone()];
+        assert_eq!(
+            ShaderDependencyGraph::compute_hash(&description, &includes),
+            ShaderDependencyGraph::compute_hash_with_version(
+                &description,
+                &includes,
+                &ShaderDependencyGraph::compiler_version()
+            )
+        );
+        assert_ne!(
+            ShaderDependencyGraph::compute_hash(&description, &includes),
+            ShaderDependencyGraph::compute_hash_with_version(&description, &includes, "0.0.0.0")
+        );
+    }
+}
@@ -1,8 +1,10 @@
 #[derive(Debug)]
 pub enum Error {
     ProcessFail(Option<String>),
+    PreProcess(rs_cprep::error::Error),
     IO(std::io::Error, Option<String>),
     FromUtf8Error(std::string::FromUtf8Error),
+    Validation(String),
 }
 
 impl std::fmt::Display for Error {
@@ -1,6 +1,7 @@
 pub mod compile_command;
 pub mod error;
 pub mod pre_process;
+pub mod slang;
 
 #[cfg(test)]
 mod test {
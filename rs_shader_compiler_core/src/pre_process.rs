@@ -5,146 +5,23 @@ pub struct ShaderDescription {
     pub definitions: Vec<String>,
 }
 
+/// Returns the WGSL code of the shader, Slang shaders are compiled to WGSL after processed.
 pub fn pre_process(
     shader_path: &std::path::Path,
     include_dirs: impl Iterator<Item = impl AsRef<std::path::Path>>,
     definitions: impl Iterator<Item = impl AsRef<str>>,
 ) -> crate::error::Result<String> {
-    #[cfg(all(feature = "editor", not(feature = "force_builtin_processor")))]
-    if rs_foundation::is_program_in_path("cl.exe") {
-        return pre_process_cl(shader_path, include_dirs, definitions);
-    } else if rs_foundation::is_program_in_path("clang.exe") {
-        return pre_process_clang(shader_path, include_dirs, definitions);
-    }
-    return pre_process_builtin(shader_path, include_dirs, definitions);
-}
-
-#[cfg(all(feature = "editor", not(feature = "force_builtin_processor")))]
-fn pre_process_clang(
-    shader_path: &std::path::Path,
-    include_dirs: impl Iterator<Item = impl AsRef<std::path::Path>>,
-    definitions: impl Iterator<Item = impl AsRef<str>>,
-) -> crate::error::Result<String> {
-    let shader_path = dunce::canonicalize(shader_path).map_err(|err| {
-        crate::error::Error::IO(err, Some(format!("{:?} is not exist.", shader_path)))
-    })?;
-    let mut clang = std::process::Command::new("clang");
-    clang.arg("-E");
-    clang.arg("-P");
-    clang.arg("-x");
-    clang.arg("c");
-    clang.arg("-std=c11");
-    for include_dir in include_dirs {
-        let include_dir = include_dir.as_ref();
-        let include_dir = dunce::canonicalize(include_dir).map_err(|err| {
-            crate::error::Error::IO(err, Some(format!("{:?} is not exist.", include_dir)))
-        })?;
-        let include_dir = path_slash::PathBufExt::to_slash_lossy(&include_dir);
-        if include_dir.is_empty() {
-            return Err(crate::error::Error::ProcessFail(Some(String::from(
-                "Empty include path",
-            ))));
-        }
-        clang.arg(format!("-I{}", include_dir));
-    }
-    for definition in definitions {
-        if definition.as_ref().is_empty() {
-            return Err(crate::error::Error::ProcessFail(Some(String::from(
-                "Empty macro definition",
-            ))));
-        }
-        clang.arg(format!("-D{}", definition.as_ref()));
-    }
-    let path_arg = shader_path.to_str().ok_or(crate::error::Error::IO(
-        std::io::ErrorKind::Other.into(),
-        None,
-    ))?;
-    clang.arg(path_arg);
-    let output = clang.output();
-    let output = output.map_err(|err| crate::error::Error::IO(err, None))?;
-    let stderr = String::from_utf8(output.stderr);
-    let stdout = String::from_utf8(output.stdout);
-    let stdout = stdout.map_err(|err| crate::error::Error::FromUtf8Error(err))?;
-    let stderr = stderr.map_err(|err| crate::error::Error::FromUtf8Error(err))?;
-    if output.status.success() {
-        Ok(stdout.to_string())
-    } else {
-        Err(crate::error::Error::ProcessFail(Some(stderr)))
-    }
-}
-
-#[cfg(all(feature = "editor", not(feature = "force_builtin_processor")))]
-fn pre_process_cl(
-    shader_path: &std::path::Path,
-    include_dirs: impl Iterator<Item = impl AsRef<std::path::Path>>,
-    definitions: impl Iterator<Item = impl AsRef<str>>,
-) -> crate::error::Result<String> {
-    let shader_path = dunce::canonicalize(shader_path).map_err(|err| {
-        crate::error::Error::IO(err, Some(format!("{:?} is not exist.", shader_path)))
-    })?;
-    let mut cl = std::process::Command::new("cl.exe");
-    cl.arg("/C");
-    cl.arg("/EP");
-    for include_dir in include_dirs {
-        let include_dir = include_dir.as_ref();
-        let include_dir = dunce::canonicalize(include_dir).map_err(|err| {
-            crate::error::Error::IO(err, Some(format!("{:?} is not exist.", include_dir)))
-        })?;
-        let include_dir = path_slash::PathBufExt::to_slash_lossy(&include_dir);
-        if include_dir.is_empty() {
-            return Err(crate::error::Error::ProcessFail(Some(String::from(
-                "Empty include path",
-            ))));
-        }
-        cl.arg(format!("/I\"{}\"", include_dir));
-    }
-    for definition in definitions {
-        if definition.as_ref().is_empty() {
-            return Err(crate::error::Error::ProcessFail(Some(String::from(
-                "Empty macro definition",
-            ))));
-        }
-        cl.arg(format!("/D{}", definition.as_ref()));
-    }
-    let path_arg = shader_path.to_str().ok_or(crate::error::Error::IO(
-        std::io::ErrorKind::Other.into(),
-        None,
-    ))?;
-    cl.arg(path_arg);
-    let output = cl.output();
-    let output = output.map_err(|err| crate::error::Error::IO(err, None))?;
-
-    #[cfg(feature = "detect_encoding")]
-    {
-        let mut encoding_detector = chardetng::EncodingDetector::new();
-        if !output.stdout.is_empty() {
-            encoding_detector.feed(&output.stdout, true);
-        } else if !output.stderr.is_empty() {
-            encoding_detector.feed(&output.stderr, true);
-        }
-        let guess_encoding = encoding_detector.guess(None, true);
-        let stderr = guess_encoding.decode(&output.stderr).0;
-        let stdout = guess_encoding.decode(&output.stdout).0;
-        if output.status.success() {
-            Ok(stdout.to_string())
-        } else {
-            Err(crate::error::Error::ProcessFail(Some(stderr.to_string())))
-        }
-    }
-    #[cfg(not(feature = "detect_encoding"))]
-    {
-        let stderr = String::from_utf8(output.stderr);
-        let stdout = String::from_utf8(output.stdout);
-        let stdout = stdout.map_err(|err| crate::error::Error::FromUtf8Error(err))?;
-        let stderr = stderr.map_err(|err| crate::error::Error::FromUtf8Error(err))?;
-        if output.status.success() {
-            Ok(stdout.to_string())
-        } else {
-            Err(crate::error::Error::ProcessFail(Some(stderr)))
-        }
+    if crate::slang::is_slang_shader(shader_path) {
+        let include_dirs: Vec<std::path::PathBuf> =
+            include_dirs.map(|x| x.as_ref().to_path_buf()).collect();
+        let output = pre_process_with_source_map(shader_path, include_dirs.iter(), definitions)?;
+        return crate::slang::compile_to_wgsl(&output, shader_path, include_dirs.iter());
     }
+    pre_process_builtin(shader_path, include_dirs, definitions)
 }
 
+/// Parses the definitions in the form of `NAME=VALUE`. A bare `NAME` is defined as `1`
+/// and `NAME=` is defined as empty, the same as `-DNAME` and `-DNAME=` of a C preprocessor.
 fn parse_definitions_strict<I, S>(
     definitions: I,
 ) -> Result<std::collections::HashMap<String, String>, String>
@@ -158,7 +35,8 @@ where
         let s = def.as_ref();
 
         let Some(eq_pos) = s.find('=') else {
-            map.insert(s.to_string(), "".to_string());
+            // Same as `-D<name>` of a C preprocessor.
+            map.insert(s.to_string(), "1".to_string());
             continue;
             // return Err(format!(
             //     "Invalid definition at index {idx}: missing '=' -> {s:?}"
@@ -187,11 +65,87 @@ where
     Ok(map)
 }
 
-fn pre_process_builtin(
+/// The output of the builtin processor, the lines of the code map back to the original shader source.
+#[derive(Debug, Clone)]
+pub struct PreProcessOutput {
+    pub code: String,
+    pub source_map: rs_cprep::source_map::SourceMap,
+    /// Collected from `#warning`.
+    pub warnings: Vec<rs_cprep::source_map::Diagnostic>,
+    /// All files included by the shader directly or indirectly.
+    pub includes: Vec<std::path::PathBuf>,
+    /// Holds the macros defined at the end of the shader.
+    pub preprocessor: rs_cprep::processor::Preprocessor,
+}
+
+/// A parse or validation error of the processed code.
+#[derive(Debug, Clone)]
+pub struct ValidationError {
+    /// The location in the original shader source.
+    pub location: Option<rs_cprep::source_map::SourceLocation>,
+    /// The length of the erroneous span in the processed code.
+    pub length: usize,
+    pub message: String,
+}
+
+impl PreProcessOutput {
+    fn locate(&self, location: Option<naga::SourceLocation>, message: String) -> ValidationError {
+        ValidationError {
+            location: location.and_then(|location| {
+                self.source_map.resolve(
+                    location.line_number as usize,
+                    location.line_position as usize,
+                )
+            }),
+            length: location.map(|x| x.length as usize).unwrap_or_default(),
+            message,
+        }
+    }
+
+    /// Parses and validates the code, the errors are reported against the original shader source.
+    pub fn validate(&self) -> crate::error::Result<(naga::Module, naga::valid::ModuleInfo)> {
+        self.validate_located().map_err(|err| {
+            crate::error::Error::Validation(format!(
+                "{}: {}",
+                err.location.map(|x| x.to_string()).unwrap_or_default(),
+                err.message
+            ))
+        })
+    }
+
+    pub fn validate_located(
+        &self,
+    ) -> std::result::Result<(naga::Module, naga::valid::ModuleInfo), ValidationError> {
+        let module = self.parse()?;
+        let module_info = self.validate_module(&module)?;
+        Ok((module, module_info))
+    }
+
+    pub fn parse(&self) -> std::result::Result<naga::Module, ValidationError> {
+        naga::front::wgsl::parse_str(&self.code)
+            .map_err(|err| self.locate(err.location(&self.code), err.message().to_string()))
+    }
+
+    /// `module` is parsed from the code of this output.
+    pub fn validate_module(
+        &self,
+        module: &naga::Module,
+    ) -> std::result::Result<naga::valid::ModuleInfo, ValidationError> {
+        let mut validator = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        );
+        validator
+            .validate(module)
+            .map_err(|err| self.locate(err.location(&self.code), err.to_string()))
+    }
+}
+
+fn new_preprocessor(
     shader_path: &std::path::Path,
     include_dirs: impl Iterator<Item = impl AsRef<std::path::Path>>,
     definitions: impl Iterator<Item = impl AsRef<str>>,
-) -> crate::error::Result<String> {
+) -> crate::error::Result<rs_cprep::processor::Preprocessor> {
     let defines = parse_definitions_strict(definitions)
         .map_err(|err| crate::error::Error::ProcessFail(Some(err)))?;
     let mut include_dirs: std::collections::HashSet<String> = include_dirs
@@ -199,8 +153,126 @@ fn pre_process_builtin(
         .collect();
     let parent = shader_path.parent().expect("Valid parent path");
     include_dirs.insert(parent.to_string_lossy().to_string());
-    let mut processor = rs_cprep::processor::Preprocessor::new(include_dirs, defines);
-    processor
-        .process_file(shader_path)
-        .map_err(|err| crate::error::Error::ProcessFail(Some(format!("{err}"))))
+    Ok(rs_cprep::processor::Preprocessor::new(
+        include_dirs,
+        defines,
+    ))
+}
+
+fn to_pre_process_output(
+    output: rs_cprep::processor::ProcessOutput,
+    preprocessor: rs_cprep::processor::Preprocessor,
+) -> PreProcessOutput {
+    PreProcessOutput {
+        code: output.contents,
+        source_map: output.source_map,
+        warnings: output.warnings,
+        includes: output.includes.into_iter().map(Into::into).collect(),
+        preprocessor,
+    }
+}
+
+pub fn pre_process_with_source_map(
+    shader_path: &std::path::Path,
+    include_dirs: impl Iterator<Item = impl AsRef<std::path::Path>>,
+    definitions: impl Iterator<Item = impl AsRef<str>>,
+) -> crate::error::Result<PreProcessOutput> {
+    let mut preprocessor = new_preprocessor(shader_path, include_dirs, definitions)?;
+    let output = preprocessor
+        .process_file_with_source_map(shader_path)
+        .map_err(crate::error::Error::PreProcess)?;
+    Ok(to_pre_process_output(output, preprocessor))
+}
+
+/// Same as `pre_process_with_source_map`, but the contents of the shader are not read from the disk,
+/// for example, a shader being edited.
+pub fn pre_process_contents_with_source_map(
+    shader_path: &std::path::Path,
+    contents: &str,
+    include_dirs: impl Iterator<Item = impl AsRef<std::path::Path>>,
+    definitions: impl Iterator<Item = impl AsRef<str>>,
+) -> crate::error::Result<PreProcessOutput> {
+    let mut preprocessor = new_preprocessor(shader_path, include_dirs, definitions)?;
+    let output = preprocessor
+        .process_with_source_map(&shader_path.to_string_lossy(), contents)
+        .map_err(crate::error::Error::PreProcess)?;
+    Ok(to_pre_process_output(output, preprocessor))
+}
+
+fn pre_process_builtin(
+    shader_path: &std::path::Path,
+    include_dirs: impl Iterator<Item = impl AsRef<std::path::Path>>,
+    definitions: impl Iterator<Item = impl AsRef<str>>,
+) -> crate::error::Result<String> {
+    pre_process_with_source_map(shader_path, include_dirs, definitions).map(|output| output.code)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        parse_definitions_strict, pre_process_contents_with_source_map, pre_process_with_source_map,
+    };
+
+    #[test]
+    fn test_parse_definitions() {
+        let defines = parse_definitions_strict(["SKIN", "VALUE=4", "EMPTY="]).unwrap();
+        assert_eq!(defines["SKIN"], "1");
+        assert_eq!(defines["VALUE"], "4");
+        assert_eq!(defines["EMPTY"], "");
+        assert!(parse_definitions_strict(["=1"]).is_err());
+        assert!(parse_definitions_strict(["A=1=2"]).is_err());
+    }
+
+    #[test]
+    fn test_source_map() {
+        let dir = std::env::temp_dir().join("rs_shader_compiler_core_test_source_map");
+        let _ = std::fs::create_dir_all(&dir);
+        std::fs::write(
+            dir.join("common.wgsl"),
+            "fn add(a: f32, b: f32) -> f32 {\n    return a + c;\n}\n",
+        )
+        .unwrap();
+        let shader_path = dir.join("main.wgsl");
+        std::fs::write(
+            &shader_path,
+            "#include \"common.wgsl\"\n#ifdef A\n#endif\nfn main() {}\n",
+        )
+        .unwrap();
+        let output = pre_process_with_source_map(
+            &shader_path,
+            std::iter::empty::<&std::path::Path>(),
+            std::iter::empty::<&str>(),
+        )
+        .unwrap();
+        let Err(crate::error::Error::Validation(err)) = output.validate() else {
+            panic!("The shader should fail to validate");
+        };
+        let common_path = dir.join("common.wgsl").to_string_lossy().to_string();
+        assert!(err.contains(&format!("{}:2:16", common_path)), "{err}");
+        assert_eq!(output.includes, vec![std::path::PathBuf::from(common_path)]);
+    }
+
+    #[test]
+    fn test_contents() {
+        let dir = std::env::temp_dir().join("rs_shader_compiler_core_test_contents");
+        let _ = std::fs::create_dir_all(&dir);
+        std::fs::write(dir.join("common.wgsl"), "#define SCALE 2.0\n").unwrap();
+        let shader_path = dir.join("main.wgsl");
+        let output = pre_process_contents_with_source_map(
+            &shader_path,
+            "#include \"common.wgsl\"\nconst a: f32 = SCALE;\nconst b: f32 = c;\n",
+            std::iter::empty::<&std::path::Path>(),
+            std::iter::once("C=1"),
+        )
+        .unwrap();
+        assert_eq!(
+            output.preprocessor.get_define("SCALE").unwrap(),
+            "SCALE 2.0"
+        );
+        assert_eq!(output.preprocessor.get_define("C").unwrap(), "C 1");
+        let err = output.validate_located().unwrap_err();
+        let location = err.location.unwrap();
+        assert_eq!(location.file, shader_path.to_string_lossy());
+        assert_eq!((location.line, location.column, err.length), (3, 16, 1));
+    }
 }
@@ -0,0 +1,245 @@
+use crate::pre_process::PreProcessOutput;
+use rs_cprep::source_map::SourceLocation;
+use rs_slang_compiler::CompileOptions;
+pub use rs_slang_compiler::{diagnostic::ESeverity, ECompileTarget};
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+pub const SLANG_EXTENSION: &str = "slang";
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Slang is supported for the shader files, the material graphs are still resolved to WGSL.
+pub fn is_slang_shader(shader_path: &Path) -> bool {
+    shader_path
+        .extension()
+        .is_some_and(|extension| extension == SLANG_EXTENSION)
+}
+
+/// A problem reported by slangc.
+#[derive(Debug, Clone)]
+pub struct SlangDiagnostic {
+    /// The location in the original shader source.
+    pub location: Option<SourceLocation>,
+    pub severity: ESeverity,
+    pub message: String,
+}
+
+impl std::fmt::Display for SlangDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "{}: {}", location, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+fn new_temp_path(shader_path: &Path) -> PathBuf {
+    let stem = shader_path
+        .file_stem()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default();
+    std::env::temp_dir()
+        .join("rs_shader_compiler_core_slang")
+        .join(format!(
+            "{}_{}_{}",
+            stem,
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ))
+}
+
+fn locate(
+    output: &PreProcessOutput,
+    input_path: &Path,
+    diagnostic: rs_slang_compiler::diagnostic::Diagnostic,
+) -> SlangDiagnostic {
+    let column = diagnostic.column.unwrap_or(1);
+    let location = if diagnostic.path.file_name() == input_path.file_name() {
+        output.source_map.resolve(diagnostic.line, column)
+    } else if diagnostic.path.as_os_str().is_empty() {
+        None
+    } else {
+        Some(SourceLocation {
+            file: diagnostic.path.to_string_lossy().to_string(),
+            line: diagnostic.line,
+            column,
+        })
+    };
+    SlangDiagnostic {
+        location,
+        severity: diagnostic.severity,
+        message: diagnostic.message,
+    }
+}
+
+/// Compiles the code processed from the Slang shader at `shader_path`,
+/// the diagnostics are reported against the original shader source.
+/// The include directories are also the search paths of the modules imported by the shader.
+pub fn compile_located(
+    output: &PreProcessOutput,
+    shader_path: &Path,
+    include_dirs: impl Iterator<Item = impl AsRef<Path>>,
+    target: ECompileTarget,
+) -> std::result::Result<Vec<u8>, Vec<SlangDiagnostic>> {
+    let to_diagnostic = |message: String| SlangDiagnostic {
+        location: None,
+        severity: ESeverity::Error,
+        message,
+    };
+    let temp_path = new_temp_path(shader_path);
+    let input_path = temp_path.with_extension(SLANG_EXTENSION);
+    let output_path = temp_path.with_extension(match target {
+        ECompileTarget::Wgsl => "wgsl",
+        ECompileTarget::SpirV => "spv",
+    });
+    let mut compile_options = CompileOptions::default();
+    for include_dir in include_dirs {
+        compile_options.add_include(include_dir.as_ref());
+    }
+    if let Some(parent) = shader_path.parent() {
+        compile_options.add_include(parent);
+    }
+    compile_options.target = Some(target);
+    compile_options.input_path = input_path.clone();
+    compile_options.output_path = output_path.clone();
+
+    let result = (|| {
+        std::fs::create_dir_all(temp_path.parent().expect("Valid parent path"))
+            .map_err(|err| vec![to_diagnostic(err.to_string())])?;
+        std::fs::write(&input_path, &output.code)
+            .map_err(|err| vec![to_diagnostic(err.to_string())])?;
+        match rs_slang_compiler::compile(&compile_options, None) {
+            Ok(_) => {
+                std::fs::read(&output_path).map_err(|err| vec![to_diagnostic(err.to_string())])
+            }
+            Err(rs_slang_compiler::error::Error::Compilation(message)) => {
+                let diagnostics: Vec<SlangDiagnostic> =
+                    rs_slang_compiler::diagnostic::parse_diagnostics(&message)
+                        .into_iter()
+                        .map(|x| locate(output, &input_path, x))
+                        .collect();
+                if diagnostics.is_empty() {
+                    Err(vec![to_diagnostic(message)])
+                } else {
+                    Err(diagnostics)
+                }
+            }
+            Err(err) => Err(vec![to_diagnostic(format!("slangc: {}", err))]),
+        }
+    })();
+    let _ = std::fs::remove_file(&input_path);
+    let _ = std::fs::remove_file(&output_path);
+    result
+}
+
+pub fn compile(
+    output: &PreProcessOutput,
+    shader_path: &Path,
+    include_dirs: impl Iterator<Item = impl AsRef<Path>>,
+    target: ECompileTarget,
+) -> crate::error::Result<Vec<u8>> {
+    compile_located(output, shader_path, include_dirs, target).map_err(|diagnostics| {
+        crate::error::Error::Validation(
+            diagnostics
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<String>>()
+                .join("\n"),
+        )
+    })
+}
+
+pub fn compile_to_wgsl(
+    output: &PreProcessOutput,
+    shader_path: &Path,
+    include_dirs: impl Iterator<Item = impl AsRef<Path>>,
+) -> crate::error::Result<String> {
+    let data = compile(output, shader_path, include_dirs, ECompileTarget::Wgsl)?;
+    String::from_utf8(data).map_err(crate::error::Error::FromUtf8Error)
+}
+
+/// The SPIR-V words of the shader, slangc writes them in little endian.
+pub fn compile_to_spirv(
+    output: &PreProcessOutput,
+    shader_path: &Path,
+    include_dirs: impl Iterator<Item = impl AsRef<Path>>,
+) -> crate::error::Result<Vec<u32>> {
+    let data = compile(output, shader_path, include_dirs, ECompileTarget::SpirV)?;
+    if data.len() % 4 != 0 {
+        return Err(crate::error::Error::ProcessFail(Some(format!(
+            "The size of SPIR-V is not a multiple of 4: {}",
+            data.len()
+        ))));
+    }
+    Ok(data
+        .chunks_exact(4)
+        .map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]]))
+        .collect())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{is_slang_shader, locate};
+    use crate::pre_process::pre_process_contents_with_source_map;
+    use rs_slang_compiler::diagnostic::{Diagnostic, ESeverity};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_locate() {
+        assert!(is_slang_shader(Path::new("shaders/main.slang")));
+        assert!(!is_slang_shader(Path::new("shaders/main.wgsl")));
+
+        let dir = std::env::temp_dir().join("rs_shader_compiler_core_test_slang_locate");
+        let _ = std::fs::create_dir_all(&dir);
+        std::fs::write(
+            dir.join("common.slang"),
+            "float add(float a) {\n    return a + c;\n}\n",
+        )
+        .unwrap();
+        let shader_path = dir.join("main.slang");
+        let output = pre_process_contents_with_source_map(
+            &shader_path,
+            "#include \"common.slang\"\nfloat main() { return 0.0; }\n",
+            std::iter::empty::<&Path>(),
+            std::iter::empty::<&str>(),
+        )
+        .unwrap();
+
+        let input_path = PathBuf::from("/tmp/main_1_0.slang");
+        let diagnostic = locate(
+            &output,
+            &input_path,
+            Diagnostic {
+                path: input_path.clone(),
+                line: 2,
+                column: Some(16),
+                severity: ESeverity::Error,
+                code: Some(30015),
+                message: "undefined identifier 'c'.".to_string(),
+            },
+        );
+        let location = diagnostic.location.unwrap();
+        assert_eq!(
+            location.file,
+            dir.join("common.slang").to_string_lossy().to_string()
+        );
+        assert_eq!((location.line, location.column), (2, 16));
+
+        let diagnostic = locate(
+            &output,
+            &input_path,
+            Diagnostic {
+                path: PathBuf::new(),
+                line: 0,
+                column: None,
+                severity: ESeverity::Note,
+                code: None,
+                message: String::new(),
+            },
+        );
+        assert!(diagnostic.location.is_none());
+    }
+}
@@ -0,0 +1,383 @@
+use crate::{misc, wgsl};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, Diagnostic, DiagnosticSeverity, Location, Position, Range,
+};
+use naga::common::wgsl::TypeContext;
+use rs_shader_compiler_core::{pre_process::PreProcessOutput, slang::ESeverity};
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+/// The result of processing and validating a shader.
+pub struct Analysis {
+    /// The output of the last successful preprocessing.
+    pub output: Option<PreProcessOutput>,
+    /// The last successfully parsed module, the info is none if the module is invalid.
+    pub module: Option<(naga::Module, Option<naga::valid::ModuleInfo>)>,
+    /// The problems of the shader, keyed by the file which contains them.
+    pub diagnostics: HashMap<PathBuf, Vec<Diagnostic>>,
+    definitions: Vec<String>,
+}
+
+fn to_range(location: &rs_cprep::source_map::SourceLocation, length: usize) -> Range {
+    let start = Position::new(
+        location.line.saturating_sub(1) as u32,
+        location.column.saturating_sub(1) as u32,
+    );
+    let end = Position::new(start.line, start.character + length as u32);
+    Range::new(start, end)
+}
+
+impl Analysis {
+    /// `previous` provides the output and the module when the shader is broken while editing.
+    pub fn new(
+        shader_path: &Path,
+        contents: &str,
+        include_dirs: &[PathBuf],
+        definitions: &[String],
+        previous: Option<Analysis>,
+    ) -> Analysis {
+        let (mut output, mut module) = match previous {
+            Some(previous) => (previous.output, previous.module),
+            None => (None, None),
+        };
+        let mut diagnostics: HashMap<PathBuf, Vec<Diagnostic>> = HashMap::new();
+        let mut report = |location: Option<&rs_cprep::source_map::SourceLocation>,
+                          length: usize,
+                          message: String,
+                          severity: DiagnosticSeverity| {
+            let (file, range) = match location {
+                Some(location) => (
+                    misc::normalize_path(Path::new(&location.file)),
+                    to_range(location, length),
+                ),
+                None => (shader_path.to_path_buf(), Range::default()),
+            };
+            diagnostics.entry(file).or_default().push(Diagnostic {
+                range,
+                severity: Some(severity),
+                source: Some(String::from("shader_compiler")),
+                message,
+                ..Default::default()
+            });
+        };
+
+        match rs_shader_compiler_core::pre_process::pre_process_contents_with_source_map(
+            shader_path,
+            contents,
+            include_dirs.iter(),
+            definitions.iter(),
+        ) {
+            Ok(new_output) => {
+                for warning in &new_output.warnings {
+                    report(
+                        Some(&warning.location),
+                        0,
+                        warning.message.clone(),
+                        DiagnosticSeverity::WARNING,
+                    );
+                }
+                if rs_shader_compiler_core::slang::is_slang_shader(shader_path) {
+                    // The names of the generated WGSL are mangled, so the module is not kept.
+                    let result = rs_shader_compiler_core::slang::compile_located(
+                        &new_output,
+                        shader_path,
+                        include_dirs.iter(),
+                        rs_shader_compiler_core::slang::ECompileTarget::Wgsl,
+                    );
+                    for diagnostic in result.err().unwrap_or_default() {
+                        let severity = match diagnostic.severity {
+                            ESeverity::Error => DiagnosticSeverity::ERROR,
+                            ESeverity::Warning => DiagnosticSeverity::WARNING,
+                            ESeverity::Note => DiagnosticSeverity::INFORMATION,
+                        };
+                        report(
+                            diagnostic.location.as_ref(),
+                            0,
+                            diagnostic.message,
+                            severity,
+                        );
+                    }
+                    module = None;
+                } else {
+                    match new_output.parse() {
+                        Ok(new_module) => {
+                            let info = new_output.validate_module(&new_module);
+                            if let Err(err) = &info {
+                                report(
+                                    err.location.as_ref(),
+                                    err.length,
+                                    err.message.clone(),
+                                    DiagnosticSeverity::ERROR,
+                                );
+                            }
+                            module = Some((new_module, info.ok()));
+                        }
+                        Err(err) => report(
+                            err.location.as_ref(),
+                            err.length,
+                            err.message,
+                            DiagnosticSeverity::ERROR,
+                        ),
+                    }
+                }
+                output = Some(new_output);
+            }
+            Err(rs_shader_compiler_core::error::Error::PreProcess(err)) => {
+                let message = err.to_string();
+                let message = match err.location() {
+                    Some(location) => message
+                        .strip_prefix(&format!("{}: ", location))
+                        .unwrap_or(&message)
+                        .to_string(),
+                    None => message,
+                };
+                report(err.location(), 0, message, DiagnosticSeverity::ERROR);
+            }
+            Err(err) => report(None, 0, err.to_string(), DiagnosticSeverity::ERROR),
+        }
+
+        Analysis {
+            output,
+            module,
+            diagnostics,
+            definitions: definitions.to_vec(),
+        }
+    }
+
+    pub fn completion(&self) -> Vec<CompletionItem> {
+        let mut labels = HashSet::new();
+        let mut items = vec![];
+        let mut push = |label: &str, kind: CompletionItemKind, detail: Option<String>| {
+            if labels.insert(label.to_string()) {
+                items.push(CompletionItem {
+                    label: label.to_string(),
+                    kind: Some(kind),
+                    detail,
+                    ..Default::default()
+                });
+            }
+        };
+
+        match &self.output {
+            Some(output) => {
+                for name in output.preprocessor.get_define_names() {
+                    let detail = output
+                        .preprocessor
+                        .get_define(name)
+                        .map(|x| format!("#define {x}"));
+                    push(name, CompletionItemKind::CONSTANT, detail);
+                }
+            }
+            None => {
+                for definition in &self.definitions {
+                    let name = definition.split('=').next().unwrap_or_default();
+                    push(name, CompletionItemKind::CONSTANT, None);
+                }
+            }
+        }
+        if let Some((module, _)) = &self.module {
+            let ctx = module.to_ctx();
+            for (_, ty) in module.types.iter() {
+                if let (Some(name), naga::TypeInner::Struct { .. }) = (&ty.name, &ty.inner) {
+                    push(name, CompletionItemKind::STRUCT, None);
+                }
+            }
+            for (_, function) in module.functions.iter() {
+                if let Some(name) = &function.name {
+                    let detail = signature(&ctx, name, function);
+                    push(name, CompletionItemKind::FUNCTION, Some(detail));
+                }
+            }
+            for (_, global) in module.global_variables.iter() {
+                if let Some(name) = &global.name {
+                    let detail = ctx.type_to_string(global.ty);
+                    push(name, CompletionItemKind::VARIABLE, Some(detail));
+                }
+            }
+            for (_, constant) in module.constants.iter() {
+                if let Some(name) = &constant.name {
+                    let detail = ctx.type_to_string(constant.ty);
+                    push(name, CompletionItemKind::CONSTANT, Some(detail));
+                }
+            }
+        }
+        for keyword in wgsl::KEYWORDS {
+            push(keyword, CompletionItemKind::KEYWORD, None);
+        }
+        for ty in wgsl::BUILTIN_TYPES {
+            push(ty, CompletionItemKind::CLASS, None);
+        }
+        for function in wgsl::BUILTIN_FUNCTIONS {
+            push(function, CompletionItemKind::FUNCTION, None);
+        }
+        items
+    }
+
+    /// The markdown describing the macro or the declaration named `word`.
+    pub fn hover(&self, word: &str) -> Option<String> {
+        if let Some(output) = &self.output {
+            if let Some(definition) = output.preprocessor.get_define(word) {
+                let mut value = format!("```c\n#define {definition}\n```");
+                if let Ok(expanded) = output.preprocessor.expand(word) {
+                    let expanded = expanded.trim();
+                    if !expanded.is_empty() && expanded != word {
+                        value += &format!("\n\nExpands to `{expanded}`");
+                    }
+                }
+                return Some(value);
+            }
+        }
+        let (module, info) = self.module.as_ref()?;
+        let declaration = describe(module, info.as_ref(), word)?;
+        Some(format!("```wgsl\n{declaration}\n```"))
+    }
+
+    /// Looks for the declaration in the shader and then in its includes.
+    /// `read` returns the contents of the file, which may be different from the disk.
+    pub fn definition(
+        &self,
+        shader_path: &Path,
+        word: &str,
+        read: impl Fn(&Path) -> Option<String>,
+    ) -> Option<Location> {
+        let includes = self
+            .output
+            .as_ref()
+            .map(|x| x.includes.clone())
+            .unwrap_or_default();
+        for file_path in std::iter::once(shader_path.to_path_buf()).chain(includes) {
+            let Some(contents) = read(&file_path) else {
+                continue;
+            };
+            if let Some(position) = wgsl::find_declaration(&contents, word) {
+                let uri = misc::path_to_uri(&misc::normalize_path(&file_path))?;
+                let end = Position::new(position.line, position.character + word.len() as u32);
+                return Some(Location::new(uri, Range::new(position, end)));
+            }
+        }
+        None
+    }
+}
+
+fn signature(ctx: &naga::proc::GlobalCtx, name: &str, function: &naga::Function) -> String {
+    let arguments: Vec<String> = function
+        .arguments
+        .iter()
+        .map(|x| {
+            format!(
+                "{}: {}",
+                x.name.as_deref().unwrap_or_default(),
+                ctx.type_to_string(x.ty)
+            )
+        })
+        .collect();
+    let mut signature = format!("fn {name}({})", arguments.join(", "));
+    if let Some(result) = &function.result {
+        signature += &format!(" -> {}", ctx.type_to_string(result.ty));
+    }
+    signature
+}
+
+fn describe(
+    module: &naga::Module,
+    info: Option<&naga::valid::ModuleInfo>,
+    name: &str,
+) -> Option<String> {
+    let ctx = module.to_ctx();
+    for (_, ty) in module.types.iter() {
+        if let (Some(ty_name), naga::TypeInner::Struct { members, .. }) = (&ty.name, &ty.inner) {
+            if ty_name == name {
+                let mut declaration = format!("struct {name} {{\n");
+                for member in members {
+                    declaration += &format!(
+                        "    {}: {},\n",
+                        member.name.as_deref().unwrap_or_default(),
+                        ctx.type_to_string(member.ty)
+                    );
+                }
+                declaration += "}";
+                return Some(declaration);
+            }
+        }
+    }
+    for (_, global) in module.global_variables.iter() {
+        if global.name.as_deref() == Some(name) {
+            let space = match global.space {
+                naga::AddressSpace::Private => "<private>",
+                naga::AddressSpace::WorkGroup => "<workgroup>",
+                naga::AddressSpace::Uniform => "<uniform>",
+                naga::AddressSpace::Storage { .. } => "<storage>",
+                _ => "",
+            };
+            return Some(format!(
+                "var{space} {name}: {}",
+                ctx.type_to_string(global.ty)
+            ));
+        }
+    }
+    for (_, constant) in module.constants.iter() {
+        if constant.name.as_deref() == Some(name) {
+            return Some(format!("const {name}: {}", ctx.type_to_string(constant.ty)));
+        }
+    }
+    for (_, r#override) in module.overrides.iter() {
+        if r#override.name.as_deref() == Some(name) {
+            return Some(format!(
+                "override {name}: {}",
+                ctx.type_to_string(r#override.ty)
+            ));
+        }
+    }
+    for (_, function) in module.functions.iter() {
+        if function.name.as_deref() == Some(name) {
+            return Some(signature(&ctx, name, function));
+        }
+    }
+    for entry_point in &module.entry_points {
+        if entry_point.name == name {
+            let stage = format!("{:?}", entry_point.stage).to_lowercase();
+            return Some(format!(
+                "@{stage} {}",
+                signature(&ctx, name, &entry_point.function)
+            ));
+        }
+    }
+
+    let functions = module
+        .functions
+        .iter()
+        .map(|(handle, function)| (function, info.map(|x| &x[handle])))
+        .chain(
+            module
+                .entry_points
+                .iter()
+                .enumerate()
+                .map(|(index, x)| (&x.function, info.map(|x| x.get_entry_point(index)))),
+        );
+    for (function, function_info) in functions {
+        for argument in &function.arguments {
+            if argument.name.as_deref() == Some(name) {
+                return Some(format!("{name}: {}", ctx.type_to_string(argument.ty)));
+            }
+        }
+        for (_, local) in function.local_variables.iter() {
+            if local.name.as_deref() == Some(name) {
+                return Some(format!("var {name}: {}", ctx.type_to_string(local.ty)));
+            }
+        }
+        let Some(function_info) = function_info else {
+            continue;
+        };
+        for (handle, expression_name) in &function.named_expressions {
+            if expression_name == name {
+                return Some(format!(
+                    "let {name}: {}",
+                    ctx.type_resolution_to_string(&function_info[*handle].ty)
+                ));
+            }
+        }
+    }
+    None
+}
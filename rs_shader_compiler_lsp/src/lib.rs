@@ -1,3 +1,5 @@
+pub mod analysis;
 pub mod lsp_types_ext;
 pub mod misc;
 pub mod server;
+pub mod wgsl;
@@ -1,7 +1,11 @@
 use anyhow::anyhow;
 use lsp_server::Connection;
-use lsp_types::{MessageType, notification::Notification, request::Request};
-use std::path::Path;
+use lsp_types::{MessageType, Uri, notification::Notification, request::Request};
+use path_slash::PathExt;
+use std::{
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 fn base_log<S: AsRef<str>>(
     connection: &Connection,
@@ -124,3 +128,54 @@ pub fn strip_fix<S: AsRef<str>>(str: S) -> String {
         .unwrap()
         .to_string()
 }
+
+pub fn normalize_path(path: &Path) -> PathBuf {
+    dunce::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+pub fn uri_to_path(uri: &Uri) -> Option<PathBuf> {
+    if uri.scheme().map(|x| x.as_str()) != Some("file") {
+        return None;
+    }
+    let path = uri.path().as_estr().decode().into_string().ok()?;
+    // `/C:/path` on Windows.
+    let path = match path.strip_prefix('/') {
+        Some(stripped) if stripped.chars().nth(1) == Some(':') => stripped,
+        _ => &path,
+    };
+    Some(normalize_path(Path::new(path)))
+}
+
+pub fn path_to_uri(path: &Path) -> Option<Uri> {
+    let path = path.to_slash()?;
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/:".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    Uri::from_str(&uri).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{path_to_uri, uri_to_path};
+    use std::{path::Path, str::FromStr};
+
+    #[test]
+    fn test_uri() {
+        let uri = path_to_uri(Path::new("/shaders/my shader.wgsl")).unwrap();
+        assert_eq!(uri.as_str(), "file:///shaders/my%20shader.wgsl");
+        assert_eq!(
+            uri_to_path(&uri).unwrap(),
+            Path::new("/shaders/my shader.wgsl")
+        );
+        let uri = lsp_types::Uri::from_str("untitled:Untitled-1").unwrap();
+        assert_eq!(uri_to_path(&uri), None);
+    }
+}
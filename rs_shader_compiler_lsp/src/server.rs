@@ -1,16 +1,17 @@
-use crate::{lsp_types_ext, misc};
-use lsp_server::{Connection, Response};
+use crate::{analysis::Analysis, lsp_types_ext, misc, wgsl};
+use lsp_server::{Connection, ResponseError};
 use lsp_types::{
-    CompletionOptions, ConfigurationItem, HoverProviderCapability, InitializeParams, OneOf,
-    Registration, RegistrationParams, ServerCapabilities, TextDocumentSyncCapability,
-    TextDocumentSyncKind, WorkDoneProgressOptions, notification::Notification, request::Request,
+    CompletionOptions, ConfigurationItem, Diagnostic, HoverProviderCapability, InitializeParams,
+    OneOf, Registration, RegistrationParams, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, TextDocumentSyncOptions, TextDocumentSyncSaveOptions,
+    WorkDoneProgressOptions, notification::Notification, request::Request,
 };
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 use std::{
+    collections::{BTreeSet, HashMap},
     error::Error,
     fs::File,
     io::BufReader,
-    iter::zip,
     path::{Path, PathBuf},
 };
 
@@ -20,6 +21,24 @@ pub struct CompileCommand {
     pub file: String,
 }
 
+impl CompileCommand {
+    pub fn include_dirs(&self) -> Vec<PathBuf> {
+        self.arguments
+            .iter()
+            .filter_map(|x| x.strip_prefix("-I"))
+            .map(PathBuf::from)
+            .collect()
+    }
+
+    pub fn definitions(&self) -> Vec<String> {
+        self.arguments
+            .iter()
+            .filter_map(|x| x.strip_prefix("-D"))
+            .map(|x| x.to_string())
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Settings {
     pub shader_compile_commands: Option<PathBuf>,
@@ -28,6 +47,12 @@ pub struct Settings {
 
 pub struct Server {
     settings: Settings,
+    compile_commands: Vec<CompileCommand>,
+    /// The contents of the opened documents.
+    documents: HashMap<PathBuf, String>,
+    analyses: HashMap<PathBuf, Analysis>,
+    /// The published diagnostics of every shader, keyed by the file which contains them.
+    diagnostics: HashMap<PathBuf, HashMap<PathBuf, Vec<Diagnostic>>>,
 }
 
 impl Server {
@@ -37,11 +62,20 @@ impl Server {
                 clang_path: None,
                 shader_compile_commands: None,
             },
+            compile_commands: vec![],
+            documents: HashMap::new(),
+            analyses: HashMap::new(),
+            diagnostics: HashMap::new(),
         }
     }
 
-    pub fn run(mut self) {
+    pub fn run(self) {
         let (connection, _io_threads) = Connection::stdio();
+        self.serve(connection);
+    }
+
+    /// Serves until the client exits, `Connection::memory()` can be used to drive the server in tests.
+    pub fn serve(mut self, connection: Connection) {
         let (initialize_id, initialize_params) = connection.initialize_start().unwrap();
         let initialize_params: InitializeParams =
             serde_json::from_value(initialize_params).unwrap();
@@ -61,85 +95,15 @@ impl Server {
         if let Some(_) = initialize_params.initialization_options {}
         register_capability(&connection);
 
-        let shader_compile_commands_cfg_item = ConfigurationItem {
-            scope_uri: None,
-            section: Some("shaderCompiler.shaderCompileCommands".to_string()),
-        };
-        let clang_path_cfg_item = ConfigurationItem {
-            scope_uri: None,
-            section: Some("shaderCompiler.clangPath".to_string()),
-        };
-        let cfg_items = vec![
-            shader_compile_commands_cfg_item.clone(),
-            clang_path_cfg_item.clone(),
-        ];
-
-        let _ = send_get_cfg_request(&connection, cfg_items.clone());
+        let _ = send_get_cfg_request(&connection, cfg_items());
 
         while let Ok(event) = connection.receiver.recv() {
             match event {
                 lsp_server::Message::Request(request) => {
-                    let _ = connection.handle_shutdown(&request);
-                    if request.method == lsp_types::request::Completion::METHOD {
-                        let result = lsp_types::CompletionResponse::Array(vec![]);
-                        let result = serde_json::to_value(&result);
-                        let response_result = result.map_err(|err| lsp_server::ResponseError {
-                            code: lsp_server::ErrorCode::ParseError as i32,
-                            message: err.to_string(),
-                            data: None,
-                        });
-                        let response = Response {
-                            id: request.id,
-                            response_result,
-                        };
-                        let msg = lsp_server::Message::Response(response);
-                        let _ = connection.sender.send(msg);
-                    } else if request.method == lsp_types_ext::request::ShaderPreview::METHOD {
-                        let shader_preview_params = serde_json::from_value::<
-                            lsp_types_ext::ShaderPreviewParams,
-                        >(request.params);
-                        let mut result: lsp_types_ext::ShaderPreviewResult =
-                            lsp_types_ext::ShaderPreviewResult { code: None };
-                        if let Ok(shader_preview_params) = shader_preview_params {
-                            if let (Some(shader_compile_commands), Some(clang_path)) = (
-                                self.settings.shader_compile_commands.clone(),
-                                self.settings.clang_path.clone(),
-                            ) {
-                                if shader_compile_commands.exists() {
-                                    let commands =
-                                        from_shader_compile_commands_path(shader_compile_commands)
-                                            .unwrap_or(vec![]);
-                                    for command in commands {
-                                        let arguments = command
-                                            .arguments
-                                            .iter()
-                                            .fold("".to_string(), |acc, x| acc + " " + x);
-                                        let code = misc::pre_process(
-                                            &clang_path,
-                                            Path::new(&command.file),
-                                            &arguments,
-                                        );
-
-                                        if Path::new(&shader_preview_params.shader_file_path)
-                                            == Path::new(&command.file)
-                                        {
-                                            if let Ok(code) = code {
-                                                result.code = Some(code);
-                                                break;
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        let result = serde_json::to_value(&result);
-                        let response_result = result.map_err(|err| lsp_server::ResponseError {
-                            code: lsp_server::ErrorCode::ParseError as i32,
-                            message: err.to_string(),
-                            data: None,
-                        });
-                        let _ = misc::send_response(&connection, request.id, response_result);
+                    if connection.handle_shutdown(&request).unwrap_or(true) {
+                        break;
                     }
+                    self.on_request(&connection, request);
                 }
                 lsp_server::Message::Response(response) => {
                     if response.id
@@ -147,52 +111,348 @@ impl Server {
                             .to_string()
                             .into()
                     {
-                        let Ok(workspace_configuration) = response.response_result else {
-                            return;
-                        };
-                        if workspace_configuration.is_array() == false {
-                            return;
-                        }
-                        let workspace_configurations = workspace_configuration.as_array().unwrap();
-                        if cfg_items.len() != workspace_configurations.len() {
-                            return;
-                        }
-                        for (cfg_item, workspace_configuration) in
-                            zip(cfg_items.clone(), workspace_configurations)
-                        {
-                            if cfg_item.section.clone().unwrap()
-                                == shader_compile_commands_cfg_item.section.clone().unwrap()
-                            {
-                                if workspace_configuration.is_string() {
-                                    self.settings.shader_compile_commands = Some(
-                                        misc::strip_fix(workspace_configuration.to_string()).into(),
-                                    );
-                                }
-                            }
-                            if cfg_item.section.clone().unwrap()
-                                == clang_path_cfg_item.section.clone().unwrap()
-                            {
-                                if workspace_configuration.is_string() {
-                                    self.settings.clang_path = Some(
-                                        misc::strip_fix(workspace_configuration.to_string()).into(),
-                                    );
-                                }
-                            }
+                        if let Ok(workspace_configuration) = response.response_result {
+                            self.on_configuration(&connection, workspace_configuration);
                         }
                     }
                 }
                 lsp_server::Message::Notification(notification) => {
                     if notification.method == lsp_types::notification::Exit::METHOD {
                         break;
-                    } else if notification.method
-                        == lsp_types::notification::DidChangeConfiguration::METHOD
-                    {
-                        let _ = send_get_cfg_request(&connection, cfg_items.clone());
                     }
+                    self.on_notification(&connection, notification);
                 }
             }
         }
     }
+
+    fn on_configuration(
+        &mut self,
+        connection: &Connection,
+        workspace_configuration: serde_json::Value,
+    ) {
+        let Some(workspace_configurations) = workspace_configuration.as_array() else {
+            return;
+        };
+        let [shader_compile_commands, clang_path] = workspace_configurations.as_slice() else {
+            return;
+        };
+        if let Some(shader_compile_commands) = shader_compile_commands.as_str() {
+            self.settings.shader_compile_commands = Some(shader_compile_commands.into());
+        }
+        if let Some(clang_path) = clang_path.as_str() {
+            self.settings.clang_path = Some(clang_path.into());
+        }
+        self.compile_commands = self
+            .settings
+            .shader_compile_commands
+            .as_ref()
+            .and_then(|x| from_shader_compile_commands_path(x).ok())
+            .unwrap_or_default();
+        let paths: Vec<PathBuf> = self.documents.keys().cloned().collect();
+        for path in paths {
+            self.analyze(connection, &path);
+        }
+    }
+
+    fn on_notification(&mut self, connection: &Connection, notification: lsp_server::Notification) {
+        use lsp_types::notification::{
+            DidChangeConfiguration, DidChangeTextDocument, DidCloseTextDocument,
+            DidOpenTextDocument, DidSaveTextDocument,
+        };
+        match notification.method.as_str() {
+            DidChangeConfiguration::METHOD => {
+                let _ = send_get_cfg_request(connection, cfg_items());
+            }
+            DidOpenTextDocument::METHOD => {
+                let Ok(params) =
+                    from_params::<lsp_types::DidOpenTextDocumentParams>(notification.params)
+                else {
+                    return;
+                };
+                let Some(path) = misc::uri_to_path(&params.text_document.uri) else {
+                    return;
+                };
+                self.documents
+                    .insert(path.clone(), params.text_document.text);
+                self.analyze(connection, &path);
+            }
+            DidChangeTextDocument::METHOD => {
+                let Ok(params) =
+                    from_params::<lsp_types::DidChangeTextDocumentParams>(notification.params)
+                else {
+                    return;
+                };
+                let Some(path) = misc::uri_to_path(&params.text_document.uri) else {
+                    return;
+                };
+                // The documents are synchronized in full.
+                let Some(change) = params.content_changes.into_iter().last() else {
+                    return;
+                };
+                self.documents.insert(path.clone(), change.text);
+                self.analyze(connection, &path);
+            }
+            DidSaveTextDocument::METHOD => {
+                // The saved file may be included by other shaders.
+                let paths: Vec<PathBuf> = self.documents.keys().cloned().collect();
+                for path in paths {
+                    self.analyze(connection, &path);
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                let Ok(params) =
+                    from_params::<lsp_types::DidCloseTextDocumentParams>(notification.params)
+                else {
+                    return;
+                };
+                let Some(path) = misc::uri_to_path(&params.text_document.uri) else {
+                    return;
+                };
+                self.documents.remove(&path);
+                self.analyses.remove(&path);
+                self.publish_diagnostics(connection, &path, HashMap::new());
+                self.diagnostics.remove(&path);
+            }
+            _ => {}
+        }
+    }
+
+    fn on_request(&mut self, connection: &Connection, request: lsp_server::Request) {
+        use lsp_types::request::{Completion, GotoDefinition, HoverRequest};
+        let response_result = match request.method.as_str() {
+            Completion::METHOD => self.completion(request.params),
+            HoverRequest::METHOD => self.hover(request.params),
+            GotoDefinition::METHOD => self.definition(request.params),
+            lsp_types_ext::request::ShaderPreview::METHOD => self.shader_preview(request.params),
+            _ => Err(ResponseError {
+                code: lsp_server::ErrorCode::MethodNotFound as i32,
+                message: format!("{} is not supported", request.method),
+                data: None,
+            }),
+        };
+        let _ = misc::send_response(connection, request.id, response_result);
+    }
+
+    fn find_compile_command(&self, path: &Path) -> Option<&CompileCommand> {
+        self.compile_commands
+            .iter()
+            .find(|x| misc::normalize_path(Path::new(&x.file)) == path)
+    }
+
+    fn analyze(&mut self, connection: &Connection, path: &Path) {
+        let Some(contents) = self.documents.get(path) else {
+            return;
+        };
+        let command = self.find_compile_command(path);
+        let is_shader = command.is_some();
+        let (include_dirs, definitions) = match command {
+            Some(command) => (command.include_dirs(), command.definitions()),
+            // An included file, the macros of all shaders are visible.
+            None => {
+                let mut include_dirs = BTreeSet::new();
+                let mut definitions = BTreeSet::new();
+                for command in &self.compile_commands {
+                    include_dirs.extend(command.include_dirs());
+                    definitions.extend(command.definitions());
+                }
+                (
+                    include_dirs.into_iter().collect(),
+                    definitions.into_iter().collect(),
+                )
+            }
+        };
+        let previous = self.analyses.remove(path);
+        let analysis = Analysis::new(path, contents, &include_dirs, &definitions, previous);
+        // An included file may depend on the macros defined by the shader including it.
+        let diagnostics = if is_shader {
+            analysis.diagnostics.clone()
+        } else {
+            HashMap::new()
+        };
+        self.analyses.insert(path.to_path_buf(), analysis);
+        self.publish_diagnostics(connection, path, diagnostics);
+    }
+
+    /// Replaces the diagnostics reported by the shader, the files which are no longer reported are cleared.
+    fn publish_diagnostics(
+        &mut self,
+        connection: &Connection,
+        shader_path: &Path,
+        mut diagnostics: HashMap<PathBuf, Vec<Diagnostic>>,
+    ) {
+        diagnostics.entry(shader_path.to_path_buf()).or_default();
+        let mut files: BTreeSet<PathBuf> = diagnostics.keys().cloned().collect();
+        if let Some(previous) = self
+            .diagnostics
+            .insert(shader_path.to_path_buf(), diagnostics)
+        {
+            files.extend(previous.into_keys());
+        }
+        for file in files {
+            let Some(uri) = misc::path_to_uri(&file) else {
+                continue;
+            };
+            let diagnostics: Vec<Diagnostic> = self
+                .diagnostics
+                .values()
+                .filter_map(|x| x.get(&file))
+                .flatten()
+                .cloned()
+                .collect();
+            let params = lsp_types::PublishDiagnosticsParams {
+                uri,
+                diagnostics,
+                version: None,
+            };
+            let _ = misc::send_notification(
+                connection,
+                lsp_types::notification::PublishDiagnostics::METHOD,
+                serde_json::to_value(&params).unwrap(),
+            );
+        }
+    }
+
+    fn get_document(
+        &self,
+        params: &lsp_types::TextDocumentPositionParams,
+    ) -> Option<(PathBuf, &String, &Analysis)> {
+        let path = misc::uri_to_path(&params.text_document.uri)?;
+        let contents = self.documents.get(&path)?;
+        let analysis = self.analyses.get(&path)?;
+        Some((path, contents, analysis))
+    }
+
+    fn completion(&self, params: serde_json::Value) -> Result<serde_json::Value, ResponseError> {
+        let params = from_params::<lsp_types::CompletionParams>(params)?;
+        let items = self
+            .get_document(&params.text_document_position)
+            .map(|(_, _, analysis)| analysis.completion())
+            .unwrap_or_default();
+        to_result(lsp_types::CompletionResponse::Array(items))
+    }
+
+    fn hover(&self, params: serde_json::Value) -> Result<serde_json::Value, ResponseError> {
+        let params = from_params::<lsp_types::HoverParams>(params)?;
+        let params = params.text_document_position_params;
+        let hover = self
+            .get_document(&params)
+            .and_then(|(_, contents, analysis)| {
+                let (word, range) = wgsl::get_word_at(contents, params.position)?;
+                let value = analysis.hover(&word)?;
+                Some(lsp_types::Hover {
+                    contents: lsp_types::HoverContents::Markup(lsp_types::MarkupContent {
+                        kind: lsp_types::MarkupKind::Markdown,
+                        value,
+                    }),
+                    range: Some(range),
+                })
+            });
+        to_result(hover)
+    }
+
+    fn definition(&self, params: serde_json::Value) -> Result<serde_json::Value, ResponseError> {
+        let params = from_params::<lsp_types::GotoDefinitionParams>(params)?;
+        let params = params.text_document_position_params;
+        let location = self
+            .get_document(&params)
+            .and_then(|(path, contents, analysis)| {
+                let line = contents.lines().nth(params.position.line as usize)?;
+                if let Some(file) = wgsl::get_include(line) {
+                    let include_dirs = self
+                        .find_compile_command(&path)
+                        .map(|x| x.include_dirs())
+                        .unwrap_or_default();
+                    let include_path = path
+                        .parent()
+                        .into_iter()
+                        .chain(include_dirs.iter().map(|x| x.as_path()))
+                        .map(|x| x.join(file))
+                        .find(|x| x.exists())?;
+                    let uri = misc::path_to_uri(&misc::normalize_path(&include_path))?;
+                    return Some(lsp_types::Location::new(uri, lsp_types::Range::default()));
+                }
+                let (word, _) = wgsl::get_word_at(contents, params.position)?;
+                analysis.definition(&path, &word, |file_path| {
+                    let file_path = misc::normalize_path(file_path);
+                    match self.documents.get(&file_path) {
+                        Some(contents) => Some(contents.clone()),
+                        None => std::fs::read_to_string(file_path).ok(),
+                    }
+                })
+            })
+            .map(lsp_types::GotoDefinitionResponse::Scalar);
+        to_result(location)
+    }
+
+    fn shader_preview(
+        &self,
+        params: serde_json::Value,
+    ) -> Result<serde_json::Value, ResponseError> {
+        let shader_preview_params =
+            serde_json::from_value::<lsp_types_ext::ShaderPreviewParams>(params);
+        let mut result: lsp_types_ext::ShaderPreviewResult =
+            lsp_types_ext::ShaderPreviewResult { code: None };
+        if let Ok(shader_preview_params) = shader_preview_params {
+            if let (Some(shader_compile_commands), Some(clang_path)) = (
+                self.settings.shader_compile_commands.clone(),
+                self.settings.clang_path.clone(),
+            ) {
+                if shader_compile_commands.exists() {
+                    let commands = from_shader_compile_commands_path(shader_compile_commands)
+                        .unwrap_or(vec![]);
+                    for command in commands {
+                        let arguments = command
+                            .arguments
+                            .iter()
+                            .fold("".to_string(), |acc, x| acc + " " + x);
+                        let code =
+                            misc::pre_process(&clang_path, Path::new(&command.file), &arguments);
+
+                        if Path::new(&shader_preview_params.shader_file_path)
+                            == Path::new(&command.file)
+                        {
+                            if let Ok(code) = code {
+                                result.code = Some(code);
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        to_result(result)
+    }
+}
+
+fn from_params<P: DeserializeOwned>(params: serde_json::Value) -> Result<P, ResponseError> {
+    serde_json::from_value(params).map_err(|err| ResponseError {
+        code: lsp_server::ErrorCode::InvalidParams as i32,
+        message: err.to_string(),
+        data: None,
+    })
+}
+
+fn to_result<R: Serialize>(result: R) -> Result<serde_json::Value, ResponseError> {
+    serde_json::to_value(&result).map_err(|err| ResponseError {
+        code: lsp_server::ErrorCode::ParseError as i32,
+        message: err.to_string(),
+        data: None,
+    })
+}
+
+/// The items of the configuration request, in the order of the response.
+fn cfg_items() -> Vec<ConfigurationItem> {
+    vec![
+        ConfigurationItem {
+            scope_uri: None,
+            section: Some("shaderCompiler.shaderCompileCommands".to_string()),
+        },
+        ConfigurationItem {
+            scope_uri: None,
+            section: Some("shaderCompiler.clangPath".to_string()),
+        },
+    ]
 }
 
 pub fn from_shader_compile_commands_path<P: AsRef<Path>>(
@@ -206,8 +466,13 @@ pub fn from_shader_compile_commands_path<P: AsRef<Path>>(
 
 pub fn server_capabilities() -> ServerCapabilities {
     ServerCapabilities {
-        text_document_sync: Some(TextDocumentSyncCapability::Kind(
-            TextDocumentSyncKind::INCREMENTAL,
+        text_document_sync: Some(TextDocumentSyncCapability::Options(
+            TextDocumentSyncOptions {
+                open_close: Some(true),
+                change: Some(TextDocumentSyncKind::FULL),
+                save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                ..Default::default()
+            },
         )),
         definition_provider: Some(OneOf::Left(true)),
         completion_provider: Some(CompletionOptions {
@@ -219,9 +484,7 @@ pub fn server_capabilities() -> ServerCapabilities {
                 work_done_progress: None,
             },
         }),
-        document_formatting_provider: Some(OneOf::Left(true)),
         hover_provider: Some(HoverProviderCapability::Simple(true)),
-        inlay_hint_provider: Some(OneOf::Left(true)),
         ..Default::default()
     }
 }
@@ -258,3 +521,282 @@ pub fn register_capability(connection: &lsp_server::Connection) {
         serde_json::to_value(&registration_params).unwrap(),
     );
 }
+
+#[cfg(test)]
+mod test {
+    use super::Server;
+    use crate::misc;
+    use lsp_server::{Connection, Message, RequestId};
+    use lsp_types::{
+        Position, TextDocumentIdentifier, TextDocumentPositionParams, Uri,
+        notification::Notification, request::Request,
+    };
+    use std::path::{Path, PathBuf};
+
+    struct Client {
+        connection: Connection,
+        compile_commands: PathBuf,
+        notifications: Vec<lsp_server::Notification>,
+        id: i32,
+    }
+
+    impl Client {
+        fn reply(&self, request: lsp_server::Request) {
+            let result = if request.method == lsp_types::request::WorkspaceConfiguration::METHOD {
+                serde_json::json!([self.compile_commands, null])
+            } else {
+                serde_json::Value::Null
+            };
+            let response = lsp_server::Response::new_ok(request.id, result);
+            self.connection
+                .sender
+                .send(Message::Response(response))
+                .unwrap();
+        }
+
+        fn receive(&mut self) {
+            match self.connection.receiver.recv().unwrap() {
+                Message::Request(request) => self.reply(request),
+                Message::Notification(notification) => self.notifications.push(notification),
+                Message::Response(_) => {}
+            }
+        }
+
+        fn request<R: Request>(&mut self, params: R::Params) -> R::Result {
+            self.id += 1;
+            let id = RequestId::from(self.id);
+            let request = lsp_server::Request::new(id.clone(), R::METHOD.to_string(), params);
+            self.connection
+                .sender
+                .send(Message::Request(request))
+                .unwrap();
+            loop {
+                match self.connection.receiver.recv().unwrap() {
+                    Message::Response(response) if response.id == id => {
+                        return serde_json::from_value(response.response_result.unwrap()).unwrap();
+                    }
+                    Message::Request(request) => self.reply(request),
+                    Message::Notification(notification) => self.notifications.push(notification),
+                    Message::Response(_) => {}
+                }
+            }
+        }
+
+        fn notify<N: Notification>(&self, params: N::Params) {
+            let notification = lsp_server::Notification::new(N::METHOD.to_string(), params);
+            self.connection
+                .sender
+                .send(Message::Notification(notification))
+                .unwrap();
+        }
+
+        fn wait_diagnostics(&mut self, uri: &Uri) -> Vec<lsp_types::Diagnostic> {
+            loop {
+                let position = self.notifications.iter().position(|x| {
+                    x.method == lsp_types::notification::PublishDiagnostics::METHOD
+                        && x.params["uri"] == uri.as_str()
+                });
+                if let Some(position) = position {
+                    let notification = self.notifications.remove(position);
+                    let params: lsp_types::PublishDiagnosticsParams =
+                        serde_json::from_value(notification.params).unwrap();
+                    return params.diagnostics;
+                }
+                self.receive();
+            }
+        }
+
+        fn position_params(uri: &Uri, position: Position) -> TextDocumentPositionParams {
+            TextDocumentPositionParams {
+                text_document: TextDocumentIdentifier { uri: uri.clone() },
+                position,
+            }
+        }
+
+        fn hover(&mut self, uri: &Uri, position: Position) -> String {
+            let hover = self.request::<lsp_types::request::HoverRequest>(lsp_types::HoverParams {
+                text_document_position_params: Self::position_params(uri, position),
+                work_done_progress_params: Default::default(),
+            });
+            let lsp_types::HoverContents::Markup(markup) = hover.unwrap().contents else {
+                panic!("Expected markup");
+            };
+            markup.value
+        }
+
+        fn definition(&mut self, uri: &Uri, position: Position) -> lsp_types::Location {
+            let response = self.request::<lsp_types::request::GotoDefinition>(
+                lsp_types::GotoDefinitionParams {
+                    text_document_position_params: Self::position_params(uri, position),
+                    work_done_progress_params: Default::default(),
+                    partial_result_params: Default::default(),
+                },
+            );
+            let Some(lsp_types::GotoDefinitionResponse::Scalar(location)) = response else {
+                panic!("Expected a location");
+            };
+            location
+        }
+
+        fn change(&self, uri: &Uri, version: i32, text: &str) {
+            self.notify::<lsp_types::notification::DidChangeTextDocument>(
+                lsp_types::DidChangeTextDocumentParams {
+                    text_document: lsp_types::VersionedTextDocumentIdentifier {
+                        uri: uri.clone(),
+                        version,
+                    },
+                    content_changes: vec![lsp_types::TextDocumentContentChangeEvent {
+                        range: None,
+                        range_length: None,
+                        text: text.to_string(),
+                    }],
+                },
+            );
+        }
+    }
+
+    fn find(contents: &str, line: u32, word: &str) -> Position {
+        let column = contents
+            .lines()
+            .nth(line as usize)
+            .unwrap()
+            .find(word)
+            .unwrap();
+        Position::new(line, column as u32)
+    }
+
+    fn uri(path: &Path) -> Uri {
+        misc::path_to_uri(&misc::normalize_path(path)).unwrap()
+    }
+
+    #[test]
+    fn test_language_features() {
+        let dir = std::env::temp_dir().join("rs_shader_compiler_lsp_test_language_features");
+        let _ = std::fs::create_dir_all(&dir);
+        let common = "#define SCALE 2.0
+struct Light {
+    color: vec3<f32>,
+    intensity: f32,
+}
+";
+        std::fs::write(dir.join("common.wgsl"), common).unwrap();
+        std::fs::write(dir.join("broken.wgsl"), "const b: f32 = c;\n").unwrap();
+        let main = "#include \"common.wgsl\"
+fn shade(light: Light) -> vec3<f32> {
+    let scaled = light.color * SCALE * FACTOR;
+    return scaled * light.intensity;
+}
+";
+        let main_path = dir.join("main.wgsl");
+        std::fs::write(&main_path, main).unwrap();
+        let compile_commands = dir.join("shader_compile_commands.json");
+        let command = super::CompileCommand {
+            arguments: vec![format!("-I{}", dir.display()), "-DFACTOR=1.0".to_string()],
+            file: main_path.to_string_lossy().to_string(),
+        };
+        std::fs::write(
+            &compile_commands,
+            serde_json::to_string(&vec![command]).unwrap(),
+        )
+        .unwrap();
+
+        let (server_connection, connection) = Connection::memory();
+        let server = std::thread::spawn(move || Server::new().serve(server_connection));
+        let mut client = Client {
+            connection,
+            compile_commands,
+            notifications: vec![],
+            id: 0,
+        };
+        #[allow(deprecated)]
+        let initialize_params = lsp_types::InitializeParams::default();
+        let _ = client.request::<lsp_types::request::Initialize>(initialize_params);
+        client.notify::<lsp_types::notification::Initialized>(lsp_types::InitializedParams {});
+        // The server reads the compile commands before the following messages.
+        loop {
+            if let Message::Request(request) = client.connection.receiver.recv().unwrap() {
+                let is_configuration =
+                    request.method == lsp_types::request::WorkspaceConfiguration::METHOD;
+                client.reply(request);
+                if is_configuration {
+                    break;
+                }
+            }
+        }
+
+        let main_uri = uri(&main_path);
+        let common_uri = uri(&dir.join("common.wgsl"));
+        let broken_uri = uri(&dir.join("broken.wgsl"));
+        client.notify::<lsp_types::notification::DidOpenTextDocument>(
+            lsp_types::DidOpenTextDocumentParams {
+                text_document: lsp_types::TextDocumentItem::new(
+                    main_uri.clone(),
+                    "wgsl".to_string(),
+                    0,
+                    main.to_string(),
+                ),
+            },
+        );
+        assert_eq!(client.wait_diagnostics(&main_uri), vec![]);
+
+        let completion =
+            client.request::<lsp_types::request::Completion>(lsp_types::CompletionParams {
+                text_document_position: Client::position_params(&main_uri, Position::new(3, 4)),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                context: None,
+            });
+        let Some(lsp_types::CompletionResponse::Array(items)) = completion else {
+            panic!("Expected completion items");
+        };
+        for label in ["Light", "shade", "SCALE", "FACTOR", "fn", "vec3", "dot"] {
+            assert!(items.iter().any(|x| x.label == label), "{label}");
+        }
+
+        let hover = client.hover(&main_uri, find(main, 1, "Light"));
+        assert!(
+            hover.contains("struct Light {\n    color: vec3<f32>,"),
+            "{hover}"
+        );
+        let hover = client.hover(&main_uri, find(main, 1, "shade"));
+        assert!(
+            hover.contains("fn shade(light: Light) -> vec3<f32>"),
+            "{hover}"
+        );
+        let hover = client.hover(&main_uri, find(main, 2, "scaled"));
+        assert!(hover.contains("let scaled: vec3<f32>"), "{hover}");
+        let hover = client.hover(&main_uri, find(main, 2, "SCALE"));
+        assert!(hover.contains("#define SCALE 2.0"), "{hover}");
+        let hover = client.hover(&main_uri, find(main, 2, "FACTOR"));
+        assert!(hover.contains("#define FACTOR 1.0"), "{hover}");
+
+        let location = client.definition(&main_uri, find(main, 1, "Light"));
+        assert_eq!(location.uri, common_uri);
+        assert_eq!(location.range.start, Position::new(1, 7));
+        let location = client.definition(&main_uri, find(main, 0, "common"));
+        assert_eq!(location.uri, common_uri);
+        assert_eq!(location.range.start, Position::new(0, 0));
+        let location = client.definition(&main_uri, find(main, 3, "scaled"));
+        assert_eq!(location.uri, main_uri);
+        assert_eq!(location.range.start, Position::new(2, 8));
+
+        client.change(&main_uri, 1, "const a: f32 = b;\n");
+        let diagnostics = client.wait_diagnostics(&main_uri);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range.start, Position::new(0, 15));
+
+        client.change(&main_uri, 2, "#include \"broken.wgsl\"\n");
+        let diagnostics = client.wait_diagnostics(&broken_uri);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].range.start, Position::new(0, 15));
+        assert_eq!(client.wait_diagnostics(&main_uri), vec![]);
+
+        client.change(&main_uri, 3, main);
+        assert_eq!(client.wait_diagnostics(&main_uri), vec![]);
+        assert_eq!(client.wait_diagnostics(&broken_uri), vec![]);
+
+        client.request::<lsp_types::request::Shutdown>(());
+        client.notify::<lsp_types::notification::Exit>(());
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,337 @@
+use lsp_types::{Position, Range};
+
+pub const KEYWORDS: &[&str] = &[
+    "alias",
+    "break",
+    "case",
+    "const",
+    "const_assert",
+    "continue",
+    "continuing",
+    "default",
+    "diagnostic",
+    "discard",
+    "else",
+    "enable",
+    "false",
+    "fn",
+    "for",
+    "if",
+    "let",
+    "loop",
+    "override",
+    "requires",
+    "return",
+    "struct",
+    "switch",
+    "true",
+    "var",
+    "while",
+];
+
+pub const BUILTIN_TYPES: &[&str] = &[
+    "bool",
+    "f16",
+    "f32",
+    "i32",
+    "u32",
+    "vec2",
+    "vec3",
+    "vec4",
+    "mat2x2",
+    "mat2x3",
+    "mat2x4",
+    "mat3x2",
+    "mat3x3",
+    "mat3x4",
+    "mat4x2",
+    "mat4x3",
+    "mat4x4",
+    "array",
+    "atomic",
+    "ptr",
+    "sampler",
+    "sampler_comparison",
+    "texture_1d",
+    "texture_2d",
+    "texture_2d_array",
+    "texture_3d",
+    "texture_cube",
+    "texture_cube_array",
+    "texture_multisampled_2d",
+    "texture_depth_2d",
+    "texture_depth_2d_array",
+    "texture_depth_cube",
+    "texture_depth_cube_array",
+    "texture_depth_multisampled_2d",
+    "texture_storage_1d",
+    "texture_storage_2d",
+    "texture_storage_2d_array",
+    "texture_storage_3d",
+    "texture_external",
+];
+
+pub const BUILTIN_FUNCTIONS: &[&str] = &[
+    "bitcast",
+    "all",
+    "any",
+    "select",
+    "arrayLength",
+    "abs",
+    "acos",
+    "acosh",
+    "asin",
+    "asinh",
+    "atan",
+    "atanh",
+    "atan2",
+    "ceil",
+    "clamp",
+    "cos",
+    "cosh",
+    "countLeadingZeros",
+    "countOneBits",
+    "countTrailingZeros",
+    "cross",
+    "degrees",
+    "determinant",
+    "distance",
+    "dot",
+    "exp",
+    "exp2",
+    "extractBits",
+    "faceForward",
+    "firstLeadingBit",
+    "firstTrailingBit",
+    "floor",
+    "fma",
+    "fract",
+    "frexp",
+    "insertBits",
+    "inverseSqrt",
+    "ldexp",
+    "length",
+    "log",
+    "log2",
+    "max",
+    "min",
+    "mix",
+    "modf",
+    "normalize",
+    "pow",
+    "quantizeToF16",
+    "radians",
+    "reflect",
+    "refract",
+    "reverseBits",
+    "round",
+    "saturate",
+    "sign",
+    "sin",
+    "sinh",
+    "smoothstep",
+    "sqrt",
+    "step",
+    "tan",
+    "tanh",
+    "transpose",
+    "trunc",
+    "dpdx",
+    "dpdxCoarse",
+    "dpdxFine",
+    "dpdy",
+    "dpdyCoarse",
+    "dpdyFine",
+    "fwidth",
+    "fwidthCoarse",
+    "fwidthFine",
+    "textureDimensions",
+    "textureGather",
+    "textureGatherCompare",
+    "textureLoad",
+    "textureNumLayers",
+    "textureNumLevels",
+    "textureNumSamples",
+    "textureSample",
+    "textureSampleBias",
+    "textureSampleCompare",
+    "textureSampleCompareLevel",
+    "textureSampleGrad",
+    "textureSampleLevel",
+    "textureSampleBaseClampToEdge",
+    "textureStore",
+    "atomicLoad",
+    "atomicStore",
+    "atomicAdd",
+    "atomicSub",
+    "atomicMax",
+    "atomicMin",
+    "atomicAnd",
+    "atomicOr",
+    "atomicXor",
+    "atomicExchange",
+    "atomicCompareExchangeWeak",
+    "pack4x8snorm",
+    "pack4x8unorm",
+    "pack2x16snorm",
+    "pack2x16unorm",
+    "pack2x16float",
+    "unpack4x8snorm",
+    "unpack4x8unorm",
+    "unpack2x16snorm",
+    "unpack2x16unorm",
+    "unpack2x16float",
+    "storageBarrier",
+    "textureBarrier",
+    "workgroupBarrier",
+    "workgroupUniformLoad",
+];
+
+/// The keywords which are followed by the name of the declaration.
+const DECLARATION_KEYWORDS: &[&str] = &["alias", "const", "fn", "let", "override", "struct", "var"];
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// The identifier under the position.
+pub fn get_word_at(contents: &str, position: Position) -> Option<(String, Range)> {
+    let line: Vec<char> = contents
+        .lines()
+        .nth(position.line as usize)?
+        .chars()
+        .collect();
+    let character = (position.character as usize).min(line.len());
+    let mut start = character;
+    while start > 0 && is_word_char(line[start - 1]) {
+        start -= 1;
+    }
+    let mut end = character;
+    while end < line.len() && is_word_char(line[end]) {
+        end += 1;
+    }
+    if start == end || line[start].is_ascii_digit() {
+        return None;
+    }
+    let range = Range::new(
+        Position::new(position.line, start as u32),
+        Position::new(position.line, end as u32),
+    );
+    Some((line[start..end].iter().collect(), range))
+}
+
+/// The file name of `#include "name"` on the line.
+pub fn get_include(line: &str) -> Option<&str> {
+    let line = line
+        .trim_start()
+        .strip_prefix('#')?
+        .trim_start()
+        .strip_prefix("include")?
+        .trim();
+    let line = line.strip_prefix('"').or(line.strip_prefix('<'))?;
+    line.split(['"', '>']).next()
+}
+
+/// Finds the first declaration of `name` by a declaration keyword or `#define`.
+pub fn find_declaration(contents: &str, name: &str) -> Option<Position> {
+    for (line_index, line) in contents.lines().enumerate() {
+        let line = line.split("//").next().unwrap_or_default();
+        let mut search_start = 0;
+        while let Some(offset) = line[search_start..].find(name) {
+            let start = search_start + offset;
+            let end = start + name.len();
+            search_start = end;
+            let before = &line[..start];
+            if before.ends_with(is_word_char) || line[end..].starts_with(is_word_char) {
+                continue;
+            }
+            let mut before = before.trim_end();
+            // `var<uniform> name`
+            if before.ends_with('>') {
+                if let Some(template_start) = before.rfind('<') {
+                    before = before[..template_start].trim_end();
+                }
+            }
+            let is_define = before
+                .trim_start()
+                .strip_prefix('#')
+                .is_some_and(|x| x.trim() == "define");
+            let is_declaration = DECLARATION_KEYWORDS.iter().any(|keyword| {
+                before
+                    .strip_suffix(keyword)
+                    .is_some_and(|x| !x.ends_with(is_word_char))
+            });
+            if is_define || is_declaration {
+                return Some(Position::new(
+                    line_index as u32,
+                    line[..start].chars().count() as u32,
+                ));
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::{find_declaration, get_include, get_word_at};
+    use lsp_types::Position;
+
+    #[test]
+    fn test_get_word_at() {
+        let contents = "fn main() {\n    let color = vec3<f32>(1.0);\n}";
+        let (word, range) = get_word_at(contents, Position::new(1, 10)).unwrap();
+        assert_eq!(word, "color");
+        assert_eq!((range.start.character, range.end.character), (8, 13));
+        assert_eq!(
+            get_word_at(contents, Position::new(1, 13)).unwrap().0,
+            "color"
+        );
+        assert_eq!(get_word_at(contents, Position::new(1, 27)), None);
+        assert_eq!(get_word_at(contents, Position::new(5, 0)), None);
+    }
+
+    #[test]
+    fn test_get_include() {
+        assert_eq!(get_include("#include \"common.wgsl\""), Some("common.wgsl"));
+        assert_eq!(
+            get_include("  #  include <common.wgsl>"),
+            Some("common.wgsl")
+        );
+        assert_eq!(get_include("let a = 1;"), None);
+    }
+
+    #[test]
+    fn test_find_declaration() {
+        let contents = "#define SCALE 2.0
+struct Light {
+    color: vec3<f32>,
+}
+@group(0) @binding(0) var<uniform> light: Light;
+fn shade(light: Light) -> f32 {
+    let lighting = 1.0;
+    return lighting;
+}";
+        assert_eq!(
+            find_declaration(contents, "SCALE"),
+            Some(Position::new(0, 8))
+        );
+        assert_eq!(
+            find_declaration(contents, "Light"),
+            Some(Position::new(1, 7))
+        );
+        assert_eq!(
+            find_declaration(contents, "light"),
+            Some(Position::new(4, 35))
+        );
+        assert_eq!(
+            find_declaration(contents, "shade"),
+            Some(Position::new(5, 3))
+        );
+        assert_eq!(
+            find_declaration(contents, "lighting"),
+            Some(Position::new(6, 8))
+        );
+        assert_eq!(find_declaration(contents, "color"), None);
+    }
+}
@@ -52,7 +52,10 @@ export function activate(context: vscode.ExtensionContext) {
 
         const clientOptions: LanguageClientOptions = {
             outputChannel: new MyLogOutputChannel(),
-            documentSelector: [{ scheme: 'file', language: 'wgsl' }],
+            documentSelector: [
+                { scheme: 'file', language: 'wgsl' },
+                { scheme: 'file', language: 'slang' },
+            ],
             synchronize: {
                 fileEvents: vscode.workspace.createFileSystemWatcher('**/.clientrc')
             }